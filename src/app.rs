@@ -1,18 +1,58 @@
 use gio::{self, prelude::*};
-use glib;
 use gtk::{self, prelude::*};
 
 use crate::about_dialog::show_about_dialog;
+use crate::appearance::Appearance;
+use crate::asset_manager::AssetManager;
+use crate::audio_mixer::AudioMixerPanel;
 use crate::audio_vumeter;
+use crate::chat::Chat;
+use crate::checklist;
+use crate::cli;
+use crate::debug_hud::{self, DebugStats};
+use crate::gallery;
 use crate::header_bar::HeaderBar;
+use crate::layers::Layers;
+use crate::log_viewer::show_log_viewer;
+use crate::logos::Logos;
+use crate::lower_third::LowerThird;
+use crate::markup_editor::MarkupEditor;
+use crate::media_controls::MediaControls;
+use crate::midi::Midi;
+use crate::output::hls;
+use crate::overlay_variables::OverlayVariables;
+use crate::overlay_widgets::{countdown::Countdown, OverlayWidgets};
+use crate::pip_drag::PipDrag;
 use crate::pipeline::Pipeline;
-use crate::settings::show_settings_dialog;
+use crate::recovery;
+use crate::remote;
+use crate::rundown::{Cue, Rundown};
+use crate::scenes;
+use crate::scheduler::Scheduler;
+use crate::session_log::{EventKind, SessionLog};
+use crate::settings::{show_settings_dialog, MeterOrientation, OutputMode, Settings};
+use crate::shortcuts_window::{self, show_shortcuts_window};
+use crate::stats_log::StatsLog;
+use crate::stream_health::StreamHealth;
+use crate::template_library::TemplateLibrary;
+use crate::templates;
+use crate::ticker::Ticker;
+use crate::toasts::Toasts;
+use crate::uploader::Uploader;
 use crate::utils;
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::error;
 use std::ops;
 use std::rc::{Rc, Weak};
+use std::time::{Duration, Instant};
+
+// Seconds between checks of the free disk space while recording
+const DISK_SPACE_CHECK_INTERVAL: u32 = 5;
+// Milliseconds after the last edit of the markup until the overlay is updated live
+const LIVE_UPDATE_DELAY: u32 = 500;
+// Longest time to wait for the recordings to be finished when quitting
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
 
 // Our refcounted application struct for containing all the state we have to carry around.
 //
@@ -25,7 +65,7 @@ impl ops::Deref for App {
     type Target = AppInner;
 
     fn deref(&self) -> &AppInner {
-        &*self.0
+        &self.0
     }
 }
 
@@ -45,14 +85,77 @@ impl AppWeak {
 
 pub struct AppInner {
     main_window: gtk::ApplicationWindow,
+    appearance: Appearance,
     header_bar: HeaderBar,
     pipeline: Pipeline,
-    text_view: gtk::TextView,
+    markup_editor: MarkupEditor,
     css_buffer: RefCell<std::string::String>,
     html_buffer: RefCell<std::string::String>,
-    editing_markup: RefCell<Option<std::string::String>>,
-    #[allow(dead_code)]
+    // Editor of the markup, only usable while no web page is loaded instead
+    markup_box: gtk::Box,
+    live_update: gtk::CheckButton,
+    // Updates the overlay once the markup wasn't edited for a moment
+    live_update_timeout: RefCell<Option<glib::SourceId>>,
+    overlay_from_url: gtk::RadioButton,
+    overlay_url: gtk::Entry,
     audio_vumeter: audio_vumeter::AudioVuMeter,
+    // The level meter is either next to the preview or in a bar below it
+    preview_box: gtk::Box,
+    program_box: gtk::Box,
+    // The editors are either next to the program or in their own window, e.g. on another monitor
+    paned: gtk::Paned,
+    editor_box: gtk::Box,
+    editor_window: RefCell<Option<gtk::Window>>,
+    // Shows the program fullscreen on another monitor while open
+    projector_window: RefCell<Option<gtk::Window>>,
+    // Only kept alive here, the panel handles its controls itself
+    #[allow(dead_code)]
+    audio_mixer: AudioMixerPanel,
+    #[allow(dead_code)]
+    media_controls: MediaControls,
+    #[allow(dead_code)]
+    pip_drag: PipDrag,
+    #[allow(dead_code)]
+    stream_health: StreamHealth,
+    chat: Chat,
+    midi: Midi,
+    overlay_variables: OverlayVariables,
+    ticker: Ticker,
+    asset_manager: AssetManager,
+    logos: Logos,
+    #[allow(dead_code)]
+    layers: Layers,
+    #[allow(dead_code)]
+    lower_third: LowerThird,
+    #[allow(dead_code)]
+    overlay_widgets: OverlayWidgets,
+    template_library: TemplateLibrary,
+    #[allow(dead_code)]
+    uploader: Uploader,
+    session_log: SessionLog,
+    rundown: Rundown,
+    scheduler: Scheduler,
+    stats_log: StatsLog,
+    #[allow(dead_code)]
+    toasts: Toasts,
+    // Switches to the next template of the automatic rotation
+    rotation_timeout: RefCell<Option<glib::SourceId>>,
+    rotation_position: Cell<usize>,
+    // Periodically updates the delay buffer fill level while recording
+    delay_timeout: RefCell<Option<glib::SourceId>>,
+    // Updates the time on air while recording
+    on_air_timeout: RefCell<Option<glib::SourceId>>,
+    // Periodically checks the free disk space while recording
+    disk_space_timeout: RefCell<Option<glib::SourceId>>,
+    // Whether the low disk space warning was shown for the current recording
+    disk_space_warned: Cell<bool>,
+    // Go live and trigger the other actions once the pipeline runs, as asked for on the command
+    // line
+    run_cli_options: Cell<bool>,
+    // Updates the debug HUD while it is enabled
+    debug_hud_timeout: RefCell<Option<glib::SourceId>>,
+    // Previous statistics sample, to calculate rates from
+    debug_hud_sample: RefCell<Option<(Instant, DebugStats)>>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -61,7 +164,7 @@ pub enum RecordState {
     Recording,
 }
 
-impl<'a> From<&'a glib::Variant> for RecordState {
+impl From<&glib::Variant> for RecordState {
     fn from(v: &glib::Variant) -> RecordState {
         v.get::<bool>().expect("Invalid record state type").into()
     }
@@ -86,7 +189,7 @@ impl From<RecordState> for glib::Variant {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Action {
     Quit,
     Settings,
@@ -94,6 +197,50 @@ pub enum Action {
     Record(RecordState),
     #[allow(dead_code)]
     UpdateOverlay,
+    #[allow(dead_code)]
+    RepairRecordings,
+    // Display name of the microphone, empty for the automatically selected one
+    AudioInput(String),
+    Scene(String),
+    // Switches to the nth scene, counted from 1
+    SwitchScene(i32),
+    Panic(bool),
+    // Covers the program with the slate while true, without stopping the outputs
+    PauseStream(bool),
+    MuteAudio(bool),
+    // Plays the program audio locally while true
+    MonitorAudio(bool),
+    DebugHud(bool),
+    // Shows the editors in their own window while true
+    DetachEditor(bool),
+    // Shows the program fullscreen on the configured monitor while true
+    Projector(bool),
+    #[allow(dead_code)]
+    Marker,
+    Snapshot,
+    TemplateGallery,
+    Rundown,
+    #[allow(dead_code)]
+    RundownNext,
+    #[allow(dead_code)]
+    RundownRun(bool),
+    Shortcuts,
+    LogViewer,
+    // Goes live once confirmed
+    GoLive,
+}
+
+// Where the program is sent when live, for showing it to the user
+fn destination(settings: &Settings) -> String {
+    match settings.output_mode {
+        OutputMode::Rtmp => settings.rtmp_location.clone().unwrap_or_default(),
+        OutputMode::Srt => settings.srt.uri.clone().unwrap_or_default(),
+        OutputMode::Whip => settings.whip.endpoint.clone().unwrap_or_default(),
+        OutputMode::Rtp => format!(
+            "rtp://{}:{}/{}",
+            settings.rtp.host, settings.rtp.video_port, settings.rtp.audio_port
+        ),
+    }
 }
 
 impl App {
@@ -111,80 +258,323 @@ impl App {
 
         let vumeter = audio_vumeter::AudioVuMeter::new();
 
-        // Create the pipeline and if that fail return
-        let pipeline = Pipeline::new(vumeter.downgrade())
-            .map_err(|err| format!("Error creating pipeline: {:?}", err))?;
-
-        let text_view = gtk::TextView::new();
-        text_view.set_size_request(400, 300);
-
-        let scrolled_window = gtk::ScrolledWindow::new(gtk::NONE_ADJUSTMENT, gtk::NONE_ADJUSTMENT);
-        scrolled_window.set_size_request(400, 300);
-        scrolled_window.add(&text_view);
+        let uploader = Uploader::new();
 
-        let css_buffer = RefCell::new(include_str!("../data/style.css").to_string());
-        let html_buffer = RefCell::new(include_str!("../data/index.html").to_string());
+        let session_log = SessionLog::new();
 
-        let menu = gtk::ComboBoxText::new();
+        // Create the pipeline and if that fail return
+        let pipeline = Pipeline::new(
+            vumeter.downgrade(),
+            uploader.downgrade(),
+            session_log.downgrade(),
+        )
+        .map_err(|err| format!("Error creating pipeline: {:?}", err))?;
 
-        menu.append_text("CSS");
-        menu.append_text("HTML");
+        let (html, css) = templates::load_current();
+        let markup_editor = MarkupEditor::new(&html, &css);
+        let css_buffer = RefCell::new(css);
+        let html_buffer = RefCell::new(html);
 
-        let update_button = gtk::Button::new_with_label("Update web-page overlay");
+        let update_button = gtk::Button::new_with_mnemonic("_Update web-page overlay");
         update_button
             .clone()
             .upcast::<gtk::Actionable>()
             .set_action_name(Some("app.update_overlay"));
 
         let vumeter_widget = vumeter.get_widget();
-        vumeter_widget.set_size_request(30, -1);
+
+        // Let keyboard users reach the preview so screen readers can describe it
+        let preview = pipeline.get_widget();
+        preview.set_can_focus(true);
+        utils::set_accessible(
+            &preview,
+            "Program preview",
+            "Camera mixed with the web-page overlay, as streamed",
+        );
+
+        let pip_drag = PipDrag::new(&preview, pipeline.downgrade());
 
         let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 0);
-        hbox.pack_start(&pipeline.get_widget(), false, false, 0);
+        hbox.pack_start(&preview, false, false, 0);
         hbox.pack_start(vumeter_widget, false, false, 0);
 
-        let vbox = gtk::Box::new(gtk::Orientation::Vertical, 0);
-        vbox.pack_start(&menu, false, false, 0);
-        vbox.pack_start(&scrolled_window, true, true, 0);
-        vbox.pack_start(&update_button, false, false, 0);
+        let audio_mixer = AudioMixerPanel::new(pipeline.downgrade());
+        let midi = Midi::new(audio_mixer.downgrade());
+
+        let program_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        program_box.pack_start(&hbox, true, true, 0);
+        program_box.pack_start(audio_mixer.get_widget(), false, false, 0);
+
+        let media_controls = MediaControls::new(pipeline.downgrade());
+        program_box.pack_start(media_controls.get_widget(), false, false, 0);
+
+        let stream_health = StreamHealth::new(pipeline.downgrade());
+        program_box.pack_start(stream_health.get_widget(), false, false, 0);
+
+        let chat = Chat::new(pipeline.downgrade());
+        program_box.pack_start(chat.get_widget(), false, false, 0);
+
+        // Either the markup edited here or a web page is shown as overlay
+        let settings = utils::load_settings();
+        let overlay_from_markup = gtk::RadioButton::new_with_label("Markup");
+        let overlay_from_url =
+            gtk::RadioButton::new_with_label_from_widget(&overlay_from_markup, "URL");
+        overlay_from_url.set_active(settings.overlay_from_url);
+        let overlay_url = gtk::Entry::new();
+        overlay_url.set_placeholder_text(Some("https://…"));
+        if let Some(ref url) = settings.overlay_url {
+            overlay_url.set_text(url);
+        }
+        utils::set_accessible(
+            &overlay_url,
+            "Overlay URL",
+            "Web page shown as overlay instead of the markup",
+        );
+        let reload_button =
+            gtk::Button::new_from_icon_name(Some("view-refresh-symbolic"), gtk::IconSize::Button);
+        reload_button.set_tooltip_text(Some("Reload the overlay"));
+
+        let source_box = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        source_box.pack_start(&overlay_from_markup, false, false, 0);
+        source_box.pack_start(&overlay_from_url, false, false, 0);
+        source_box.pack_start(&overlay_url, true, true, 0);
+        source_box.pack_start(&reload_button, false, false, 0);
+
+        let markup_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        markup_box.pack_start(markup_editor.get_widget(), true, true, 0);
+        let live_update = gtk::CheckButton::new_with_mnemonic("_Live update");
+        live_update.set_active(settings.overlay_live_update);
+        utils::set_accessible(
+            &live_update,
+            "Live update",
+            "Update the web-page overlay automatically while editing",
+        );
+        let update_box = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        update_box.pack_start(&update_button, true, true, 0);
+        update_box.pack_start(&live_update, false, false, 0);
+        markup_box.pack_start(&update_box, false, false, 0);
+
+        let vbox = gtk::Box::new(gtk::Orientation::Vertical, 4);
+        vbox.pack_start(&source_box, false, false, 0);
+        vbox.pack_start(&markup_box, true, true, 0);
+
+        let overlay_variables = OverlayVariables::new(pipeline.downgrade());
+
+        let notebook = gtk::Notebook::new();
+        notebook.append_page(&vbox, Some(&gtk::Label::new(Some("Overlay"))));
+        notebook.append_page(
+            overlay_variables.get_widget(),
+            Some(&gtk::Label::new(Some("Variables"))),
+        );
+        let ticker = Ticker::new(pipeline.downgrade());
+        notebook.append_page(ticker.get_widget(), Some(&gtk::Label::new(Some("Ticker"))));
+        let asset_manager = AssetManager::new(pipeline.downgrade());
+        notebook.append_page(
+            asset_manager.get_widget(),
+            Some(&gtk::Label::new(Some("Assets"))),
+        );
+        let logos = Logos::new(pipeline.downgrade());
+        notebook.append_page(logos.get_widget(), Some(&gtk::Label::new(Some("Logos"))));
+        let layers = Layers::new(pipeline.downgrade());
+        notebook.append_page(layers.get_widget(), Some(&gtk::Label::new(Some("Layers"))));
+        let lower_third = LowerThird::new(pipeline.downgrade());
+        notebook.append_page(
+            lower_third.get_widget(),
+            Some(&gtk::Label::new(Some("Lower third"))),
+        );
+
+        let overlay_widgets = OverlayWidgets::new(pipeline.downgrade());
+        let countdown = Countdown::new();
+        notebook.append_page(
+            countdown.get_widget(),
+            Some(&gtk::Label::new(Some("Countdown"))),
+        );
+        let scheduler = Scheduler::new(application, countdown.downgrade(), session_log.downgrade());
+        let stats_log = StatsLog::new(pipeline.downgrade());
+        overlay_widgets.add(countdown);
+
+        let template_library = TemplateLibrary::new();
+
+        let editor_box = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        editor_box.pack_start(template_library.get_widget(), false, false, 0);
+        editor_box.pack_start(&notebook, true, true, 0);
 
         let paned = gtk::Paned::new(gtk::Orientation::Horizontal);
-        paned.pack1(&hbox, false, false);
-        paned.pack2(&vbox, false, false);
+        paned.pack1(&program_box, false, false);
+        paned.pack2(&editor_box, false, false);
         paned.set_position(700);
 
-        window.add(&paned);
+        let main_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        main_box.pack_start(&paned, true, true, 0);
+        main_box.pack_start(session_log.get_widget(), false, false, 0);
+        main_box.pack_start(uploader.get_widget(), false, false, 0);
+
+        let toasts = Toasts::new(&main_box);
+        window.add(toasts.get_widget());
+
+        let appearance = Appearance::new(&window);
 
         let app = App(Rc::new(AppInner {
             main_window: window,
+            appearance,
             header_bar,
             pipeline,
-            text_view,
+            markup_editor,
             css_buffer,
             html_buffer,
+            markup_box,
+            live_update,
+            overlay_from_url,
+            overlay_url,
             audio_vumeter: vumeter,
-            editing_markup: RefCell::new(None),
+            preview_box: hbox,
+            program_box,
+            paned,
+            editor_box,
+            editor_window: RefCell::new(None),
+            projector_window: RefCell::new(None),
+            audio_mixer,
+            media_controls,
+            pip_drag,
+            stream_health,
+            chat,
+            midi,
+            overlay_variables,
+            ticker,
+            asset_manager,
+            logos,
+            layers,
+            lower_third,
+            overlay_widgets,
+            template_library,
+            uploader,
+            session_log,
+            rundown: Rundown::new(),
+            scheduler,
+            stats_log,
+            toasts,
+            rotation_timeout: RefCell::new(None),
+            live_update_timeout: RefCell::new(None),
+            rotation_position: Cell::new(0),
+            delay_timeout: RefCell::new(None),
+            on_air_timeout: RefCell::new(None),
+            disk_space_timeout: RefCell::new(None),
+            disk_space_warned: Cell::new(false),
+            run_cli_options: Cell::new(true),
+            debug_hud_timeout: RefCell::new(None),
+            debug_hud_sample: RefCell::new(None),
         }));
 
-        // Create the application actions
-        Action::create(&app, &application);
+        let weak_app = app.downgrade();
+        app.template_library.set_loader(move |html, css| {
+            let app = upgrade_weak!(weak_app);
+            app.load_overlay(html, css);
+        });
 
         let weak_app = app.downgrade();
-        menu.connect_changed(move |widget| {
+        app.overlay_variables.set_changed(move || {
             let app = upgrade_weak!(weak_app);
-            if let Some(selection) = widget.get_active_text() {
-                if let Some(buffer) = app.text_view.get_buffer() {
-                    if selection == "CSS" {
-                        buffer.set_text(&*app.css_buffer.borrow());
-                    } else {
-                        buffer.set_text(&*app.html_buffer.borrow());
-                    }
-                    app.editing_markup.replace(Some(selection.to_string()));
+            app.pipeline
+                .update_overlay(&app.html_buffer.borrow(), &app.css_buffer.borrow());
+        });
+
+        let weak_app = app.downgrade();
+        app.ticker.set_changed(move || {
+            let app = upgrade_weak!(weak_app);
+            app.pipeline
+                .update_overlay(&app.html_buffer.borrow(), &app.css_buffer.borrow());
+        });
+
+        let weak_app = app.downgrade();
+        app.asset_manager.set_changed(move || {
+            let app = upgrade_weak!(weak_app);
+            app.pipeline
+                .update_overlay(&app.html_buffer.borrow(), &app.css_buffer.borrow());
+        });
+
+        let weak_app = app.downgrade();
+        app.logos.set_changed(move || {
+            let app = upgrade_weak!(weak_app);
+            app.pipeline
+                .update_overlay(&app.html_buffer.borrow(), &app.css_buffer.borrow());
+        });
+
+        let weak_app = app.downgrade();
+        app.rundown.set_executor(move |cue| {
+            let app = upgrade_weak!(weak_app);
+            app.execute_cue(cue);
+        });
+
+        // Flip the record button back when the last output failed and was stopped
+        let weak_app = app.downgrade();
+        app.pipeline
+            .get_object()
+            .connect_local("notify::recording", false, move |_| {
+                let app = upgrade_weak!(weak_app, None);
+                if !app.pipeline.is_recording() {
+                    app.header_bar.set_record_active(false);
                 }
+                None
+            })
+            .expect("Failed to connect to the recording property");
+
+        // Tell where the snapshot went, also when the window is in the background
+        let weak_application = application.downgrade();
+        app.pipeline
+            .get_object()
+            .connect_local("snapshot-saved", false, move |values| {
+                let application = upgrade_weak!(weak_application, None);
+                let path = values[1].get::<String>().ok().flatten().unwrap_or_default();
+                let notification = gio::Notification::new("Snapshot saved");
+                notification.set_body(Some(&path));
+                application.send_notification(Some("snapshot"), &notification);
+                None
+            })
+            .expect("Failed to connect to the snapshot-saved signal");
+
+        // Switching the overlay source, confirming the URL and reloading all load the overlay
+        // again
+        let weak_app = app.downgrade();
+        app.overlay_from_url.connect_toggled(move |_| {
+            let app = upgrade_weak!(weak_app);
+            app.apply_overlay_source();
+        });
+        let weak_app = app.downgrade();
+        app.overlay_url.connect_activate(move |_| {
+            let app = upgrade_weak!(weak_app);
+            app.apply_overlay_source();
+        });
+        let weak_app = app.downgrade();
+        reload_button.connect_clicked(move |_| {
+            let app = upgrade_weak!(weak_app);
+            app.apply_overlay_source();
+        });
+        app.markup_box.set_sensitive(!settings.overlay_from_url);
+
+        let weak_app = app.downgrade();
+        app.markup_editor.set_changed(move || {
+            let app = upgrade_weak!(weak_app);
+            app.schedule_live_update();
+        });
+        let weak_app = app.downgrade();
+        app.live_update.connect_toggled(move |button| {
+            let app = upgrade_weak!(weak_app);
+            let mut settings = utils::load_settings();
+            settings.overlay_live_update = button.get_active();
+            utils::save_settings(&settings);
+            if button.get_active() {
+                app.schedule_live_update();
             }
         });
 
-        menu.set_active(Some(1));
+        app.refresh_appearance();
+        app.refresh_rotation();
+        app.refresh_schedule();
+        app.refresh_chat();
+
+        // Create the application actions
+        Action::create(&app, application);
+        app.refresh_destination();
 
         Ok(app)
     }
@@ -207,6 +597,23 @@ impl App {
             }
         };
 
+        // Without window nothing would keep the application running
+        if cli::is_headless() {
+            application.hold();
+        }
+
+        // Closing the window quits, which has to be confirmed while live
+        let app_weak = app.downgrade();
+        app.main_window.connect_delete_event(move |_, _| {
+            let app = upgrade_weak!(app_weak, Inhibit(false));
+            if app.is_live() {
+                app.request_quit();
+                Inhibit(true)
+            } else {
+                Inhibit(false)
+            }
+        });
+
         // When the application is activated show the UI. This happens when the first process is
         // started, and in the first process whenever a second process is started
         let app_weak = app.downgrade();
@@ -215,6 +622,43 @@ impl App {
             app.on_activate();
         });
 
+        // Let stream decks and companion software trigger our actions
+        let settings = utils::load_settings();
+        if settings.remote_port != 0 {
+            match remote::start(settings.remote_port, settings.remote_token) {
+                Ok(receiver) => {
+                    let weak_application = application.downgrade();
+                    receiver.attach(None, move |command| {
+                        let application = upgrade_weak!(weak_application, glib::Continue(false));
+                        command.apply(&application);
+                        glib::Continue(true)
+                    });
+                }
+                Err(err) => utils::show_error_dialog(
+                    false,
+                    format!(
+                        "Failed to listen for remote control on port {}: {}",
+                        settings.remote_port, err
+                    )
+                    .as_str(),
+                ),
+            }
+        }
+
+        // Let colleagues on the local network watch the program while live
+        if settings.lan_preview_port != 0 {
+            if let Err(err) = hls::serve(settings.lan_preview_port) {
+                utils::show_error_dialog(
+                    false,
+                    format!(
+                        "Failed to serve the LAN preview on port {}: {}",
+                        settings.lan_preview_port, err
+                    )
+                    .as_str(),
+                );
+            }
+        }
+
         // When the application is shut down we drop our app struct
         //
         // It has to be stored in a RefCell<Option<T>> to be able to pass it to a Fn closure. With
@@ -233,13 +677,15 @@ impl App {
     // Called on the first application instance whenever the first application instance is started,
     // or any future second application instance
     fn on_activate(&self) {
-        // Show our window and bring it to the foreground
-        self.main_window.show_all();
+        if !cli::is_headless() {
+            // Show our window and bring it to the foreground
+            self.main_window.show_all();
 
-        // Have to call this instead of present() because of
-        // https://gitlab.gnome.org/GNOME/gtk/issues/624
-        self.main_window
-            .present_with_time((glib::get_monotonic_time() / 1000) as u32);
+            // Have to call this instead of present() because of
+            // https://gitlab.gnome.org/GNOME/gtk/issues/624
+            self.main_window
+                .present_with_time((glib::get_monotonic_time() / 1000) as u32);
+        }
 
         // Once the UI is shown, start the GStreamer pipeline. If
         // an error happens, we immediately shut down
@@ -248,55 +694,811 @@ impl App {
                 true,
                 format!("Failed to set pipeline to playing: {}", err).as_str(),
             );
+            return;
+        }
+
+        // Going live from the command line happens only once, not whenever another instance is
+        // started
+        if self.run_cli_options.replace(false) {
+            if cli::options().is_some_and(|options| options.start_streaming) {
+                self.header_bar.set_record_active(true);
+            }
+            if let Some(application) = self.main_window.get_application() {
+                for action in cli::actions() {
+                    action.clone().trigger(&application);
+                }
+            }
         }
     }
 
     // Called when the application shuts down. We drop our app struct here
     fn on_shutdown(self) {
+        if self.pipeline.is_recording() {
+            self.finish_recording();
+        }
+
         // This might fail but as we shut down right now anyway this doesn't matter
-        // TODO: If a recording is currently running we would like to finish that first
-        // before quitting the pipeline and shutting down the pipeline.
         let _ = self.pipeline.stop();
     }
 
+    fn is_live(&self) -> bool {
+        self.main_window
+            .get_application()
+            .and_then(|application| application.get_action_state("record"))
+            .is_some_and(|state| RecordState::from(&state) == RecordState::Recording)
+    }
+
+    // Quit, after asking first while live. The recordings are finished on shutdown
+    fn request_quit(&self) {
+        let application = match self.main_window.get_application() {
+            Some(application) => application,
+            None => return,
+        };
+        // Nobody could answer without window
+        if !self.is_live() || cli::is_headless() {
+            application.quit();
+            return;
+        }
+
+        let dialog = gtk::MessageDialog::new(
+            Some(&self.main_window),
+            gtk::DialogFlags::MODAL,
+            gtk::MessageType::Question,
+            gtk::ButtonsType::None,
+            "You are live — stop streaming and quit?",
+        );
+        dialog.add_buttons(&[
+            ("_Cancel", gtk::ResponseType::Cancel),
+            ("_Quit", gtk::ResponseType::Accept),
+        ]);
+        dialog.set_default_response(gtk::ResponseType::Cancel);
+
+        dialog.connect_response(move |dialog, response| {
+            dialog.destroy();
+            if response == gtk::ResponseType::Accept {
+                application.quit();
+            }
+        });
+
+        dialog.set_resizable(false);
+        dialog.show_all();
+    }
+
+    // Send EOS through the outputs and wait for it to arrive, so files are finalized and streams
+    // are ended cleanly instead of in the middle of a GOP
+    fn finish_recording(&self) {
+        self.pipeline.finish_recording();
+
+        let progress_bar = gtk::ProgressBar::new();
+        let dialog = if cli::is_headless() {
+            None
+        } else {
+            let dialog = gtk::MessageDialog::new(
+                Some(&self.main_window),
+                gtk::DialogFlags::MODAL,
+                gtk::MessageType::Info,
+                gtk::ButtonsType::None,
+                "Finishing the recordings…",
+            );
+            if let Some(area) = dialog.get_message_area() {
+                if let Ok(area) = area.downcast::<gtk::Box>() {
+                    area.pack_start(&progress_bar, false, false, 0);
+                }
+            }
+            dialog.set_resizable(false);
+            dialog.show_all();
+            Some(dialog)
+        };
+
+        // Also wakes up the main loop regularly to check whether the outputs are done, their bins
+        // are removed from another thread once EOS arrived at their sinks
+        let source_id = glib::timeout_add_local(100, move || {
+            progress_bar.pulse();
+            glib::Continue(true)
+        });
+
+        let started = Instant::now();
+        let context = glib::MainContext::default();
+        while self.pipeline.is_finishing() && started.elapsed() < SHUTDOWN_TIMEOUT {
+            context.iteration(true);
+        }
+        if self.pipeline.is_finishing() {
+            log::warn!("Timed out finishing the recordings");
+        }
+
+        glib::source_remove(source_id);
+        if let Some(dialog) = dialog {
+            dialog.destroy();
+        }
+    }
+
     // When the record button is clicked it triggers the record action, which will call this.
     // We have to start or stop recording here
     fn on_record_state_changed(&self, new_state: RecordState) {
         // Start/stop recording based on button active'ness
         match new_state {
             RecordState::Recording => {
-                if let Err(err) = self.pipeline.start_recording() {
-                    utils::show_error_dialog(
-                        false,
-                        format!("Failed to start recording: {}", err).as_str(),
+                let settings = utils::load_settings();
+                // Nobody could confirm the checklist without window
+                if !settings.checklist.enabled || cli::is_headless() {
+                    self.start_recording();
+                    return;
+                }
+
+                let application = match self.main_window.get_application() {
+                    Some(application) => application,
+                    None => return,
+                };
+
+                // Only go live once the user confirmed the pre-flight checklist
+                let destination = destination(&settings);
+                let weak_app = self.downgrade();
+                checklist::run_checks(&self.pipeline, &settings, move |results| {
+                    let app = upgrade_weak!(weak_app);
+                    let weak_app = app.downgrade();
+                    let cancel_weak_app = app.downgrade();
+                    checklist::show_checklist_dialog(
+                        &application,
+                        &results,
+                        &destination,
+                        move || {
+                            let app = upgrade_weak!(weak_app);
+                            app.start_recording();
+                        },
+                        move || {
+                            let app = upgrade_weak!(cancel_weak_app);
+                            app.header_bar.set_record_active(false);
+                        },
                     );
-                    self.header_bar.set_record_active(false);
+                });
+            }
+            RecordState::Idle => {
+                if self.pipeline.is_recording() {
+                    self.session_log.log(EventKind::Stop, "Stopped streaming");
+                }
+                self.pipeline.stop_recording();
+                self.stats_log.stop();
+                self.refresh_destination();
+
+                if let Some(source_id) = self.delay_timeout.borrow_mut().take() {
+                    glib::source_remove(source_id);
+                }
+                if let Some(source_id) = self.on_air_timeout.borrow_mut().take() {
+                    glib::source_remove(source_id);
                 }
+                self.header_bar.set_on_air_time(None, false);
+                if let Some(source_id) = self.disk_space_timeout.borrow_mut().take() {
+                    glib::source_remove(source_id);
+                }
+                self.header_bar.set_delay_fill(None);
             }
-            RecordState::Idle => self.pipeline.stop_recording(),
         }
     }
 
+    fn start_recording(&self) {
+        if let Err(err) = self.pipeline.start_recording() {
+            utils::show_error_dialog(
+                false,
+                format!("Failed to start recording: {}", err).as_str(),
+            );
+            self.header_bar.set_record_active(false);
+            return;
+        }
+
+        self.refresh_destination();
+
+        let settings = utils::load_settings();
+        self.session_log.log(
+            EventKind::Start,
+            &format!("Started streaming to {}", destination(&settings)),
+        );
+        if let Err(err) = self.stats_log.start(&settings) {
+            self.session_log.log(EventKind::Warning, &err);
+        }
+
+        let weak_app = self.downgrade();
+        let source_id = glib::timeout_add_local(500, move || {
+            let app = upgrade_weak!(weak_app, glib::Continue(false));
+            app.header_bar.set_delay_fill(app.pipeline.get_delay_fill());
+            glib::Continue(true)
+        });
+        if let Some(old_source_id) = self.delay_timeout.replace(Some(source_id)) {
+            glib::source_remove(old_source_id);
+        }
+
+        self.update_on_air_time();
+        let weak_app = self.downgrade();
+        let source_id = glib::timeout_add_local(500, move || {
+            let app = upgrade_weak!(weak_app, glib::Continue(false));
+            app.update_on_air_time();
+            glib::Continue(true)
+        });
+        if let Some(old_source_id) = self.on_air_timeout.replace(Some(source_id)) {
+            glib::source_remove(old_source_id);
+        }
+
+        self.disk_space_warned.set(false);
+        let weak_app = self.downgrade();
+        let source_id = glib::timeout_add_seconds_local(DISK_SPACE_CHECK_INTERVAL, move || {
+            let app = upgrade_weak!(weak_app, glib::Continue(false));
+            app.check_disk_space()
+        });
+        if let Some(old_source_id) = self.disk_space_timeout.replace(Some(source_id)) {
+            glib::source_remove(old_source_id);
+        }
+    }
+
+    fn update_on_air_time(&self) {
+        self.header_bar.set_on_air_time(
+            self.pipeline.get_recording_duration(),
+            self.pipeline.is_output_failing(),
+        );
+    }
+
+    // Warn when the recordings filesystem runs low and stop recording cleanly, so the muxer
+    // can still finalize the file, before it is completely full
+    fn check_disk_space(&self) -> glib::Continue {
+        let settings = utils::load_settings();
+        let free = match utils::get_free_disk_space(&settings) {
+            Some(free) => free / (1024 * 1024),
+            None => return glib::Continue(true),
+        };
+
+        if free < u64::from(settings.disk_stop_threshold) {
+            // Stopping removes this timeout, so don't let it be removed a second time
+            self.disk_space_timeout.borrow_mut().take();
+            self.session_log.log(
+                EventKind::Error,
+                &format!("Out of disk space, {} MiB left", free),
+            );
+            self.header_bar.set_record_active(false);
+            utils::show_error_dialog(
+                false,
+                format!("Recording stopped, only {} MiB of disk space left", free).as_str(),
+            );
+            return glib::Continue(false);
+        }
+
+        if free < u64::from(settings.disk_warning_threshold) && !self.disk_space_warned.get() {
+            self.disk_space_warned.set(true);
+            self.session_log.log(
+                EventKind::Warning,
+                &format!("Low disk space, {} MiB left", free),
+            );
+            utils::show_warning_dialog(
+                format!(
+                    "Running out of disk space, {} MiB left. Recording will be stopped below {} MiB",
+                    free, settings.disk_stop_threshold
+                )
+                .as_str(),
+            );
+        }
+
+        glib::Continue(true)
+    }
+
+    // Switch the microphone without touching the rest of the pipeline
+    fn on_audio_input_changed(&self, device: &str) {
+        let device = if device.is_empty() {
+            None
+        } else {
+            Some(device.to_string())
+        };
+
+        if let Err(err) = self.pipeline.set_audio_device(device.as_deref()) {
+            utils::show_error_dialog(
+                false,
+                format!("Failed to switch audio input: {}", err).as_str(),
+            );
+            return;
+        }
+
+        let mut settings = utils::load_settings();
+        settings.audio_device = device;
+        utils::save_settings(&settings);
+    }
+
     fn update_overlay(&mut self) {
-        if let Some(buffer) = self.text_view.get_buffer() {
-            if let Some(data) =
-                buffer.get_text(&buffer.get_start_iter(), &buffer.get_end_iter(), false)
-            {
-                if let Some(editing_markup) = &*self.editing_markup.borrow() {
-                    if editing_markup == "CSS" {
-                        self.css_buffer.replace(data.to_string());
-                    } else {
-                        self.html_buffer.replace(data.to_string());
-                    }
-                }
-            }
+        let (html, css) = self.markup_editor.get_markup();
+        self.html_buffer.replace(html);
+        self.css_buffer.replace(css);
+        self.markup_editor.mark_applied();
+        self.pipeline
+            .update_overlay(&self.html_buffer.borrow(), &self.css_buffer.borrow());
+        self.template_library
+            .save_selected(&self.html_buffer.borrow(), &self.css_buffer.borrow());
+        self.save_overlay();
+    }
+
+    // (Re)start the delay after which the edited markup is shown, if updating live
+    fn schedule_live_update(&self) {
+        if let Some(source_id) = self.live_update_timeout.borrow_mut().take() {
+            glib::source_remove(source_id);
         }
+        if !self.live_update.get_active() {
+            return;
+        }
+
+        let weak_app = self.downgrade();
+        let source_id = glib::timeout_add_local(LIVE_UPDATE_DELAY, move || {
+            let mut app = upgrade_weak!(weak_app, glib::Continue(false));
+            app.live_update_timeout.replace(None);
+            app.update_overlay();
+            glib::Continue(false)
+        });
+        self.live_update_timeout.replace(Some(source_id));
+    }
+
+    // Replace the overlay markup, e.g. by a template from the library
+    fn load_overlay(&self, html: &str, css: &str) {
+        self.html_buffer.replace(html.to_string());
+        self.css_buffer.replace(css.to_string());
+
+        self.markup_editor.set_markup(html, css);
+
+        self.pipeline.update_overlay(html, css);
+        self.save_overlay();
+    }
+
+    // Load the web page or the markup as overlay, depending on the selected source
+    fn apply_overlay_source(&self) {
+        let from_url = self.overlay_from_url.get_active();
+        let url = self
+            .overlay_url
+            .get_text()
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty());
+
+        let mut settings = utils::load_settings();
+        settings.overlay_from_url = from_url;
+        settings.overlay_url = url;
+        utils::save_settings(&settings);
+
+        self.markup_box.set_sensitive(!from_url);
+        self.pipeline.set_overlay_url(settings.active_overlay_url());
         self.pipeline
             .update_overlay(&self.html_buffer.borrow(), &self.css_buffer.borrow());
     }
 
+    // Keep the markup on air for the next session
+    fn save_overlay(&self) {
+        if let Err(err) =
+            templates::save_current(&self.html_buffer.borrow(), &self.css_buffer.borrow())
+        {
+            utils::show_error_dialog(
+                false,
+                format!("Failed to save the overlay: {}", err).as_str(),
+            );
+        }
+    }
+
+    fn execute_cue(&self, cue: &Cue) {
+        let application = match self.main_window.get_application() {
+            Some(application) => application,
+            None => return,
+        };
+
+        match cue {
+            Cue::LoadOverlay { template } => match templates::load(template) {
+                Ok((html, css)) => self.load_overlay(&html, &css),
+                Err(err) => self.session_log.log(
+                    EventKind::Error,
+                    &format!("Failed to load overlay template {}: {}", template, err),
+                ),
+            },
+            Cue::SwitchScene { scene } => Action::Scene(scene.clone()).trigger(&application),
+            Cue::SetVariable { name, value } => {
+                self.pipeline.set_overlay_variable(name, value);
+                self.pipeline
+                    .update_overlay(&self.html_buffer.borrow(), &self.css_buffer.borrow());
+            }
+            Cue::StartStream => Action::Record(RecordState::Recording).trigger(&application),
+            Cue::StopStream => Action::Record(RecordState::Idle).trigger(&application),
+            // Handled by the rundown itself
+            Cue::Wait { .. } => (),
+        }
+    }
+
+    // (Re)start or stop the automatic rotation through overlay templates
+    pub fn refresh_rotation(&self) {
+        if let Some(source_id) = self.rotation_timeout.borrow_mut().take() {
+            glib::source_remove(source_id);
+        }
+
+        let rotation = utils::load_settings().rotation;
+        if !rotation.enabled || (rotation.templates.is_empty() && rotation.scenes.is_empty()) {
+            return;
+        }
+
+        self.rotation_position.set(0);
+        self.rotate();
+
+        let weak_app = self.downgrade();
+        let source_id = glib::timeout_add_seconds_local(rotation.interval.max(1), move || {
+            let app = upgrade_weak!(weak_app, glib::Continue(false));
+            app.rotate();
+            glib::Continue(true)
+        });
+        self.rotation_timeout.replace(Some(source_id));
+    }
+
+    // Follow the changed schedule for going live
+    pub fn refresh_schedule(&self) {
+        self.scheduler.refresh();
+    }
+
+    // Load the next template and switch to the next scene of the rotation
+    fn rotate(&self) {
+        let rotation = utils::load_settings().rotation;
+        let position = self.rotation_position.get();
+        self.rotation_position.set(position + 1);
+
+        if !rotation.templates.is_empty() {
+            let template = &rotation.templates[position % rotation.templates.len()];
+            match templates::load(template) {
+                Ok((html, css)) => self.load_overlay(&html, &css),
+                Err(err) => self.session_log.log(
+                    EventKind::Error,
+                    &format!("Failed to load overlay template {}: {}", template, err),
+                ),
+            }
+        }
+
+        if !rotation.scenes.is_empty() {
+            if let Some(application) = self.main_window.get_application() {
+                let scene = &rotation.scenes[position % rotation.scenes.len()];
+                Action::Scene(scene.clone()).trigger(&application);
+            }
+        }
+    }
+
+    // Connect to the configured live chat again
+    pub fn refresh_chat(&self) {
+        self.chat.refresh();
+    }
+
+    // Apply the interface scale and contrast settings
+    pub fn refresh_appearance(&self) {
+        let settings = utils::load_settings();
+        self.appearance.apply(&settings);
+        self.place_vumeter(&settings);
+    }
+
+    // Move the level meter next to or below the preview as configured
+    fn place_vumeter(&self, settings: &Settings) {
+        let vumeter = self.audio_vumeter.get_widget();
+        let orientation = settings.vu_meter.orientation;
+
+        if orientation != self.audio_vumeter.get_orientation() {
+            if let Some(parent) = vumeter.get_parent() {
+                parent
+                    .downcast::<gtk::Container>()
+                    .expect("Level meter not in a container")
+                    .remove(vumeter);
+            }
+            match orientation {
+                MeterOrientation::Vertical => {
+                    self.preview_box.pack_start(vumeter, false, false, 0);
+                }
+                MeterOrientation::Horizontal => {
+                    self.program_box.pack_start(vumeter, false, false, 0);
+                    // Right below the preview
+                    self.program_box.reorder_child(vumeter, 1);
+                }
+            }
+            vumeter.show();
+        }
+
+        self.audio_vumeter.set_layout(&settings.vu_meter);
+        self.audio_vumeter.set_ui_scale(settings.ui_scale);
+    }
+
     pub fn refresh_pipeline(&self) {
         self.pipeline.refresh();
+        self.refresh_destination();
+    }
+
+    // Show in the header bar whether the program is live, and where it is or would be sent to
+    fn refresh_destination(&self) {
+        let settings = utils::load_settings();
+        self.header_bar
+            .set_on_air(self.pipeline.is_recording(), &destination(&settings));
+    }
+
+    // Ask before going live, showing where the program will be sent. The checklist already does
+    // that if enabled
+    fn confirm_go_live(&self, application: &gtk::Application) {
+        let settings = utils::load_settings();
+        if settings.checklist.enabled || cli::is_headless() {
+            Action::Record(RecordState::Recording).trigger(application);
+            return;
+        }
+
+        let dialog = gtk::MessageDialog::new(
+            Some(&self.main_window),
+            gtk::DialogFlags::MODAL,
+            gtk::MessageType::Question,
+            gtk::ButtonsType::None,
+            "Go live?",
+        );
+        dialog.set_property_secondary_text(Some(&format!(
+            "The program will be sent to {}",
+            destination(&settings)
+        )));
+        dialog.add_button("Cancel", gtk::ResponseType::Cancel);
+        if let Some(button) = dialog
+            .add_button("Go live", gtk::ResponseType::Accept)
+            .downcast_ref::<gtk::Button>()
+        {
+            button.get_style_context().add_class("suggested-action");
+        }
+        dialog.set_default_response(gtk::ResponseType::Cancel);
+
+        let weak_app = self.downgrade();
+        let weak_application = application.downgrade();
+        dialog.connect_response(move |dialog, response| {
+            dialog.destroy();
+
+            let app = upgrade_weak!(weak_app);
+            let application = upgrade_weak!(weak_application);
+            if response == gtk::ResponseType::Accept {
+                Action::Record(RecordState::Recording).trigger(&application);
+            } else {
+                app.header_bar.set_record_active(false);
+            }
+        });
+
+        dialog.show_all();
+    }
+
+    // Apply a changed second audio input to the running pipeline
+    pub fn refresh_secondary_audio(&self) {
+        let settings = utils::load_settings();
+        if let Err(err) = self
+            .pipeline
+            .set_secondary_audio_device(settings.secondary_audio_device.as_deref())
+        {
+            utils::show_error_dialog(
+                false,
+                format!("Failed to change the second audio input: {}", err).as_str(),
+            );
+        }
+    }
+
+    // Apply a changed audio/video offset to the running pipeline
+    pub fn refresh_av_offset(&self) {
+        self.pipeline
+            .set_av_offset(utils::load_settings().av_offset);
+    }
+
+    // Apply a changed output device, volume or delay while monitoring
+    pub fn refresh_monitor(&self) {
+        if !self.pipeline.is_monitoring() {
+            return;
+        }
+        let monitor = utils::load_settings().monitor;
+        if let Err(err) = self.pipeline.set_monitoring(true, &monitor) {
+            utils::show_error_dialog(
+                false,
+                format!("Failed to monitor the audio: {}", err).as_str(),
+            );
+        }
+    }
+
+    // Apply changed audio filters to the running pipeline
+    pub fn refresh_audio_filters(&self) {
+        self.pipeline
+            .update_audio_filters(&utils::load_settings().audio_filters);
+    }
+
+    // Only turns the camera, the rest of the pipeline is left as it is
+    pub fn refresh_camera_orientation(&self) {
+        self.pipeline.refresh_camera_orientation();
+    }
+
+    pub fn refresh_chroma_key(&self) {
+        self.pipeline
+            .update_chroma_key(&utils::load_settings().chroma_key);
+    }
+
+    // Apply the settings of a newly selected profile to everything running
+    pub fn apply_profile(&self) {
+        let settings = utils::load_settings();
+
+        self.refresh_pipeline();
+        self.refresh_appearance();
+        self.refresh_rotation();
+        self.refresh_schedule();
+        self.refresh_chat();
+        self.refresh_secondary_audio();
+        self.midi.refresh();
+
+        // Toggling the source already loads the overlay, but the URL could have changed alone
+        self.overlay_url
+            .set_text(settings.overlay_url.as_deref().unwrap_or(""));
+        self.overlay_from_url.set_active(settings.overlay_from_url);
+        self.apply_overlay_source();
+
+        if let Some(template) = settings
+            .profile_template
+            .filter(|template| templates::list_names().contains(template))
+        {
+            match templates::load(&template) {
+                Ok((html, css)) => self.load_overlay(&html, &css),
+                Err(err) => utils::show_error_dialog(
+                    false,
+                    format!("Failed to load overlay template {}: {}", template, err).as_str(),
+                ),
+            }
+        }
+    }
+
+    // Move the editors between the main window and a window of their own
+    fn on_detach_editor_changed(&self, detached: bool) {
+        if !detached {
+            if let Some(window) = self.editor_window.borrow_mut().take() {
+                window.remove(&self.editor_box);
+                self.paned.pack2(&self.editor_box, false, false);
+                window.destroy();
+            }
+            return;
+        }
+        if self.editor_window.borrow().is_some() {
+            return;
+        }
+
+        // Not part of the application, so closing the main window still quits
+        let window = gtk::Window::new(gtk::WindowType::Toplevel);
+        window.set_title("Overlay editor");
+        window.set_default_size(800, 600);
+        window.set_border_width(5);
+
+        self.paned.remove(&self.editor_box);
+        window.add(&self.editor_box);
+
+        // Closing the window puts the editors back
+        let weak_application = self
+            .main_window
+            .get_application()
+            .map(|application| application.downgrade());
+        window.connect_delete_event(move |_, _| {
+            if let Some(application) = weak_application.as_ref().and_then(|a| a.upgrade()) {
+                Action::DetachEditor(false).trigger(&application);
+            }
+            Inhibit(true)
+        });
+
+        window.show_all();
+        self.editor_window.replace(Some(window));
+    }
+
+    // Open or close the projector, returns false if it couldn't be opened
+    fn on_projector_changed(&self, enabled: bool) -> bool {
+        if !enabled {
+            if let Some(window) = self.projector_window.borrow_mut().take() {
+                self.pipeline.remove_projector();
+                window.destroy();
+            }
+            return true;
+        }
+        // There is no monitor to show it on
+        if self.projector_window.borrow().is_some() || cli::is_headless() {
+            return !cli::is_headless();
+        }
+
+        let widget = match self.pipeline.add_projector() {
+            Ok(widget) => widget,
+            Err(err) => {
+                utils::show_error_dialog(
+                    false,
+                    format!("Failed to open the projector: {}", err).as_str(),
+                );
+                return false;
+            }
+        };
+
+        let window = gtk::Window::new(gtk::WindowType::Toplevel);
+        window.set_title("Projector");
+        window.set_decorated(false);
+        window.add(&widget);
+
+        // Closing the window or pressing Escape on it closes the projector
+        let weak_application = self
+            .main_window
+            .get_application()
+            .map(|application| application.downgrade());
+        let close = move || {
+            if let Some(application) = weak_application.as_ref().and_then(|a| a.upgrade()) {
+                Action::Projector(false).trigger(&application);
+            }
+        };
+        let close_clone = close.clone();
+        window.connect_delete_event(move |_, _| {
+            close_clone();
+            Inhibit(true)
+        });
+        window.connect_key_press_event(move |_, event| {
+            if event.get_keyval() == gdk::enums::key::Escape {
+                close();
+                return Inhibit(true);
+            }
+            Inhibit(false)
+        });
+
+        self.projector_window.replace(Some(window));
+        self.refresh_projector();
+        true
+    }
+
+    // Move the projector to the configured monitor, or the last one if that's not connected
+    pub fn refresh_projector(&self) {
+        let window = match *self.projector_window.borrow() {
+            Some(ref window) => window.clone(),
+            None => return,
+        };
+        let screen = match gdk::Screen::get_default() {
+            Some(screen) => screen,
+            None => return,
+        };
+        let monitor = utils::load_settings()
+            .projector_monitor
+            .min(screen.get_n_monitors() - 1)
+            .max(0);
+        let geometry = screen.get_monitor_geometry(monitor);
+
+        window.unfullscreen();
+        window.move_(geometry.x, geometry.y);
+        window.set_default_size(geometry.width, geometry.height);
+        window.show_all();
+        window.fullscreen();
+    }
+
+    fn on_debug_hud_changed(&self, enabled: bool) {
+        if let Some(source_id) = self.debug_hud_timeout.borrow_mut().take() {
+            glib::source_remove(source_id);
+        }
+        self.debug_hud_sample.replace(None);
+
+        if !enabled {
+            self.pipeline.run_javascript(&debug_hud::script(None));
+            return;
+        }
+
+        self.update_debug_hud();
+        let weak_app = self.downgrade();
+        let source_id = glib::timeout_add_seconds_local(1, move || {
+            let app = upgrade_weak!(weak_app, glib::Continue(false));
+            app.update_debug_hud();
+            glib::Continue(true)
+        });
+        self.debug_hud_timeout.replace(Some(source_id));
+    }
+
+    fn update_debug_hud(&self) {
+        let now = Instant::now();
+        let stats = self.pipeline.get_debug_stats();
+        let previous = self.debug_hud_sample.replace(Some((now, stats)));
+
+        let hidden = self.pipeline.is_recording() && utils::load_settings().debug_hud_preview_only;
+        let text = match previous {
+            Some((time, ref previous)) if !hidden => Some(debug_hud::format_stats(
+                previous,
+                &stats,
+                now.duration_since(time),
+            )),
+            _ => None,
+        };
+
+        self.pipeline
+            .run_javascript(&debug_hud::script(text.as_deref()));
+    }
+
+    pub fn camera_id(&self) -> String {
+        self.pipeline.camera_id()
+    }
+
+    pub fn get_midi(&self) -> &Midi {
+        &self.midi
     }
 }
 
@@ -309,6 +1511,26 @@ impl Action {
             Action::About => "app.about",
             Action::Record(_) => "app.record",
             Action::UpdateOverlay => "app.update_overlay",
+            Action::RepairRecordings => "app.repair_recordings",
+            Action::AudioInput(_) => "app.audio_input",
+            Action::Scene(_) => "app.scene",
+            Action::SwitchScene(_) => "app.switch_scene",
+            Action::Panic(_) => "app.panic",
+            Action::PauseStream(_) => "app.pause_stream",
+            Action::MuteAudio(_) => "app.mute_audio",
+            Action::MonitorAudio(_) => "app.monitor_audio",
+            Action::DebugHud(_) => "app.debug_hud",
+            Action::DetachEditor(_) => "app.detach_editor",
+            Action::Projector(_) => "app.projector",
+            Action::Marker => "app.marker",
+            Action::Snapshot => "app.snapshot",
+            Action::TemplateGallery => "app.template_gallery",
+            Action::Rundown => "app.rundown",
+            Action::RundownNext => "app.rundown_next",
+            Action::RundownRun(_) => "app.rundown_run",
+            Action::Shortcuts => "app.shortcuts",
+            Action::LogViewer => "app.log_viewer",
+            Action::GoLive => "app.go_live",
         }
     }
 
@@ -337,12 +1559,32 @@ impl Action {
         });
         application.add_action(&about);
 
-        // When activated, shuts down the application
-        let quit = gio::SimpleAction::new("quit", None);
+        // When activated, show the window listing the keyboard shortcuts
+        let shortcuts = gio::SimpleAction::new("shortcuts", None);
         let weak_application = application.downgrade();
-        quit.connect_activate(move |_action, _parameter| {
+        shortcuts.connect_activate(move |_action, _parameter| {
             let application = upgrade_weak!(weak_application);
-            application.quit();
+            show_shortcuts_window(&application);
+        });
+        application.add_action(&shortcuts);
+        application.set_accels_for_action(Action::Shortcuts.full_name(), &["<Primary>question"]);
+
+        // When activated, show the messages logged by the application
+        let log_viewer = gio::SimpleAction::new("log_viewer", None);
+        let weak_application = application.downgrade();
+        log_viewer.connect_activate(move |_action, _parameter| {
+            let application = upgrade_weak!(weak_application);
+            show_log_viewer(&application);
+        });
+        application.add_action(&log_viewer);
+        application.set_accels_for_action(Action::LogViewer.full_name(), &["<Primary>L"]);
+
+        // When activated, shuts down the application once confirmed
+        let quit = gio::SimpleAction::new("quit", None);
+        let weak_app = app.downgrade();
+        quit.connect_activate(move |_action, _parameter| {
+            let app = upgrade_weak!(weak_app);
+            app.request_quit();
         });
         application.add_action(&quit);
 
@@ -360,8 +1602,37 @@ impl Action {
             // Let the action store the new state
             action.set_state(state);
         });
+        // Activating it toggles the record button, which changes the state as if it was clicked
+        let weak_app = app.downgrade();
+        record.connect_activate(move |action, _parameter| {
+            let app = upgrade_weak!(weak_app);
+            let recording = action
+                .get_state()
+                .and_then(|state| state.get::<bool>())
+                .unwrap_or(false);
+            if recording {
+                app.header_bar.set_record_active(false);
+            } else {
+                app.header_bar.click_record();
+            }
+        });
         application.add_action(&record);
 
+        // When activated, ask for confirmation and go live
+        let go_live = gio::SimpleAction::new("go_live", None);
+        let weak_application = application.downgrade();
+        let weak_app = app.downgrade();
+        go_live.connect_activate(move |_action, _parameter| {
+            let application = upgrade_weak!(weak_application);
+            let app = upgrade_weak!(weak_app);
+            app.confirm_go_live(&application);
+        });
+        application.add_action(&go_live);
+        application.set_accels_for_action(
+            Action::Record(RecordState::Recording).full_name(),
+            &["<Primary><Shift>R"],
+        );
+
         // When activated, reload the HTML/CSS data of the overlay
         let update_overlay = gio::SimpleAction::new("update_overlay", None);
         let weak_app = app.downgrade();
@@ -370,6 +1641,301 @@ impl Action {
             app.update_overlay();
         });
         application.add_action(&update_overlay);
+
+        // When activated, scan the recordings folder for interrupted recordings and repair them
+        let repair_recordings = gio::SimpleAction::new("repair_recordings", None);
+        let weak_app = app.downgrade();
+        repair_recordings.connect_activate(move |_action, _parameter| {
+            let app = upgrade_weak!(weak_app);
+            recovery::repair_recordings(app.pipeline.get_recording_paths());
+        });
+        application.add_action(&repair_recordings);
+
+        // audio_input action: its state is the display name of the selected microphone
+        let audio_device = utils::load_settings().audio_device.unwrap_or_default();
+        let audio_input =
+            gio::SimpleAction::new_stateful("audio_input", None, &audio_device.to_variant());
+        let weak_app = app.downgrade();
+        audio_input.connect_change_state(move |action, state| {
+            let app = upgrade_weak!(weak_app);
+            let state = state.expect("No state provided");
+            let device = state.get::<String>().expect("Invalid audio input type");
+            app.on_audio_input_changed(&device);
+
+            action.set_state(state);
+        });
+        application.add_action(&audio_input);
+
+        // scene action: its state is the name of the scene on air
+        let scene =
+            gio::SimpleAction::new_stateful("scene", None, &app.pipeline.get_scene().to_variant());
+        let weak_app = app.downgrade();
+        scene.connect_change_state(move |action, state| {
+            let app = upgrade_weak!(weak_app);
+            let state = state.expect("No state provided");
+            let scene = state.get::<String>().expect("Invalid scene type");
+            if action.get_state().and_then(|s| s.get::<String>()).as_ref() == Some(&scene) {
+                return;
+            }
+
+            match app.pipeline.switch_scene(&scene) {
+                Ok(()) => {
+                    app.session_log.log(
+                        EventKind::SceneSwitch,
+                        &format!("Switched to scene {}", scene),
+                    );
+                    app.header_bar.set_scene(&scene);
+                    action.set_state(state);
+                }
+                Err(err) => app.session_log.log(EventKind::Error, &err),
+            }
+        });
+        application.add_action(&scene);
+
+        // switch_scene action: switches to the scene with the number given as parameter, for the
+        // Ctrl+1 to Ctrl+9 shortcuts
+        let switch_scene =
+            gio::SimpleAction::new("switch_scene", Some(glib::VariantTy::new("i").unwrap()));
+        let weak_application = application.downgrade();
+        switch_scene.connect_activate(move |_action, parameter| {
+            let application = upgrade_weak!(weak_application);
+            let number = parameter
+                .and_then(|p| p.get::<i32>())
+                .expect("Invalid scene number type");
+            if number < 1 {
+                return;
+            }
+            if let Some(scene) = scenes::SCENES.get(number as usize - 1) {
+                Action::Scene(scene.name.to_string()).trigger(&application);
+            }
+        });
+        application.add_action(&switch_scene);
+        for number in 1..=scenes::SCENES.len().min(9) as i32 {
+            application.set_accels_for_action(
+                &shortcuts_window::scene_action_name(number),
+                &[&format!("<Primary>{}", number)],
+            );
+        }
+
+        // panic action: cuts to standby and mutes while its state is true. Activating it without
+        // a parameter toggles the state
+        let panic = gio::SimpleAction::new_stateful("panic", None, &false.to_variant());
+        let weak_app = app.downgrade();
+        panic.connect_change_state(move |action, state| {
+            let app = upgrade_weak!(weak_app);
+            let state = state.expect("No state provided");
+            let standby = state.get::<bool>().expect("Invalid panic state type");
+            app.pipeline.set_standby(standby);
+            app.session_log.log(
+                EventKind::Warning,
+                if standby {
+                    "Cut to standby"
+                } else {
+                    "Back from standby"
+                },
+            );
+
+            action.set_state(state);
+        });
+        application.add_action(&panic);
+        application.set_accels_for_action(Action::Panic(true).full_name(), &["F12"]);
+
+        // pause_stream action: shows the slate and mutes while its state is true, the outputs
+        // stay connected
+        let pause_stream =
+            gio::SimpleAction::new_stateful("pause_stream", None, &false.to_variant());
+        let weak_app = app.downgrade();
+        pause_stream.connect_change_state(move |action, state| {
+            let app = upgrade_weak!(weak_app);
+            let state = state.expect("No state provided");
+            let paused = state.get::<bool>().expect("Invalid pause state type");
+            if paused {
+                if let Err(err) = app.pipeline.pause_streaming() {
+                    utils::show_error_dialog(
+                        false,
+                        format!("Failed to pause the stream: {}", err).as_str(),
+                    );
+                    return;
+                }
+            } else {
+                app.pipeline.resume_streaming();
+            }
+            app.session_log.log(
+                EventKind::Marker,
+                if paused {
+                    "Stream paused"
+                } else {
+                    "Stream resumed"
+                },
+            );
+
+            action.set_state(state);
+        });
+        application.add_action(&pause_stream);
+
+        // mute_audio action: mutes the program audio while its state is true
+        let mute_audio = gio::SimpleAction::new_stateful("mute_audio", None, &false.to_variant());
+        let weak_app = app.downgrade();
+        mute_audio.connect_change_state(move |action, state| {
+            let app = upgrade_weak!(weak_app);
+            let state = state.expect("No state provided");
+            let muted = state.get::<bool>().expect("Invalid mute state type");
+            app.pipeline.set_muted(muted);
+            app.session_log.log(
+                EventKind::Marker,
+                if muted {
+                    "Audio muted"
+                } else {
+                    "Audio unmuted"
+                },
+            );
+
+            action.set_state(state);
+        });
+        application.add_action(&mute_audio);
+        application
+            .set_accels_for_action(Action::MuteAudio(true).full_name(), &["<Primary><Shift>M"]);
+
+        // monitor_audio action: plays the program audio locally while its state is true
+        let monitor_audio =
+            gio::SimpleAction::new_stateful("monitor_audio", None, &false.to_variant());
+        let weak_app = app.downgrade();
+        monitor_audio.connect_change_state(move |action, state| {
+            let app = upgrade_weak!(weak_app);
+            let state = state.expect("No state provided");
+            let enabled = state.get::<bool>().expect("Invalid monitor state type");
+            let monitor = utils::load_settings().monitor;
+            if let Err(err) = app.pipeline.set_monitoring(enabled, &monitor) {
+                utils::show_error_dialog(
+                    false,
+                    format!("Failed to monitor the audio: {}", err).as_str(),
+                );
+                return;
+            }
+
+            action.set_state(state);
+        });
+        application.add_action(&monitor_audio);
+        application.set_accels_for_action(
+            Action::MonitorAudio(true).full_name(),
+            &["<Primary><Shift>H"],
+        );
+
+        // debug_hud action: shows live pipeline statistics in the overlay while its state is true
+        let debug_hud = gio::SimpleAction::new_stateful("debug_hud", None, &false.to_variant());
+        let weak_app = app.downgrade();
+        debug_hud.connect_change_state(move |action, state| {
+            let app = upgrade_weak!(weak_app);
+            let state = state.expect("No state provided");
+            let enabled = state.get::<bool>().expect("Invalid debug HUD state type");
+            app.on_debug_hud_changed(enabled);
+
+            action.set_state(state);
+        });
+        application.add_action(&debug_hud);
+        application.set_accels_for_action(Action::DebugHud(true).full_name(), &["F9"]);
+
+        // detach_editor action: shows the editors in their own window while its state is true
+        let detach_editor =
+            gio::SimpleAction::new_stateful("detach_editor", None, &false.to_variant());
+        let weak_app = app.downgrade();
+        detach_editor.connect_change_state(move |action, state| {
+            let app = upgrade_weak!(weak_app);
+            let state = state.expect("No state provided");
+            let detached = state
+                .get::<bool>()
+                .expect("Invalid detach editor state type");
+            app.on_detach_editor_changed(detached);
+
+            action.set_state(state);
+        });
+        application.add_action(&detach_editor);
+        application.set_accels_for_action(
+            Action::DetachEditor(true).full_name(),
+            &["<Primary><Shift>E"],
+        );
+
+        // projector action: shows the program fullscreen on another monitor while its state is true
+        let projector = gio::SimpleAction::new_stateful("projector", None, &false.to_variant());
+        let weak_app = app.downgrade();
+        projector.connect_change_state(move |action, state| {
+            let app = upgrade_weak!(weak_app);
+            let state = state.expect("No state provided");
+            let enabled = state.get::<bool>().expect("Invalid projector state type");
+            if app.on_projector_changed(enabled) {
+                action.set_state(state);
+            }
+        });
+        application.add_action(&projector);
+        application.set_accels_for_action(Action::Projector(true).full_name(), &["<Primary>P"]);
+
+        // When activated, show the gallery of overlay templates to install
+        let template_gallery = gio::SimpleAction::new("template_gallery", None);
+        let weak_application = application.downgrade();
+        template_gallery.connect_activate(move |_action, _parameter| {
+            let application = upgrade_weak!(weak_application);
+            gallery::show_gallery_dialog(&application);
+        });
+        application.add_action(&template_gallery);
+
+        // When activated, show the rundown editor
+        let rundown = gio::SimpleAction::new("rundown", None);
+        let weak_application = application.downgrade();
+        let weak_app = app.downgrade();
+        rundown.connect_activate(move |_action, _parameter| {
+            let application = upgrade_weak!(weak_application);
+            let app = upgrade_weak!(weak_app);
+            app.rundown.show_editor(&application);
+        });
+        application.add_action(&rundown);
+
+        // When activated, execute the next cue of the rundown
+        let rundown_next = gio::SimpleAction::new("rundown_next", None);
+        let weak_app = app.downgrade();
+        rundown_next.connect_activate(move |_action, _parameter| {
+            let app = upgrade_weak!(weak_app);
+            app.rundown.next();
+        });
+        application.add_action(&rundown_next);
+        application.set_accels_for_action(Action::RundownNext.full_name(), &["F8"]);
+
+        // rundown_run action: runs through the rundown automatically while its state is true
+        let rundown_run = gio::SimpleAction::new_stateful("rundown_run", None, &false.to_variant());
+        let weak_app = app.downgrade();
+        rundown_run.connect_change_state(move |action, state| {
+            let app = upgrade_weak!(weak_app);
+            let state = state.expect("No state provided");
+            let automatic = state.get::<bool>().expect("Invalid rundown run state type");
+            action.set_state(state);
+
+            app.rundown.set_automatic(automatic);
+        });
+        application.add_action(&rundown_run);
+
+        // When activated, put a marker into the session log, e.g. to find problems later on
+        let marker = gio::SimpleAction::new("marker", None);
+        let weak_app = app.downgrade();
+        marker.connect_activate(move |_action, _parameter| {
+            let app = upgrade_weak!(weak_app);
+            app.session_log.log(EventKind::Marker, "Marker");
+        });
+        application.add_action(&marker);
+        application.set_accels_for_action(Action::Marker.full_name(), &["<Primary>M"]);
+
+        // When activated, save the current program frame as a PNG file
+        let snapshot = gio::SimpleAction::new("snapshot", None);
+        let weak_app = app.downgrade();
+        snapshot.connect_activate(move |_action, _parameter| {
+            let app = upgrade_weak!(weak_app);
+            if let Err(err) = app.pipeline.take_snapshot() {
+                utils::show_error_dialog(
+                    false,
+                    format!("Failed to take snapshot: {}", err).as_str(),
+                );
+            }
+        });
+        application.add_action(&snapshot);
+        application.set_accels_for_action(Action::Snapshot.full_name(), &["<Primary><Shift>S"]);
     }
 
     // Triggers the provided action on the application
@@ -380,6 +1946,42 @@ impl Action {
             Action::About => app.activate_action("about", None),
             Action::Record(new_state) => app.change_action_state("record", &new_state.into()),
             Action::UpdateOverlay => app.activate_action("update_overlay", None),
+            Action::RepairRecordings => app.activate_action("repair_recordings", None),
+            Action::AudioInput(device) => {
+                app.change_action_state("audio_input", &device.to_variant())
+            }
+            Action::Scene(scene) => app.change_action_state("scene", &scene.to_variant()),
+            Action::SwitchScene(number) => {
+                app.activate_action("switch_scene", Some(&number.to_variant()))
+            }
+            Action::Panic(standby) => app.change_action_state("panic", &standby.to_variant()),
+            Action::PauseStream(paused) => {
+                app.change_action_state("pause_stream", &paused.to_variant())
+            }
+            Action::MuteAudio(muted) => app.change_action_state("mute_audio", &muted.to_variant()),
+            Action::MonitorAudio(enabled) => {
+                app.change_action_state("monitor_audio", &enabled.to_variant())
+            }
+            Action::DebugHud(enabled) => {
+                app.change_action_state("debug_hud", &enabled.to_variant())
+            }
+            Action::DetachEditor(detached) => {
+                app.change_action_state("detach_editor", &detached.to_variant())
+            }
+            Action::Projector(enabled) => {
+                app.change_action_state("projector", &enabled.to_variant())
+            }
+            Action::Marker => app.activate_action("marker", None),
+            Action::Snapshot => app.activate_action("snapshot", None),
+            Action::TemplateGallery => app.activate_action("template_gallery", None),
+            Action::Rundown => app.activate_action("rundown", None),
+            Action::RundownNext => app.activate_action("rundown_next", None),
+            Action::RundownRun(automatic) => {
+                app.change_action_state("rundown_run", &automatic.to_variant())
+            }
+            Action::Shortcuts => app.activate_action("shortcuts", None),
+            Action::LogViewer => app.activate_action("log_viewer", None),
+            Action::GoLive => app.activate_action("go_live", None),
         }
     }
 }
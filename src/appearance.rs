@@ -0,0 +1,84 @@
+use gtk::{self, prelude::*};
+
+use crate::settings::Settings;
+
+// Font resolution used when the desktop doesn't set one, in 1024ths of a dot per inch
+const DEFAULT_XFT_DPI: i32 = 96 * 1024;
+
+// Applies the interface scale and high-contrast mode from the settings to the whole application
+pub struct Appearance {
+    provider: gtk::CssProvider,
+    // Desktop defaults, restored when scaling or high contrast get disabled again
+    default_theme: Option<glib::GString>,
+    default_xft_dpi: i32,
+}
+
+impl Appearance {
+    pub fn new<W: IsA<gtk::Widget>>(widget: &W) -> Self {
+        let provider = gtk::CssProvider::new();
+        if let Some(screen) = widget.get_screen() {
+            gtk::StyleContext::add_provider_for_screen(
+                &screen,
+                &provider,
+                gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+            );
+        }
+
+        let (default_theme, default_xft_dpi) = match gtk::Settings::get_default() {
+            Some(gtk_settings) => (
+                gtk_settings.get_property_gtk_theme_name(),
+                gtk_settings.get_property_gtk_xft_dpi(),
+            ),
+            None => (None, -1),
+        };
+
+        Appearance {
+            provider,
+            default_theme,
+            default_xft_dpi,
+        }
+    }
+
+    pub fn apply(&self, settings: &Settings) {
+        let scale = settings.ui_scale;
+
+        if let Some(gtk_settings) = gtk::Settings::get_default() {
+            if settings.high_contrast {
+                gtk_settings.set_property_gtk_theme_name(Some("HighContrast"));
+            } else {
+                gtk_settings
+                    .set_property_gtk_theme_name(self.default_theme.as_ref().map(|t| t.as_str()));
+            }
+
+            // Scaling the font resolution makes all text, and with it most widgets, bigger
+            let dpi = if self.default_xft_dpi > 0 {
+                self.default_xft_dpi
+            } else {
+                DEFAULT_XFT_DPI
+            };
+            gtk_settings.set_property_gtk_xft_dpi((f64::from(dpi) * scale) as i32);
+        }
+
+        // Bigger hit targets for touchscreens, and a bolder on-air indicator in high contrast
+        let mut css = format!(
+            "button, combobox button {{ min-height: {size}px; min-width: {size}px; }}\n\
+             .on-air {{ background-image: none; background-color: #cc0000; color: #ffffff; }}\n\
+             .panic {{ font-weight: bold; min-width: {panic_width}px; }}\n\
+             .on-air-time {{ font-family: monospace; }}\n\
+             .code-view {{ font-family: monospace; }}\n\
+             .on-air-time.failing {{ color: #cc0000; font-weight: bold; }}\n",
+            size = (24.0 * scale) as i32,
+            panic_width = (96.0 * scale) as i32
+        );
+        if settings.high_contrast {
+            css.push_str(&format!(
+                ".on-air {{ border: {border}px solid #ffff00; font-weight: bold; }}\n",
+                border = (3.0 * scale) as i32
+            ));
+        }
+
+        self.provider
+            .load_from_data(css.as_bytes())
+            .expect("Invalid interface style");
+    }
+}
@@ -0,0 +1,184 @@
+use gtk::{self, prelude::*};
+
+use std::cell::RefCell;
+use std::ops;
+use std::rc::{Rc, Weak};
+
+use crate::assets;
+use crate::pipeline::PipelineWeak;
+use crate::utils;
+
+// Our refcounted asset manager struct, importing images and fonts for use in the overlay
+#[derive(Clone)]
+pub struct AssetManager(Rc<AssetManagerInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for AssetManager {
+    type Target = AssetManagerInner;
+
+    fn deref(&self) -> &AssetManagerInner {
+        &self.0
+    }
+}
+
+pub struct AssetManagerInner {
+    container: gtk::Box,
+    rows_box: gtk::Box,
+    pipeline: PipelineWeak,
+    // Renders the overlay again with the new assets
+    changed: RefCell<Option<Box<dyn Fn()>>>,
+}
+
+pub struct AssetManagerWeak(Weak<AssetManagerInner>);
+impl AssetManagerWeak {
+    pub fn upgrade(&self) -> Option<AssetManager> {
+        self.0.upgrade().map(AssetManager)
+    }
+}
+
+impl AssetManager {
+    pub fn new(pipeline: PipelineWeak) -> Self {
+        let label = gtk::Label::new(Some(
+            "Use the images and fonts as {name} in the HTML of the overlay, e.g. <img src=\"{name}\"/>",
+        ));
+        label.set_halign(gtk::Align::Start);
+        label.set_line_wrap(true);
+
+        let rows_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
+        let scrolled_window = gtk::ScrolledWindow::new(gtk::NONE_ADJUSTMENT, gtk::NONE_ADJUSTMENT);
+        scrolled_window.add(&rows_box);
+
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some("Images and fonts"));
+        for extension in assets::supported_extensions() {
+            filter.add_pattern(&format!("*.{}", extension));
+            filter.add_pattern(&format!("*.{}", extension.to_uppercase()));
+        }
+        let add_button = gtk::FileChooserButton::new("Add asset", gtk::FileChooserAction::Open);
+        add_button.add_filter(&filter);
+        utils::set_accessible(
+            &add_button,
+            "Add asset",
+            "Import an image or font for use in the overlay",
+        );
+
+        let inline = gtk::CheckButton::new_with_mnemonic("_Embed assets in the overlay");
+        inline.set_active(utils::load_settings().inline_assets);
+        inline.set_tooltip_text(Some(
+            "Otherwise the overlay refers to the asset files, which keeps it small",
+        ));
+
+        let add_box = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        add_box.pack_start(&gtk::Label::new(Some("Add")), false, false, 0);
+        add_box.pack_start(&add_button, true, true, 0);
+
+        let container = gtk::Box::new(gtk::Orientation::Vertical, 4);
+        container.set_border_width(6);
+        container.pack_start(&label, false, false, 0);
+        container.pack_start(&scrolled_window, true, true, 0);
+        container.pack_start(&add_box, false, false, 0);
+        container.pack_start(&inline, false, false, 0);
+
+        let asset_manager = AssetManager(Rc::new(AssetManagerInner {
+            container,
+            rows_box,
+            pipeline,
+            changed: RefCell::new(None),
+        }));
+
+        asset_manager.refresh();
+
+        let asset_manager_weak = asset_manager.downgrade();
+        add_button.connect_file_set(move |button| {
+            let asset_manager = upgrade_weak!(asset_manager_weak);
+            if let Some(path) = button.get_filename() {
+                if let Err(err) = assets::import(&path) {
+                    utils::show_error_dialog(
+                        false,
+                        format!("Failed to import {}: {}", path.display(), err).as_str(),
+                    );
+                }
+            }
+            button.unselect_all();
+            asset_manager.apply();
+        });
+
+        let asset_manager_weak = asset_manager.downgrade();
+        inline.connect_toggled(move |button| {
+            let asset_manager = upgrade_weak!(asset_manager_weak);
+            let mut settings = utils::load_settings();
+            settings.inline_assets = button.get_active();
+            utils::save_settings(&settings);
+            asset_manager.apply();
+        });
+
+        asset_manager
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> AssetManagerWeak {
+        AssetManagerWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> &gtk::Box {
+        &self.container
+    }
+
+    pub fn set_changed<F: Fn() + 'static>(&self, changed: F) {
+        self.changed.replace(Some(Box::new(changed)));
+    }
+
+    // List the assets again
+    fn refresh(&self) {
+        for child in self.rows_box.get_children() {
+            self.rows_box.remove(&child);
+        }
+
+        for name in assets::list_names() {
+            let label = gtk::Label::new(Some(&format!("{{{}}}", name)));
+            label.set_halign(gtk::Align::Start);
+            label.set_selectable(true);
+
+            let container = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+            container.pack_start(&label, true, true, 0);
+
+            // The built-in assets are used by the default overlay and can't be removed
+            if !assets::is_builtin(&name) {
+                let remove_button = gtk::Button::new_from_icon_name(
+                    Some("list-remove-symbolic"),
+                    gtk::IconSize::Button,
+                );
+                remove_button.set_tooltip_text(Some("Remove asset"));
+                container.pack_start(&remove_button, false, false, 0);
+
+                let asset_manager_weak = self.downgrade();
+                remove_button.connect_clicked(move |_| {
+                    let asset_manager = upgrade_weak!(asset_manager_weak);
+                    if let Err(err) = assets::remove(&name) {
+                        utils::show_error_dialog(
+                            false,
+                            format!("Failed to remove {}: {}", name, err).as_str(),
+                        );
+                    }
+                    asset_manager.apply();
+                });
+            }
+
+            container.show_all();
+            self.rows_box.pack_start(&container, false, false, 0);
+        }
+    }
+
+    // Hand the assets to the pipeline and render the overlay with them
+    fn apply(&self) {
+        self.refresh();
+
+        let pipeline = &self.pipeline;
+        let pipeline = upgrade_weak!(pipeline);
+        pipeline.refresh_assets();
+
+        if let Some(ref changed) = *self.changed.borrow() {
+            changed();
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::templates;
+
+// Assets that are always available, used by the built-in overlay
+const BUILTIN_ASSETS: &[(&str, &str, &[u8])] = &[
+    (
+        "igalia_logo",
+        "image/png",
+        include_bytes!("../data/igalia-logo.png"),
+    ),
+    (
+        "gst_logo",
+        "image/svg+xml",
+        include_bytes!("../data/gst-logo.svg"),
+    ),
+];
+
+// File extensions that can be imported, with their MIME type
+const MIME_TYPES: &[(&str, &str)] = &[
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("svg", "image/svg+xml"),
+    ("webp", "image/webp"),
+    ("ttf", "font/ttf"),
+    ("otf", "font/otf"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+];
+
+pub fn supported_extensions() -> impl Iterator<Item = &'static str> {
+    MIME_TYPES.iter().map(|(extension, _)| *extension)
+}
+
+pub fn mime_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    MIME_TYPES
+        .iter()
+        .find(|(known, _)| *known == extension)
+        .map(|(_, mime_type)| *mime_type)
+}
+
+// Name of the variable an asset file is available as in the overlay HTML, e.g. {my_logo} for
+// my-logo.png
+pub fn variable_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+pub fn is_builtin(name: &str) -> bool {
+    BUILTIN_ASSETS
+        .iter()
+        .any(|(builtin, _, _)| *builtin == name)
+}
+
+// Names of the built-in assets followed by the imported ones, sorted
+pub fn list_names() -> Vec<String> {
+    let mut names = list()
+        .iter()
+        .map(|path| variable_name(path))
+        .collect::<Vec<_>>();
+    names.sort();
+    BUILTIN_ASSETS
+        .iter()
+        .map(|(name, _, _)| name.to_string())
+        .chain(names)
+        .collect()
+}
+
+// Files of the imported assets
+fn list() -> Vec<PathBuf> {
+    let entries = match fs::read_dir(templates::get_assets_directory()) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && mime_type(path).is_some())
+        .collect()
+}
+
+// Copy the file into the assets, replacing an asset with the same name. Returns the name of its
+// variable
+pub fn import(path: &Path) -> io::Result<String> {
+    if mime_type(path).is_none() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Only images and fonts can be used as assets",
+        ));
+    }
+
+    let name = variable_name(path);
+    if is_builtin(&name) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("There is a built-in asset called {}", name),
+        ));
+    }
+
+    // Files only differing in their extension would end up as the same variable
+    remove(&name)?;
+
+    let directory = templates::get_assets_directory();
+    fs::create_dir_all(&directory)?;
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Not a file"))?;
+    fs::copy(path, directory.join(file_name))?;
+    Ok(name)
+}
+
+pub fn remove(name: &str) -> io::Result<()> {
+    for path in list() {
+        if variable_name(&path) == name {
+            fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn data_url(mime_type: &str, data: &[u8]) -> String {
+    format!("data:{};base64,{}", mime_type, base64::encode(data))
+}
+
+// Values of the asset variables, either the assets themselves as data: URLs or file: URLs
+// pointing to them. Built-in assets are always inlined
+pub fn variables(inline: bool) -> HashMap<String, String> {
+    let mut variables = BUILTIN_ASSETS
+        .iter()
+        .map(|(name, mime_type, data)| (name.to_string(), data_url(mime_type, data)))
+        .collect::<HashMap<_, _>>();
+
+    for path in list() {
+        let value = if inline {
+            match (mime_type(&path), fs::read(&path)) {
+                (Some(mime_type), Ok(data)) => data_url(mime_type, &data),
+                _ => continue,
+            }
+        } else {
+            match glib::filename_to_uri(&path, None) {
+                Ok(uri) => uri.to_string(),
+                Err(_) => continue,
+            }
+        };
+        variables.insert(variable_name(&path), value);
+    }
+
+    variables
+}
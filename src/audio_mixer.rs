@@ -0,0 +1,186 @@
+use gtk::{self, prelude::*};
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops;
+use std::rc::{Rc, Weak};
+
+use crate::pipeline::PipelineWeak;
+use crate::settings::AudioInputSettings;
+use crate::utils;
+
+// Names of the inputs of the program audio mixer, also used as keys in the settings
+pub const MICROPHONE: &str = "microphone";
+pub const DESKTOP: &str = "desktop";
+pub const MEDIA: &str = "media";
+// Audio of the video clip played as a mixer layer
+pub const CLIP: &str = "clip";
+
+// Upper end of the gain sliders, linear
+pub const MAX_GAIN: f64 = 2.0;
+
+// Our refcounted mixer panel struct, with a gain slider and a mute button per audio input
+#[derive(Clone)]
+pub struct AudioMixerPanel(Rc<AudioMixerPanelInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for AudioMixerPanel {
+    type Target = AudioMixerPanelInner;
+
+    fn deref(&self) -> &AudioMixerPanelInner {
+        &self.0
+    }
+}
+
+pub struct AudioMixerPanelInner {
+    grid: gtk::Grid,
+    // Gain slider of each input
+    gains: RefCell<HashMap<&'static str, gtk::Scale>>,
+    pipeline: PipelineWeak,
+}
+
+pub struct AudioMixerPanelWeak(Weak<AudioMixerPanelInner>);
+impl AudioMixerPanelWeak {
+    pub fn upgrade(&self) -> Option<AudioMixerPanel> {
+        self.0.upgrade().map(AudioMixerPanel)
+    }
+}
+
+impl AudioMixerPanel {
+    pub fn new(pipeline: PipelineWeak) -> Self {
+        let grid = gtk::Grid::new();
+        grid.set_column_spacing(6);
+        grid.set_row_spacing(2);
+        grid.set_margin_top(6);
+
+        let panel = AudioMixerPanel(Rc::new(AudioMixerPanelInner {
+            grid,
+            gains: RefCell::new(HashMap::new()),
+            pipeline,
+        }));
+        let settings = utils::load_settings();
+
+        panel.add_row(0, MICROPHONE, gtk::Label::new(Some("Microphone")));
+
+        // Desktop audio is captured from the monitor of the default output
+        let desktop = gtk::CheckButton::new_with_label("Desktop audio");
+        desktop.set_active(settings.desktop_audio);
+        let panel_weak = panel.downgrade();
+        desktop.connect_toggled(move |desktop| {
+            let panel = upgrade_weak!(panel_weak);
+            let pipeline = &panel.pipeline;
+            let pipeline = upgrade_weak!(pipeline);
+            let enabled = desktop.get_active();
+            if let Err(err) = pipeline.set_desktop_audio(enabled) {
+                utils::show_error_dialog(
+                    false,
+                    format!("Failed to capture the desktop audio: {}", err).as_str(),
+                );
+                return;
+            }
+
+            let mut settings = utils::load_settings();
+            settings.desktop_audio = enabled;
+            utils::save_settings(&settings);
+        });
+        panel.add_row(1, DESKTOP, desktop);
+
+        // Music beds and jingles, played once from the start whenever a file is chosen
+        let media = gtk::FileChooserButton::new("Media file", gtk::FileChooserAction::Open);
+        media.set_tooltip_text(Some("Audio or video file mixed into the program"));
+        let panel_weak = panel.downgrade();
+        media.connect_file_set(move |media| {
+            let panel = upgrade_weak!(panel_weak);
+            let pipeline = &panel.pipeline;
+            let pipeline = upgrade_weak!(pipeline);
+            let location = media.get_filename();
+            if let Err(err) = pipeline.set_media_audio(location.as_deref()) {
+                utils::show_error_dialog(
+                    false,
+                    format!("Failed to play the media file: {}", err).as_str(),
+                );
+            }
+        });
+        panel.add_row(2, MEDIA, media);
+
+        let clip = gtk::Label::new(Some("Video clip"));
+        clip.set_halign(gtk::Align::Start);
+        panel.add_row(3, CLIP, clip);
+
+        panel
+    }
+
+    pub fn downgrade(&self) -> AudioMixerPanelWeak {
+        AudioMixerPanelWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> &gtk::Grid {
+        &self.grid
+    }
+
+    // Add the controls of one input: the given title widget, the gain slider and the mute button
+    fn add_row<W: IsA<gtk::Widget>>(&self, row: i32, input: &'static str, title: W) {
+        let input_settings = utils::load_settings()
+            .audio_inputs
+            .get(input)
+            .cloned()
+            .unwrap_or_default();
+
+        let gain = gtk::Scale::new_with_range(gtk::Orientation::Horizontal, 0.0, MAX_GAIN, 0.05);
+        gain.set_value(input_settings.volume);
+        gain.add_mark(1.0, gtk::PositionType::Bottom, None);
+        gain.set_hexpand(true);
+        gain.set_size_request(150, -1);
+        utils::set_accessible(&gain, "Gain", "Volume of the input in the program");
+
+        let mute = gtk::ToggleButton::new();
+        let mute_image =
+            gtk::Image::new_from_icon_name(Some("audio-volume-muted"), gtk::IconSize::Menu);
+        mute.set_image(Some(&mute_image));
+        mute.set_active(input_settings.muted);
+        mute.set_tooltip_text(Some("Mute"));
+        utils::set_accessible(&mute, "Mute", "Mute the input in the program");
+
+        let panel_weak = self.downgrade();
+        let mute_clone = mute.clone();
+        gain.connect_value_changed(move |gain| {
+            let panel = upgrade_weak!(panel_weak);
+            panel.on_input_changed(input, gain.get_value(), mute_clone.get_active());
+        });
+
+        let panel_weak = self.downgrade();
+        let gain_clone = gain.clone();
+        mute.connect_toggled(move |mute| {
+            let panel = upgrade_weak!(panel_weak);
+            panel.on_input_changed(input, gain_clone.get_value(), mute.get_active());
+        });
+
+        self.gains.borrow_mut().insert(input, gain.clone());
+
+        self.grid.attach(&title, 0, row, 1, 1);
+        self.grid.attach(&gain, 1, row, 1, 1);
+        self.grid.attach(&mute, 2, row, 1, 1);
+    }
+
+    // Move the gain slider of the input, e.g. from a fader of a MIDI controller
+    pub fn set_gain(&self, input: &str, volume: f64) {
+        if let Some(gain) = self.gains.borrow().get(input) {
+            gain.set_value(volume);
+        }
+    }
+
+    // Apply the new gain right away and remember it for the next start
+    fn on_input_changed(&self, input: &str, volume: f64, muted: bool) {
+        let input_settings = AudioInputSettings { volume, muted };
+
+        let pipeline = &self.pipeline;
+        let pipeline = upgrade_weak!(pipeline);
+        pipeline.set_audio_input_volume(input, input_settings);
+
+        let mut settings = utils::load_settings();
+        settings
+            .audio_inputs
+            .insert(input.to_string(), input_settings);
+        utils::save_settings(&settings);
+    }
+}
@@ -1,13 +1,41 @@
 // Ported from Voctomix's Python audio level widget:
 // https://github.com/voc/voctomix/blob/master/voctogui/lib/audioleveldisplay.py
 
-use cairo;
 use gtk::{self, prelude::*};
-use num;
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::f64::consts::FRAC_PI_2;
 use std::ops;
 use std::rc::{Rc, Weak};
+use std::time::{Duration, Instant};
+
+use crate::settings::{MeterOrientation, VuMeterSettings};
+use crate::utils;
+
+// How often the level is updated for screen readers
+const ACCESSIBLE_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+// Peaks are held at their highest level for a while, then fall slowly in dB per second
+const PEAK_HOLD_TIME: Duration = Duration::from_secs(2);
+const PEAK_HOLD_DECAY: f64 = 10.0;
+
+// Peaks at or above this level in dBFS latch the clip indicator until it is reset
+const CLIP_LEVEL: f64 = -0.1;
+
+// Height of the clip indicator on top of each channel in px
+const CLIP_INDICATOR_HEIGHT: f64 = 6.0;
+
+// Space for the bar of each channel and between the channels in px, before scaling
+const CHANNEL_WIDTH: i32 = 14;
+const CHANNEL_MARGIN: i32 = 2;
+// Space between the input and the program column in px, before scaling
+const COLUMN_MARGIN: i32 = 6;
+
+// Short names of the GStreamer audio channel positions, in the order of their channel-mask bits
+const POSITION_LABELS: &[&str] = &[
+    "L", "R", "C", "LFE", "Lb", "Rb", "Lc", "Rc", "Cb", "LFE2", "Ls", "Rs", "Tfl", "Tfr", "Tfc",
+    "Tc", "Tbl", "Tbr", "Tsl", "Tsr", "Tbc", "Bfc", "Bfl", "Bfr", "Lw", "Rw", "Lsd", "Rsd",
+];
 
 #[derive(Clone)]
 pub struct AudioVuMeter(Rc<AudioVuMeterInner>);
@@ -16,7 +44,44 @@ impl ops::Deref for AudioVuMeter {
     type Target = AudioVuMeterInner;
 
     fn deref(&self) -> &AudioVuMeterInner {
-        &*self.0
+        &self.0
+    }
+}
+
+// Names of the channels of raw audio caps, from their positions if given
+pub fn channel_labels(caps: &gst::CapsRef) -> Vec<String> {
+    let structure = match caps.get_structure(0) {
+        Some(structure) => structure,
+        None => return Vec::new(),
+    };
+    let channels = structure.get_some::<i32>("channels").unwrap_or(0).max(0) as usize;
+    let mask = structure
+        .get_some::<gst::Bitmask>("channel-mask")
+        .map(|mask| mask.0)
+        .unwrap_or(0);
+
+    let positioned = (0..POSITION_LABELS.len())
+        .filter(|bit| mask & (1 << bit) != 0)
+        .map(|bit| POSITION_LABELS[bit].to_string())
+        .collect::<Vec<_>>();
+    if positioned.len() == channels {
+        positioned
+    } else {
+        default_labels(channels)
+    }
+}
+
+// Space across the bars of a column with the given number of channels, before scaling
+fn column_thickness(channels: i32) -> i32 {
+    channels * CHANNEL_WIDTH + (channels - 1) * CHANNEL_MARGIN
+}
+
+// Names of the channels without known positions, stereo is assumed for two
+fn default_labels(channels: usize) -> Vec<String> {
+    match channels {
+        1 => vec!["M".to_string()],
+        2 => vec!["L".to_string(), "R".to_string()],
+        _ => (1..=channels).map(|channel| channel.to_string()).collect(),
     }
 }
 
@@ -27,14 +92,150 @@ struct LevelData {
     decay: Vec<f64>,
 }
 
+// Highest recent peak of a channel
+#[derive(Debug, Clone, Copy)]
+struct PeakHold {
+    db: f64,
+    since: Instant,
+}
+
+impl PeakHold {
+    // Level of the marker, falling once the hold time is over
+    fn level(&self, now: Instant) -> f64 {
+        let held = now.duration_since(self.since);
+        if held <= PEAK_HOLD_TIME {
+            self.db
+        } else {
+            self.db - (held - PEAK_HOLD_TIME).as_secs_f64() * PEAK_HOLD_DECAY
+        }
+    }
+}
+
+// Where in the audio chain the levels are measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelSource {
+    // The microphone as captured, before any processing
+    Input,
+    // The processed mix as it is encoded
+    Program,
+}
+
+impl LevelSource {
+    fn name(self) -> &'static str {
+        match self {
+            LevelSource::Input => "Input",
+            LevelSource::Program => "Program",
+        }
+    }
+}
+
+// Bars of one measuring point, one per channel
+struct Column {
+    data: RefCell<Option<LevelData>>,
+    peak_holds: RefCell<Vec<PeakHold>>,
+    // Per channel whether it clipped since the last reset
+    clipped: RefCell<Vec<bool>>,
+    channel_labels: RefCell<Vec<String>>,
+}
+
+impl Column {
+    fn new() -> Self {
+        Column {
+            data: RefCell::new(None),
+            peak_holds: RefCell::new(Vec::new()),
+            clipped: RefCell::new(Vec::new()),
+            channel_labels: RefCell::new(default_labels(2)),
+        }
+    }
+
+    fn channels(&self) -> i32 {
+        self.channel_labels.borrow().len().max(1) as i32
+    }
+
+    fn update(&self, rms: &[f64], peak: &[f64], decay: &[f64]) {
+        *self.data.borrow_mut() = Some(LevelData {
+            rms: rms.to_vec(),
+            peak: peak.to_vec(),
+            decay: decay.to_vec(),
+        });
+
+        let now = Instant::now();
+        let mut peak_holds = self.peak_holds.borrow_mut();
+        let mut clipped = self.clipped.borrow_mut();
+        peak_holds.resize(
+            peak.len(),
+            PeakHold {
+                db: f64::NEG_INFINITY,
+                since: now,
+            },
+        );
+        clipped.resize(peak.len(), false);
+
+        for ((hold, clipped), &db) in peak_holds.iter_mut().zip(clipped.iter_mut()).zip(peak) {
+            if db >= hold.level(now) {
+                *hold = PeakHold { db, since: now };
+            }
+            if db >= CLIP_LEVEL {
+                *clipped = true;
+            }
+        }
+    }
+
+    // Returns whether the channels changed
+    fn set_channels(&self, labels: Vec<String>) -> bool {
+        if *self.channel_labels.borrow() == labels {
+            return false;
+        }
+        self.channel_labels.replace(labels);
+        self.peak_holds.borrow_mut().clear();
+        self.clipped.borrow_mut().clear();
+        self.data.replace(None);
+        true
+    }
+
+    fn reset(&self) {
+        self.peak_holds.borrow_mut().clear();
+        for clipped in self.clipped.borrow_mut().iter_mut() {
+            *clipped = false;
+        }
+    }
+
+    // Current peak level of each channel, e.g. "L -12 dB, R -14 dB, clipped"
+    fn describe(&self) -> Option<String> {
+        let data = self.data.borrow();
+        let data = data.as_ref()?;
+        let levels = self
+            .channel_labels
+            .borrow()
+            .iter()
+            .zip(&data.peak)
+            .map(|(label, db)| format!("{} {:.0} dB", label, db))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let clipped = if self.clipped.borrow().contains(&true) {
+            ", clipped"
+        } else {
+            ""
+        };
+        Some(format!("{}{}", levels, clipped))
+    }
+}
+
 pub struct AudioVuMeterInner {
     drawing_area: gtk::DrawingArea,
-    data: RefCell<Option<LevelData>>,
+    input: Column,
+    program: Column,
+    ui_scale: Cell<f64>,
+    orientation: Cell<MeterOrientation>,
+    // Lowest level shown and the labeled levels, in dBFS
+    min_db: Cell<f64>,
+    ticks: RefCell<Vec<f64>>,
     cached_height: RefCell<Option<i32>>,
     bg_lg: RefCell<Option<cairo::LinearGradient>>,
     rms_lg: RefCell<Option<cairo::LinearGradient>>,
     peak_lg: RefCell<Option<cairo::LinearGradient>>,
     decay_lg: RefCell<Option<cairo::LinearGradient>>,
+    last_accessible_update: Cell<Option<Instant>>,
 }
 
 pub struct AudioVuMeterWeak(Weak<AudioVuMeterInner>);
@@ -48,16 +249,51 @@ impl AudioVuMeter {
     pub fn new() -> Self {
         let vumeter = AudioVuMeter(Rc::new(AudioVuMeterInner {
             drawing_area: gtk::DrawingArea::new(),
-            data: RefCell::new(None),
+            input: Column::new(),
+            program: Column::new(),
+            ui_scale: Cell::new(1.0),
+            orientation: Cell::new(MeterOrientation::Vertical),
+            min_db: Cell::new(-60.0),
+            ticks: RefCell::new(Vec::new()),
             cached_height: RefCell::new(None),
             bg_lg: RefCell::new(None),
             rms_lg: RefCell::new(None),
             peak_lg: RefCell::new(None),
             decay_lg: RefCell::new(None),
+            last_accessible_update: Cell::new(None),
         }));
 
-        let vumeter_weak = vumeter.downgrade();
         let area = vumeter.get_widget();
+        area.set_can_focus(true);
+        area.set_tooltip_text(Some(
+            "Microphone input and program levels. Click to reset the peak hold and clip indicators",
+        ));
+        utils::set_accessible(area, "Audio level meter", "No audio levels received");
+
+        // Clicking or activating the meter acknowledges the clipping
+        area.add_events(gdk::EventMask::BUTTON_PRESS_MASK);
+        let vumeter_weak = vumeter.downgrade();
+        area.connect_button_press_event(move |_, _| {
+            let vumeter = upgrade_weak!(vumeter_weak, Inhibit(false));
+            vumeter.reset();
+            Inhibit(true)
+        });
+        let vumeter_weak = vumeter.downgrade();
+        area.connect_key_press_event(move |_, event| {
+            let vumeter = upgrade_weak!(vumeter_weak, Inhibit(false));
+            match event.get_keyval() {
+                gdk::enums::key::Return | gdk::enums::key::KP_Enter | gdk::enums::key::space => {
+                    vumeter.reset();
+                    Inhibit(true)
+                }
+                _ => Inhibit(false),
+            }
+        });
+
+        vumeter.set_layout(&VuMeterSettings::default());
+        vumeter.update_size_request();
+
+        let vumeter_weak = vumeter.downgrade();
         area.connect_draw(move |_, cr| {
             if let Some(mut vumeter) = vumeter_weak.upgrade() {
                 vumeter.on_draw(cr)
@@ -77,19 +313,118 @@ impl AudioVuMeter {
         &self.0.drawing_area
     }
 
-    pub fn update(&mut self, rms: &[f64], peak: &[f64], decay: &[f64]) {
-        *self.0.data.borrow_mut() = Some(LevelData {
-            rms: rms.to_vec(),
-            peak: peak.to_vec(),
-            decay: decay.to_vec(),
-        });
+    fn column(&self, source: LevelSource) -> &Column {
+        match source {
+            LevelSource::Input => &self.input,
+            LevelSource::Program => &self.program,
+        }
+    }
+
+    pub fn update(&mut self, source: LevelSource, rms: &[f64], peak: &[f64], decay: &[f64]) {
+        // Only complete channels are shown
+        let channels = rms.len().min(peak.len()).min(decay.len());
+        let (rms, peak, decay) = (&rms[..channels], &peak[..channels], &decay[..channels]);
+        if self.column(source).channel_labels.borrow().len() != channels {
+            self.set_channels(source, default_labels(channels));
+        }
+
+        self.column(source).update(rms, peak, decay);
+
         self.0.drawing_area.queue_draw();
+        self.update_accessible();
+    }
+
+    // Name the channels of the following levels, e.g. after the caps changed. The held peaks and
+    // clipping belong to the previous channels
+    pub fn set_channels(&self, source: LevelSource, labels: Vec<String>) {
+        if self.column(source).set_channels(labels) {
+            self.update_size_request();
+            self.drawing_area.queue_draw();
+        }
+    }
+
+    pub fn set_ui_scale(&self, scale: f64) {
+        self.ui_scale.set(scale);
+        self.update_size_request();
+    }
+
+    // Make room for all channels of both columns across the bars, the length is up to the
+    // container
+    fn update_size_request(&self) {
+        let thickness = f64::from(
+            column_thickness(self.input.channels())
+                + COLUMN_MARGIN
+                + column_thickness(self.program.channels()),
+        ) * self.ui_scale.get();
+        match self.orientation.get() {
+            MeterOrientation::Vertical => self.drawing_area.set_size_request(thickness as i32, -1),
+            MeterOrientation::Horizontal => {
+                self.drawing_area.set_size_request(-1, thickness as i32)
+            }
+        }
+    }
+
+    // Apply the orientation and scale, the caller places the widget accordingly
+    pub fn set_layout(&self, settings: &VuMeterSettings) {
+        self.orientation.set(settings.orientation);
+        self.min_db.set(f64::from(settings.min_db.min(-1)));
+        // Labels outside of the range would all pile up at its ends
+        *self.ticks.borrow_mut() = settings
+            .ticks
+            .iter()
+            .filter(|&&tick| tick < 0 && tick > settings.min_db)
+            .map(|&tick| f64::from(tick))
+            .collect();
+        self.update_size_request();
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn get_orientation(&self) -> MeterOrientation {
+        self.orientation.get()
+    }
+
+    // Forget the held peaks and clear the clip indicators
+    pub fn reset(&self) {
+        self.input.reset();
+        self.program.reset();
+        self.last_accessible_update.set(None);
+        self.drawing_area.queue_draw();
+    }
+
+    // Describe the current peak level of each channel for screen readers, throttled so they
+    // don't get flooded
+    fn update_accessible(&self) {
+        let now = Instant::now();
+        if let Some(last) = self.last_accessible_update.get() {
+            if now.duration_since(last) < ACCESSIBLE_UPDATE_INTERVAL {
+                return;
+            }
+        }
+        self.last_accessible_update.set(Some(now));
+
+        let description = [LevelSource::Input, LevelSource::Program]
+            .iter()
+            .filter_map(|&source| {
+                self.column(source)
+                    .describe()
+                    .map(|levels| format!("{} peak levels: {}", source.name(), levels))
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        utils::set_accessible(&self.drawing_area, "Audio level meter", &description);
     }
 
     fn on_draw(&mut self, cr: &cairo::Context) -> Inhibit {
         let area = &self.0.drawing_area;
-        let width = area.get_allocated_width();
-        let height = area.get_allocated_height();
+        let orientation = self.orientation.get();
+        // The bars are drawn as if vertical, horizontal meters are rotated to grow to the right.
+        // width and height are across and along the bars
+        let (width, height) = match orientation {
+            MeterOrientation::Vertical => (area.get_allocated_width(), area.get_allocated_height()),
+            MeterOrientation::Horizontal => {
+                (area.get_allocated_height(), area.get_allocated_width())
+            }
+        };
 
         let update_gradients = match *self.cached_height.borrow() {
             Some(h) => h != height,
@@ -105,133 +440,255 @@ impl AudioVuMeter {
             *self.decay_lg.borrow_mut() = Some(self.gradient(1.0, 0.5, height.into()));
         }
 
-        if let Some(data) = &*self.0.data.borrow() {
-            let channels = data.rms.len() as i32;
-
-            // space between the channels in px
-            let margin = 2;
-
-            // 1 channel -> 0 margins, 2 channels -> 1 margin, 3 channels…
-            let channel_width = (width - (margin * (channels - 1))) / channels;
-
-            let height_float = f64::from(height);
-
-            // normalize db-value to 0…1 and multiply with the height
-            let rms_px = data
-                .rms
-                .iter()
-                .map(|db| self.normalize_db(*db) * height_float)
-                .collect::<Vec<_>>();
-            let peak_px = data
-                .peak
-                .iter()
-                .map(|db| self.normalize_db(*db) * height_float)
-                .collect::<Vec<_>>();
-            let decay_px = data
-                .decay
-                .iter()
-                .map(|db| self.normalize_db(*db) * height_float)
-                .collect::<Vec<_>>();
-
-            for channel in 0..channels {
-                // start-coordinate for this channel
-                let x = (channel * channel_width) + (channel * margin);
-                let channel_idx = channel as usize;
-
-                // draw background
-                cr.rectangle(
-                    x.into(),
-                    0.0,
-                    channel_width.into(),
-                    height_float - peak_px[channel_idx],
-                );
+        // The columns share the width by their number of channels, input first
+        let input_channels = self.input.channels();
+        let program_channels = self.program.channels();
+        let available = (width - COLUMN_MARGIN).max(2);
+        let input_width = (available * input_channels / (input_channels + program_channels)).max(1);
+        let program_x = input_width + COLUMN_MARGIN;
+        let program_width = (width - program_x).max(1);
+
+        let input_drawn = self.draw_column(cr, &self.input, 0, input_width, height);
+        let program_drawn = self.draw_column(cr, &self.program, program_x, program_width, height);
+        if !input_drawn && !program_drawn {
+            return Inhibit(false);
+        }
 
-                if let Some(gradient) = self.bg_lg.borrow().as_ref() {
-                    cr.set_source(gradient);
-                    cr.fill();
-                }
+        // Separate the columns
+        cr.save();
+        if orientation == MeterOrientation::Horizontal {
+            cr.translate(f64::from(height), 0.0);
+            cr.rotate(FRAC_PI_2);
+        }
+        cr.rectangle(
+            f64::from(input_width),
+            0.0,
+            f64::from(COLUMN_MARGIN),
+            f64::from(height),
+        );
+        cr.set_source_rgb(0.3, 0.3, 0.3);
+        cr.fill();
+        cr.restore();
+
+        // The scale is labeled on the program column
+        let height_float = f64::from(height);
+        let last_peak_px = self
+            .program
+            .data
+            .borrow()
+            .as_ref()
+            .and_then(|data| data.peak.last().copied())
+            .map(|db| self.normalize_db(db) * height_float)
+            .unwrap_or(0.0);
+        for db in self.ticks.borrow().iter() {
+            let text = format!("{}", db);
+            let extents = cr.text_extents(&text);
+            let textwidth = extents.width;
+            let textheight = extents.height;
+
+            let y = self.normalize_db(*db) * height_float;
+            if y > last_peak_px {
+                cr.set_source_rgb(1.0, 1.0, 1.0);
+            } else {
+                cr.set_source_rgb(0.0, 0.0, 0.0);
+            }
 
-                // draw peak bar
-                cr.rectangle(
-                    x.into(),
-                    height_float - peak_px[channel_idx],
-                    channel_width.into(),
-                    peak_px[channel_idx],
-                );
-                if let Some(gradient) = self.peak_lg.borrow().as_ref() {
-                    cr.set_source(gradient);
-                    cr.fill();
+            match orientation {
+                MeterOrientation::Vertical => cr.move_to(
+                    (f64::from(width) - textwidth) - 2.0,
+                    height_float - y - textheight,
+                ),
+                MeterOrientation::Horizontal => {
+                    cr.move_to(y - textwidth - 2.0, f64::from(width) - 2.0)
                 }
+            }
+            cr.show_text(&text);
+        }
 
-                // draw rms bar below
-                cr.rectangle(
-                    x.into(),
-                    height_float - rms_px[channel_idx],
-                    channel_width.into(),
-                    rms_px[channel_idx] - peak_px[channel_idx],
-                );
-                if let Some(gradient) = self.rms_lg.borrow().as_ref() {
-                    cr.set_source(gradient);
-                    cr.fill();
-                }
+        Inhibit(true)
+    }
 
-                // draw decay bar
-                cr.rectangle(
-                    x.into(),
-                    height_float - decay_px[channel_idx],
-                    channel_width.into(),
-                    2.0,
-                );
-                if let Some(gradient) = self.decay_lg.borrow().as_ref() {
-                    cr.set_source(gradient);
-                    cr.fill();
-                }
+    // Draw the bars of the column starting at x across the bars. Returns false if there are no
+    // levels to show yet
+    fn draw_column(
+        &self,
+        cr: &cairo::Context,
+        column: &Column,
+        x_offset: i32,
+        width: i32,
+        height: i32,
+    ) -> bool {
+        let data = column.data.borrow();
+        let data = match data.as_ref().filter(|d| !d.rms.is_empty()) {
+            Some(data) => data,
+            None => return false,
+        };
+        let orientation = self.orientation.get();
+        let channels = data.rms.len() as i32;
+        let now = Instant::now();
+        let peak_holds = column.peak_holds.borrow();
+        let clipped = column.clipped.borrow();
+
+        cr.save();
+        if orientation == MeterOrientation::Horizontal {
+            cr.translate(f64::from(height), 0.0);
+            cr.rotate(FRAC_PI_2);
+        }
 
-                // draw medium grey margin bar
-                if margin > 0 {
-                    cr.rectangle(
-                        f64::from(x) + f64::from(channel_width),
-                        0.0,
-                        margin.into(),
-                        height.into(),
-                    );
-                    cr.set_source_rgb(0.5, 0.5, 0.5);
-                    cr.fill();
-                }
+        // space between the channels in px
+        let margin = CHANNEL_MARGIN;
+
+        // 1 channel -> 0 margins, 2 channels -> 1 margin, 3 channels…
+        let channel_width = ((width - (margin * (channels - 1))) / channels).max(1);
+
+        let height_float = f64::from(height);
+
+        // normalize db-value to 0…1 and multiply with the height
+        let rms_px = data
+            .rms
+            .iter()
+            .map(|db| self.normalize_db(*db) * height_float)
+            .collect::<Vec<_>>();
+        let peak_px = data
+            .peak
+            .iter()
+            .map(|db| self.normalize_db(*db) * height_float)
+            .collect::<Vec<_>>();
+        let decay_px = data
+            .decay
+            .iter()
+            .map(|db| self.normalize_db(*db) * height_float)
+            .collect::<Vec<_>>();
+
+        for channel in 0..channels {
+            // start-coordinate for this channel
+            let x = x_offset + (channel * channel_width) + (channel * margin);
+            let channel_idx = channel as usize;
+
+            // draw background
+            cr.rectangle(
+                x.into(),
+                0.0,
+                channel_width.into(),
+                height_float - peak_px[channel_idx],
+            );
+
+            if let Some(gradient) = self.bg_lg.borrow().as_ref() {
+                cr.set_source(gradient);
+                cr.fill();
             }
 
-            for db in [-40, -20, -10, -5, -4, -3, -2, -1].iter() {
-                let text = format!("{}", db);
-                let extents = cr.text_extents(&text);
-                let textwidth = extents.width;
-                let textheight = extents.height;
+            // draw peak bar
+            cr.rectangle(
+                x.into(),
+                height_float - peak_px[channel_idx],
+                channel_width.into(),
+                peak_px[channel_idx],
+            );
+            if let Some(gradient) = self.peak_lg.borrow().as_ref() {
+                cr.set_source(gradient);
+                cr.fill();
+            }
+
+            // draw rms bar below
+            cr.rectangle(
+                x.into(),
+                height_float - rms_px[channel_idx],
+                channel_width.into(),
+                rms_px[channel_idx] - peak_px[channel_idx],
+            );
+            if let Some(gradient) = self.rms_lg.borrow().as_ref() {
+                cr.set_source(gradient);
+                cr.fill();
+            }
+
+            // draw decay bar
+            cr.rectangle(
+                x.into(),
+                height_float - decay_px[channel_idx],
+                channel_width.into(),
+                2.0,
+            );
+            if let Some(gradient) = self.decay_lg.borrow().as_ref() {
+                cr.set_source(gradient);
+                cr.fill();
+            }
 
-                let y = self.normalize_db(f64::from(*db)) * height_float;
-                if y > peak_px[channels as usize - 1] {
+            // draw peak hold marker
+            if let Some(hold) = peak_holds.get(channel_idx) {
+                let hold_px = self.normalize_db(hold.level(now)) * height_float;
+                if hold_px > 0.0 {
+                    cr.rectangle(x.into(), height_float - hold_px, channel_width.into(), 2.0);
                     cr.set_source_rgb(1.0, 1.0, 1.0);
-                } else {
-                    cr.set_source_rgb(0.0, 0.0, 0.0);
+                    cr.fill();
                 }
+            }
 
-                cr.move_to(
-                    (f64::from(width) - textwidth) - 2.0,
-                    height_float - y - textheight,
+            // draw clip indicator, bright red once the channel clipped
+            cr.rectangle(x.into(), 0.0, channel_width.into(), CLIP_INDICATOR_HEIGHT);
+            if clipped.get(channel_idx).copied().unwrap_or(false) {
+                cr.set_source_rgb(1.0, 0.0, 0.0);
+            } else {
+                cr.set_source_rgb(0.3, 0.0, 0.0);
+            }
+            cr.fill();
+
+            // draw medium grey margin bar between the channels
+            if margin > 0 && channel < channels - 1 {
+                cr.rectangle(
+                    f64::from(x) + f64::from(channel_width),
+                    0.0,
+                    margin.into(),
+                    height.into(),
                 );
-                cr.show_text(&text);
+                cr.set_source_rgb(0.5, 0.5, 0.5);
+                cr.fill();
             }
-            Inhibit(true)
-        } else {
-            Inhibit(false)
         }
+
+        // The labels stay upright
+        cr.restore();
+
+        // name the channels at the quiet end, dark on top of a level bar
+        for (channel, label) in column.channel_labels.borrow().iter().enumerate() {
+            let peak = match peak_px.get(channel) {
+                Some(peak) => *peak,
+                None => break,
+            };
+            let extents = cr.text_extents(label);
+            let center = f64::from(x_offset + channel as i32 * (channel_width + margin))
+                + f64::from(channel_width) / 2.0;
+
+            let length = match orientation {
+                MeterOrientation::Vertical => extents.height,
+                MeterOrientation::Horizontal => extents.width,
+            };
+            if peak > length + 2.0 {
+                cr.set_source_rgb(0.0, 0.0, 0.0);
+            } else {
+                cr.set_source_rgb(1.0, 1.0, 1.0);
+            }
+
+            match orientation {
+                MeterOrientation::Vertical => {
+                    cr.move_to(center - extents.width / 2.0, height_float - 2.0)
+                }
+                MeterOrientation::Horizontal => cr.move_to(2.0, center + extents.height / 2.0),
+            }
+            cr.show_text(label);
+        }
+
+        true
     }
 
     fn normalize_db(&self, db: f64) -> f64 {
+        // Distance from the top for the default range of 60 dB, others are stretched to fit:
         // -60db -> 1.00 (very quiet)
         // -30db -> 0.75
         // -15db -> 0.50
         //  -5db -> 0.25
         //  -0db -> 0.00 (very loud)
-        let val = -0.15 * db + 1.0;
+        let val = 9.0 / self.min_db.get() * db + 1.0;
         let logscale = 1.0 - val.log10();
         num::clamp(logscale, 0.0, 1.0)
     }
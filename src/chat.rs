@@ -0,0 +1,458 @@
+use gtk::{self, prelude::*};
+
+use serde::Deserialize;
+
+use std::cell::{Cell, RefCell};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::ops;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::pipeline::PipelineWeak;
+use crate::settings::{ChatProvider, ChatSettings};
+use crate::utils;
+
+// Plain text IRC interface of the Twitch chat
+const TWITCH_ADDRESS: &str = "irc.chat.twitch.tv:6667";
+const YOUTUBE_MESSAGES_URL: &str = "https://www.googleapis.com/youtube/v3/liveChat/messages";
+// Seconds to wait before connecting again after the chat connection was lost
+const RECONNECT_INTERVAL: u64 = 5;
+// Messages kept in the chat pane
+const MAX_MESSAGES: i32 = 200;
+// Messages shown at the same time in the overlay
+const OVERLAY_MESSAGES: usize = 6;
+
+// Id of the element injected into the overlay page
+const ELEMENT_ID: &str = "broadcast-chat";
+
+const STYLESHEET: &str = r#"
+#broadcast-chat {
+    position: fixed;
+    right: 2vw;
+    bottom: 12vh;
+    width: 28vw;
+    font-family: sans-serif;
+    font-size: 1.4vw;
+    color: white;
+}
+#broadcast-chat div {
+    margin-top: 0.4vw;
+    padding: 0.4vw 0.8vw;
+    background: rgba(20, 20, 20, 0.75);
+    border-radius: 0.4vw;
+}
+#broadcast-chat .author {
+    font-weight: bold;
+    color: #99c1f1;
+    margin-right: 0.5vw;
+}
+"#;
+
+#[derive(Debug, Clone)]
+pub struct ChatMessage {
+    pub author: String,
+    pub text: String,
+}
+
+enum ChatEvent {
+    Message(ChatMessage),
+    // Human readable connection state
+    Status(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LiveChatMessages {
+    next_page_token: Option<String>,
+    #[serde(default)]
+    polling_interval_millis: u32,
+    #[serde(default)]
+    items: Vec<LiveChatMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LiveChatMessage {
+    snippet: LiveChatSnippet,
+    author_details: LiveChatAuthor,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LiveChatSnippet {
+    #[serde(default)]
+    display_message: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LiveChatAuthor {
+    #[serde(default)]
+    display_name: String,
+}
+
+// Chat message out of an IRC line like ":nick!nick@nick.tmi.twitch.tv PRIVMSG #channel :text"
+fn parse_twitch_line(line: &str) -> Option<ChatMessage> {
+    let line = line.trim_end();
+    if !line.starts_with(':') {
+        return None;
+    }
+
+    let mut parts = line[1..].splitn(4, ' ');
+    let prefix = parts.next()?;
+    if parts.next()? != "PRIVMSG" {
+        return None;
+    }
+    let _channel = parts.next()?;
+    let text = parts.next()?;
+
+    Some(ChatMessage {
+        author: prefix.split('!').next()?.to_string(),
+        text: text.trim_start_matches(':').to_string(),
+    })
+}
+
+// Read the chat of the channel until the connection is lost or we're asked to stop
+fn twitch_session(
+    channel: &str,
+    stop: &AtomicBool,
+    sender: &glib::Sender<ChatEvent>,
+) -> io::Result<()> {
+    let mut stream = TcpStream::connect(TWITCH_ADDRESS)?;
+    // Wake up regularly to check whether we should stop
+    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+
+    // Anonymous users are called justinfan followed by some number and can only read
+    write!(
+        stream,
+        "NICK justinfan{}\r\nJOIN #{}\r\n",
+        std::process::id() % 100_000,
+        channel
+    )?;
+    let _ = sender.send(ChatEvent::Status(format!("Connected to #{}", channel)));
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    while !stop.load(Ordering::Relaxed) {
+        match reader.read_line(&mut line) {
+            Ok(0) => return Ok(()),
+            Ok(_) => {
+                if let Some(server) = line.strip_prefix("PING") {
+                    write!(stream, "PONG{}\r\n", server.trim_end())?;
+                } else if let Some(message) = parse_twitch_line(&line) {
+                    if sender.send(ChatEvent::Message(message)).is_err() {
+                        return Ok(());
+                    }
+                }
+                line.clear();
+            }
+            // Partial lines stay in the buffer until the rest arrives
+            Err(ref err)
+                if err.kind() == io::ErrorKind::WouldBlock
+                    || err.kind() == io::ErrorKind::TimedOut => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+// JavaScript adding the message to the chat box in the overlay, dropping the oldest ones
+fn overlay_script(message: &ChatMessage) -> String {
+    format!(
+        r#"(function() {{
+    var root = document.getElementById("{id}");
+    if (!root) {{
+        var style = document.createElement("style");
+        style.textContent = {stylesheet};
+        (document.head || document.documentElement).appendChild(style);
+        root = document.createElement("div");
+        root.id = "{id}";
+        document.body.appendChild(root);
+    }}
+    var line = document.createElement("div");
+    var author = document.createElement("span");
+    author.className = "author";
+    author.textContent = {author};
+    line.appendChild(author);
+    line.appendChild(document.createTextNode({text}));
+    root.appendChild(line);
+    while (root.children.length > {max}) {{
+        root.removeChild(root.firstChild);
+    }}
+}})();"#,
+        id = ELEMENT_ID,
+        stylesheet = serde_json::Value::from(STYLESHEET),
+        author = serde_json::Value::from(message.author.as_str()),
+        text = serde_json::Value::from(message.text.as_str()),
+        max = OVERLAY_MESSAGES,
+    )
+}
+
+// Our refcounted chat struct, connecting to the live chat of the stream and showing its messages
+// in a read-only pane and the overlay
+#[derive(Clone)]
+pub struct Chat(Rc<ChatInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for Chat {
+    type Target = ChatInner;
+
+    fn deref(&self) -> &ChatInner {
+        &self.0
+    }
+}
+
+pub struct ChatInner {
+    expander: gtk::Expander,
+    status: gtk::Label,
+    text_view: gtk::TextView,
+    pipeline: PipelineWeak,
+    show_in_overlay: Cell<bool>,
+    // Increased with every (re)connection, events of previous connections are ignored
+    generation: Cell<u32>,
+    // Tells the Twitch thread to disconnect
+    stop: RefCell<Option<Arc<AtomicBool>>>,
+    // Fetches the next YouTube messages, or connects again after a failure
+    poll_timeout: RefCell<Option<glib::SourceId>>,
+}
+
+pub struct ChatWeak(Weak<ChatInner>);
+impl ChatWeak {
+    pub fn upgrade(&self) -> Option<Chat> {
+        self.0.upgrade().map(Chat)
+    }
+}
+
+impl Chat {
+    pub fn new(pipeline: PipelineWeak) -> Self {
+        let expander = gtk::Expander::new(Some("Chat"));
+        expander.set_margin_top(6);
+
+        let status = gtk::Label::new(None);
+        status.set_halign(gtk::Align::Start);
+
+        let text_view = gtk::TextView::new();
+        text_view.set_editable(false);
+        text_view.set_cursor_visible(false);
+        text_view.set_wrap_mode(gtk::WrapMode::WordChar);
+        utils::set_accessible(&text_view, "Chat", "Messages of the live chat");
+        // Authors are shown in bold in front of their messages
+        let author_tag = gtk::TextTag::new(Some("author"));
+        author_tag.set_property_weight(700);
+        if let Some(tag_table) = text_view.get_buffer().and_then(|b| b.get_tag_table()) {
+            tag_table.add(&author_tag);
+        }
+
+        let scrolled_window = gtk::ScrolledWindow::new(gtk::NONE_ADJUSTMENT, gtk::NONE_ADJUSTMENT);
+        scrolled_window.set_size_request(-1, 150);
+        scrolled_window.add(&text_view);
+
+        let vbox = gtk::Box::new(gtk::Orientation::Vertical, 4);
+        vbox.pack_start(&status, false, false, 0);
+        vbox.pack_start(&scrolled_window, true, true, 0);
+        expander.add(&vbox);
+        // Only shown while the chat is enabled
+        vbox.show_all();
+        expander.set_no_show_all(true);
+
+        Chat(Rc::new(ChatInner {
+            expander,
+            status,
+            text_view,
+            pipeline,
+            show_in_overlay: Cell::new(false),
+            generation: Cell::new(0),
+            stop: RefCell::new(None),
+            poll_timeout: RefCell::new(None),
+        }))
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> ChatWeak {
+        ChatWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> &gtk::Expander {
+        &self.expander
+    }
+
+    // Connect according to the current settings, dropping any previous connection
+    pub fn refresh(&self) {
+        self.disconnect();
+
+        let settings = utils::load_settings().chat;
+        self.show_in_overlay.set(settings.show_in_overlay);
+        self.expander.set_visible(settings.enabled);
+        if !settings.enabled {
+            return;
+        }
+
+        match settings.provider {
+            ChatProvider::Twitch => self.connect_twitch(&settings),
+            ChatProvider::YouTube => self.poll_youtube(self.generation.get(), None),
+        }
+    }
+
+    fn disconnect(&self) {
+        self.generation.set(self.generation.get().wrapping_add(1));
+        if let Some(stop) = self.stop.borrow_mut().take() {
+            stop.store(true, Ordering::Relaxed);
+        }
+        if let Some(source_id) = self.poll_timeout.borrow_mut().take() {
+            glib::source_remove(source_id);
+        }
+    }
+
+    fn connect_twitch(&self, settings: &ChatSettings) {
+        let channel = match settings.twitch_channel {
+            Some(ref channel) => channel.trim_start_matches('#').to_lowercase(),
+            None => {
+                self.status.set_text("No Twitch channel configured");
+                return;
+            }
+        };
+
+        let stop = Arc::new(AtomicBool::new(false));
+        self.stop.replace(Some(stop.clone()));
+
+        let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+        let generation = self.generation.get();
+        let chat_weak = self.downgrade();
+        receiver.attach(None, move |event| {
+            let chat = upgrade_weak!(chat_weak, glib::Continue(false));
+            if chat.generation.get() != generation {
+                return glib::Continue(false);
+            }
+            chat.on_event(event);
+            glib::Continue(true)
+        });
+
+        thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                let _ = sender.send(ChatEvent::Status(format!("Connecting to #{}…", channel)));
+                let status = match twitch_session(&channel, &stop, &sender) {
+                    Ok(()) => "Disconnected from the chat".to_string(),
+                    Err(err) => format!("Chat connection failed: {}", err),
+                };
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                let _ = sender.send(ChatEvent::Status(status));
+                thread::sleep(Duration::from_secs(RECONNECT_INTERVAL));
+            }
+        });
+    }
+
+    // Fetch the messages since the given page, the first request also returns the recent ones
+    fn poll_youtube(&self, generation: u32, page_token: Option<String>) {
+        let settings = utils::load_settings().chat;
+        let (api_key, live_chat_id) =
+            match (settings.youtube_api_key, settings.youtube_live_chat_id) {
+                (Some(api_key), Some(live_chat_id)) => (api_key, live_chat_id),
+                _ => {
+                    self.status
+                        .set_text("No YouTube API key or live chat ID configured");
+                    return;
+                }
+            };
+
+        let mut url = format!(
+            "{}?part=snippet,authorDetails&liveChatId={}&key={}",
+            YOUTUBE_MESSAGES_URL, live_chat_id, api_key
+        );
+        if let Some(ref page_token) = page_token {
+            url.push_str(&format!("&pageToken={}", page_token));
+        }
+
+        let chat_weak = self.downgrade();
+        utils::fetch(&url, move |result| {
+            let chat = upgrade_weak!(chat_weak);
+            if chat.generation.get() != generation {
+                return;
+            }
+
+            let result = result.and_then(|data| {
+                serde_json::from_slice::<LiveChatMessages>(&data).map_err(|err| err.to_string())
+            });
+            let (interval, page_token) = match result {
+                Ok(messages) => {
+                    chat.status.set_text("Connected to the YouTube live chat");
+                    for item in messages.items {
+                        chat.on_event(ChatEvent::Message(ChatMessage {
+                            author: item.author_details.display_name,
+                            text: item.snippet.display_message,
+                        }));
+                    }
+                    (
+                        messages.polling_interval_millis.max(1000),
+                        messages.next_page_token.or(page_token),
+                    )
+                }
+                Err(err) => {
+                    chat.status
+                        .set_text(&format!("Chat connection failed: {}", err));
+                    (RECONNECT_INTERVAL as u32 * 1000, page_token)
+                }
+            };
+
+            let chat_weak = chat.downgrade();
+            let source_id = glib::timeout_add_local(interval, move || {
+                let chat = upgrade_weak!(chat_weak, glib::Continue(false));
+                chat.poll_timeout.replace(None);
+                chat.poll_youtube(generation, page_token.clone());
+                glib::Continue(false)
+            });
+            chat.poll_timeout.replace(Some(source_id));
+        });
+    }
+
+    fn on_event(&self, event: ChatEvent) {
+        match event {
+            ChatEvent::Status(status) => self.status.set_text(&status),
+            ChatEvent::Message(message) => {
+                self.append_message(&message);
+
+                let pipeline = &self.pipeline;
+                let pipeline = upgrade_weak!(pipeline);
+                if self.show_in_overlay.get() {
+                    pipeline.run_javascript(&overlay_script(&message));
+                }
+                pipeline.send_overlay_message(&serde_json::json!({
+                    "type": "chat",
+                    "author": message.author,
+                    "text": message.text,
+                }));
+            }
+        }
+    }
+
+    // Add the message to the end of the pane, dropping the oldest ones
+    fn append_message(&self, message: &ChatMessage) {
+        let buffer = match self.text_view.get_buffer() {
+            Some(buffer) => buffer,
+            None => return,
+        };
+
+        let offset = buffer.get_char_count();
+        let mut end = buffer.get_end_iter();
+        buffer.insert(&mut end, &message.author);
+        buffer.apply_tag_by_name("author", &buffer.get_iter_at_offset(offset), &end);
+        buffer.insert(&mut end, &format!(": {}\n", message.text));
+
+        let excess = buffer.get_line_count() - MAX_MESSAGES - 1;
+        if excess > 0 {
+            let mut start = buffer.get_start_iter();
+            let mut cut = buffer.get_iter_at_line(excess);
+            buffer.delete(&mut start, &mut cut);
+        }
+
+        let mut end = buffer.get_end_iter();
+        self.text_view
+            .scroll_to_iter(&mut end, 0.0, false, 0.0, 1.0);
+    }
+}
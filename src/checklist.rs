@@ -0,0 +1,241 @@
+use gtk::{self, prelude::*};
+
+use std::net::{TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::Duration;
+
+use crate::pipeline::{OverlayState, Pipeline};
+use crate::settings::{CheckSeverity, OutputMode, Settings};
+use crate::utils;
+use crate::validation;
+
+// Below this much free space in the recordings folder the disk space check fails
+const MIN_FREE_DISK_SPACE: u64 = 2 * 1024 * 1024 * 1024;
+
+// Audio peaks below this level (in dB) are considered silence
+const MIN_AUDIO_PEAK: f64 = -60.0;
+
+// How long the RTMP server may take to accept a connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Outcome of a single pre-flight check
+pub struct CheckResult {
+    pub label: &'static str,
+    pub severity: CheckSeverity,
+    pub passed: bool,
+    pub details: String,
+}
+
+// Run all checks that are not disabled in the settings. Connecting to the server takes a while, so
+// the results are passed to the callback from the main thread once all checks are done
+pub fn run_checks<F: FnOnce(Vec<CheckResult>) + 'static>(
+    pipeline: &Pipeline,
+    settings: &Settings,
+    callback: F,
+) {
+    let checklist = &settings.checklist;
+    let mut results = Vec::new();
+
+    if checklist.camera != CheckSeverity::Off {
+        let passed = pipeline.is_camera_running();
+        results.push(CheckResult {
+            label: "Camera",
+            severity: checklist.camera,
+            passed,
+            details: if passed {
+                "Capturing".to_string()
+            } else {
+                "The camera is not capturing".to_string()
+            },
+        });
+    }
+
+    if checklist.audio != CheckSeverity::Off {
+        let (passed, details) = match pipeline.get_audio_peak() {
+            Some(peak) if peak > MIN_AUDIO_PEAK => (true, format!("Peak at {:.1} dB", peak)),
+            Some(peak) => (false, format!("Silence, peak at {:.1} dB", peak)),
+            None => (false, "No audio levels received".to_string()),
+        };
+        results.push(CheckResult {
+            label: "Audio levels",
+            severity: checklist.audio,
+            passed,
+            details,
+        });
+    }
+
+    // SRT runs over UDP, so there is no connection to try without doing the whole handshake
+    let mut server_address = None;
+    if checklist.rtmp != CheckSeverity::Off {
+        let (passed, details) = match settings.output_mode {
+            OutputMode::Srt => match settings.srt.uri {
+                Some(ref uri) => match validation::check_srt_uri(uri) {
+                    Ok(()) => (true, uri.clone()),
+                    Err(err) => (false, err),
+                },
+                None => (false, "No SRT URI configured".to_string()),
+            },
+            _ => match settings.rtmp_location {
+                Some(ref location) => match validation::rtmp_server_address(location) {
+                    Ok(address) => {
+                        server_address = Some((results.len(), address));
+                        (true, location.clone())
+                    }
+                    Err(err) => (false, err),
+                },
+                None => (false, "No RTMP end-point configured".to_string()),
+            },
+        };
+        results.push(CheckResult {
+            label: if settings.output_mode == OutputMode::Srt {
+                "SRT end-point"
+            } else {
+                "RTMP end-point"
+            },
+            severity: checklist.rtmp,
+            passed,
+            details,
+        });
+    }
+
+    if checklist.disk_space != CheckSeverity::Off {
+        let (passed, details) = match utils::get_free_disk_space(settings) {
+            Some(free) => (
+                free >= MIN_FREE_DISK_SPACE,
+                format!("{:.1} GiB free", free as f64 / (1024.0 * 1024.0 * 1024.0)),
+            ),
+            None => (false, "Unable to query free disk space".to_string()),
+        };
+        results.push(CheckResult {
+            label: "Disk space",
+            severity: checklist.disk_space,
+            passed,
+            details,
+        });
+    }
+
+    if checklist.overlay != CheckSeverity::Off {
+        let (passed, details) = match pipeline.get_overlay_state() {
+            OverlayState::Loaded => (true, "Loaded"),
+            OverlayState::Loading => (false, "The overlay is still loading"),
+            OverlayState::Failed => (false, "The overlay failed to load"),
+        };
+        results.push(CheckResult {
+            label: "Web-page overlay",
+            severity: checklist.overlay,
+            passed,
+            details: details.to_string(),
+        });
+    }
+
+    let (index, (host, port)) = match server_address {
+        Some(server_address) => server_address,
+        None => return callback(results),
+    };
+
+    let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+    thread::spawn(move || {
+        let _ = sender.send(connect(&host, port));
+    });
+
+    let mut callback = Some(callback);
+    receiver.attach(None, move |result| {
+        if let Err(err) = result {
+            results[index].passed = false;
+            results[index].details = err;
+        }
+        if let Some(callback) = callback.take() {
+            callback(std::mem::take(&mut results));
+        }
+        glib::Continue(false)
+    });
+}
+
+// Try to open a TCP connection to the server, which fails early for typos in the host name,
+// closed ports and networks that don't let the stream through
+fn connect(host: &str, port: u16) -> Result<(), String> {
+    let addresses = (host, port)
+        .to_socket_addrs()
+        .map_err(|err| format!("Failed to resolve {}: {}", host, err))?;
+
+    let mut last_error = format!("{} has no addresses", host);
+    for address in addresses {
+        match TcpStream::connect_timeout(&address, CONNECT_TIMEOUT) {
+            Ok(_) => return Ok(()),
+            Err(err) => last_error = format!("Failed to connect to {}:{}: {}", host, port, err),
+        }
+    }
+    Err(last_error)
+}
+
+// Show the results of the checks and where the program will be sent, and call go_live if the user
+// decides to proceed, or cancel otherwise. Going live is not offered if any blocker failed
+pub fn show_checklist_dialog<F, G>(
+    application: &gtk::Application,
+    results: &[CheckResult],
+    destination: &str,
+    go_live: F,
+    cancel: G,
+) where
+    F: Fn() + 'static,
+    G: Fn() + 'static,
+{
+    let blocked = results
+        .iter()
+        .any(|r| !r.passed && r.severity == CheckSeverity::Blocker);
+
+    let dialog = gtk::Dialog::new_with_buttons(
+        Some("Go-live checklist"),
+        application.get_active_window().as_ref(),
+        gtk::DialogFlags::MODAL,
+        &[("Cancel", gtk::ResponseType::Cancel)],
+    );
+    if !blocked {
+        dialog.add_button("Go live", gtk::ResponseType::Accept);
+    }
+
+    let grid = gtk::Grid::new();
+    grid.set_column_spacing(12);
+    grid.set_row_spacing(4);
+    grid.set_margin_bottom(12);
+
+    for (row, result) in results.iter().enumerate() {
+        let icon_name = match (result.passed, result.severity) {
+            (true, _) => "emblem-ok-symbolic",
+            (false, CheckSeverity::Blocker) => "dialog-error-symbolic",
+            (false, _) => "dialog-warning-symbolic",
+        };
+        let icon = gtk::Image::new_from_icon_name(Some(icon_name), gtk::IconSize::Menu);
+        let label = gtk::Label::new(Some(result.label));
+        let details = gtk::Label::new(Some(&result.details));
+
+        label.set_halign(gtk::Align::Start);
+        details.set_halign(gtk::Align::Start);
+
+        grid.attach(&icon, 0, row as i32, 1, 1);
+        grid.attach(&label, 1, row as i32, 1, 1);
+        grid.attach(&details, 2, row as i32, 1, 1);
+    }
+
+    let destination = gtk::Label::new(Some(&format!("Going live to {}", destination)));
+    destination.set_halign(gtk::Align::Start);
+    destination.set_margin_bottom(12);
+
+    let content_area = dialog.get_content_area();
+    content_area.pack_start(&destination, false, false, 0);
+    content_area.pack_start(&grid, true, true, 0);
+    content_area.set_border_width(10);
+
+    dialog.connect_response(move |dialog, response| {
+        dialog.destroy();
+
+        if response == gtk::ResponseType::Accept {
+            go_live();
+        } else {
+            cancel();
+        }
+    });
+
+    dialog.set_resizable(false);
+    dialog.show_all();
+}
@@ -0,0 +1,218 @@
+use gio::prelude::*;
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::app::{Action, RecordState};
+use crate::scenes;
+use crate::settings::{OutputMode, Settings, VideoResolution};
+
+pub const USAGE: &str = "Usage: gst-wpe-broadcast-demo [OPTION…]
+
+Options:
+  --start-streaming       Start streaming once the program is running
+  --rtmp-url URL          Stream to this RTMP end-point
+  --resolution RES        Output resolution: WIDTHxHEIGHT, 480p, 720p or 1080p
+  --overlay-file FILE     Show this HTML file as overlay
+  --profile NAME          Use the settings of this profile
+  --headless              Run without window and preview, e.g. on appliances
+  --test-sources          Use test patterns and a tone instead of camera and microphone
+  --record                Go live
+  --stop                  Stop streaming
+  --scene NUMBER|NAME     Switch to this scene, counted from 1
+  --marker                Add a marker to the session log
+  --snapshot              Save a snapshot of the program
+  -h, --help              Show this help
+
+The streaming options only apply to this run and are not stored in the settings.
+
+If the application is already running, --record, --stop, --scene, --marker and --snapshot are
+forwarded to it without bringing its window to the front, e.g. for scripts and hotkey daemons.";
+
+// Options given on the command line. Only the actions are forwarded by a second instance
+#[derive(Debug, Default)]
+pub struct Options {
+    pub help: bool,
+    pub start_streaming: bool,
+    pub headless: bool,
+    pub profile: Option<String>,
+    rtmp_url: Option<String>,
+    resolution: Option<VideoResolution>,
+    overlay_file: Option<PathBuf>,
+    test_sources: bool,
+    // Triggered in the running instance, or once started
+    actions: Vec<Action>,
+}
+
+static OPTIONS: OnceLock<Options> = OnceLock::new();
+
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        // Values are either the next argument or follow a "="
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) => (name.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
+        let mut value = || {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("Missing value for {}", name))
+        };
+
+        match name.as_str() {
+            "-h" | "--help" => options.help = true,
+            "--start-streaming" => options.start_streaming = true,
+            "--headless" => options.headless = true,
+            "--test-sources" => options.test_sources = true,
+            "--rtmp-url" => options.rtmp_url = Some(value()?),
+            "--profile" => options.profile = Some(value()?),
+            "--resolution" => options.resolution = Some(value()?.parse::<VideoResolution>()?),
+            "--record" => options.actions.push(Action::Record(RecordState::Recording)),
+            "--stop" => options.actions.push(Action::Record(RecordState::Idle)),
+            "--scene" => options.actions.push(parse_scene(&value()?)?),
+            "--marker" => options.actions.push(Action::Marker),
+            "--snapshot" => options.actions.push(Action::Snapshot),
+            "--overlay-file" => {
+                let path = PathBuf::from(value()?);
+                if !path.is_file() {
+                    return Err(format!("No overlay file {}", path.display()));
+                }
+                // The overlay is loaded by URL, which has to be absolute
+                options.overlay_file = Some(path.canonicalize().map_err(|err| err.to_string())?);
+            }
+            _ => return Err(format!("Unknown option {}", name)),
+        }
+    }
+
+    Ok(options)
+}
+
+// Scenes are given by their number in the scene switcher or their name
+fn parse_scene(value: &str) -> Result<Action, String> {
+    match value.parse::<usize>() {
+        Ok(number) if (1..=scenes::SCENES.len()).contains(&number) => {
+            Ok(Action::SwitchScene(number as i32))
+        }
+        Ok(_) => Err(format!(
+            "No scene {}, there are {}",
+            value,
+            scenes::SCENES.len()
+        )),
+        Err(_) => scenes::find(value)
+            .map(|scene| Action::Scene(scene.name.to_string()))
+            .ok_or_else(|| format!("No scene {}", value)),
+    }
+}
+
+// Make the options available to the rest of the application, once at startup
+pub fn set_options(options: Options) {
+    let _ = OPTIONS.set(options);
+}
+
+pub fn options() -> Option<&'static Options> {
+    OPTIONS.get()
+}
+
+pub fn is_headless() -> bool {
+    options().is_some_and(|options| options.headless)
+}
+
+// The actions given on the command line, in their order
+pub fn actions() -> &'static [Action] {
+    options().map_or(&[], |options| options.actions.as_slice())
+}
+
+// Trigger the actions given on the command line in the already running instance, if there is one.
+// Returns the exit status for GApplication's handle-local-options, -1 to carry on starting up
+pub fn forward_actions(application: &gtk::Application) -> i32 {
+    if actions().is_empty() {
+        return -1;
+    }
+    if let Err(err) = application.register(gio::NONE_CANCELLABLE) {
+        eprintln!("Failed to register the application: {}", err);
+        return 1;
+    }
+    // The first instance triggers them itself once started
+    if !application.get_is_remote() {
+        return -1;
+    }
+
+    // Sent over D-Bus, the application flushes them before exiting
+    for action in actions() {
+        action.clone().trigger(application);
+    }
+    0
+}
+
+impl Options {
+    fn overrides_settings(&self) -> bool {
+        self.rtmp_url.is_some()
+            || self.resolution.is_some()
+            || self.overlay_file.is_some()
+            || self.test_sources
+    }
+
+    fn apply(&self, settings: &mut Settings) {
+        if let Some(ref url) = self.rtmp_url {
+            settings.output_mode = OutputMode::Rtmp;
+            settings.rtmp_location = Some(url.clone());
+            // The URL given already contains the stream key, and the login is for the stored one
+            settings.rtmp_stream_key = None;
+            settings.rtmp_username = None;
+            settings.rtmp_password = None;
+        }
+        if let Some(ref resolution) = self.resolution {
+            settings.video_resolution = *resolution;
+        }
+        if let Some(ref path) = self.overlay_file {
+            if let Ok(uri) = glib::filename_to_uri(path, None) {
+                settings.overlay_from_url = true;
+                settings.overlay_url = Some(uri.to_string());
+            }
+        }
+        if self.test_sources {
+            settings.test_sources = true;
+        }
+    }
+
+    // Put the stored values back in place of the overridden ones
+    fn restore(&self, settings: &mut Settings, stored: &Settings) {
+        if self.rtmp_url.is_some() {
+            settings.output_mode = stored.output_mode;
+            settings.rtmp_location = stored.rtmp_location.clone();
+            settings.rtmp_username = stored.rtmp_username.clone();
+        }
+        if self.resolution.is_some() {
+            settings.video_resolution = stored.video_resolution;
+        }
+        if self.overlay_file.is_some() {
+            settings.overlay_from_url = stored.overlay_from_url;
+            settings.overlay_url = stored.overlay_url.clone();
+        }
+        if self.test_sources {
+            settings.test_sources = stored.test_sources;
+        }
+    }
+}
+
+// Override the loaded settings with the ones given on the command line
+pub fn apply_overrides(settings: &mut Settings) {
+    if let Some(options) = options() {
+        options.apply(settings);
+    }
+}
+
+// Settings about to be saved with the overrides replaced by the stored values, so they only apply
+// to this run. Returns None without overrides
+pub fn without_overrides<F: FnOnce() -> Settings>(
+    settings: &Settings,
+    stored: F,
+) -> Option<Settings> {
+    let options = options().filter(|options| options.overrides_settings())?;
+    let mut settings = settings.clone();
+    options.restore(&mut settings, &stored());
+    Some(settings)
+}
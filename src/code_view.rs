@@ -0,0 +1,136 @@
+use gtk::{self, prelude::*};
+use sourceview::{self, prelude::*};
+
+use std::cell::{Cell, RefCell};
+use std::ops;
+use std::rc::{Rc, Weak};
+
+// Markup languages the editor highlights
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Language {
+    Html,
+    Css,
+}
+
+impl Language {
+    // Identifier of the language definition shipped with GtkSourceView
+    fn id(self) -> &'static str {
+        match self {
+            Language::Html => "html",
+            Language::Css => "css",
+        }
+    }
+}
+
+// Our refcounted code view struct, a GtkSourceView with line numbers, syntax highlighting and
+// undo for editing markup
+#[derive(Clone)]
+pub struct CodeView(Rc<CodeViewInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for CodeView {
+    type Target = CodeViewInner;
+
+    fn deref(&self) -> &CodeViewInner {
+        &self.0
+    }
+}
+
+pub struct CodeViewInner {
+    scrolled_window: gtk::ScrolledWindow,
+    view: sourceview::View,
+    buffer: sourceview::Buffer,
+    // Set while the text is replaced, which is no edit
+    loading: Cell<bool>,
+    // Called whenever the text was edited
+    changed: RefCell<Option<Box<dyn Fn()>>>,
+}
+
+pub struct CodeViewWeak(Weak<CodeViewInner>);
+impl CodeViewWeak {
+    pub fn upgrade(&self) -> Option<CodeView> {
+        self.0.upgrade().map(CodeView)
+    }
+}
+
+impl CodeView {
+    pub fn new() -> Self {
+        let buffer = sourceview::Buffer::new(gtk::NONE_TEXT_TAG_TABLE);
+        buffer.set_highlight_syntax(true);
+
+        let view = sourceview::View::new_with_buffer(&buffer);
+        view.get_style_context().add_class("code-view");
+        view.set_show_line_numbers(true);
+        view.set_auto_indent(true);
+        view.set_tab_width(2);
+
+        let scrolled_window = gtk::ScrolledWindow::new(gtk::NONE_ADJUSTMENT, gtk::NONE_ADJUSTMENT);
+        scrolled_window.add(&view);
+
+        let code_view = CodeView(Rc::new(CodeViewInner {
+            scrolled_window,
+            view,
+            buffer,
+            loading: Cell::new(false),
+            changed: RefCell::new(None),
+        }));
+
+        let code_view_weak = code_view.downgrade();
+        code_view.buffer.connect_changed(move |_| {
+            let code_view = upgrade_weak!(code_view_weak);
+            if !code_view.loading.get() {
+                code_view.notify_changed();
+            }
+        });
+
+        code_view
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> CodeViewWeak {
+        CodeViewWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> &gtk::ScrolledWindow {
+        &self.scrolled_window
+    }
+
+    pub fn get_text_view(&self) -> &sourceview::View {
+        &self.view
+    }
+
+    pub fn set_changed<F: Fn() + 'static>(&self, changed: F) {
+        self.changed.replace(Some(Box::new(changed)));
+    }
+
+    fn notify_changed(&self) {
+        if let Some(ref changed) = *self.changed.borrow() {
+            changed();
+        }
+    }
+
+    pub fn get_text(&self) -> String {
+        self.buffer
+            .get_text(
+                &self.buffer.get_start_iter(),
+                &self.buffer.get_end_iter(),
+                false,
+            )
+            .map(String::from)
+            .unwrap_or_default()
+    }
+
+    // Replace the whole text, which starts a new undo history
+    pub fn set_text(&self, text: &str, language: Language) {
+        let language = sourceview::LanguageManager::get_default()
+            .and_then(|manager| manager.get_language(language.id()));
+        self.buffer.set_language(language.as_ref());
+
+        self.loading.set(true);
+        self.buffer.begin_not_undoable_action();
+        self.buffer.set_text(text);
+        self.buffer.end_not_undoable_action();
+        self.loading.set(false);
+        self.buffer.place_cursor(&self.buffer.get_start_iter());
+    }
+}
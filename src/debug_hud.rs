@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+// Raw counters of the pipeline, the HUD shows the rates between two samples
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DebugStats {
+    // Frames that reached the preview sink
+    pub frames: u64,
+    // Encoded bytes sent to the outputs
+    pub output_bytes: u64,
+    // Frames dropped by all elements according to their QoS messages
+    pub dropped_frames: u64,
+    // In dB
+    pub audio_peak: Option<f64>,
+    // Delay from capture to the preview and to the encoder output of the stream
+    pub preview_latency: Option<Duration>,
+    pub encode_latency: Option<Duration>,
+}
+
+// Text of the HUD for the period between the previous and current sample
+pub fn format_stats(previous: &DebugStats, current: &DebugStats, elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs_f64().max(0.001);
+    let fps = current.frames.saturating_sub(previous.frames) as f64 / seconds;
+    let kbps =
+        current.output_bytes.saturating_sub(previous.output_bytes) as f64 * 8.0 / seconds / 1000.0;
+    let audio = match current.audio_peak {
+        Some(peak) => format!("{:.1} dB", peak),
+        None => "-".to_string(),
+    };
+
+    let latency = |latency: Option<Duration>| match latency {
+        Some(latency) => format!("{} ms", latency.as_millis()),
+        None => "-".to_string(),
+    };
+
+    format!(
+        "{:.1} fps\n{:.0} kbit/s\n{} dropped\naudio {}\nlatency {} / {}",
+        fps,
+        kbps,
+        current.dropped_frames,
+        audio,
+        latency(current.preview_latency),
+        latency(current.encode_latency)
+    )
+}
+
+// JavaScript creating, updating or, if text is None, removing the HUD element in the overlay page
+pub fn script(text: Option<&str>) -> String {
+    match text {
+        Some(text) => format!(
+            "(function() {{\
+               var hud = document.getElementById('debug-hud');\
+               if (!hud) {{\
+                 hud = document.createElement('pre');\
+                 hud.id = 'debug-hud';\
+                 hud.style.cssText = 'position: fixed; top: 8px; right: 8px; z-index: 2147483647; \
+                   margin: 0; padding: 4px 8px; font: 14px monospace; color: #00ff00; \
+                   background: rgba(0, 0, 0, 0.6);';\
+                 document.body.appendChild(hud);\
+               }}\
+               hud.textContent = {text};\
+             }})();",
+            text = js_string(text)
+        ),
+        None => "(function() {\
+                   var hud = document.getElementById('debug-hud');\
+                   if (hud) hud.remove();\
+                 })();"
+            .to_string(),
+    }
+}
+
+// Quote the text as a JavaScript string literal
+fn js_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('\'');
+    for c in text.chars() {
+        match c {
+            '\'' => quoted.push_str("\\'"),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
@@ -0,0 +1,135 @@
+use gst::{self, prelude::*};
+
+use std::fs::{self, OpenOptions};
+use std::path::Path;
+
+use crate::settings::CameraBackend;
+
+// List the devices of the given class (e.g. "Audio/Source") currently known to GStreamer
+pub fn list_devices(class: &str) -> Vec<gst::Device> {
+    let monitor = gst::DeviceMonitor::new();
+    monitor.add_filter(Some(class), None);
+
+    if monitor.start().is_err() {
+        return vec![];
+    }
+    let devices = monitor.get_devices();
+    monitor.stop();
+
+    devices
+}
+
+// Display names of the devices of the given class. Devices listed by more than one provider, e.g.
+// cameras by V4L2 and PipeWire, are only named once
+pub fn list_device_names(class: &str) -> Vec<String> {
+    let mut names = Vec::<String>::new();
+    for device in list_devices(class) {
+        let name = device.get_display_name().to_string();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+// Whether the device is the one with the given display name or, if None, the one behind the given
+// device node
+pub fn matches(device: &gst::Device, display_name: Option<&str>, path: &str) -> bool {
+    match display_name {
+        Some(display_name) => device.get_display_name() == display_name,
+        None => device.get_properties().is_some_and(|properties| {
+            properties.get::<&str>("device.path").ok().flatten() == Some(path)
+        }),
+    }
+}
+
+// The device of the given class matching as in matches()
+pub fn find_device(class: &str, display_name: Option<&str>, path: &str) -> Option<gst::Device> {
+    list_devices(class)
+        .into_iter()
+        .find(|device| matches(device, display_name, path))
+}
+
+// Create a source or sink element for the device with the given display name, falling back to the
+// provided automatic element if none is configured or the device is gone
+pub fn create_source(
+    class: &str,
+    display_name: Option<&str>,
+    fallback: &str,
+    name: &str,
+) -> Result<gst::Element, glib::BoolError> {
+    if let Some(display_name) = display_name {
+        if let Some(device) = list_devices(class)
+            .iter()
+            .find(|d| d.get_display_name() == display_name)
+        {
+            return device.create_element(Some(name));
+        }
+    }
+
+    gst::ElementFactory::make(fallback, Some(name))
+}
+
+// Whether we run inside a Flatpak or Snap sandbox, where the camera devices are only reachable
+// through PipeWire
+pub fn is_sandboxed() -> bool {
+    Path::new("/.flatpak-info").exists() || std::env::var_os("SNAP").is_some()
+}
+
+// Whether any V4L2 device node can be opened by us. Where the access is mediated the nodes exist
+// but belong to the PipeWire daemon only
+fn v4l2_accessible() -> bool {
+    let entries = match fs::read_dir("/dev") {
+        Ok(entries) => entries,
+        Err(_) => return false,
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("video"))
+        .any(|entry| {
+            OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(entry.path())
+                .is_ok()
+        })
+}
+
+// The backend the automatic choice of the settings stands for on this system
+pub fn camera_backend(backend: CameraBackend) -> CameraBackend {
+    match backend {
+        CameraBackend::Auto if is_sandboxed() || !v4l2_accessible() => CameraBackend::PipeWire,
+        CameraBackend::Auto => CameraBackend::V4l2,
+        backend => backend,
+    }
+}
+
+// Create the source element for the camera with the given display name through the given backend,
+// the default camera of the backend if None or the device is gone. The same camera can be listed
+// by both backends, so only the devices of the backend's provider are considered
+pub fn create_camera_source(
+    display_name: Option<&str>,
+    backend: CameraBackend,
+    name: &str,
+) -> Result<gst::Element, glib::BoolError> {
+    let (provider, fallback) = match camera_backend(backend) {
+        CameraBackend::PipeWire => ("pipewiredeviceprovider", "pipewiresrc"),
+        _ => ("v4l2deviceprovider", "v4l2src"),
+    };
+
+    if let Some(display_name) = display_name {
+        let device = list_devices("Video/Source").into_iter().find(|device| {
+            device.get_display_name() == display_name
+                && device
+                    .get_parent()
+                    .and_then(|parent| parent.downcast::<gst::DeviceProvider>().ok())
+                    .and_then(|parent| parent.get_factory())
+                    .is_some_and(|factory| factory.get_name() == provider)
+        });
+        if let Some(device) = device {
+            return device.create_element(Some(name));
+        }
+    }
+
+    gst::ElementFactory::make(fallback, Some(name))
+}
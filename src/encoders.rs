@@ -0,0 +1,311 @@
+use gst::{self, prelude::*};
+
+use std::collections::HashMap;
+
+// Selects the best available H.264 encoder instead of a specific family
+pub const AUTO: &str = "auto";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoCodec {
+    H264,
+    H265,
+    Vp9,
+}
+
+impl VideoCodec {
+    pub fn label(self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "H.264",
+            VideoCodec::H265 => "H.265",
+            VideoCodec::Vp9 => "VP9",
+        }
+    }
+
+    // Parser putting the encoded stream into the shape muxers expect, if needed
+    pub fn parser(self) -> Option<&'static str> {
+        match self {
+            VideoCodec::H264 => Some("h264parse"),
+            VideoCodec::H265 => Some("h265parse"),
+            VideoCodec::Vp9 => None,
+        }
+    }
+
+    fn caps_name(self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "video/x-h264",
+            VideoCodec::H265 => "video/x-h265",
+            VideoCodec::Vp9 => "video/x-vp9",
+        }
+    }
+}
+
+// An encoder family we know how to drive with structured options instead of a raw launch string
+pub struct EncoderFamily {
+    // Name of the GStreamer element factory
+    pub factory: &'static str,
+    pub label: &'static str,
+    pub codec: VideoCodec,
+    // Raw video caps the encoder expects as input
+    input_caps: &'static str,
+    // Properties for the bitrate and the distance between keyframes
+    bitrate_property: &'static str,
+    // Factor from kbit/s to the unit of the bitrate property
+    bitrate_scale: u32,
+    keyframe_property: &'static str,
+    // The V4L2 encoders take the bitrate and keyframe distance as extra controls instead of
+    // properties
+    extra_controls: bool,
+    // Properties that are always set on the encoder
+    base_properties: &'static str,
+    // Enum or flags properties exposed as dropdowns in the settings dialog
+    pub options: &'static [&'static str],
+    // Profiles that can be asked for in the caps after the encoder
+    pub profiles: &'static [&'static str],
+}
+
+const H264_PROFILES: &[&str] = &["baseline", "main", "high"];
+const H265_PROFILES: &[&str] = &["main", "main-10"];
+
+// Families of the same codec are in the order of preference for the automatic selection, i.e.
+// hardware encoders first
+pub const ENCODER_FAMILIES: &[EncoderFamily] = &[
+    EncoderFamily {
+        factory: "vaapih264enc",
+        label: "VA-API H.264 (Intel/AMD)",
+        codec: VideoCodec::H264,
+        input_caps: "video/x-raw,format=NV12",
+        bitrate_property: "bitrate",
+        bitrate_scale: 1,
+        keyframe_property: "keyframe-period",
+        extra_controls: false,
+        base_properties: "",
+        options: &["rate-control"],
+        profiles: H264_PROFILES,
+    },
+    EncoderFamily {
+        factory: "nvh264enc",
+        label: "NVENC H.264 (NVIDIA)",
+        codec: VideoCodec::H264,
+        input_caps: "video/x-raw,format=NV12",
+        bitrate_property: "bitrate",
+        bitrate_scale: 1,
+        keyframe_property: "gop-size",
+        extra_controls: false,
+        base_properties: "",
+        options: &["preset"],
+        profiles: H264_PROFILES,
+    },
+    EncoderFamily {
+        factory: "v4l2h264enc",
+        label: "V4L2 H.264 (Raspberry Pi and other SoCs)",
+        codec: VideoCodec::H264,
+        input_caps: "video/x-raw,format=I420",
+        bitrate_property: "video_bitrate",
+        bitrate_scale: 1000,
+        keyframe_property: "h264_i_frame_period",
+        extra_controls: true,
+        base_properties: "",
+        options: &[],
+        profiles: H264_PROFILES,
+    },
+    EncoderFamily {
+        factory: "x264enc",
+        label: "x264 H.264 (software)",
+        codec: VideoCodec::H264,
+        input_caps: "video/x-raw,format=I420",
+        bitrate_property: "bitrate",
+        bitrate_scale: 1,
+        keyframe_property: "key-int-max",
+        extra_controls: false,
+        base_properties: "",
+        options: &["speed-preset", "tune"],
+        profiles: H264_PROFILES,
+    },
+    EncoderFamily {
+        factory: "vaapih265enc",
+        label: "VA-API H.265 (Intel/AMD)",
+        codec: VideoCodec::H265,
+        input_caps: "video/x-raw,format=NV12",
+        bitrate_property: "bitrate",
+        bitrate_scale: 1,
+        keyframe_property: "keyframe-period",
+        extra_controls: false,
+        base_properties: "",
+        options: &["rate-control"],
+        profiles: H265_PROFILES,
+    },
+    EncoderFamily {
+        factory: "nvh265enc",
+        label: "NVENC H.265 (NVIDIA)",
+        codec: VideoCodec::H265,
+        input_caps: "video/x-raw,format=NV12",
+        bitrate_property: "bitrate",
+        bitrate_scale: 1,
+        keyframe_property: "gop-size",
+        extra_controls: false,
+        base_properties: "",
+        options: &["preset"],
+        profiles: H265_PROFILES,
+    },
+    EncoderFamily {
+        factory: "x265enc",
+        label: "x265 H.265 (software)",
+        codec: VideoCodec::H265,
+        input_caps: "video/x-raw,format=I420",
+        bitrate_property: "bitrate",
+        bitrate_scale: 1,
+        keyframe_property: "key-int-max",
+        extra_controls: false,
+        base_properties: "",
+        options: &["speed-preset", "tune"],
+        profiles: H265_PROFILES,
+    },
+    EncoderFamily {
+        factory: "vaapivp9enc",
+        label: "VA-API VP9 (Intel/AMD)",
+        codec: VideoCodec::Vp9,
+        input_caps: "video/x-raw,format=NV12",
+        bitrate_property: "bitrate",
+        bitrate_scale: 1,
+        keyframe_property: "keyframe-period",
+        extra_controls: false,
+        base_properties: "",
+        options: &["rate-control"],
+        profiles: &[],
+    },
+    EncoderFamily {
+        factory: "vp9enc",
+        label: "libvpx VP9 (software)",
+        codec: VideoCodec::Vp9,
+        input_caps: "video/x-raw,format=I420",
+        bitrate_property: "target-bitrate",
+        bitrate_scale: 1000,
+        keyframe_property: "keyframe-max-dist",
+        extra_controls: false,
+        base_properties: "deadline=1 cpu-used=8",
+        options: &["end-usage"],
+        profiles: &[],
+    },
+];
+
+impl EncoderFamily {
+    // Build the launch fragment for this encoder with the given bitrate in kbit/s, keyframe
+    // distance in frames, profile and selected option values
+    pub fn launch_description(
+        &self,
+        bitrate: u32,
+        keyframe_interval: u32,
+        profile: Option<&str>,
+        options: &HashMap<String, String>,
+    ) -> String {
+        let rate_control = format!(
+            "{}={},{}={}",
+            self.bitrate_property,
+            u64::from(bitrate) * u64::from(self.bitrate_scale),
+            self.keyframe_property,
+            keyframe_interval
+        );
+
+        let mut encoder = if self.extra_controls {
+            format!(
+                "{} extra-controls=\"controls,{}\"",
+                self.factory, rate_control
+            )
+        } else {
+            format!("{} {}", self.factory, rate_control.replace(',', " "))
+        };
+        if !self.base_properties.is_empty() {
+            encoder.push_str(&format!(" {}", self.base_properties));
+        }
+        for option in self.options {
+            if let Some(value) = options.get(*option) {
+                encoder.push_str(&format!(" {}={}", option, value));
+            }
+        }
+
+        let mut output_caps = self.codec.caps_name().to_string();
+        if let Some(profile) = profile.filter(|profile| self.profiles.contains(profile)) {
+            output_caps.push_str(&format!(",profile={}", profile));
+        }
+
+        format!("{} ! {} ! {}", self.input_caps, encoder, output_caps)
+    }
+}
+
+// Set the keyframe distance in frames on the encoders of a custom launch fragment that we know,
+// unless the fragment already sets it itself
+pub fn with_keyframe_interval(description: &str, keyframe_interval: u32) -> String {
+    description
+        .split('!')
+        .map(|element| {
+            let family = element
+                .split_whitespace()
+                .next()
+                .and_then(find_family)
+                .filter(|family| !family.extra_controls);
+            match family {
+                Some(family) if !element.contains(&format!("{}=", family.keyframe_property)) => {
+                    let trailing = &element[element.trim_end().len()..];
+                    format!(
+                        "{} {}={}{}",
+                        element.trim_end(),
+                        family.keyframe_property,
+                        keyframe_interval,
+                        trailing
+                    )
+                }
+                _ => element.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("!")
+}
+
+pub fn find_family(factory: &str) -> Option<&'static EncoderFamily> {
+    ENCODER_FAMILIES.iter().find(|f| f.factory == factory)
+}
+
+// The preferred H.264 encoder that is installed, H.264 being the codec all outputs can carry
+pub fn best_family() -> Option<&'static EncoderFamily> {
+    available_families()
+        .into_iter()
+        .find(|f| f.codec == VideoCodec::H264)
+}
+
+// The encoder families for which the element is present in the GStreamer registry
+pub fn available_families() -> Vec<&'static EncoderFamily> {
+    ENCODER_FAMILIES
+        .iter()
+        .filter(|f| gst::ElementFactory::find(f.factory).is_some())
+        .collect()
+}
+
+// Introspect the possible values of an enum or flags property of the given encoder
+pub fn option_values(factory: &str, property: &str) -> Vec<String> {
+    let element = match gst::ElementFactory::make(factory, None) {
+        Ok(element) => element,
+        Err(_) => return vec![],
+    };
+
+    let pspec = match element.find_property(property) {
+        Some(pspec) => pspec,
+        None => return vec![],
+    };
+
+    let type_ = pspec.get_value_type();
+    if let Some(class) = glib::EnumClass::new(type_) {
+        class
+            .get_values()
+            .iter()
+            .map(|v| v.get_nick().to_string())
+            .collect()
+    } else if let Some(class) = glib::FlagsClass::new(type_) {
+        class
+            .get_values()
+            .iter()
+            .map(|v| v.get_nick().to_string())
+            .collect()
+    } else {
+        vec![]
+    }
+}
@@ -0,0 +1,265 @@
+use gdk_pixbuf::{self, prelude::*};
+use gtk::{self, prelude::*};
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde::Deserialize;
+
+use crate::templates;
+use crate::utils;
+
+// Width the template previews are scaled to
+const PREVIEW_WIDTH: i32 = 320;
+
+// One template as listed in the gallery index. URLs may be relative to the index
+#[derive(Debug, Clone, Deserialize)]
+struct GalleryEntry {
+    name: String,
+    #[serde(default)]
+    description: String,
+    html: String,
+    #[serde(default)]
+    css: Option<String>,
+    #[serde(default)]
+    preview: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GalleryIndex {
+    templates: Vec<GalleryEntry>,
+}
+
+// Resolve a URL from the index against the URL of the index itself
+fn resolve_url(index_url: &str, url: &str) -> String {
+    if url.contains("://") {
+        return url.to_string();
+    }
+
+    match index_url.rfind('/') {
+        Some(pos) => format!("{}/{}", &index_url[..pos], url.trim_start_matches('/')),
+        None => url.to_string(),
+    }
+}
+
+fn entry_text(entry: &gtk::Entry) -> String {
+    entry.get_text().map(|t| t.to_string()).unwrap_or_default()
+}
+
+fn load_preview(image: &gtk::Image, data: &[u8]) -> Result<(), glib::Error> {
+    let loader = gdk_pixbuf::PixbufLoader::new();
+    loader.write(data)?;
+    loader.close()?;
+
+    if let Some(pixbuf) = loader.get_pixbuf() {
+        let height = pixbuf.get_height() * PREVIEW_WIDTH / pixbuf.get_width().max(1);
+        image.set_from_pixbuf(
+            pixbuf
+                .scale_simple(PREVIEW_WIDTH, height, gdk_pixbuf::InterpType::Bilinear)
+                .as_ref(),
+        );
+    }
+
+    Ok(())
+}
+
+// Download the markup of the template and add it to the local library
+fn install(index_url: &str, entry: &GalleryEntry, status: &gtk::Label) {
+    status.set_text(&format!("Downloading {}…", entry.name));
+
+    let name = entry.name.clone();
+    let css_url = entry.css.as_ref().map(|css| resolve_url(index_url, css));
+    let status = status.clone();
+    utils::fetch(&resolve_url(index_url, &entry.html), move |html| {
+        let html = match html {
+            Ok(html) => String::from_utf8_lossy(&html).into_owned(),
+            Err(err) => {
+                status.set_text(&format!("Failed to download {}: {}", name, err));
+                return;
+            }
+        };
+
+        let finish = move |css: String| match templates::install(&name, &html, &css) {
+            Ok(directory) => {
+                status.set_text(&format!("Installed {} into {}", name, directory.display()))
+            }
+            Err(err) => status.set_text(&format!("Failed to install {}: {}", name, err)),
+        };
+
+        match css_url {
+            Some(css_url) => utils::fetch(&css_url, move |css| {
+                finish(
+                    css.map(|css| String::from_utf8_lossy(&css).into_owned())
+                        .unwrap_or_default(),
+                )
+            }),
+            None => finish(String::new()),
+        }
+    });
+}
+
+// Fetch the index and fill the list with its templates
+fn refresh(
+    index_url: &str,
+    list_box: &gtk::ListBox,
+    entries: &Rc<RefCell<Vec<GalleryEntry>>>,
+    status: &gtk::Label,
+) {
+    for child in list_box.get_children() {
+        list_box.remove(&child);
+    }
+    entries.borrow_mut().clear();
+
+    if index_url.is_empty() {
+        status.set_text("Please enter the URL of a template index");
+        return;
+    }
+    status.set_text("Loading template index…");
+
+    let list_box = list_box.clone();
+    let entries = entries.clone();
+    let status = status.clone();
+    utils::fetch(index_url, move |data| {
+        let index = match data.map_err(|err| err.to_string()).and_then(|data| {
+            serde_json::from_slice::<GalleryIndex>(&data).map_err(|err| err.to_string())
+        }) {
+            Ok(index) => index,
+            Err(err) => {
+                status.set_text(&format!("Failed to load the template index: {}", err));
+                return;
+            }
+        };
+
+        for entry in &index.templates {
+            let label = gtk::Label::new(Some(&entry.name));
+            label.set_halign(gtk::Align::Start);
+            label.show();
+            list_box.insert(&label, -1);
+        }
+        status.set_text(&format!("{} templates available", index.templates.len()));
+        entries.replace(index.templates);
+    });
+}
+
+// Show the gallery of overlay templates published at the configured index URL
+pub fn show_gallery_dialog(application: &gtk::Application) {
+    let dialog = gtk::Dialog::new_with_buttons(
+        Some("Template gallery"),
+        application.get_active_window().as_ref(),
+        gtk::DialogFlags::MODAL,
+        &[("Close", gtk::ResponseType::Close)],
+    );
+
+    let settings = utils::load_settings();
+    let entries = Rc::new(RefCell::new(Vec::<GalleryEntry>::new()));
+
+    let index_url = gtk::Entry::new();
+    index_url.set_placeholder_text(Some("https://…/index.json"));
+    if let Some(ref url) = settings.template_gallery_url {
+        index_url.set_text(url);
+    }
+    index_url.set_hexpand(true);
+    let refresh_button = gtk::Button::new_with_mnemonic("_Refresh");
+
+    let url_box = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+    url_box.pack_start(&index_url, true, true, 0);
+    url_box.pack_start(&refresh_button, false, false, 0);
+
+    let list_box = gtk::ListBox::new();
+    let scrolled_window = gtk::ScrolledWindow::new(gtk::NONE_ADJUSTMENT, gtk::NONE_ADJUSTMENT);
+    scrolled_window.set_size_request(200, 300);
+    scrolled_window.add(&list_box);
+
+    let preview = gtk::Image::new();
+    preview.set_size_request(PREVIEW_WIDTH, -1);
+    let description = gtk::Label::new(None);
+    description.set_line_wrap(true);
+    description.set_max_width_chars(40);
+    let install_button = gtk::Button::new_with_mnemonic("_Install");
+    install_button.set_sensitive(false);
+
+    let details_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
+    details_box.pack_start(&preview, false, false, 0);
+    details_box.pack_start(&description, false, false, 0);
+    details_box.pack_end(&install_button, false, false, 0);
+
+    let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    hbox.pack_start(&scrolled_window, true, true, 0);
+    hbox.pack_start(&details_box, false, false, 0);
+
+    let status = gtk::Label::new(None);
+    status.set_halign(gtk::Align::Start);
+
+    let content_area = dialog.get_content_area();
+    content_area.set_spacing(8);
+    content_area.pack_start(&url_box, false, false, 0);
+    content_area.pack_start(&hbox, true, true, 0);
+    content_area.pack_start(&status, false, false, 0);
+    content_area.set_border_width(10);
+
+    let list_box_clone = list_box.clone();
+    let entries_clone = entries.clone();
+    let status_clone = status.clone();
+    let index_url_clone = index_url.clone();
+    refresh_button.connect_clicked(move |_| {
+        let url = entry_text(&index_url_clone);
+
+        let mut settings = utils::load_settings();
+        settings.template_gallery_url = Some(url.clone()).filter(|url| !url.is_empty());
+        utils::save_settings(&settings);
+
+        refresh(&url, &list_box_clone, &entries_clone, &status_clone);
+    });
+
+    let entries_clone = entries.clone();
+    let index_url_clone = index_url.clone();
+    let install_button_clone = install_button.clone();
+    list_box.connect_row_selected(move |_, row| {
+        let entry = match row.and_then(|row| {
+            entries_clone
+                .borrow()
+                .get(row.get_index() as usize)
+                .cloned()
+        }) {
+            Some(entry) => entry,
+            None => {
+                install_button_clone.set_sensitive(false);
+                return;
+            }
+        };
+
+        description.set_text(&entry.description);
+        install_button_clone.set_sensitive(true);
+        preview.clear();
+
+        if let Some(ref url) = entry.preview {
+            let url = resolve_url(&entry_text(&index_url_clone), url);
+            let preview = preview.clone();
+            utils::fetch(&url, move |data| {
+                if let Ok(data) = data {
+                    let _ = load_preview(&preview, &data);
+                }
+            });
+        }
+    });
+
+    let list_box_clone = list_box.clone();
+    install_button.connect_clicked(move |_| {
+        let entry = list_box_clone
+            .get_selected_row()
+            .and_then(|row| entries.borrow().get(row.get_index() as usize).cloned());
+        if let Some(entry) = entry {
+            install(&entry_text(&index_url), &entry, &status);
+        }
+    });
+
+    dialog.connect_response(|dialog, _| {
+        dialog.destroy();
+    });
+
+    dialog.show_all();
+
+    if settings.template_gallery_url.is_some() {
+        refresh_button.clicked();
+    }
+}
@@ -0,0 +1,98 @@
+use gst::{self, prelude::*, MessageView};
+
+use std::sync::Mutex;
+
+// Context types of the GL elements, see gst_gl_handle_set_context()
+const GL_DISPLAY: &str = "gst.gl.GLDisplay";
+const APP_CONTEXT: &str = "gst.gl.app_context";
+
+// GL display and context shared by all GL elements. They are kept for the lifetime of the
+// application, so elements added later and new pipelines reuse the ones of the preview sink
+// instead of creating and leaking new ones
+static CONTEXTS: Mutex<Vec<gst::Context>> = Mutex::new(Vec::new());
+
+fn lookup(context_type: &str) -> Option<gst::Context> {
+    CONTEXTS
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|context| context.get_context_type() == context_type)
+        .cloned()
+}
+
+// Keep the context unless there already is one of its type. Returns whether it was kept
+fn store(context: &gst::Context) -> bool {
+    let context_type = context.get_context_type();
+    if context_type != GL_DISPLAY && context_type != APP_CONTEXT {
+        return false;
+    }
+
+    let mut contexts = CONTEXTS.lock().unwrap();
+    if contexts
+        .iter()
+        .any(|stored| stored.get_context_type() == context_type)
+    {
+        return false;
+    }
+    contexts.push(context.clone());
+    true
+}
+
+// Wrap the GL context GTK draws the preview with, so the GL elements create theirs shared with it
+// and the frames can be handed over without copies. None until the sink was started
+fn app_context(sink: &gst::Element) -> Option<gst::Context> {
+    let other_context = sink.get_property("other-context").ok()?;
+    let other_context = other_context.downcast::<gst::Object>().ok()?;
+    other_context.get()?;
+
+    let mut context = gst::Context::new(APP_CONTEXT, true);
+    context
+        .get_mut()
+        .unwrap()
+        .get_mut_structure()
+        .set_value("context", other_context.into_send_value());
+    store(&context);
+    Some(context)
+}
+
+// Hand the shared GL display and context to the GL elements of the pipeline when they ask for
+// them, and share the ones they create. The preview sink, if any, provides the application context
+pub fn share(pipeline: &gst::Pipeline, preview_sink: Option<&gst::Element>) {
+    // Bins pass their contexts on to all elements added to them
+    for context in CONTEXTS.lock().unwrap().iter() {
+        pipeline.set_context(context);
+    }
+
+    let bus = pipeline.get_bus().expect("Pipeline had no bus");
+    let pipeline_weak = pipeline.downgrade();
+    let preview_sink = preview_sink.cloned();
+    bus.set_sync_handler(move |_bus, msg| {
+        match msg.view() {
+            MessageView::NeedContext(need_context) => {
+                let context_type = need_context.get_context_type();
+                let context = lookup(context_type).or_else(|| match preview_sink {
+                    Some(ref sink) if context_type == APP_CONTEXT => app_context(sink),
+                    _ => None,
+                });
+                let element = msg
+                    .get_src()
+                    .and_then(|src| src.downcast::<gst::Element>().ok());
+                if let (Some(context), Some(element)) = (context, element) {
+                    element.set_context(&context);
+                }
+            }
+            MessageView::HaveContext(have_context) => {
+                let context = have_context.get_context();
+                if store(&context) {
+                    if let Some(pipeline) = pipeline_weak.upgrade() {
+                        pipeline.set_context(&context);
+                    }
+                }
+            }
+            _ => (),
+        }
+
+        // The bus watch still gets to see all messages
+        gst::BusSyncReply::Pass
+    });
+}
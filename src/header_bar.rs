@@ -1,10 +1,22 @@
-use gio;
 use gtk::{self, prelude::*};
 
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
 use crate::app::{Action, RecordState};
+use crate::devices;
+use crate::scenes;
+use crate::utils;
 
 pub struct HeaderBar {
+    header_bar: gtk::HeaderBar,
     record: gtk::ToggleButton,
+    // True while the record button is toggled from code, which doesn't need to be confirmed
+    record_from_code: Rc<Cell<bool>>,
+    on_air_time: gtk::Label,
+    scene: gtk::ComboBoxText,
+    delay: gtk::LevelBar,
 }
 
 // Create headerbar for the application
@@ -22,11 +34,37 @@ impl HeaderBar {
         let main_menu_image =
             gtk::Image::new_from_icon_name(Some("open-menu-symbolic"), gtk::IconSize::Menu);
         main_menu.set_image(Some(&main_menu_image));
+        main_menu.set_tooltip_text(Some("Main menu"));
+        utils::set_accessible(&main_menu, "Main menu", "Settings, recordings and about");
 
         // Create the menu model with the menu items. These directly activate our application
         // actions by their name
         let main_menu_model = gio::Menu::new();
         main_menu_model.append(Some("Settings"), Some(Action::Settings.full_name()));
+        main_menu_model.append(
+            Some("Repair recordings"),
+            Some(Action::RepairRecordings.full_name()),
+        );
+        main_menu_model.append(Some("Rundown"), Some(Action::Rundown.full_name()));
+        main_menu_model.append(
+            Some("Template gallery"),
+            Some(Action::TemplateGallery.full_name()),
+        );
+        main_menu_model.append(
+            Some("Monitor audio"),
+            Some(Action::MonitorAudio(true).full_name()),
+        );
+        main_menu_model.append(Some("Debug HUD"), Some(Action::DebugHud(true).full_name()));
+        main_menu_model.append(Some("Projector"), Some(Action::Projector(true).full_name()));
+        main_menu_model.append(
+            Some("Detach editor"),
+            Some(Action::DetachEditor(true).full_name()),
+        );
+        main_menu_model.append(Some("Log"), Some(Action::LogViewer.full_name()));
+        main_menu_model.append(
+            Some("Keyboard shortcuts"),
+            Some(Action::Shortcuts.full_name()),
+        );
         main_menu_model.append(Some("About"), Some(Action::About.full_name()));
         main_menu.set_menu_model(Some(&main_menu_model));
 
@@ -38,24 +76,282 @@ impl HeaderBar {
         let record_button_image =
             gtk::Image::new_from_icon_name(Some("network-cellular"), gtk::IconSize::Menu);
         record_button.set_image(Some(&record_button_image));
+        record_button.set_tooltip_text(Some("Go live"));
+        utils::set_accessible(
+            &record_button,
+            "Go live",
+            "Stream the program to the configured end-point",
+        );
 
-        record_button.connect_toggled(|record_button| {
+        // Going live by hand is confirmed first, stopping happens right away
+        let record_from_code = Rc::new(Cell::new(false));
+        let record_from_code_clone = record_from_code.clone();
+        record_button.connect_toggled(move |record_button| {
             let app = gio::Application::get_default().expect("No default application");
-            Action::Record(RecordState::from(record_button.get_active())).trigger(&app);
+            let active = record_button.get_active();
+            if active && !record_from_code_clone.get() {
+                Action::GoLive.trigger(&app);
+            } else {
+                Action::Record(RecordState::from(active)).trigger(&app);
+            }
         });
 
         // Place the record button on the left
         header_bar.pack_start(&record_button);
 
+        // Time on air next to the record button, only shown while streaming
+        let on_air_time = gtk::Label::new(None);
+        on_air_time.get_style_context().add_class("on-air-time");
+        on_air_time.set_no_show_all(true);
+        utils::set_accessible(
+            &on_air_time,
+            "Time on air",
+            "How long the program has been live",
+        );
+
+        header_bar.pack_start(&on_air_time);
+
+        // Create the microphone selector, switching the audio input right away
+        let audio_input = gtk::ComboBoxText::new();
+        audio_input.append(Some(""), "Automatic microphone");
+        for name in devices::list_device_names("Audio/Source") {
+            audio_input.append(Some(&name), &name);
+        }
+        let audio_device = utils::load_settings().audio_device.unwrap_or_default();
+        if !audio_input.set_active_id(Some(&audio_device)) {
+            audio_input.set_active_id(Some(""));
+        }
+
+        audio_input.connect_changed(|audio_input| {
+            let app = gio::Application::get_default().expect("No default application");
+            if let Some(device) = audio_input.get_active_id() {
+                Action::AudioInput(device.to_string()).trigger(&app);
+            }
+        });
+
+        audio_input.set_tooltip_text(Some("Microphone"));
+        utils::set_accessible(
+            &audio_input,
+            "Microphone",
+            "Audio input used for the stream",
+        );
+
+        header_bar.pack_start(&audio_input);
+
+        // Mutes the microphone feed on air
+        let mute_button = gtk::ToggleButton::new();
+        let mute_button_image =
+            gtk::Image::new_from_icon_name(Some("audio-volume-muted"), gtk::IconSize::Menu);
+        mute_button.set_image(Some(&mute_button_image));
+        mute_button
+            .clone()
+            .upcast::<gtk::Actionable>()
+            .set_action_name(Some(Action::MuteAudio(true).full_name()));
+        mute_button.set_tooltip_text(Some("Mute (Ctrl+Shift+M)"));
+        utils::set_accessible(&mute_button, "Mute", "Mute the audio of the program");
+
+        header_bar.pack_start(&mute_button);
+
+        // Plays the program audio on the monitor output, so the operator isn't streaming blind
+        let monitor_button = gtk::ToggleButton::new();
+        let monitor_button_image =
+            gtk::Image::new_from_icon_name(Some("audio-headphones"), gtk::IconSize::Menu);
+        monitor_button.set_image(Some(&monitor_button_image));
+        monitor_button
+            .clone()
+            .upcast::<gtk::Actionable>()
+            .set_action_name(Some(Action::MonitorAudio(true).full_name()));
+        monitor_button.set_tooltip_text(Some("Monitor audio (Ctrl+Shift+H)"));
+        utils::set_accessible(
+            &monitor_button,
+            "Monitor audio",
+            "Play the program audio on the monitor output",
+        );
+
+        header_bar.pack_start(&monitor_button);
+
+        // Scene selector, switching the composition of camera and overlay right away
+        let scene = gtk::ComboBoxText::new();
+        for s in scenes::SCENES {
+            scene.append(Some(s.name), s.label);
+        }
+        scene.set_active_id(Some(scenes::DEFAULT_SCENE));
+
+        scene.connect_changed(|scene| {
+            let app = gio::Application::get_default().expect("No default application");
+            if let Some(name) = scene.get_active_id() {
+                Action::Scene(name.to_string()).trigger(&app);
+            }
+        });
+
+        scene.set_tooltip_text(Some("Scene"));
+        utils::set_accessible(&scene, "Scene", "Composition of camera and overlay on air");
+
+        header_bar.pack_start(&scene);
+
+        // Fill level of the broadcast delay buffer, only shown while streaming with a delay
+        let delay = gtk::LevelBar::new_for_interval(0.0, 1.0);
+        delay.set_size_request(80, -1);
+        delay.set_valign(gtk::Align::Center);
+        delay.set_tooltip_text(Some("Broadcast delay buffer"));
+        delay.set_no_show_all(true);
+        utils::set_accessible(
+            &delay,
+            "Broadcast delay buffer",
+            "How much of the configured delay is buffered",
+        );
+
+        header_bar.pack_start(&delay);
+
+        // Big panic button, bound to the panic action so it also reflects the hotkey
+        let panic_button = gtk::ToggleButton::new_with_label("PANIC");
+        panic_button
+            .clone()
+            .upcast::<gtk::Actionable>()
+            .set_action_name(Some(Action::Panic(true).full_name()));
+        let style_context = panic_button.get_style_context();
+        style_context.add_class("destructive-action");
+        style_context.add_class("panic");
+        panic_button.set_tooltip_text(Some("Cut to standby and mute (F12)"));
+        utils::set_accessible(
+            &panic_button,
+            "Panic",
+            "Cut the program to standby, mute all audio and drop the delay buffer",
+        );
+
+        header_bar.pack_end(&panic_button);
+
+        // Shows the slate instead of the program without disconnecting the viewers
+        let pause_button = gtk::ToggleButton::new();
+        let pause_button_image =
+            gtk::Image::new_from_icon_name(Some("media-playback-pause"), gtk::IconSize::Menu);
+        pause_button.set_image(Some(&pause_button_image));
+        pause_button
+            .clone()
+            .upcast::<gtk::Actionable>()
+            .set_action_name(Some(Action::PauseStream(true).full_name()));
+        pause_button.set_tooltip_text(Some("Be right back"));
+        utils::set_accessible(
+            &pause_button,
+            "Be right back",
+            "Show the slate and mute the audio while staying connected",
+        );
+
+        header_bar.pack_end(&pause_button);
+
+        // Saves the current program frame, e.g. for thumbnails
+        let snapshot_button =
+            gtk::Button::new_from_icon_name(Some("camera-photo-symbolic"), gtk::IconSize::Menu);
+        snapshot_button
+            .clone()
+            .upcast::<gtk::Actionable>()
+            .set_action_name(Some(Action::Snapshot.full_name()));
+        snapshot_button.set_tooltip_text(Some("Snapshot (Ctrl+Shift+S)"));
+        utils::set_accessible(
+            &snapshot_button,
+            "Snapshot",
+            "Save the current program frame as an image",
+        );
+
+        header_bar.pack_end(&snapshot_button);
+
         // Insert the headerbar as titlebar into the window
         window.set_titlebar(Some(&header_bar));
 
         HeaderBar {
+            header_bar,
             record: record_button,
+            record_from_code,
+            on_air_time,
+            scene,
+            delay,
         }
     }
 
     pub fn set_record_active(&self, active: bool) {
+        self.record_from_code.set(true);
         self.record.set_active(active);
+        self.record_from_code.set(false);
+    }
+
+    // Toggle the record button as if it was clicked
+    pub fn click_record(&self) {
+        self.record.set_active(!self.record.get_active());
+    }
+
+    // Reflect scene switches triggered from elsewhere, e.g. the rundown
+    pub fn set_scene(&self, name: &str) {
+        self.scene.set_active_id(Some(name));
+    }
+
+    pub fn set_delay_fill(&self, fill: Option<f64>) {
+        match fill {
+            Some(fill) => {
+                self.delay.set_value(fill);
+                self.delay.show();
+            }
+            None => self.delay.hide(),
+        }
+    }
+
+    // Show how long we have been live, in red if an output has problems
+    pub fn set_on_air_time(&self, duration: Option<Duration>, failing: bool) {
+        let seconds = match duration {
+            Some(duration) => duration.as_secs(),
+            None => {
+                self.on_air_time.hide();
+                return;
+            }
+        };
+
+        self.on_air_time.set_text(&format!(
+            "{:02}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        ));
+        let style_context = self.on_air_time.get_style_context();
+        if failing {
+            style_context.add_class("failing");
+        } else {
+            style_context.remove_class("failing");
+        }
+        self.on_air_time.show();
+    }
+
+    // Show whether we are live, both visually and for screen readers which announce the name
+    // change of the record button
+    // Show whether the program is on air or only previewed locally, and where it goes when live
+    pub fn set_on_air(&self, on_air: bool, destination: &str) {
+        let (state, name, description) = if on_air {
+            (
+                "On air",
+                "Stop streaming",
+                "Streaming started, the program is on air",
+            )
+        } else {
+            (
+                "Preview",
+                "Go live",
+                "Streaming stopped, the program is only previewed locally",
+            )
+        };
+
+        let destination = if destination.is_empty() {
+            "no destination configured"
+        } else {
+            destination
+        };
+        self.header_bar
+            .set_subtitle(Some(&format!("{} · {}", state, destination)));
+
+        let style_context = self.record.get_style_context();
+        if on_air {
+            style_context.add_class("on-air");
+        } else {
+            style_context.remove_class("on-air");
+        }
+        self.record.set_tooltip_text(Some(name));
+        utils::set_accessible(&self.record, name, description);
     }
 }
@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+use crate::APPLICATION_NAME;
+
+// Settings kept in the keyring instead of the settings file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Secret {
+    StreamKey,
+    RtmpPassword,
+    UploadCredential,
+    ExtraStreamKeys,
+    RtspPassword,
+    SrtPassphrase,
+    WhipToken,
+    YouTubeApiKey,
+    RemoteToken,
+}
+
+impl Secret {
+    pub const ALL: &'static [Secret] = &[
+        Secret::StreamKey,
+        Secret::RtmpPassword,
+        Secret::UploadCredential,
+        Secret::ExtraStreamKeys,
+        Secret::RtspPassword,
+        Secret::SrtPassphrase,
+        Secret::WhipToken,
+        Secret::YouTubeApiKey,
+        Secret::RemoteToken,
+    ];
+
+    fn kind(self) -> &'static str {
+        match self {
+            Secret::StreamKey => "rtmp-stream-key",
+            Secret::RtmpPassword => "rtmp-password",
+            Secret::UploadCredential => "upload-secret",
+            Secret::ExtraStreamKeys => "extra-rtmp-stream-keys",
+            Secret::RtspPassword => "rtsp-password",
+            Secret::SrtPassphrase => "srt-passphrase",
+            Secret::WhipToken => "whip-token",
+            Secret::YouTubeApiKey => "youtube-api-key",
+            Secret::RemoteToken => "remote-token",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Secret::StreamKey => "stream key",
+            Secret::RtmpPassword => "RTMP password",
+            Secret::UploadCredential => "upload secret",
+            Secret::ExtraStreamKeys => "stream keys of the further RTMP end-points",
+            Secret::RtspPassword => "RTSP camera password",
+            Secret::SrtPassphrase => "SRT passphrase",
+            Secret::WhipToken => "WHIP bearer token",
+            Secret::YouTubeApiKey => "YouTube API key",
+            Secret::RemoteToken => "remote control token",
+        }
+    }
+}
+
+// Secrets per profile as last read from or written to the keyring. Looking them up is slow and
+// the settings are loaded all the time, so the keyring is only asked once per profile
+static SECRETS: Mutex<BTreeMap<(String, Secret), Option<String>>> = Mutex::new(BTreeMap::new());
+
+// Attributes identifying the secret of the profile in the Secret Service keyring
+fn attributes(profile: &str, secret: Secret) -> Vec<&str> {
+    vec![
+        "application",
+        APPLICATION_NAME,
+        "profile",
+        profile,
+        "kind",
+        secret.kind(),
+    ]
+}
+
+// Run secret-tool from libsecret, which talks to whatever keyring the session provides
+fn secret_tool(arguments: &[&str], input: Option<&str>) -> io::Result<Vec<u8>> {
+    let mut child = Command::new("secret-tool")
+        .args(arguments)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| io::Error::new(err.kind(), format!("Failed to run secret-tool: {}", err)))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.unwrap_or_default().as_bytes())?;
+    }
+
+    let output = child.wait_with_output()?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+// The secret of the profile, None if there is none or the keyring is not available
+pub fn lookup(profile: &str, secret: Secret) -> Option<String> {
+    SECRETS
+        .lock()
+        .unwrap()
+        .entry((profile.to_string(), secret))
+        .or_insert_with(|| {
+            let mut arguments = vec!["lookup"];
+            arguments.extend(attributes(profile, secret));
+            // Nothing is printed and the lookup fails if there is no such secret
+            secret_tool(&arguments, None)
+                .ok()
+                .map(|value| String::from_utf8_lossy(&value).trim().to_string())
+                .filter(|value| !value.is_empty())
+        })
+        .clone()
+}
+
+// Store the secret of the profile in the keyring, or remove it if None. Without keyring the
+// value is still used until the application quits
+pub fn store(profile: &str, secret: Secret, value: Option<&str>) -> io::Result<()> {
+    let value = value.map(str::trim).filter(|value| !value.is_empty());
+    SECRETS
+        .lock()
+        .unwrap()
+        .insert((profile.to_string(), secret), value.map(String::from));
+
+    match value {
+        Some(value) => {
+            let label = format!("{} {} ({})", APPLICATION_NAME, secret.label(), profile);
+            let mut arguments = vec!["store", "--label", &label];
+            arguments.extend(attributes(profile, secret));
+            secret_tool(&arguments, Some(value))?;
+        }
+        None => {
+            let mut arguments = vec!["clear"];
+            arguments.extend(attributes(profile, secret));
+            secret_tool(&arguments, None)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,174 @@
+use gtk::{self, prelude::*};
+
+use std::ops;
+use std::rc::{Rc, Weak};
+
+use crate::pipeline::PipelineWeak;
+use crate::settings::LayerSettings;
+use crate::utils;
+
+// Controls of one layer, all in percent
+struct LayerControls {
+    opacity: gtk::Scale,
+    crop_left: gtk::SpinButton,
+    crop_top: gtk::SpinButton,
+    crop_right: gtk::SpinButton,
+    crop_bottom: gtk::SpinButton,
+}
+
+impl LayerControls {
+    fn new(layer: &LayerSettings) -> Self {
+        let opacity = gtk::Scale::new_with_range(gtk::Orientation::Horizontal, 0.0, 100.0, 1.0);
+        opacity.set_value(layer.opacity * 100.0);
+        opacity.set_hexpand(true);
+
+        let crop_spin = |value: f64| {
+            let spin = gtk::SpinButton::new_with_range(0.0, 45.0, 1.0);
+            spin.set_value(value * 100.0);
+            spin
+        };
+
+        LayerControls {
+            opacity,
+            crop_left: crop_spin(layer.crop_left),
+            crop_top: crop_spin(layer.crop_top),
+            crop_right: crop_spin(layer.crop_right),
+            crop_bottom: crop_spin(layer.crop_bottom),
+        }
+    }
+
+    fn spin_buttons(&self) -> [&gtk::SpinButton; 4] {
+        [
+            &self.crop_left,
+            &self.crop_top,
+            &self.crop_right,
+            &self.crop_bottom,
+        ]
+    }
+
+    fn get(&self) -> LayerSettings {
+        LayerSettings {
+            opacity: self.opacity.get_value() / 100.0,
+            crop_left: self.crop_left.get_value() / 100.0,
+            crop_top: self.crop_top.get_value() / 100.0,
+            crop_right: self.crop_right.get_value() / 100.0,
+            crop_bottom: self.crop_bottom.get_value() / 100.0,
+        }
+    }
+}
+
+// Our refcounted layers panel struct, changing the opacity and cropping of the camera and the
+// overlay while live
+#[derive(Clone)]
+pub struct Layers(Rc<LayersInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for Layers {
+    type Target = LayersInner;
+
+    fn deref(&self) -> &LayersInner {
+        &self.0
+    }
+}
+
+pub struct LayersInner {
+    container: gtk::Grid,
+    camera: LayerControls,
+    overlay: LayerControls,
+    pipeline: PipelineWeak,
+}
+
+pub struct LayersWeak(Weak<LayersInner>);
+impl LayersWeak {
+    pub fn upgrade(&self) -> Option<Layers> {
+        self.0.upgrade().map(Layers)
+    }
+}
+
+impl Layers {
+    pub fn new(pipeline: PipelineWeak) -> Self {
+        let settings = utils::load_settings();
+        let camera = LayerControls::new(&settings.camera_layer);
+        let overlay = LayerControls::new(&settings.overlay_layer);
+
+        let container = gtk::Grid::new();
+        container.set_border_width(6);
+        container.set_column_spacing(4);
+        container.set_row_spacing(4);
+
+        for (column, title) in [
+            "Opacity (%)",
+            "Left crop (%)",
+            "Top crop (%)",
+            "Right crop (%)",
+            "Bottom crop (%)",
+        ]
+        .iter()
+        .enumerate()
+        {
+            let label = gtk::Label::new(Some(title));
+            label.set_halign(gtk::Align::Start);
+            container.attach(&label, column as i32 + 1, 0, 1, 1);
+        }
+
+        for (row, (name, controls)) in [("Camera", &camera), ("Overlay", &overlay)]
+            .iter()
+            .enumerate()
+        {
+            let row = row as i32 + 1;
+            let label = gtk::Label::new(Some(name));
+            label.set_halign(gtk::Align::Start);
+            container.attach(&label, 0, row, 1, 1);
+            container.attach(&controls.opacity, 1, row, 1, 1);
+            for (column, spin) in controls.spin_buttons().iter().enumerate() {
+                container.attach(*spin, column as i32 + 2, row, 1, 1);
+            }
+        }
+
+        let layers = Layers(Rc::new(LayersInner {
+            container,
+            camera,
+            overlay,
+            pipeline,
+        }));
+
+        for controls in &[&layers.camera, &layers.overlay] {
+            let layers_weak = layers.downgrade();
+            controls.opacity.connect_value_changed(move |_| {
+                let layers = upgrade_weak!(layers_weak);
+                layers.apply();
+            });
+
+            for spin in &controls.spin_buttons() {
+                let layers_weak = layers.downgrade();
+                spin.connect_value_changed(move |_| {
+                    let layers = upgrade_weak!(layers_weak);
+                    layers.apply();
+                });
+            }
+        }
+
+        layers
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> LayersWeak {
+        LayersWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> &gtk::Grid {
+        &self.container
+    }
+
+    // Store the layer settings and show the program with them
+    fn apply(&self) {
+        let mut settings = utils::load_settings();
+        settings.camera_layer = self.camera.get();
+        settings.overlay_layer = self.overlay.get();
+        utils::save_settings(&settings);
+
+        let pipeline = &self.pipeline;
+        let pipeline = upgrade_weak!(pipeline);
+        pipeline.update_layers(&settings);
+    }
+}
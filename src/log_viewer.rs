@@ -0,0 +1,124 @@
+use gtk::{self, prelude::*};
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use log::Level;
+
+use crate::logger::{self, Entry};
+
+// Name of the viewer window, to find it again instead of opening a second one
+const WINDOW_NAME: &str = "log-viewer";
+
+fn entry_row(entry: &Entry) -> gtk::ListBoxRow {
+    let time = entry.time.get(11..19).unwrap_or(&entry.time);
+    let label = gtk::Label::new(Some(&format!(
+        "{}  {:<5}  {}: {}",
+        time, entry.level, entry.target, entry.message
+    )));
+    label.set_halign(gtk::Align::Start);
+    label.set_selectable(true);
+    label.set_line_wrap(true);
+    label.get_style_context().add_class("monospace");
+
+    let row = gtk::ListBoxRow::new();
+    // The filter only has the row to go by
+    row.set_widget_name(&entry.level.to_string());
+    row.add(&label);
+    row.show_all();
+    row
+}
+
+// Show the messages logged by the application, with the least severe ones to show selectable. The
+// window is not modal so it can be kept open next to the main window while live
+pub fn show_log_viewer(application: &gtk::Application) {
+    if let Some(window) = application
+        .get_windows()
+        .into_iter()
+        .find(|window| window.get_widget_name().as_deref() == Some(WINDOW_NAME))
+    {
+        window.present();
+        return;
+    }
+
+    let dialog = gtk::Dialog::new_with_buttons(
+        Some("Log"),
+        application.get_active_window().as_ref(),
+        gtk::DialogFlags::DESTROY_WITH_PARENT,
+        &[("Close", gtk::ResponseType::Close)],
+    );
+    dialog.set_widget_name(WINDOW_NAME);
+    dialog.set_default_size(700, 400);
+
+    let level_label = gtk::Label::new(Some("Show"));
+    let level = gtk::ComboBoxText::new();
+    level.append(Some("error"), "Errors");
+    level.append(Some("warn"), "Warnings and errors");
+    level.append(Some("info"), "All messages");
+    level.set_active_id(Some("warn"));
+
+    let level_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    level_box.pack_start(&level_label, false, false, 0);
+    level_box.pack_start(&level, false, false, 0);
+
+    let list_box = gtk::ListBox::new();
+    list_box.set_selection_mode(gtk::SelectionMode::None);
+    let scrolled_window = gtk::ScrolledWindow::new(gtk::NONE_ADJUSTMENT, gtk::NONE_ADJUSTMENT);
+    scrolled_window.add(&list_box);
+
+    let content_area = dialog.get_content_area();
+    content_area.set_spacing(8);
+    content_area.pack_start(&level_box, false, false, 0);
+    content_area.pack_start(&scrolled_window, true, true, 0);
+    content_area.set_border_width(10);
+
+    // Least severe level shown
+    let shown = Rc::new(Cell::new(Level::Warn));
+    let shown_clone = shown.clone();
+    list_box.set_filter_func(Some(Box::new(move |row| {
+        row.get_widget_name()
+            .and_then(|name| name.parse::<Level>().ok())
+            .is_some_and(|level| level <= shown_clone.get())
+    })));
+
+    let list_box_clone = list_box.clone();
+    level.connect_changed(move |level| {
+        let id = level.get_active_id();
+        shown.set(
+            id.and_then(|id| id.parse::<Level>().ok())
+                .unwrap_or(Level::Warn),
+        );
+        list_box_clone.invalidate_filter();
+    });
+
+    let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+    for entry in logger::subscribe(sender) {
+        list_box.insert(&entry_row(&entry), -1);
+    }
+
+    // Follow new messages unless scrolled up to read older ones
+    let adjustment = scrolled_window.get_vadjustment();
+    let list_box_clone = list_box.clone();
+    receiver.attach(None, move |entry| {
+        let following = adjustment.as_ref().is_none_or(|adjustment| {
+            adjustment.get_value() + adjustment.get_page_size() >= adjustment.get_upper() - 1.0
+        });
+        list_box_clone.insert(&entry_row(&entry), -1);
+        if following {
+            if let Some(ref adjustment) = adjustment {
+                let adjustment = adjustment.clone();
+                glib::idle_add_local(move || {
+                    adjustment.set_value(adjustment.get_upper() - adjustment.get_page_size());
+                    glib::Continue(false)
+                });
+            }
+        }
+        glib::Continue(true)
+    });
+
+    dialog.connect_response(|dialog, _| {
+        dialog.destroy();
+    });
+
+    dialog.show_all();
+}
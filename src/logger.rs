@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::cli;
+
+// Older entries are dropped, the viewer is for what happened recently
+const MAX_ENTRIES: usize = 1000;
+
+// One message logged by the application
+#[derive(Debug, Clone)]
+pub struct Entry {
+    // ISO 8601 local time
+    pub time: String,
+    pub level: Level,
+    // Module the message was logged from
+    pub target: String,
+    pub message: String,
+}
+
+// Keeps the recent messages for the log viewer and forwards them to our GStreamer debug category,
+// so GST_DEBUG=broadcast-demo:5 shows them in between the ones of the elements
+struct Logger {
+    state: Mutex<State>,
+    category: OnceLock<gst::DebugCategory>,
+}
+
+struct State {
+    entries: VecDeque<Entry>,
+    // Only one viewer is open at a time
+    listener: Option<glib::Sender<Entry>>,
+}
+
+static LOGGER: Logger = Logger {
+    state: Mutex::new(State {
+        entries: VecDeque::new(),
+        listener: None,
+    }),
+    category: OnceLock::new(),
+};
+
+fn gst_level(level: Level) -> gst::DebugLevel {
+    match level {
+        Level::Error => gst::DebugLevel::Error,
+        Level::Warn => gst::DebugLevel::Warning,
+        Level::Info => gst::DebugLevel::Info,
+        Level::Debug => gst::DebugLevel::Debug,
+        Level::Trace => gst::DebugLevel::Trace,
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let category = self.category.get_or_init(|| {
+            gst::DebugCategory::new(
+                "broadcast-demo",
+                gst::DebugColorFlags::empty(),
+                Some("Broadcast demo application"),
+            )
+        });
+        category.log(
+            None::<&gst::Object>,
+            gst_level(record.level()),
+            record.file().unwrap_or_default(),
+            record.module_path().unwrap_or_default(),
+            record.line().unwrap_or_default(),
+            *record.args(),
+        );
+
+        // Nobody would look at the viewer
+        if cli::is_headless() && record.level() <= Level::Warn {
+            eprintln!("{}: {}", record.level(), record.args());
+        }
+
+        let entry = Entry {
+            time: glib::DateTime::new_now_local()
+                .format("%FT%T%z")
+                .map(|t| t.to_string())
+                .unwrap_or_default(),
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+        };
+
+        let mut state = self.state.lock().unwrap();
+        if let Some(ref sender) = state.listener {
+            // The viewer was closed
+            if sender.send(entry.clone()).is_err() {
+                state.listener = None;
+            }
+        }
+        if state.entries.len() == MAX_ENTRIES {
+            state.entries.pop_front();
+        }
+        state.entries.push_back(entry);
+    }
+
+    fn flush(&self) {}
+}
+
+// Install our logger, once at startup after GStreamer was initialized
+pub fn init() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(LevelFilter::Info);
+    }
+}
+
+// Get all messages logged from now on sent to the given channel, replacing the previous listener.
+// Returns the ones logged so far, oldest first
+pub fn subscribe(sender: glib::Sender<Entry>) -> Vec<Entry> {
+    let mut state = LOGGER.state.lock().unwrap();
+    state.listener = Some(sender);
+    state.entries.iter().cloned().collect()
+}
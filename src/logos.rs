@@ -0,0 +1,250 @@
+use gtk::{self, prelude::*};
+
+use std::cell::RefCell;
+use std::fs;
+use std::ops;
+use std::path::Path;
+use std::rc::{Rc, Weak};
+
+use crate::assets;
+use crate::pipeline::PipelineWeak;
+use crate::settings::{Logo, LogoPosition};
+use crate::utils;
+
+const STYLESHEET: &str = r#"
+.broadcast-logos {
+    position: fixed;
+    display: flex;
+    flex-direction: column;
+    gap: 1vh;
+    margin: 2vh 2vw;
+    pointer-events: none;
+}
+.broadcast-logos.top-left { top: 0; left: 0; align-items: flex-start; }
+.broadcast-logos.top-right { top: 0; right: 0; align-items: flex-end; }
+.broadcast-logos.bottom-left { bottom: 0; left: 0; align-items: flex-start; }
+.broadcast-logos.bottom-right { bottom: 0; right: 0; align-items: flex-end; }
+"#;
+
+// Markup showing the logos in their corners, appended to the overlay HTML. Logos that can't be
+// read are left out
+pub fn overlay_html(logos: &[Logo]) -> String {
+    let mut html = String::new();
+    for position in LogoPosition::ALL {
+        let images = logos
+            .iter()
+            .filter(|logo| logo.position == *position)
+            .filter_map(|logo| {
+                let path = Path::new(logo.path.as_ref()?);
+                let data = fs::read(path).ok()?;
+                Some(format!(
+                    "<img src=\"{}\" style=\"width: {}vw\"/>",
+                    assets::data_url(assets::mime_type(path)?, &data),
+                    logo.size
+                ))
+            })
+            .collect::<String>();
+        if !images.is_empty() {
+            html.push_str(&format!(
+                "<div class=\"broadcast-logos {}\">{}</div>\n",
+                position.id(),
+                images
+            ));
+        }
+    }
+
+    if html.is_empty() {
+        html
+    } else {
+        format!("<style>{}</style>\n{}", STYLESHEET, html)
+    }
+}
+
+// Controls of one logo
+struct LogoRow {
+    container: gtk::Box,
+    file: gtk::FileChooserButton,
+    position: gtk::ComboBoxText,
+    size: gtk::SpinButton,
+}
+
+impl LogoRow {
+    fn get_logo(&self) -> Logo {
+        Logo {
+            path: self
+                .file
+                .get_filename()
+                .map(|path| path.to_string_lossy().into_owned()),
+            position: LogoPosition::from(self.position.get_active_id()),
+            size: self.size.get_value_as_int() as u32,
+        }
+    }
+}
+
+// Our refcounted logos panel struct, for branding the stream with images in the corners of the
+// overlay
+#[derive(Clone)]
+pub struct Logos(Rc<LogosInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for Logos {
+    type Target = LogosInner;
+
+    fn deref(&self) -> &LogosInner {
+        &self.0
+    }
+}
+
+pub struct LogosInner {
+    container: gtk::Box,
+    rows_box: gtk::Box,
+    rows: RefCell<Vec<LogoRow>>,
+    pipeline: PipelineWeak,
+    // Renders the overlay again with the new logos
+    changed: RefCell<Option<Box<dyn Fn()>>>,
+}
+
+pub struct LogosWeak(Weak<LogosInner>);
+impl LogosWeak {
+    pub fn upgrade(&self) -> Option<Logos> {
+        self.0.upgrade().map(Logos)
+    }
+}
+
+impl Logos {
+    pub fn new(pipeline: PipelineWeak) -> Self {
+        let label = gtk::Label::new(Some(
+            "Images shown on top of the overlay, with their width in percent of the video width",
+        ));
+        label.set_halign(gtk::Align::Start);
+        label.set_line_wrap(true);
+
+        let rows_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
+        let scrolled_window = gtk::ScrolledWindow::new(gtk::NONE_ADJUSTMENT, gtk::NONE_ADJUSTMENT);
+        scrolled_window.add(&rows_box);
+
+        let add_button = gtk::Button::new_with_mnemonic("_Add logo");
+
+        let container = gtk::Box::new(gtk::Orientation::Vertical, 4);
+        container.set_border_width(6);
+        container.pack_start(&label, false, false, 0);
+        container.pack_start(&scrolled_window, true, true, 0);
+        container.pack_start(&add_button, false, false, 0);
+
+        let logos = Logos(Rc::new(LogosInner {
+            container,
+            rows_box,
+            rows: RefCell::new(Vec::new()),
+            pipeline,
+            changed: RefCell::new(None),
+        }));
+
+        for logo in &utils::load_settings().logos {
+            logos.add_row(logo);
+        }
+
+        let logos_weak = logos.downgrade();
+        add_button.connect_clicked(move |_| {
+            let logos = upgrade_weak!(logos_weak);
+            logos.add_row(&Logo::default());
+        });
+
+        logos
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> LogosWeak {
+        LogosWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> &gtk::Box {
+        &self.container
+    }
+
+    pub fn set_changed<F: Fn() + 'static>(&self, changed: F) {
+        self.changed.replace(Some(Box::new(changed)));
+    }
+
+    fn add_row(&self, logo: &Logo) {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some("Images"));
+        filter.add_mime_type("image/*");
+        let file = gtk::FileChooserButton::new("Logo", gtk::FileChooserAction::Open);
+        file.add_filter(&filter);
+        if let Some(ref path) = logo.path {
+            file.set_filename(path);
+        }
+
+        let position = gtk::ComboBoxText::new();
+        for logo_position in LogoPosition::ALL {
+            position.append(Some(logo_position.id()), logo_position.label());
+        }
+        position.set_active_id(Some(logo.position.id()));
+
+        let size = gtk::SpinButton::new_with_range(1.0, 100.0, 1.0);
+        size.set_value(f64::from(logo.size));
+        size.set_tooltip_text(Some("Width in percent of the video width"));
+
+        let remove_button =
+            gtk::Button::new_from_icon_name(Some("list-remove-symbolic"), gtk::IconSize::Button);
+        remove_button.set_tooltip_text(Some("Remove logo"));
+
+        let container = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        container.pack_start(&file, true, true, 0);
+        container.pack_start(&position, false, false, 0);
+        container.pack_start(&size, false, false, 0);
+        container.pack_start(&remove_button, false, false, 0);
+        container.show_all();
+        self.rows_box.pack_start(&container, false, false, 0);
+
+        let logos_weak = self.downgrade();
+        file.connect_file_set(move |_| {
+            let logos = upgrade_weak!(logos_weak);
+            logos.apply();
+        });
+        let logos_weak = self.downgrade();
+        position.connect_changed(move |_| {
+            let logos = upgrade_weak!(logos_weak);
+            logos.apply();
+        });
+        let logos_weak = self.downgrade();
+        size.connect_value_changed(move |_| {
+            let logos = upgrade_weak!(logos_weak);
+            logos.apply();
+        });
+
+        let logos_weak = self.downgrade();
+        let row_container = container.clone();
+        remove_button.connect_clicked(move |_| {
+            let logos = upgrade_weak!(logos_weak);
+            logos
+                .rows
+                .borrow_mut()
+                .retain(|row| row.container != row_container);
+            logos.rows_box.remove(&row_container);
+            logos.apply();
+        });
+
+        self.rows.borrow_mut().push(LogoRow {
+            container,
+            file,
+            position,
+            size,
+        });
+    }
+
+    // Store the logos and render the overlay with them
+    fn apply(&self) {
+        let mut settings = utils::load_settings();
+        settings.logos = self.rows.borrow().iter().map(LogoRow::get_logo).collect();
+        utils::save_settings(&settings);
+
+        let pipeline = &self.pipeline;
+        let pipeline = upgrade_weak!(pipeline);
+        pipeline.set_logos(&settings.logos);
+
+        if let Some(ref changed) = *self.changed.borrow() {
+            changed();
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use gtk::{self, prelude::*};
+
+use std::cell::RefCell;
+use std::ops;
+use std::rc::{Rc, Weak};
+
+use crate::pipeline::PipelineWeak;
+use crate::settings::{LowerThirdSettings, LowerThirdStyle};
+use crate::utils;
+
+// Id of the element injected into the overlay page
+const ELEMENT_ID: &str = "broadcast-lower-third";
+
+// Injected together with the element, the styles only differ in look and animation
+const STYLESHEET: &str = r#"
+#broadcast-lower-third {
+    position: fixed;
+    left: 6vw;
+    bottom: 8vh;
+    font-family: sans-serif;
+    opacity: 0;
+    pointer-events: none;
+    transition: opacity 0.5s ease, transform 0.5s ease;
+}
+#broadcast-lower-third.visible {
+    opacity: 1;
+    transform: none;
+}
+#broadcast-lower-third .name {
+    font-size: 3.2vw;
+    font-weight: bold;
+}
+#broadcast-lower-third .title {
+    font-size: 2vw;
+}
+#broadcast-lower-third.classic {
+    transform: translateX(-110%);
+    padding: 0.6vw 2vw;
+    color: white;
+    background: rgba(20, 20, 20, 0.85);
+    border-left: 0.5vw solid #e01b24;
+}
+#broadcast-lower-third.modern {
+    transform: translateY(50%);
+}
+#broadcast-lower-third.modern .name {
+    display: inline-block;
+    padding: 0.4vw 1.5vw;
+    color: white;
+    background: #3584e4;
+}
+#broadcast-lower-third.modern .title {
+    padding: 0.4vw 1.5vw;
+    color: #241f31;
+    background: rgba(255, 255, 255, 0.9);
+}
+#broadcast-lower-third.minimal {
+    color: white;
+    text-shadow: 0 0 0.4vw black, 0 0 0.8vw black;
+}
+"#;
+
+// JavaScript adding the lower third to the page if needed, filling it in and fading it in
+fn show_script(settings: &LowerThirdSettings) -> String {
+    format!(
+        r#"(function() {{
+    var root = document.getElementById("{id}");
+    if (!root) {{
+        var style = document.createElement("style");
+        style.textContent = {stylesheet};
+        (document.head || document.documentElement).appendChild(style);
+        root = document.createElement("div");
+        root.id = "{id}";
+        root.innerHTML = '<div class="name"></div><div class="title"></div>';
+        document.body.appendChild(root);
+    }}
+    root.className = "{style}";
+    root.querySelector(".name").textContent = {name};
+    root.querySelector(".title").textContent = {title};
+    // Apply the hidden state first so the transition runs
+    void root.offsetWidth;
+    root.classList.add("visible");
+}})();"#,
+        id = ELEMENT_ID,
+        stylesheet = serde_json::Value::from(STYLESHEET),
+        style = settings.style.id(),
+        name = serde_json::Value::from(settings.name.as_str()),
+        title = serde_json::Value::from(settings.title.as_str()),
+    )
+}
+
+fn hide_script() -> String {
+    format!(
+        r#"(function() {{
+    var root = document.getElementById("{id}");
+    if (root) {{
+        root.classList.remove("visible");
+    }}
+}})();"#,
+        id = ELEMENT_ID,
+    )
+}
+
+// Our refcounted lower third editor struct, showing a name and title on top of the overlay
+// without editing its markup
+#[derive(Clone)]
+pub struct LowerThird(Rc<LowerThirdInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for LowerThird {
+    type Target = LowerThirdInner;
+
+    fn deref(&self) -> &LowerThirdInner {
+        &self.0
+    }
+}
+
+pub struct LowerThirdInner {
+    container: gtk::Grid,
+    name: gtk::Entry,
+    title: gtk::Entry,
+    style: gtk::ComboBoxText,
+    duration: gtk::SpinButton,
+    pipeline: PipelineWeak,
+    // Hides the lower third once its duration is over
+    hide_timeout: RefCell<Option<glib::SourceId>>,
+}
+
+pub struct LowerThirdWeak(Weak<LowerThirdInner>);
+impl LowerThirdWeak {
+    pub fn upgrade(&self) -> Option<LowerThird> {
+        self.0.upgrade().map(LowerThird)
+    }
+}
+
+impl LowerThird {
+    pub fn new(pipeline: PipelineWeak) -> Self {
+        let settings = utils::load_settings().lower_third;
+
+        let container = gtk::Grid::new();
+        container.set_column_spacing(4);
+        container.set_row_spacing(4);
+        container.set_border_width(6);
+
+        let name = gtk::Entry::new();
+        name.set_text(&settings.name);
+        name.set_hexpand(true);
+        let title = gtk::Entry::new();
+        title.set_text(&settings.title);
+
+        let style = gtk::ComboBoxText::new();
+        for lower_third_style in LowerThirdStyle::ALL {
+            style.append(Some(lower_third_style.id()), lower_third_style.label());
+        }
+        style.set_active_id(Some(settings.style.id()));
+
+        let duration = gtk::SpinButton::new_with_range(0.0, 600.0, 1.0);
+        duration.set_value(f64::from(settings.duration));
+        duration.set_tooltip_text(Some("0 keeps the lower third on screen until it is hidden"));
+
+        for (row, (label, widget)) in [
+            ("Name", name.upcast_ref::<gtk::Widget>()),
+            ("Title", title.upcast_ref()),
+            ("Style", style.upcast_ref()),
+            ("Duration (s)", duration.upcast_ref()),
+        ]
+        .iter()
+        .enumerate()
+        {
+            let label = gtk::Label::new(Some(label));
+            label.set_halign(gtk::Align::Start);
+            container.attach(&label, 0, row as i32, 1, 1);
+            container.attach(*widget, 1, row as i32, 2, 1);
+        }
+
+        let show_button = gtk::Button::new_with_mnemonic("_Show");
+        utils::set_accessible(
+            &show_button,
+            "Show lower third",
+            "Animate the lower third into the overlay",
+        );
+        let hide_button = gtk::Button::new_with_mnemonic("_Hide");
+        utils::set_accessible(
+            &hide_button,
+            "Hide lower third",
+            "Animate the lower third out of the overlay",
+        );
+        container.attach(&show_button, 1, 4, 1, 1);
+        container.attach(&hide_button, 2, 4, 1, 1);
+
+        let lower_third = LowerThird(Rc::new(LowerThirdInner {
+            container,
+            name,
+            title,
+            style,
+            duration,
+            pipeline,
+            hide_timeout: RefCell::new(None),
+        }));
+
+        let lower_third_weak = lower_third.downgrade();
+        show_button.connect_clicked(move |_| {
+            let lower_third = upgrade_weak!(lower_third_weak);
+            lower_third.show();
+        });
+
+        let lower_third_weak = lower_third.downgrade();
+        hide_button.connect_clicked(move |_| {
+            let lower_third = upgrade_weak!(lower_third_weak);
+            lower_third.hide();
+        });
+
+        lower_third
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> LowerThirdWeak {
+        LowerThirdWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> &gtk::Grid {
+        &self.container
+    }
+
+    fn get_settings(&self) -> LowerThirdSettings {
+        LowerThirdSettings {
+            name: self.name.get_text().map(String::from).unwrap_or_default(),
+            title: self.title.get_text().map(String::from).unwrap_or_default(),
+            style: LowerThirdStyle::from(self.style.get_active_id()),
+            duration: self.duration.get_value_as_int() as u32,
+        }
+    }
+
+    // Show the lower third with the current contents, replacing one that is already shown
+    fn show(&self) {
+        let lower_third = self.get_settings();
+
+        let mut settings = utils::load_settings();
+        settings.lower_third = lower_third.clone();
+        utils::save_settings(&settings);
+
+        let pipeline = &self.pipeline;
+        let pipeline = upgrade_weak!(pipeline);
+        pipeline.run_javascript(&show_script(&lower_third));
+
+        if let Some(source_id) = self.hide_timeout.borrow_mut().take() {
+            glib::source_remove(source_id);
+        }
+        if lower_third.duration > 0 {
+            let lower_third_weak = self.downgrade();
+            let source_id = glib::timeout_add_seconds_local(lower_third.duration, move || {
+                let lower_third = upgrade_weak!(lower_third_weak, glib::Continue(false));
+                lower_third.hide_timeout.replace(None);
+                lower_third.hide();
+                glib::Continue(false)
+            });
+            self.hide_timeout.replace(Some(source_id));
+        }
+    }
+
+    fn hide(&self) {
+        if let Some(source_id) = self.hide_timeout.borrow_mut().take() {
+            glib::source_remove(source_id);
+        }
+
+        let pipeline = &self.pipeline;
+        let pipeline = upgrade_weak!(pipeline);
+        pipeline.run_javascript(&hide_script());
+    }
+}
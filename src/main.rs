@@ -1,17 +1,66 @@
+#[macro_use]
+extern crate glib;
+
 #[macro_use]
 mod macros;
 mod about_dialog;
 mod app;
+mod appearance;
+mod asset_manager;
+mod assets;
+mod audio_mixer;
 mod audio_vumeter;
+mod chat;
+mod checklist;
+mod cli;
+mod code_view;
+mod debug_hud;
+mod devices;
+mod encoders;
+mod gallery;
+mod gl_context;
 mod header_bar;
+mod keyring;
+mod layers;
+mod log_viewer;
+mod logger;
+mod logos;
+mod lower_third;
+mod markup_editor;
+mod media_controls;
+mod midi;
+mod output;
+mod overlay_bridge;
+mod overlay_variables;
+mod overlay_widgets;
+mod pip_drag;
 mod pipeline;
+mod pipeline_object;
+mod profiles;
+mod recovery;
+mod remote;
+mod rtp;
+mod rundown;
+mod scenes;
+mod scheduler;
+mod session_log;
 mod settings;
+mod shortcuts_window;
+mod stats_log;
+mod stream_health;
+mod template_library;
+mod templates;
+mod ticker;
+mod toasts;
+mod uploader;
 mod utils;
+mod validation;
 
 use gio::prelude::*;
 
 use std::env::args;
 use std::error;
+use std::process;
 
 use crate::app::App;
 
@@ -21,22 +70,59 @@ use crate::app::App;
 pub const APPLICATION_NAME: &str = "com.igalia.gstwpe.broadcast.demo";
 
 fn main() -> Result<(), Box<dyn error::Error>> {
+    let mut args = args();
+    let program = args.next().unwrap_or_default();
+    let options = match cli::parse(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, cli::USAGE);
+            process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+    // Only for this run, the profile selected in the settings stays the same
+    if let Some(ref profile) = options.profile {
+        if !profiles::exists(profile) {
+            eprintln!(
+                "No profile {}, available are: {}",
+                profile,
+                profiles::list_names().join(", ")
+            );
+            process::exit(2);
+        }
+        profiles::set_active(profile, false)?;
+    }
+    cli::set_options(options);
+
     // Initialize GStreamer. This checks, among other things, what plugins are available
     gst::init()?;
+    logger::init();
 
     // Create an application with our name and the default flags. By default, applications can only
     // have a single instance and any second instance will only activate the first one again
     let application =
         gtk::Application::new(Some(APPLICATION_NAME), gio::ApplicationFlags::empty())?;
 
+    // A second instance started with actions on the command line forwards them to the first one
+    // and exits, without activating it
+    application
+        .connect_local("handle-local-options", false, |values| {
+            let application = values[0].get::<gtk::Application>().ok().flatten()?;
+            Some(cli::forward_actions(&application).to_value())
+        })
+        .expect("Failed to connect to handle-local-options");
+
     // On application startup (of the first instance) we create our application. A second instance
     // would not run this
     application.connect_startup(|application| {
         App::on_startup(application);
     });
 
-    // And now run the application until the end
-    application.run(&args().collect::<Vec<_>>());
+    // And now run the application until the end. Our options were handled above already
+    application.run(&[program]);
 
     Ok(())
 }
@@ -0,0 +1,191 @@
+use gtk::{self, prelude::*};
+
+use std::cell::RefCell;
+use std::ops;
+use std::rc::{Rc, Weak};
+
+use crate::code_view::{CodeView, Language};
+use crate::utils;
+
+// One tab of the editor, with the markup that is currently on air
+struct MarkupTab {
+    title: &'static str,
+    code_view: CodeView,
+    label: gtk::Label,
+    revert_button: gtk::Button,
+    applied: RefCell<String>,
+}
+
+impl MarkupTab {
+    fn new(title: &'static str, description: &str) -> Self {
+        let code_view = CodeView::new();
+        utils::set_accessible(code_view.get_text_view(), title, description);
+
+        let label = gtk::Label::new(Some(title));
+        let revert_button =
+            gtk::Button::new_from_icon_name(Some("edit-undo-symbolic"), gtk::IconSize::Menu);
+        revert_button.set_relief(gtk::ReliefStyle::None);
+        revert_button.set_sensitive(false);
+        utils::set_accessible(
+            &revert_button,
+            &format!("Revert {}", title),
+            &format!("Discard the changes to the {} since the last update", title),
+        );
+        revert_button.set_tooltip_text(Some("Revert changes"));
+
+        MarkupTab {
+            title,
+            code_view,
+            label,
+            revert_button,
+            applied: RefCell::new(String::new()),
+        }
+    }
+
+    // Box with the label and revert button, shown as tab
+    fn tab_widget(&self) -> gtk::Box {
+        let tab = gtk::Box::new(gtk::Orientation::Horizontal, 2);
+        tab.pack_start(&self.label, false, false, 0);
+        tab.pack_start(&self.revert_button, false, false, 0);
+        tab.show_all();
+        tab
+    }
+
+    fn load(&self, text: &str, language: Language) {
+        self.applied.replace(text.to_string());
+        self.code_view.set_text(text, language);
+        self.refresh_dirty_state();
+    }
+
+    // Mark the tab if it contains changes that are not on air yet
+    fn refresh_dirty_state(&self) {
+        let dirty = self.code_view.get_text() != *self.applied.borrow();
+        if dirty {
+            self.label.set_text(&format!("{} •", self.title));
+        } else {
+            self.label.set_text(self.title);
+        }
+        self.revert_button.set_sensitive(dirty);
+    }
+}
+
+// Our refcounted markup editor struct, editing the HTML and CSS of the overlay in separate tabs
+#[derive(Clone)]
+pub struct MarkupEditor(Rc<MarkupEditorInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for MarkupEditor {
+    type Target = MarkupEditorInner;
+
+    fn deref(&self) -> &MarkupEditorInner {
+        &self.0
+    }
+}
+
+pub struct MarkupEditorInner {
+    notebook: gtk::Notebook,
+    html: MarkupTab,
+    css: MarkupTab,
+    // Called whenever the markup was edited
+    changed: RefCell<Option<Box<dyn Fn()>>>,
+}
+
+pub struct MarkupEditorWeak(Weak<MarkupEditorInner>);
+impl MarkupEditorWeak {
+    pub fn upgrade(&self) -> Option<MarkupEditor> {
+        self.0.upgrade().map(MarkupEditor)
+    }
+}
+
+impl MarkupEditor {
+    pub fn new(html: &str, css: &str) -> Self {
+        let html_tab = MarkupTab::new("HTML", "HTML markup of the web-page overlay");
+        let css_tab = MarkupTab::new("CSS", "CSS style sheet of the web-page overlay");
+
+        let notebook = gtk::Notebook::new();
+        notebook.set_size_request(400, 300);
+        for tab in &[&html_tab, &css_tab] {
+            notebook.append_page(tab.code_view.get_widget(), Some(&tab.tab_widget()));
+        }
+
+        let editor = MarkupEditor(Rc::new(MarkupEditorInner {
+            notebook,
+            html: html_tab,
+            css: css_tab,
+            changed: RefCell::new(None),
+        }));
+
+        editor.html.load(html, Language::Html);
+        editor.css.load(css, Language::Css);
+
+        for language in &[Language::Html, Language::Css] {
+            let language = *language;
+            let tab = editor.get_tab(language);
+
+            let editor_weak = editor.downgrade();
+            tab.code_view.set_changed(move || {
+                let editor = upgrade_weak!(editor_weak);
+                editor.get_tab(language).refresh_dirty_state();
+                editor.notify_changed();
+            });
+
+            let editor_weak = editor.downgrade();
+            tab.revert_button.connect_clicked(move |_| {
+                let editor = upgrade_weak!(editor_weak);
+                let tab = editor.get_tab(language);
+                let applied = tab.applied.borrow().clone();
+                tab.load(&applied, language);
+            });
+        }
+
+        editor
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> MarkupEditorWeak {
+        MarkupEditorWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> &gtk::Notebook {
+        &self.notebook
+    }
+
+    pub fn set_changed<F: Fn() + 'static>(&self, changed: F) {
+        self.changed.replace(Some(Box::new(changed)));
+    }
+
+    fn notify_changed(&self) {
+        if let Some(ref changed) = *self.changed.borrow() {
+            changed();
+        }
+    }
+
+    fn get_tab(&self, language: Language) -> &MarkupTab {
+        match language {
+            Language::Html => &self.html,
+            Language::Css => &self.css,
+        }
+    }
+
+    // The edited HTML and CSS
+    pub fn get_markup(&self) -> (String, String) {
+        (
+            self.html.code_view.get_text(),
+            self.css.code_view.get_text(),
+        )
+    }
+
+    // Replace the edited markup, e.g. by a template
+    pub fn set_markup(&self, html: &str, css: &str) {
+        self.html.load(html, Language::Html);
+        self.css.load(css, Language::Css);
+    }
+
+    // Remember the edited markup as on air, which clears the dirty markers
+    pub fn mark_applied(&self) {
+        for tab in &[&self.html, &self.css] {
+            tab.applied.replace(tab.code_view.get_text());
+            tab.refresh_dirty_state();
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use gtk::{self, prelude::*};
+
+use std::ops;
+use std::rc::{Rc, Weak};
+
+use crate::pipeline::PipelineWeak;
+use crate::utils;
+
+// Our refcounted media controls struct, for playing video clips like stingers and pre-rolls on
+// top of the program
+#[derive(Clone)]
+pub struct MediaControls(Rc<MediaControlsInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for MediaControls {
+    type Target = MediaControlsInner;
+
+    fn deref(&self) -> &MediaControlsInner {
+        &self.0
+    }
+}
+
+pub struct MediaControlsInner {
+    container: gtk::Box,
+    file: gtk::FileChooserButton,
+    play: gtk::ToggleButton,
+    looping: gtk::CheckButton,
+    pipeline: PipelineWeak,
+}
+
+pub struct MediaControlsWeak(Weak<MediaControlsInner>);
+impl MediaControlsWeak {
+    pub fn upgrade(&self) -> Option<MediaControls> {
+        self.0.upgrade().map(MediaControls)
+    }
+}
+
+impl MediaControls {
+    pub fn new(pipeline: PipelineWeak) -> Self {
+        let container = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+        container.set_margin_top(6);
+
+        let label = gtk::Label::new(Some("Video clip"));
+
+        let file = gtk::FileChooserButton::new("Video clip", gtk::FileChooserAction::Open);
+        file.set_hexpand(true);
+
+        let play = gtk::ToggleButton::new();
+        let play_image =
+            gtk::Image::new_from_icon_name(Some("media-playback-start"), gtk::IconSize::Menu);
+        play.set_image(Some(&play_image));
+        play.set_tooltip_text(Some("Play or pause the clip"));
+        utils::set_accessible(&play, "Play clip", "Play or pause the video clip on air");
+
+        let stop =
+            gtk::Button::new_from_icon_name(Some("media-playback-stop"), gtk::IconSize::Menu);
+        stop.set_tooltip_text(Some("Stop and remove the clip"));
+        utils::set_accessible(
+            &stop,
+            "Stop clip",
+            "Stop the video clip and take it off air",
+        );
+
+        let looping = gtk::CheckButton::new_with_label("Loop");
+
+        container.pack_start(&label, false, false, 0);
+        container.pack_start(&file, true, true, 0);
+        container.pack_start(&play, false, false, 0);
+        container.pack_start(&stop, false, false, 0);
+        container.pack_start(&looping, false, false, 0);
+
+        let controls = MediaControls(Rc::new(MediaControlsInner {
+            container,
+            file,
+            play,
+            looping,
+            pipeline,
+        }));
+
+        let controls_weak = controls.downgrade();
+        controls.play.connect_toggled(move |_| {
+            let controls = upgrade_weak!(controls_weak);
+            controls.on_play_toggled();
+        });
+
+        let controls_weak = controls.downgrade();
+        stop.connect_clicked(move |_| {
+            let controls = upgrade_weak!(controls_weak);
+            let pipeline = &controls.pipeline;
+            let pipeline = upgrade_weak!(pipeline);
+            pipeline.stop_media();
+        });
+
+        let controls_weak = controls.downgrade();
+        controls.looping.connect_toggled(move |looping| {
+            let controls = upgrade_weak!(controls_weak);
+            let pipeline = &controls.pipeline;
+            let pipeline = upgrade_weak!(pipeline);
+            pipeline.set_media_looping(looping.get_active());
+        });
+
+        // Reset the play button once the clip ended or was stopped
+        if let Some(pipeline) = controls.pipeline.upgrade() {
+            let controls_weak = controls.downgrade();
+            pipeline
+                .get_object()
+                .connect_local("notify::media-playing", false, move |_| {
+                    let controls = upgrade_weak!(controls_weak, None);
+                    let pipeline = &controls.pipeline;
+                    let pipeline = upgrade_weak!(pipeline, None);
+                    if !pipeline.is_media_playing() {
+                        controls.play.set_active(false);
+                    }
+                    None
+                })
+                .expect("Failed to connect to the media-playing property");
+        }
+
+        controls
+    }
+
+    pub fn downgrade(&self) -> MediaControlsWeak {
+        MediaControlsWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> &gtk::Box {
+        &self.container
+    }
+
+    // Start the selected clip, or pause and resume the one that is loaded
+    fn on_play_toggled(&self) {
+        let pipeline = &self.pipeline;
+        let pipeline = upgrade_weak!(pipeline);
+        let active = self.play.get_active();
+
+        if pipeline.is_media_playing() {
+            pipeline.pause_media(!active);
+            return;
+        }
+        if !active {
+            return;
+        }
+
+        let location = match self.file.get_filename() {
+            Some(location) => location,
+            None => {
+                self.play.set_active(false);
+                return;
+            }
+        };
+        if let Err(err) = pipeline.play_media(&location, self.looping.get_active()) {
+            self.play.set_active(false);
+            utils::show_error_dialog(
+                false,
+                format!("Failed to play the video clip: {}", err).as_str(),
+            );
+        }
+    }
+}
@@ -0,0 +1,546 @@
+use gio::prelude::*;
+use gtk::{self, prelude::*};
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::ops;
+use std::rc::{Rc, Weak};
+use std::thread;
+
+use crate::app::Action;
+use crate::audio_mixer::{self, AudioMixerPanelWeak};
+use crate::scenes;
+use crate::settings::{MidiControl, MidiControlKind, MidiMapping};
+use crate::utils;
+
+// Actions buttons can trigger, activated without parameter so stateful ones are toggled
+const ACTION_TARGETS: &[(&str, &str)] = &[
+    ("record", "Start or stop streaming"),
+    ("mute_audio", "Mute or unmute the audio"),
+    ("panic", "Cut to standby"),
+    ("pause_stream", "Be right back"),
+    ("snapshot", "Save a snapshot"),
+    ("marker", "Add a marker"),
+    ("rundown_next", "Next rundown item"),
+    ("update_overlay", "Update the overlay"),
+];
+
+// Audio inputs faders and knobs can set the gain of
+const GAIN_TARGETS: &[(&str, &str)] = &[
+    (audio_mixer::MICROPHONE, "Microphone gain"),
+    (audio_mixer::DESKTOP, "Desktop audio gain"),
+    (audio_mixer::MEDIA, "Media gain"),
+    (audio_mixer::CLIP, "Video clip gain"),
+];
+
+// Ids and labels of everything a control can drive: "action:<name>", "scene:<number>" and
+// "gain:<input>"
+pub fn targets() -> Vec<(String, String)> {
+    let actions = ACTION_TARGETS
+        .iter()
+        .map(|(name, label)| (format!("action:{}", name), label.to_string()));
+    let scenes = scenes::SCENES.iter().zip(1..).map(|(scene, number)| {
+        (
+            format!("scene:{}", number),
+            format!("Scene: {}", scene.label),
+        )
+    });
+    let gains = GAIN_TARGETS
+        .iter()
+        .map(|(input, label)| (format!("gain:{}", input), label.to_string()));
+
+    actions.chain(scenes).chain(gains).collect()
+}
+
+fn control_label(control: MidiControl) -> String {
+    let kind = match control.kind {
+        MidiControlKind::Note => "Note",
+        MidiControlKind::Controller => "Controller",
+    };
+    format!(
+        "{} {}, channel {}",
+        kind,
+        control.number,
+        control.channel + 1
+    )
+}
+
+// Paths and names of the raw MIDI devices, e.g. ("/dev/snd/midiC1D0", "nanoKONTROL2")
+pub fn list_devices() -> Vec<(String, String)> {
+    let entries = match fs::read_dir("/dev/snd") {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut devices = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let (card, device) = file_name.strip_prefix("midiC")?.split_once('D')?;
+            // The first line of the device info is the name of the controller
+            let name = fs::read_to_string(format!("/proc/asound/card{}/midi{}", card, device))
+                .ok()
+                .and_then(|info| info.lines().next().map(|line| line.trim().to_string()))
+                .filter(|name| !name.is_empty())
+                .unwrap_or(file_name);
+            Some((entry.path().to_string_lossy().into_owned(), name))
+        })
+        .collect::<Vec<_>>();
+    devices.sort();
+    devices
+}
+
+// Called with a control of the controller while learning
+type LearnCallback = Box<dyn Fn(MidiControl)>;
+
+struct Message {
+    control: MidiControl,
+    value: u8,
+}
+
+// Read the MIDI messages of the device in a thread. Returns a receiver for the note and
+// controller messages to be attached to the main context
+fn start(path: &str) -> io::Result<glib::Receiver<Message>> {
+    let device = File::open(path)?;
+
+    let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+    thread::spawn(move || {
+        let mut status = 0;
+        let mut data = Vec::with_capacity(2);
+        for byte in BufReader::new(device).bytes() {
+            let byte = match byte {
+                Ok(byte) => byte,
+                Err(_) => break,
+            };
+
+            // Real-time messages like the clock can appear anywhere, even between data bytes
+            if byte >= 0xf8 {
+                continue;
+            }
+            // Data bytes without status byte reuse the last one ("running status"), system
+            // messages cancel it
+            if byte & 0x80 != 0 {
+                status = if byte < 0xf0 { byte } else { 0 };
+                data.clear();
+                continue;
+            }
+            if status == 0 {
+                continue;
+            }
+
+            data.push(byte);
+            let length = match status & 0xf0 {
+                0xc0 | 0xd0 => 1,
+                _ => 2,
+            };
+            if data.len() < length {
+                continue;
+            }
+
+            let (kind, value) = match status & 0xf0 {
+                // A note on without velocity is a note off
+                0x80 => (MidiControlKind::Note, 0),
+                0x90 => (MidiControlKind::Note, data[1]),
+                0xb0 => (MidiControlKind::Controller, data[1]),
+                _ => {
+                    data.clear();
+                    continue;
+                }
+            };
+            let control = MidiControl {
+                kind,
+                channel: status & 0x0f,
+                number: data[0],
+            };
+            data.clear();
+
+            if sender.send(Message { control, value }).is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(receiver)
+}
+
+// Our refcounted MIDI struct, applying the mappings of the controls of the configured controller
+#[derive(Clone)]
+pub struct Midi(Rc<MidiInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for Midi {
+    type Target = MidiInner;
+
+    fn deref(&self) -> &MidiInner {
+        &self.0
+    }
+}
+
+pub struct MidiInner {
+    audio_mixer: AudioMixerPanelWeak,
+    // Device the messages are currently read from
+    device: RefCell<Option<String>>,
+    mappings: RefCell<Vec<MidiMapping>>,
+    // Last value of each control, to tell presses of buttons from their release
+    values: RefCell<HashMap<MidiControl, u8>>,
+    // Called with the next control that is used, instead of applying its mappings
+    learn: RefCell<Option<LearnCallback>>,
+    // Messages of previously read devices are dropped
+    generation: Cell<u32>,
+}
+
+pub struct MidiWeak(Weak<MidiInner>);
+impl MidiWeak {
+    pub fn upgrade(&self) -> Option<Midi> {
+        self.0.upgrade().map(Midi)
+    }
+}
+
+impl Midi {
+    pub fn new(audio_mixer: AudioMixerPanelWeak) -> Self {
+        let midi = Midi(Rc::new(MidiInner {
+            audio_mixer,
+            device: RefCell::new(None),
+            mappings: RefCell::new(Vec::new()),
+            values: RefCell::new(HashMap::new()),
+            learn: RefCell::new(None),
+            generation: Cell::new(0),
+        }));
+
+        midi.refresh();
+
+        midi
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> MidiWeak {
+        MidiWeak(Rc::downgrade(&self.0))
+    }
+
+    // Apply changed MIDI settings, only opening the device again if it changed as raw MIDI
+    // devices can only be opened once
+    pub fn refresh(&self) {
+        let settings = utils::load_settings().midi;
+        self.mappings.replace(settings.mappings);
+        if *self.device.borrow() == settings.device {
+            return;
+        }
+
+        self.device.replace(settings.device.clone());
+        self.values.borrow_mut().clear();
+        self.generation.set(self.generation.get().wrapping_add(1));
+
+        let path = match settings.device {
+            Some(path) => path,
+            None => return,
+        };
+        match start(&path) {
+            Ok(receiver) => {
+                let generation = self.generation.get();
+                let midi_weak = self.downgrade();
+                receiver.attach(None, move |message| {
+                    let midi = upgrade_weak!(midi_weak, glib::Continue(false));
+                    if midi.generation.get() != generation {
+                        return glib::Continue(false);
+                    }
+                    midi.on_message(message);
+                    glib::Continue(true)
+                });
+            }
+            Err(err) => utils::show_error_dialog(
+                false,
+                format!("Failed to open the MIDI controller {}: {}", path, err).as_str(),
+            ),
+        }
+    }
+
+    // Hand the next used control to the callback instead of applying its mappings, or stop
+    // learning with None
+    pub fn set_learn(&self, learn: Option<LearnCallback>) {
+        self.learn.replace(learn);
+    }
+
+    fn on_message(&self, message: Message) {
+        let previous = self
+            .values
+            .borrow_mut()
+            .insert(message.control, message.value)
+            .unwrap_or(0);
+
+        // Learning only happens once, the callback may start learning again
+        let learn = self.learn.borrow_mut().take();
+        if let Some(learn) = learn {
+            learn(message.control);
+            return;
+        }
+
+        let targets = self
+            .mappings
+            .borrow()
+            .iter()
+            .filter(|mapping| mapping.control == message.control)
+            .map(|mapping| mapping.target.clone())
+            .collect::<Vec<_>>();
+        for target in targets {
+            self.apply(&target, message.value, previous);
+        }
+    }
+
+    fn apply(&self, target: &str, value: u8, previous: u8) {
+        if let Some(input) = target.strip_prefix("gain:") {
+            let audio_mixer = &self.audio_mixer;
+            let audio_mixer = upgrade_weak!(audio_mixer);
+            audio_mixer.set_gain(input, f64::from(value) / 127.0 * audio_mixer::MAX_GAIN);
+            return;
+        }
+
+        // Buttons trigger when pressed, knobs and faders when crossing the middle upwards
+        if value < 64 || previous >= 64 {
+            return;
+        }
+
+        let application = match gio::Application::get_default() {
+            Some(application) => application,
+            None => return,
+        };
+        if let Some(number) = target
+            .strip_prefix("scene:")
+            .and_then(|number| number.parse().ok())
+        {
+            Action::SwitchScene(number).trigger(&application);
+        } else if let Some(name) = target.strip_prefix("action:") {
+            if ACTION_TARGETS.iter().any(|(action, _)| *action == name) {
+                application.activate_action(name, None);
+            }
+        }
+    }
+}
+
+// Controls of one mapping
+struct MappingRow {
+    container: gtk::Box,
+    control: MidiControl,
+    target: gtk::ComboBoxText,
+}
+
+// Our refcounted MIDI settings page struct, selecting the controller and mapping its controls
+#[derive(Clone)]
+pub struct MidiPage(Rc<MidiPageInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for MidiPage {
+    type Target = MidiPageInner;
+
+    fn deref(&self) -> &MidiPageInner {
+        &self.0
+    }
+}
+
+pub struct MidiPageInner {
+    container: gtk::Box,
+    device: gtk::ComboBoxText,
+    rows_box: gtk::Box,
+    rows: RefCell<Vec<MappingRow>>,
+    learn: gtk::ToggleButton,
+    midi: MidiWeak,
+}
+
+pub struct MidiPageWeak(Weak<MidiPageInner>);
+impl MidiPageWeak {
+    pub fn upgrade(&self) -> Option<MidiPage> {
+        self.0.upgrade().map(MidiPage)
+    }
+}
+
+impl MidiPage {
+    pub fn new(midi: MidiWeak) -> Self {
+        let settings = utils::load_settings().midi;
+
+        let device_label = gtk::Label::new(Some("Controller"));
+        let device = gtk::ComboBoxText::new();
+        device.append(Some(""), "None");
+        let devices = list_devices();
+        for (path, name) in &devices {
+            device.append(Some(path), name);
+        }
+        // Keep the configured controller while it is unplugged
+        if let Some(ref path) = settings.device {
+            if !devices.iter().any(|(known, _)| known == path) {
+                device.append(Some(path), &format!("{} (disconnected)", path));
+            }
+        }
+        device.set_active_id(Some(settings.device.as_deref().unwrap_or("")));
+        device.set_hexpand(true);
+
+        let device_box = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        device_box.pack_start(&device_label, false, false, 0);
+        device_box.pack_start(&device, true, true, 0);
+
+        let label = gtk::Label::new(Some(
+            "Press Learn, then press a button or move a fader of the controller to map it",
+        ));
+        label.set_halign(gtk::Align::Start);
+        label.set_line_wrap(true);
+
+        let rows_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
+        let scrolled_window = gtk::ScrolledWindow::new(gtk::NONE_ADJUSTMENT, gtk::NONE_ADJUSTMENT);
+        scrolled_window.set_min_content_height(200);
+        scrolled_window.add(&rows_box);
+
+        let learn = gtk::ToggleButton::new_with_mnemonic("_Learn");
+        learn.set_tooltip_text(Some("Map the next control used on the controller"));
+
+        let container = gtk::Box::new(gtk::Orientation::Vertical, 4);
+        container.set_margin_bottom(12);
+        container.pack_start(&device_box, false, false, 0);
+        container.pack_start(&label, false, false, 0);
+        container.pack_start(&scrolled_window, true, true, 0);
+        container.pack_start(&learn, false, false, 0);
+
+        let page = MidiPage(Rc::new(MidiPageInner {
+            container,
+            device,
+            rows_box,
+            rows: RefCell::new(Vec::new()),
+            learn,
+            midi,
+        }));
+
+        for mapping in &settings.mappings {
+            page.add_row(mapping);
+        }
+
+        let page_weak = page.downgrade();
+        page.device.connect_changed(move |_| {
+            let page = upgrade_weak!(page_weak);
+            page.apply();
+        });
+
+        let page_weak = page.downgrade();
+        page.learn.connect_toggled(move |learn| {
+            let page = upgrade_weak!(page_weak);
+            let midi = &page.midi;
+            let midi = upgrade_weak!(midi);
+            if !learn.get_active() {
+                midi.set_learn(None);
+                return;
+            }
+
+            let page_weak = page.downgrade();
+            midi.set_learn(Some(Box::new(move |control| {
+                let page = upgrade_weak!(page_weak);
+                page.on_learned(control);
+            })));
+        });
+
+        // Stop learning once the settings dialog is closed
+        let page_weak = page.downgrade();
+        page.container.connect_destroy(move |_| {
+            let page = upgrade_weak!(page_weak);
+            let midi = &page.midi;
+            let midi = upgrade_weak!(midi);
+            midi.set_learn(None);
+        });
+
+        page
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> MidiPageWeak {
+        MidiPageWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> &gtk::Box {
+        &self.container
+    }
+
+    // Add a mapping for the learned control, unless it is mapped already
+    fn on_learned(&self, control: MidiControl) {
+        self.learn.set_active(false);
+
+        if self.rows.borrow().iter().any(|row| row.control == control) {
+            return;
+        }
+
+        // Faders and knobs most likely control a gain, buttons an action
+        let target = match control.kind {
+            MidiControlKind::Controller => format!("gain:{}", audio_mixer::MICROPHONE),
+            MidiControlKind::Note => format!("action:{}", ACTION_TARGETS[0].0),
+        };
+        self.add_row(&MidiMapping { control, target });
+        self.apply();
+    }
+
+    fn add_row(&self, mapping: &MidiMapping) {
+        let label = gtk::Label::new(Some(&control_label(mapping.control)));
+        label.set_halign(gtk::Align::Start);
+
+        let target = gtk::ComboBoxText::new();
+        for (id, label) in targets() {
+            target.append(Some(&id), &label);
+        }
+        target.set_active_id(Some(&mapping.target));
+
+        let remove_button =
+            gtk::Button::new_from_icon_name(Some("list-remove-symbolic"), gtk::IconSize::Button);
+        remove_button.set_tooltip_text(Some("Remove mapping"));
+
+        let container = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        container.pack_start(&label, true, true, 0);
+        container.pack_start(&target, false, false, 0);
+        container.pack_start(&remove_button, false, false, 0);
+        container.show_all();
+        self.rows_box.pack_start(&container, false, false, 0);
+
+        let page_weak = self.downgrade();
+        target.connect_changed(move |_| {
+            let page = upgrade_weak!(page_weak);
+            page.apply();
+        });
+
+        let page_weak = self.downgrade();
+        let row_container = container.clone();
+        remove_button.connect_clicked(move |_| {
+            let page = upgrade_weak!(page_weak);
+            page.rows
+                .borrow_mut()
+                .retain(|row| row.container != row_container);
+            page.rows_box.remove(&row_container);
+            page.apply();
+        });
+
+        self.rows.borrow_mut().push(MappingRow {
+            container,
+            control: mapping.control,
+            target,
+        });
+    }
+
+    // Store the controller and mappings and start using them
+    fn apply(&self) {
+        let mut settings = utils::load_settings();
+        settings.midi.device = self
+            .device
+            .get_active_id()
+            .filter(|id| !id.is_empty())
+            .map(|id| id.to_string());
+        settings.midi.mappings = self
+            .rows
+            .borrow()
+            .iter()
+            .filter_map(|row| {
+                Some(MidiMapping {
+                    control: row.control,
+                    target: row.target.get_active_id()?.to_string(),
+                })
+            })
+            .collect();
+        utils::save_settings(&settings);
+
+        let midi = &self.midi;
+        let midi = upgrade_weak!(midi);
+        midi.refresh();
+    }
+}
@@ -0,0 +1,103 @@
+use gst::{self, prelude::*};
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::recovery;
+use crate::settings::Settings;
+use crate::utils;
+
+// Launch description of the local recording bin, or None if no local copy is kept. It has its
+// own encoders so the file keeps being written whatever happens to the other outputs
+pub fn bin_description(settings: &Settings, mixdown: &str) -> Option<String> {
+    settings.local_recording.extension()?;
+
+    Some(format!(
+        "queue name=video-queue ! {scale}gldownload ! videoconvert ! {h264_encoder} ! {parser}\
+         {muxer} name=file-mux ! filesink name=file-sink async=0 \
+         {mixdown}queue name=audio-queue ! {audio_encoder} ! file-mux.",
+        scale = settings.output_scale_description(),
+        h264_encoder = settings.video_encoder_description(),
+        parser = settings
+            .video_codec()
+            .parser()
+            .map(|parser| format!("{} ! ", parser))
+            .unwrap_or_default(),
+        audio_encoder = settings.audio_encoder_description(),
+        muxer = settings.local_recording.muxer(),
+        mixdown = mixdown,
+    ))
+}
+
+// Point the file sink of the bin at a new file in the recordings folder. The file carries the
+// partial extension until it was finalized, so interrupted recordings can be found and repaired
+// later
+pub fn configure(bin: &gst::Bin, settings: &Settings) -> Result<(), String> {
+    let extension = settings
+        .local_recording
+        .extension()
+        .expect("Local recording is disabled");
+
+    let directory = utils::get_recordings_directory(settings);
+    fs::create_dir_all(&directory).map_err(|err| {
+        format!(
+            "Failed to create recordings folder '{}': {}",
+            directory.display(),
+            err
+        )
+    })?;
+
+    let location = directory.join(format!(
+        "broadcast-{}.{}",
+        glib::DateTime::new_now_local()
+            .format("%Y-%m-%d-%H%M%S")
+            .map(|t| t.to_string())
+            .unwrap_or_default(),
+        extension
+    ));
+    let partial = recovery::partial_path(&location);
+
+    let file_sink = bin.get_by_name("file-sink").expect("No file-sink found");
+    file_sink
+        .set_property("location", &partial.to_string_lossy().as_ref())
+        .expect("No location property on filesink");
+
+    // Lets "Repair recordings" rebuild the index if the application dies mid-show
+    if let Some(mux) = bin.get_by_name("file-mux") {
+        if mux.find_property("moov-recovery-file").is_some() {
+            mux.set_property(
+                "moov-recovery-file",
+                &recovery::moov_recovery_path(&partial)
+                    .to_string_lossy()
+                    .as_ref(),
+            )
+            .expect("Failed to set moov-recovery-file");
+        }
+    }
+
+    Ok(())
+}
+
+// Give the file its final name once the file sink closed it, returning the new path
+pub fn finalize(bin: &gst::Bin) -> Result<PathBuf, String> {
+    let file_sink = bin.get_by_name("file-sink").expect("No file-sink found");
+    let partial = file_sink
+        .get_property("location")
+        .ok()
+        .and_then(|value| value.get::<String>().ok().flatten())
+        .map(PathBuf::from)
+        .ok_or_else(|| "Recording has no location".to_string())?;
+
+    // Strip the partial extension to get the name of the recording
+    let location = partial.with_extension("");
+    fs::rename(&partial, &location).map_err(|err| {
+        format!(
+            "Failed to finalize recording '{}': {}",
+            location.display(),
+            err
+        )
+    })?;
+    let _ = fs::remove_file(recovery::moov_recovery_path(&partial));
+
+    Ok(location)
+}
@@ -0,0 +1,178 @@
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use crate::settings::Settings;
+use crate::APPLICATION_NAME;
+
+const PLAYLIST: &str = "index.m3u8";
+// In seconds, segments are cut at the next keyframe after that
+const TARGET_DURATION: u32 = 2;
+const PLAYLIST_LENGTH: u32 = 5;
+// Segments that were dropped from the playlist are kept a bit for slow clients
+const MAX_FILES: u32 = 10;
+
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Served at /, browsers other than Safari need hls.js to play HLS
+const PLAYER_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Program preview</title>
+<style>
+body { margin: 0; background: black; }
+video { width: 100vw; height: 100vh; }
+</style>
+</head>
+<body>
+<video id="video" controls autoplay muted></video>
+<script src="https://cdn.jsdelivr.net/npm/hls.js@1"></script>
+<script>
+const video = document.getElementById("video");
+if (video.canPlayType("application/vnd.apple.mpegurl")) {
+  video.src = "index.m3u8";
+} else if (window.Hls && Hls.isSupported()) {
+  const hls = new Hls({ manifestLoadingMaxRetry: 100 });
+  hls.loadSource("index.m3u8");
+  hls.attachMedia(video);
+}
+</script>
+</body>
+</html>
+"#;
+
+// Where the playlist and segments are written while live
+fn directory() -> PathBuf {
+    env::temp_dir().join(format!("{}-hls", APPLICATION_NAME))
+}
+
+// Launch description of the LAN preview bin, writing HLS segments of H.264 and AAC
+pub fn bin_description(settings: &Settings, mixdown: &str) -> String {
+    let directory = directory();
+    format!(
+        "queue name=video-queue ! {scale}gldownload ! videoconvert ! {h264_encoder} ! h264parse ! \
+         hlssink2 name=hls-sink location=\"{segments}\" playlist-location=\"{playlist}\" \
+         target-duration={target_duration} playlist-length={playlist_length} max-files={max_files} \
+         {mixdown}queue name=audio-queue ! {audio_encoder} ! hls-sink.audio",
+        scale = settings.output_scale_description(),
+        h264_encoder = settings.video_encoder_description(),
+        audio_encoder = settings.audio_encoder_description(),
+        segments = directory.join("segment%05d.ts").display(),
+        playlist = directory.join(PLAYLIST).display(),
+        target_duration = TARGET_DURATION,
+        playlist_length = PLAYLIST_LENGTH,
+        max_files = MAX_FILES,
+        mixdown = mixdown,
+    )
+}
+
+// Start over with an empty directory, the segments of an earlier session are of no use
+pub fn prepare() -> Result<(), String> {
+    let directory = directory();
+    match fs::remove_dir_all(&directory) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => {
+            return Err(format!("Failed to remove old HLS segments: {}", err))
+        }
+        _ => (),
+    }
+    fs::create_dir_all(&directory)
+        .map_err(|err| format!("Failed to create {}: {}", directory.display(), err))
+}
+
+// Serve the player page, the playlist and the segments on the given port of all interfaces, so
+// browsers on the local network can watch the program while live
+pub fn serve(port: u16) -> io::Result<()> {
+    let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, port))?;
+    // Nothing is served before going live
+    let _ = prepare();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => continue,
+            };
+            thread::spawn(move || {
+                let _ = handle_connection(stream);
+            });
+        }
+    });
+
+    Ok(())
+}
+
+fn handle_connection(mut stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default().to_string();
+
+    // The headers are of no interest
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+    }
+
+    if method != "GET" {
+        return respond(&mut stream, "405 Method Not Allowed", "text/plain", b"");
+    }
+
+    let path = target.split('?').next().unwrap_or_default();
+    let name = path.trim_start_matches('/');
+    if name.is_empty() {
+        return respond(&mut stream, "200 OK", "text/html", PLAYER_PAGE.as_bytes());
+    }
+
+    // Only the files written by hlssink2, never anything outside of the directory
+    let content_type = if name.contains(['/', '\\']) || name.starts_with('.') {
+        None
+    } else if name.ends_with(".m3u8") {
+        Some("application/vnd.apple.mpegurl")
+    } else if name.ends_with(".ts") {
+        Some("video/mp2t")
+    } else {
+        None
+    };
+
+    match content_type.and_then(|content_type| {
+        fs::read(directory().join(name))
+            .ok()
+            .map(|body| (content_type, body))
+    }) {
+        Some((content_type, body)) => respond(&mut stream, "200 OK", content_type, &body),
+        None => respond(&mut stream, "404 Not Found", "text/plain", b""),
+    }
+}
+
+fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> io::Result<()> {
+    // The playlist changes with every segment
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\n\
+         Access-Control-Allow-Origin: *\r\n\
+         Cache-Control: no-cache\r\n\
+         Content-Type: {}\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body)
+}
@@ -0,0 +1,365 @@
+use gst::{self, prelude::*};
+
+use std::cell::RefCell;
+use std::error;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+pub mod file;
+pub mod hls;
+pub mod whip;
+
+// One output bin fed from the tees
+struct Output {
+    name: String,
+    bin: gst::Bin,
+    // Request pads of the video and audio tees linked to the bin
+    tee_pads: Vec<gst::Pad>,
+}
+
+// The output bins the program is currently sent to, e.g. an RTMP stream, a second platform and a
+// local file. Each of them is attached, detached and fails on its own without affecting the
+// others
+pub struct Outputs {
+    pipeline: gst::Pipeline,
+    video_tee: gst::Element,
+    outputs: RefCell<Vec<Output>>,
+}
+
+impl Outputs {
+    pub fn new(pipeline: &gst::Pipeline, video_tee: &gst::Element) -> Self {
+        Outputs {
+            pipeline: pipeline.clone(),
+            video_tee: video_tee.clone(),
+            outputs: RefCell::new(Vec::new()),
+        }
+    }
+
+    // Add the bin to the pipeline and feed it from the video tee and the given audio tees
+    //
+    // The bin needs a video-queue element and an audio-queue element, which is preceded by an
+    // audiomixer called audio-mix if multiple audio tees are given
+    pub fn attach(
+        &self,
+        name: &str,
+        bin: gst::Bin,
+        audio_tees: &[gst::Element],
+    ) -> Result<(), Box<dyn error::Error>> {
+        // A detached output keeps its bin until it was shut down, e.g. while a file is finalized
+        let bin_name = format!("output-{}", name);
+        if self.pipeline.get_by_name(&bin_name).is_some() {
+            return Err(format!("The previous '{}' output is still being finalized", name).into());
+        }
+        bin.set_name(&bin_name)
+            .map_err(|err| format!("Failed to set output bin name: {}", err))?;
+
+        let video_queue = bin
+            .get_by_name("video-queue")
+            .expect("No video-queue found");
+        let audio_queue = bin
+            .get_by_name("audio-queue")
+            .expect("No audio-queue found");
+        let audio_mix = bin.get_by_name("audio-mix");
+
+        // Add the bin to the pipeline. This would only fail if there was
+        // already a bin with the same name, which we ensured can't happen
+        self.pipeline.add(&bin).expect("Failed to add output bin");
+
+        let mut tee_pads = Vec::new();
+        let mut result = link_tee(
+            &bin,
+            &self.video_tee,
+            &video_queue
+                .get_static_pad("sink")
+                .expect("Failed to get sink pad from video-queue"),
+            "video_sink",
+            &mut tee_pads,
+        );
+
+        for (i, audio_tee) in audio_tees.iter().enumerate() {
+            if result.is_err() {
+                break;
+            }
+
+            let sinkpad = match audio_mix {
+                Some(ref audio_mix) => audio_mix
+                    .get_request_pad("sink_%u")
+                    .expect("Failed to request new pad from audiomixer"),
+                None => audio_queue
+                    .get_static_pad("sink")
+                    .expect("Failed to get sink pad from audio-queue"),
+            };
+            result = link_tee(
+                &bin,
+                audio_tee,
+                &sinkpad,
+                &format!("audio_sink_{}", i),
+                &mut tee_pads,
+            );
+        }
+
+        if result.is_ok() && bin.set_state(gst::State::Playing).is_err() {
+            result = Err(format!("Failed to start output '{}'", name));
+        }
+
+        if let Err(err) = result {
+            // This might fail but we don't care anymore: we're in an error path
+            for pad in tee_pads {
+                if let Some(peer) = pad.get_peer() {
+                    let _ = pad.unlink(&peer);
+                }
+                if let Some(tee) = pad.get_parent_element() {
+                    tee.release_request_pad(&pad);
+                }
+            }
+            let _ = self.pipeline.remove(&bin);
+            let _ = bin.set_state(gst::State::Null);
+
+            return Err(err.into());
+        }
+
+        self.outputs.borrow_mut().push(Output {
+            name: name.to_string(),
+            bin,
+            tee_pads,
+        });
+
+        Ok(())
+    }
+
+    // Stop feeding the output and remove it, returns false if there was no such output
+    //
+    // Outputs writing a file get EOS first so the muxer can finalize it, the bin is then removed
+    // once EOS arrived at the file sink
+    pub fn detach(&self, name: &str) -> bool {
+        self.detach_output(name, false)
+    }
+
+    pub fn detach_all(&self) {
+        for name in self.get_names() {
+            self.detach(&name);
+        }
+    }
+
+    // Detach all outputs after sending EOS into every one of them, so streams are also ended
+    // cleanly instead of being cut off. is_finishing() tells when they are all shut down
+    pub fn finish_all(&self) {
+        for name in self.get_names() {
+            self.detach_output(&name, true);
+        }
+    }
+
+    // Whether detached outputs are still waiting for EOS to reach their sinks
+    pub fn is_finishing(&self) -> bool {
+        self.pipeline
+            .get_children()
+            .iter()
+            .any(|child| child.get_name().starts_with("output-"))
+    }
+
+    // Files still being written by attached or finishing outputs
+    pub fn get_recording_paths(&self) -> Vec<PathBuf> {
+        self.pipeline
+            .get_children()
+            .iter()
+            .filter(|child| child.get_name().starts_with("output-"))
+            .filter_map(|child| child.downcast_ref::<gst::Bin>()?.get_by_name("file-sink"))
+            .filter_map(|file_sink| file_sink.get_property("location").ok())
+            .filter_map(|value| value.get::<String>().ok().flatten())
+            .map(PathBuf::from)
+            .collect()
+    }
+
+    fn detach_output(&self, name: &str, finish: bool) -> bool {
+        let output = {
+            let mut outputs = self.outputs.borrow_mut();
+            match outputs.iter().position(|output| output.name == name) {
+                Some(position) => outputs.remove(position),
+                None => return false,
+            }
+        };
+
+        // The bin is removed once EOS arrived at all of its sinks
+        let sink_pads = if finish || output.bin.get_by_name("file-sink").is_some() {
+            output
+                .bin
+                .iterate_sinks()
+                .into_iter()
+                .flatten()
+                .filter_map(|sink| sink.get_sink_pads().into_iter().next())
+                .collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        };
+        let pending_sinks = Arc::new(AtomicUsize::new(sink_pads.len()));
+        for sink_pad in &sink_pads {
+            let pipeline_weak = self.pipeline.downgrade();
+            let bin = output.bin.clone();
+            let pending_sinks = pending_sinks.clone();
+            sink_pad.add_probe(gst::PadProbeType::EVENT_DOWNSTREAM, move |_, info| {
+                match info.data {
+                    Some(gst::PadProbeData::Event(ref event))
+                        if event.get_type() == gst::EventType::Eos => {}
+                    _ => return gst::PadProbeReturn::Ok,
+                }
+
+                if pending_sinks.fetch_sub(1, Ordering::SeqCst) == 1 {
+                    let pipeline = upgrade_weak!(pipeline_weak, gst::PadProbeReturn::Remove);
+                    remove_bin(&pipeline, &bin);
+                }
+
+                gst::PadProbeReturn::Remove
+            });
+        }
+        let send_eos = !sink_pads.is_empty();
+
+        // Once the tee source pads are idle and we wouldn't interfere with any data flow, unlink
+        // the tees and the output bin. The bin is removed when the last of them is done
+        //
+        // The closures below might be called directly from the main UI thread here or at a later
+        // time from a GStreamer streaming thread
+        let remaining = Arc::new(AtomicUsize::new(output.tee_pads.len()));
+        for srcpad in output.tee_pads {
+            let pipeline_weak = self.pipeline.downgrade();
+            let bin = output.bin.clone();
+            let remaining = remaining.clone();
+            srcpad.add_probe(gst::PadProbeType::IDLE, move |srcpad, _| {
+                // Get the parent of the tee source pad, i.e. the tee itself
+                if let Some(tee) = srcpad.get_parent_element() {
+                    let peer = srcpad.get_peer();
+                    if let Some(ref peer) = peer {
+                        let _ = srcpad.unlink(peer);
+                    }
+                    tee.release_request_pad(srcpad);
+
+                    if send_eos {
+                        if let Some(peer) = peer {
+                            peer.send_event(gst::Event::new_eos().build());
+                        }
+                    } else if remaining.fetch_sub(1, Ordering::SeqCst) == 1 {
+                        let pipeline = upgrade_weak!(pipeline_weak, gst::PadProbeReturn::Remove);
+                        remove_bin(&pipeline, &bin);
+                    }
+
+                    // Don't block the pad but remove the probe to let everything
+                    // continue as normal
+                    return gst::PadProbeReturn::Remove;
+                }
+                gst::PadProbeReturn::Ok
+            });
+        }
+
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.outputs.borrow().is_empty()
+    }
+
+    pub fn get_names(&self) -> Vec<String> {
+        self.outputs
+            .borrow()
+            .iter()
+            .map(|output| output.name.clone())
+            .collect()
+    }
+
+    // Elements with the given name in all attached outputs
+    pub fn get_elements_by_name(&self, name: &str) -> Vec<gst::Element> {
+        self.outputs
+            .borrow()
+            .iter()
+            .filter_map(|output| output.bin.get_by_name(name))
+            .collect()
+    }
+
+    // Name of the attached output the given object is part of
+    pub fn find_output(&self, object: &gst::Object) -> Option<String> {
+        self.outputs
+            .borrow()
+            .iter()
+            .find(|output| object.has_as_ancestor(&output.bin))
+            .map(|output| output.name.clone())
+    }
+
+    // Whether the object is part of an output that was detached but is not removed yet
+    pub fn is_detached(&self, object: &gst::Object) -> bool {
+        let mut object = Some(object.clone());
+        while let Some(current) = object {
+            if current.get_name().starts_with("output-") && self.find_output(&current).is_none() {
+                return true;
+            }
+            object = current.get_parent();
+        }
+        false
+    }
+}
+
+// Link a new request pad of the tee to a ghost pad of the bin targeting sinkpad
+fn link_tee(
+    bin: &gst::Bin,
+    tee: &gst::Element,
+    sinkpad: &gst::Pad,
+    ghost_pad_name: &str,
+    tee_pads: &mut Vec<gst::Pad>,
+) -> Result<(), String> {
+    let srcpad = tee
+        .get_request_pad("src_%u")
+        .expect("Failed to request new pad from tee");
+    tee_pads.push(srcpad.clone());
+
+    let ghost_pad = gst::GhostPad::new(Some(ghost_pad_name), sinkpad)
+        .map_err(|err| format!("Failed to create ghost pad: {}", err))?;
+    bin.add_pad(&ghost_pad).unwrap();
+
+    srcpad
+        .link(&ghost_pad)
+        .map(|_| ())
+        .map_err(|err| format!("Failed to link output {}: {}", ghost_pad_name, err))
+}
+
+// Remove and shut down the bin from a separate thread, which is safe to call from streaming
+// threads. Files written by the bin are finalized afterwards and handed to the uploader
+fn remove_bin(pipeline: &gst::Pipeline, bin: &gst::Bin) {
+    let bin = bin.clone();
+    pipeline.call_async(move |pipeline| {
+        // Ignore if the bin was not in the pipeline anymore for whatever
+        // reason. It's not a problem
+        let _ = pipeline.remove(&bin);
+
+        let bus = pipeline.get_bus().expect("Pipeline has no bus");
+        if let Err(err) = bin.set_state(gst::State::Null) {
+            post_warning(&bus, &format!("Failed to stop output: {}", err));
+            return;
+        }
+
+        if bin.get_by_name("file-sink").is_none() {
+            return;
+        }
+        match file::finalize(&bin) {
+            Ok(location) => {
+                let _ = bus.post(
+                    &gst::Message::new_application(
+                        gst::Structure::builder("recording-finalized")
+                            .field("location", &location.to_string_lossy().as_ref())
+                            .build(),
+                    )
+                    .build(),
+                );
+            }
+            Err(err) => post_warning(&bus, &err),
+        }
+    });
+}
+
+fn post_warning(bus: &gst::Bus, text: &str) {
+    let _ = bus.post(
+        &gst::Message::new_application(
+            gst::Structure::builder("warning")
+                .field("text", &text)
+                .build(),
+        )
+        .build(),
+    );
+}
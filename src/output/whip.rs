@@ -0,0 +1,63 @@
+use gst::{self, prelude::*};
+
+use crate::encoders::VideoCodec;
+use crate::settings::WhipSettings;
+
+// Payload types offered to the WHIP endpoint
+const VIDEO_PAYLOAD: u32 = 96;
+const AUDIO_PAYLOAD: u32 = 97;
+
+// Launch description of the WHIP output bin
+//
+// whipsink wraps a webrtcbin and does the WHIP offer/answer exchange with the endpoint over HTTP
+// by itself, it only needs RTP streams. Browsers and SFUs don't take AAC, so the audio is sent
+// as Opus instead.
+pub fn bin_description(
+    settings: &WhipSettings,
+    scale: &str,
+    video_encoder: &str,
+    video_codec: VideoCodec,
+    mixdown: &str,
+) -> String {
+    let (video_pay, encoding_name) = match video_codec {
+        VideoCodec::H264 => (
+            "h264parse ! rtph264pay name=video-pay config-interval=-1 aggregate-mode=zero-latency",
+            "H264",
+        ),
+        VideoCodec::H265 => (
+            "h265parse ! rtph265pay name=video-pay config-interval=-1",
+            "H265",
+        ),
+        VideoCodec::Vp9 => ("rtpvp9pay name=video-pay", "VP9"),
+    };
+
+    format!(
+        "whipsink name=whipsink whip-endpoint=\"{endpoint}\" \
+         queue name=video-queue ! {scale}gldownload ! videoconvert ! {video_encoder} ! \
+         {video_pay} pt={video_payload} ! \
+         application/x-rtp,media=video,encoding-name={encoding_name},payload={video_payload} ! whipsink. \
+         {mixdown}queue name=audio-queue ! audioconvert ! audioresample ! opusenc ! \
+         rtpopuspay name=audio-pay pt={audio_payload} ! \
+         application/x-rtp,media=audio,encoding-name=OPUS,payload={audio_payload} ! whipsink.",
+        endpoint = settings.endpoint.as_deref().unwrap_or_default(),
+        scale = scale,
+        video_encoder = video_encoder,
+        video_pay = video_pay,
+        encoding_name = encoding_name,
+        mixdown = mixdown,
+        video_payload = VIDEO_PAYLOAD,
+        audio_payload = AUDIO_PAYLOAD,
+    )
+}
+
+// Bearer token sent with the offer, if the endpoint needs one
+pub fn configure(bin: &gst::Bin, settings: &WhipSettings) -> Result<(), String> {
+    let whipsink = bin.get_by_name("whipsink").expect("No whipsink found");
+    if let Some(ref token) = settings.auth_token {
+        whipsink
+            .set_property("auth-token", token)
+            .map_err(|err| format!("Failed to set WHIP token: {}", err))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+// DOM event the overlay page receives our messages as, with the message as its detail
+pub const MESSAGE_EVENT: &str = "broadcast-message";
+
+// Bodies larger than this are refused, events are supposed to be small
+const MAX_BODY_SIZE: usize = 64 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// JavaScript dispatching the message to the overlay page, e.g.
+// window.addEventListener("broadcast-message", (event) => console.log(event.detail.type))
+pub fn message_script(message: &serde_json::Value) -> String {
+    // JSON is valid JavaScript, except for these two line terminators inside strings
+    let detail = message
+        .to_string()
+        .replace('\u{2028}', "\\u2028")
+        .replace('\u{2029}', "\\u2029");
+    format!(
+        "window.dispatchEvent(new CustomEvent(\"{}\", {{ detail: {} }}));",
+        MESSAGE_EVENT, detail
+    )
+}
+
+// Listen on the given local port for events the overlay page posts as JSON to
+// http://127.0.0.1:<port>/events, 0 picks a free port. Returns the URL to post to and a receiver
+// for the events to be attached to the main context
+pub fn start(port: u16) -> io::Result<(String, glib::Receiver<serde_json::Value>)> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    let url = format!("http://127.0.0.1:{}/events", listener.local_addr()?.port());
+
+    let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => continue,
+            };
+            // Broken requests only affect the page that sent them
+            if let Ok(Some(event)) = handle_request(stream) {
+                if sender.send(event).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok((url, receiver))
+}
+
+// Just enough HTTP for fetch() and XMLHttpRequest, including the CORS preflight as pages loaded
+// from memory have no origin of their own
+fn handle_request(mut stream: TcpStream) -> io::Result<Option<serde_json::Value>> {
+    // Don't let a stalled page block all others
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        let mut header = line.splitn(2, ':');
+        if let (Some(name), Some(value)) = (header.next(), header.next()) {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+
+    let status = match (method.as_str(), path.as_str()) {
+        ("OPTIONS", _) => "204 No Content",
+        ("POST", "/events") if content_length > MAX_BODY_SIZE => "413 Payload Too Large",
+        ("POST", "/events") => {
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body)?;
+            match serde_json::from_slice(&body) {
+                Ok(event) => {
+                    respond(&mut stream, "204 No Content")?;
+                    return Ok(Some(event));
+                }
+                Err(_) => "400 Bad Request",
+            }
+        }
+        _ => "404 Not Found",
+    };
+
+    respond(&mut stream, status)?;
+    Ok(None)
+}
+
+fn respond(stream: &mut TcpStream, status: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\n\
+         Access-Control-Allow-Origin: *\r\n\
+         Access-Control-Allow-Methods: POST, OPTIONS\r\n\
+         Access-Control-Allow-Headers: Content-Type\r\n\
+         Content-Length: 0\r\n\
+         Connection: close\r\n\r\n",
+        status
+    )
+}
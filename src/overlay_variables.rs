@@ -0,0 +1,199 @@
+use gtk::{self, prelude::*};
+
+use std::cell::RefCell;
+use std::ops;
+use std::rc::{Rc, Weak};
+
+use crate::pipeline::PipelineWeak;
+use crate::settings::OverlayVariable;
+use crate::utils;
+
+// Name and value entries of one variable
+struct VariableRow {
+    container: gtk::Box,
+    name: gtk::Entry,
+    value: gtk::Entry,
+}
+
+// Our refcounted variables editor struct, for the values substituted into the overlay HTML
+#[derive(Clone)]
+pub struct OverlayVariables(Rc<OverlayVariablesInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for OverlayVariables {
+    type Target = OverlayVariablesInner;
+
+    fn deref(&self) -> &OverlayVariablesInner {
+        &self.0
+    }
+}
+
+pub struct OverlayVariablesInner {
+    container: gtk::Box,
+    rows_box: gtk::Box,
+    rows: RefCell<Vec<VariableRow>>,
+    // Names handed to the pipeline, to remove the ones that are gone
+    applied: RefCell<Vec<String>>,
+    pipeline: PipelineWeak,
+    // Renders the overlay again with the new values
+    changed: RefCell<Option<Box<dyn Fn()>>>,
+}
+
+pub struct OverlayVariablesWeak(Weak<OverlayVariablesInner>);
+impl OverlayVariablesWeak {
+    pub fn upgrade(&self) -> Option<OverlayVariables> {
+        self.0.upgrade().map(OverlayVariables)
+    }
+}
+
+impl OverlayVariables {
+    pub fn new(pipeline: PipelineWeak) -> Self {
+        let label = gtk::Label::new(Some(
+            "Use the variables as {name} in the HTML of the overlay",
+        ));
+        label.set_halign(gtk::Align::Start);
+        label.set_line_wrap(true);
+
+        let rows_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
+        let scrolled_window = gtk::ScrolledWindow::new(gtk::NONE_ADJUSTMENT, gtk::NONE_ADJUSTMENT);
+        scrolled_window.add(&rows_box);
+
+        let add_button = gtk::Button::new_with_mnemonic("_Add variable");
+
+        let container = gtk::Box::new(gtk::Orientation::Vertical, 4);
+        container.set_border_width(6);
+        container.pack_start(&label, false, false, 0);
+        container.pack_start(&scrolled_window, true, true, 0);
+        container.pack_start(&add_button, false, false, 0);
+
+        let variables = OverlayVariables(Rc::new(OverlayVariablesInner {
+            container,
+            rows_box,
+            rows: RefCell::new(Vec::new()),
+            applied: RefCell::new(Vec::new()),
+            pipeline,
+            changed: RefCell::new(None),
+        }));
+
+        let settings = utils::load_settings();
+        for variable in &settings.overlay_variables {
+            variables.add_row(variable);
+        }
+        variables.applied.replace(
+            settings
+                .overlay_variables
+                .into_iter()
+                .map(|variable| variable.name)
+                .collect(),
+        );
+
+        let variables_weak = variables.downgrade();
+        add_button.connect_clicked(move |_| {
+            let variables = upgrade_weak!(variables_weak);
+            variables.add_row(&OverlayVariable::default());
+        });
+
+        variables
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> OverlayVariablesWeak {
+        OverlayVariablesWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> &gtk::Box {
+        &self.container
+    }
+
+    pub fn set_changed<F: Fn() + 'static>(&self, changed: F) {
+        self.changed.replace(Some(Box::new(changed)));
+    }
+
+    fn add_row(&self, variable: &OverlayVariable) {
+        let name = gtk::Entry::new();
+        name.set_placeholder_text(Some("Name"));
+        name.set_text(&variable.name);
+        name.set_width_chars(12);
+        let value = gtk::Entry::new();
+        value.set_placeholder_text(Some("Value"));
+        value.set_text(&variable.value);
+        let remove_button =
+            gtk::Button::new_from_icon_name(Some("list-remove-symbolic"), gtk::IconSize::Button);
+        remove_button.set_tooltip_text(Some("Remove variable"));
+
+        let container = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        container.pack_start(&name, false, false, 0);
+        container.pack_start(&value, true, true, 0);
+        container.pack_start(&remove_button, false, false, 0);
+        container.show_all();
+        self.rows_box.pack_start(&container, false, false, 0);
+
+        for entry in &[&name, &value] {
+            let variables_weak = self.downgrade();
+            entry.connect_changed(move |_| {
+                let variables = upgrade_weak!(variables_weak);
+                variables.apply();
+            });
+        }
+
+        let variables_weak = self.downgrade();
+        let row_container = container.clone();
+        remove_button.connect_clicked(move |_| {
+            let variables = upgrade_weak!(variables_weak);
+            variables
+                .rows
+                .borrow_mut()
+                .retain(|row| row.container != row_container);
+            variables.rows_box.remove(&row_container);
+            variables.apply();
+        });
+
+        self.rows.borrow_mut().push(VariableRow {
+            container,
+            name,
+            value,
+        });
+    }
+
+    // Store the variables and render the overlay with them
+    fn apply(&self) {
+        let variables = self
+            .rows
+            .borrow()
+            .iter()
+            .filter_map(|row| {
+                let name = row.name.get_text()?.trim().to_string();
+                if name.is_empty() {
+                    return None;
+                }
+                Some(OverlayVariable {
+                    name,
+                    value: row.value.get_text().map(String::from).unwrap_or_default(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let pipeline = &self.pipeline;
+        let pipeline = upgrade_weak!(pipeline);
+        for name in self.applied.borrow().iter() {
+            pipeline.remove_overlay_variable(name);
+        }
+        for variable in &variables {
+            pipeline.set_overlay_variable(&variable.name, &variable.value);
+        }
+        self.applied.replace(
+            variables
+                .iter()
+                .map(|variable| variable.name.clone())
+                .collect(),
+        );
+
+        let mut settings = utils::load_settings();
+        settings.overlay_variables = variables;
+        utils::save_settings(&settings);
+
+        if let Some(ref changed) = *self.changed.borrow() {
+            changed();
+        }
+    }
+}
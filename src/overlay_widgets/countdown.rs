@@ -0,0 +1,285 @@
+use gtk::{self, prelude::*};
+
+use std::cell::Cell;
+use std::ops;
+use std::rc::{Rc, Weak};
+use std::time::{Duration, Instant};
+
+use crate::overlay_widgets::{self, OverlayWidget};
+use crate::settings::CountdownSettings;
+use crate::utils;
+
+// Id of the element injected into the overlay page
+const ELEMENT_ID: &str = "broadcast-countdown";
+
+const STYLESHEET: &str = r#"
+#broadcast-countdown {
+    position: fixed;
+    top: 50%;
+    left: 50%;
+    transform: translate(-50%, -50%);
+    padding: 1vw 3vw;
+    font-family: sans-serif;
+    font-size: 5vw;
+    font-weight: bold;
+    line-height: 1.2;
+    text-align: center;
+    white-space: pre-line;
+    color: white;
+    background: rgba(20, 20, 20, 0.8);
+    border-radius: 1vw;
+}
+"#;
+
+// Parse a local time of day given as HH:MM
+fn parse_time(time: &str) -> Option<(i32, i32)> {
+    let mut parts = time.trim().splitn(2, ':');
+    let hour = parts.next()?.trim().parse().ok()?;
+    let minute = parts.next()?.trim().parse().ok()?;
+    if hour < 24 && minute < 60 {
+        Some((hour, minute))
+    } else {
+        None
+    }
+}
+
+// Time left until the next time the clock shows the given time of day
+fn time_until(hour: i32, minute: i32) -> Duration {
+    let now = glib::DateTime::new_now_local();
+    let (year, month, day) = now.get_ymd();
+    let mut target = glib::DateTime::new_local(year, month, day, hour, minute, 0.0);
+    if target.difference(&now) < 0 {
+        target = target.add_days(1).unwrap_or(target);
+    }
+    Duration::from_micros(target.difference(&now).max(0) as u64)
+}
+
+fn format_remaining(remaining: Duration) -> String {
+    // Round up, so the countdown only shows 00:00 once it is over
+    let seconds = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+    if seconds >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    } else {
+        format!("{:02}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+// Our refcounted countdown struct, the controls and state of the countdown shown in the overlay,
+// e.g. for "Starting soon" screens
+#[derive(Clone)]
+pub struct Countdown(Rc<CountdownInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for Countdown {
+    type Target = CountdownInner;
+
+    fn deref(&self) -> &CountdownInner {
+        &self.0
+    }
+}
+
+pub struct CountdownInner {
+    container: gtk::Grid,
+    title: gtk::Entry,
+    until_time: gtk::RadioButton,
+    duration: gtk::SpinButton,
+    target_time: gtk::Entry,
+    start_button: gtk::ToggleButton,
+    remaining_label: gtk::Label,
+    // When the countdown reaches zero, while it is running
+    deadline: Cell<Option<Instant>>,
+    // Time left while paused, None if the countdown was reset and is hidden
+    remaining: Cell<Option<Duration>>,
+}
+
+pub struct CountdownWeak(Weak<CountdownInner>);
+impl CountdownWeak {
+    pub fn upgrade(&self) -> Option<Countdown> {
+        self.0.upgrade().map(Countdown)
+    }
+}
+
+impl Countdown {
+    pub fn new() -> Self {
+        let settings = utils::load_settings().countdown;
+
+        let container = gtk::Grid::new();
+        container.set_column_spacing(4);
+        container.set_row_spacing(4);
+        container.set_border_width(6);
+
+        let title_label = gtk::Label::new(Some("Title"));
+        title_label.set_halign(gtk::Align::Start);
+        let title = gtk::Entry::new();
+        title.set_text(&settings.title);
+        title.set_hexpand(true);
+        container.attach(&title_label, 0, 0, 1, 1);
+        container.attach(&title, 1, 0, 2, 1);
+
+        let for_duration = gtk::RadioButton::new_with_label("Duration (s)");
+        let duration = gtk::SpinButton::new_with_range(1.0, 86400.0, 10.0);
+        duration.set_value(f64::from(settings.duration));
+        container.attach(&for_duration, 0, 1, 1, 1);
+        container.attach(&duration, 1, 1, 2, 1);
+
+        let until_time = gtk::RadioButton::new_with_label_from_widget(&for_duration, "Until");
+        until_time.set_active(settings.until_time);
+        let target_time = gtk::Entry::new();
+        target_time.set_text(&settings.target_time);
+        target_time.set_placeholder_text(Some("HH:MM"));
+        container.attach(&until_time, 0, 2, 1, 1);
+        container.attach(&target_time, 1, 2, 2, 1);
+
+        let start_button = gtk::ToggleButton::new_with_mnemonic("_Start");
+        utils::set_accessible(
+            &start_button,
+            "Start countdown",
+            "Start or pause the countdown in the overlay",
+        );
+        let reset_button = gtk::Button::new_with_mnemonic("_Reset");
+        utils::set_accessible(
+            &reset_button,
+            "Reset countdown",
+            "Stop the countdown and take it out of the overlay",
+        );
+        let remaining_label = gtk::Label::new(Some("-"));
+        container.attach(&remaining_label, 0, 3, 1, 1);
+        container.attach(&start_button, 1, 3, 1, 1);
+        container.attach(&reset_button, 2, 3, 1, 1);
+
+        let countdown = Countdown(Rc::new(CountdownInner {
+            container,
+            title,
+            until_time,
+            duration,
+            target_time,
+            start_button,
+            remaining_label,
+            deadline: Cell::new(None),
+            remaining: Cell::new(None),
+        }));
+
+        let countdown_weak = countdown.downgrade();
+        countdown.start_button.connect_toggled(move |button| {
+            let countdown = upgrade_weak!(countdown_weak);
+            if button.get_active() {
+                countdown.start();
+            } else {
+                countdown.pause();
+            }
+        });
+
+        let countdown_weak = countdown.downgrade();
+        reset_button.connect_clicked(move |_| {
+            let countdown = upgrade_weak!(countdown_weak);
+            countdown.reset();
+        });
+
+        countdown
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> CountdownWeak {
+        CountdownWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> &gtk::Grid {
+        &self.container
+    }
+
+    fn get_settings(&self) -> CountdownSettings {
+        CountdownSettings {
+            title: self.title.get_text().map(String::from).unwrap_or_default(),
+            until_time: self.until_time.get_active(),
+            duration: self.duration.get_value_as_int() as u32,
+            target_time: self
+                .target_time
+                .get_text()
+                .map(String::from)
+                .unwrap_or_default(),
+        }
+    }
+
+    // Start from the beginning, or resume where the countdown was paused
+    fn start(&self) {
+        let countdown = self.get_settings();
+
+        let remaining = match self.remaining.get() {
+            Some(remaining) => remaining,
+            None if countdown.until_time => match parse_time(&countdown.target_time) {
+                Some((hour, minute)) => time_until(hour, minute),
+                None => {
+                    utils::show_error_dialog(
+                        false,
+                        format!(
+                            "Invalid countdown time {}, please enter it as HH:MM",
+                            countdown.target_time
+                        )
+                        .as_str(),
+                    );
+                    self.start_button.set_active(false);
+                    return;
+                }
+            },
+            None => Duration::from_secs(u64::from(countdown.duration)),
+        };
+
+        let mut settings = utils::load_settings();
+        settings.countdown = countdown;
+        utils::save_settings(&settings);
+
+        self.remaining.set(Some(remaining));
+        self.deadline.set(Some(Instant::now() + remaining));
+    }
+
+    fn pause(&self) {
+        if let Some(deadline) = self.deadline.take() {
+            self.remaining
+                .set(Some(deadline.saturating_duration_since(Instant::now())));
+        }
+    }
+
+    pub fn reset(&self) {
+        self.deadline.set(None);
+        self.remaining.set(None);
+        self.start_button.set_active(false);
+    }
+
+    // Count down the given time from now on, e.g. until a scheduled start
+    pub fn run_for(&self, remaining: Duration) {
+        self.reset();
+        self.remaining.set(Some(remaining));
+        self.start_button.set_active(true);
+    }
+
+    // Time left, None if the countdown is not shown
+    fn get_remaining(&self) -> Option<Duration> {
+        match self.deadline.get() {
+            Some(deadline) => Some(deadline.saturating_duration_since(Instant::now())),
+            None => self.remaining.get(),
+        }
+    }
+}
+
+impl OverlayWidget for Countdown {
+    fn update_script(&self) -> String {
+        let remaining = self.get_remaining().map(format_remaining);
+        self.remaining_label
+            .set_text(remaining.as_deref().unwrap_or("-"));
+
+        let text = remaining.map(|remaining| {
+            let title = self.title.get_text().map(String::from).unwrap_or_default();
+            if title.is_empty() {
+                remaining
+            } else {
+                format!("{}\n{}", title, remaining)
+            }
+        });
+        overlay_widgets::text_element_script(ELEMENT_ID, STYLESHEET, text.as_deref())
+    }
+}
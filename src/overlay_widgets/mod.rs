@@ -0,0 +1,107 @@
+use std::cell::RefCell;
+use std::ops;
+use std::rc::{Rc, Weak};
+
+use crate::pipeline::PipelineWeak;
+
+pub mod countdown;
+
+// Milliseconds between two updates of the widgets in the page
+const UPDATE_INTERVAL: u32 = 500;
+
+// Element of the overlay page driven by the application, e.g. a countdown
+pub trait OverlayWidget {
+    // JavaScript bringing the widget in the page up to date. This runs periodically, so it also
+    // has to add the widget again after the page was reloaded
+    fn update_script(&self) -> String;
+}
+
+// JavaScript showing the text in the element with the given id, which is added to the page
+// together with its stylesheet if needed. None hides the element
+pub fn text_element_script(id: &str, stylesheet: &str, text: Option<&str>) -> String {
+    format!(
+        r#"(function() {{
+    var element = document.getElementById("{id}");
+    var text = {text};
+    if (!element) {{
+        if (text === null) {{
+            return;
+        }}
+        var style = document.createElement("style");
+        style.textContent = {stylesheet};
+        (document.head || document.documentElement).appendChild(style);
+        element = document.createElement("div");
+        element.id = "{id}";
+        document.body.appendChild(element);
+    }}
+    element.style.display = text === null ? "none" : "";
+    if (text !== null && element.textContent !== text) {{
+        element.textContent = text;
+    }}
+}})();"#,
+        id = id,
+        text = text.map(serde_json::Value::from).unwrap_or_default(),
+        stylesheet = serde_json::Value::from(stylesheet),
+    )
+}
+
+// Our refcounted overlay widgets struct, periodically injecting the state of all widgets into
+// the overlay page
+#[derive(Clone)]
+pub struct OverlayWidgets(Rc<OverlayWidgetsInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for OverlayWidgets {
+    type Target = OverlayWidgetsInner;
+
+    fn deref(&self) -> &OverlayWidgetsInner {
+        &self.0
+    }
+}
+
+pub struct OverlayWidgetsInner {
+    widgets: RefCell<Vec<Box<dyn OverlayWidget>>>,
+    pipeline: PipelineWeak,
+}
+
+pub struct OverlayWidgetsWeak(Weak<OverlayWidgetsInner>);
+impl OverlayWidgetsWeak {
+    pub fn upgrade(&self) -> Option<OverlayWidgets> {
+        self.0.upgrade().map(OverlayWidgets)
+    }
+}
+
+impl OverlayWidgets {
+    pub fn new(pipeline: PipelineWeak) -> Self {
+        let overlay_widgets = OverlayWidgets(Rc::new(OverlayWidgetsInner {
+            widgets: RefCell::new(Vec::new()),
+            pipeline,
+        }));
+
+        let overlay_widgets_weak = overlay_widgets.downgrade();
+        glib::timeout_add_local(UPDATE_INTERVAL, move || {
+            let overlay_widgets = upgrade_weak!(overlay_widgets_weak, glib::Continue(false));
+            overlay_widgets.update();
+            glib::Continue(true)
+        });
+
+        overlay_widgets
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> OverlayWidgetsWeak {
+        OverlayWidgetsWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn add<W: OverlayWidget + 'static>(&self, widget: W) {
+        self.widgets.borrow_mut().push(Box::new(widget));
+    }
+
+    fn update(&self) {
+        let pipeline = &self.pipeline;
+        let pipeline = upgrade_weak!(pipeline);
+        for widget in self.widgets.borrow().iter() {
+            pipeline.run_javascript(&widget.update_script());
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use gtk::{self, prelude::*};
+
+use std::cell::RefCell;
+use std::ops;
+use std::rc::{Rc, Weak};
+
+use crate::pipeline::{self, PipelineWeak};
+use crate::scenes;
+use crate::settings::{PipSettings, Settings};
+use crate::utils;
+
+// Part of the camera at its bottom right corner that resizes instead of moving it
+const RESIZE_HANDLE: f64 = 0.25;
+// Smallest camera, as fraction of the output size
+const MIN_SIZE: f64 = 0.1;
+
+// Our refcounted handler for placing the picture-in-picture camera with the mouse. Dragging the
+// camera in the preview moves it, dragging its bottom right corner resizes it and the context
+// menu puts it into one of the corners
+#[derive(Clone)]
+pub struct PipDrag(Rc<PipDragInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for PipDrag {
+    type Target = PipDragInner;
+
+    fn deref(&self) -> &PipDragInner {
+        &self.0
+    }
+}
+
+pub struct PipDragInner {
+    preview: gtk::Widget,
+    pipeline: PipelineWeak,
+    drag: RefCell<Option<Drag>>,
+}
+
+// A drag in progress
+struct Drag {
+    // Settings when the drag started, with the camera placement updated while dragging
+    settings: Settings,
+    origin: PipSettings,
+    // Where the drag started, as fractions of the output size
+    start: (f64, f64),
+    resizing: bool,
+}
+
+pub struct PipDragWeak(Weak<PipDragInner>);
+impl PipDragWeak {
+    pub fn upgrade(&self) -> Option<PipDrag> {
+        self.0.upgrade().map(PipDrag)
+    }
+}
+
+impl PipDrag {
+    pub fn new(preview: &gtk::Widget, pipeline: PipelineWeak) -> Self {
+        preview.add_events(
+            gdk::EventMask::BUTTON_PRESS_MASK
+                | gdk::EventMask::BUTTON_RELEASE_MASK
+                | gdk::EventMask::BUTTON1_MOTION_MASK,
+        );
+
+        let pip_drag = PipDrag(Rc::new(PipDragInner {
+            preview: preview.clone(),
+            pipeline,
+            drag: RefCell::new(None),
+        }));
+
+        let pip_drag_weak = pip_drag.downgrade();
+        preview.connect_button_press_event(move |_, event| {
+            let pip_drag = upgrade_weak!(pip_drag_weak, Inhibit(false));
+            Inhibit(pip_drag.on_button_press(event))
+        });
+
+        let pip_drag_weak = pip_drag.downgrade();
+        preview.connect_motion_notify_event(move |_, event| {
+            let pip_drag = upgrade_weak!(pip_drag_weak, Inhibit(false));
+            Inhibit(pip_drag.on_motion(event.get_position()))
+        });
+
+        let pip_drag_weak = pip_drag.downgrade();
+        preview.connect_button_release_event(move |_, event| {
+            let pip_drag = upgrade_weak!(pip_drag_weak, Inhibit(false));
+            Inhibit(pip_drag.on_button_release(event.get_position()))
+        });
+
+        pip_drag
+    }
+
+    pub fn downgrade(&self) -> PipDragWeak {
+        PipDragWeak(Rc::downgrade(&self.0))
+    }
+
+    // Map a point of the preview widget to fractions of the output size. The video is scaled to
+    // fit the widget keeping its aspect ratio, centered between black bars
+    fn to_output(&self, settings: &Settings, (x, y): (f64, f64)) -> (f64, f64) {
+        let (width, height) = pipeline::output_size(settings);
+        let (width, height) = (f64::from(width), f64::from(height));
+        let allocation = self.preview.get_allocation();
+        let (widget_width, widget_height) =
+            (f64::from(allocation.width), f64::from(allocation.height));
+
+        let scale = (widget_width / width).min(widget_height / height);
+        let left = (widget_width - width * scale) / 2.0;
+        let top = (widget_height - height * scale) / 2.0;
+        ((x - left) / (width * scale), (y - top) / (height * scale))
+    }
+
+    fn is_pip_scene(&self) -> bool {
+        self.pipeline
+            .upgrade()
+            .is_some_and(|pipeline| pipeline.get_scene() == scenes::PICTURE_IN_PICTURE)
+    }
+
+    fn on_button_press(&self, event: &gdk::EventButton) -> bool {
+        if event.get_event_type() != gdk::EventType::ButtonPress || !self.is_pip_scene() {
+            return false;
+        }
+
+        let settings = utils::load_settings();
+        let (x, y) = self.to_output(&settings, event.get_position());
+        let pip = settings.pip;
+        let inside = x >= pip.x && x <= pip.x + pip.size && y >= pip.y && y <= pip.y + pip.size;
+        if !inside {
+            return false;
+        }
+
+        match event.get_button() {
+            1 => {
+                let handle = pip.size * (1.0 - RESIZE_HANDLE);
+                self.drag.replace(Some(Drag {
+                    origin: pip,
+                    start: (x, y),
+                    resizing: x >= pip.x + handle && y >= pip.y + handle,
+                    settings,
+                }));
+                true
+            }
+            3 => {
+                self.show_corner_menu(event);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn on_motion(&self, position: (f64, f64)) -> bool {
+        let mut drag = self.drag.borrow_mut();
+        let drag = match *drag {
+            Some(ref mut drag) => drag,
+            None => return false,
+        };
+
+        let (x, y) = self.to_output(&drag.settings, position);
+        let (dx, dy) = (x - drag.start.0, y - drag.start.1);
+        let origin = drag.origin;
+        let pip = if drag.resizing {
+            // Grows towards the bottom right but never out of the output
+            let size = (origin.size + dx.max(dy))
+                .max(MIN_SIZE)
+                .min(1.0 - origin.x)
+                .min(1.0 - origin.y);
+            PipSettings { size, ..origin }
+        } else {
+            PipSettings {
+                x: (origin.x + dx).max(0.0).min(1.0 - origin.size),
+                y: (origin.y + dy).max(0.0).min(1.0 - origin.size),
+                ..origin
+            }
+        };
+
+        drag.settings.pip = pip;
+        if let Some(pipeline) = self.pipeline.upgrade() {
+            pipeline.place_pip(&drag.settings);
+        }
+        true
+    }
+
+    fn on_button_release(&self, position: (f64, f64)) -> bool {
+        if !self.on_motion(position) {
+            return false;
+        }
+        let drag = match self.drag.borrow_mut().take() {
+            Some(drag) => drag,
+            None => return false,
+        };
+
+        self.save(drag.settings.pip.snapped());
+        true
+    }
+
+    // Offer the corner presets
+    fn show_corner_menu(&self, event: &gdk::EventButton) {
+        let menu = gtk::Menu::new();
+        for &(label, right, bottom) in &[
+            ("Top left", false, false),
+            ("Top right", true, false),
+            ("Bottom left", false, true),
+            ("Bottom right", true, true),
+        ] {
+            let item = gtk::MenuItem::new_with_label(label);
+            let pip_drag_weak = self.downgrade();
+            item.connect_activate(move |_| {
+                let pip_drag = upgrade_weak!(pip_drag_weak);
+                let pip = utils::load_settings().pip;
+                pip_drag.save(pip.at_corner(right, bottom));
+            });
+            menu.append(&item);
+        }
+        menu.set_property_attach_widget(Some(&self.preview));
+        menu.show_all();
+        menu.popup_easy(event.get_button(), event.get_time());
+    }
+
+    // Keep the camera where it was put, on top of whatever changed in the settings meanwhile
+    fn save(&self, pip: PipSettings) {
+        let mut settings = utils::load_settings();
+        settings.pip = pip;
+        utils::save_settings(&settings);
+
+        if let Some(pipeline) = self.pipeline.upgrade() {
+            pipeline.place_pip(&settings);
+        }
+    }
+}
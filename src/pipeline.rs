@@ -1,18 +1,64 @@
-use base64;
-use glib;
 use gst::{self, prelude::*};
-use gtk;
-use strfmt::strfmt;
+use strfmt::{strfmt_map, Formatter};
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::error;
+use std::fs;
 use std::ops;
+use std::path::{Path, PathBuf};
 use std::rc::{Rc, Weak};
-
-use crate::audio_vumeter::AudioVuMeterWeak;
-use crate::settings::VideoResolution;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::assets;
+use crate::audio_mixer;
+use crate::audio_vumeter::{self, AudioVuMeterWeak, LevelSource};
+use crate::cli;
+use crate::debug_hud::DebugStats;
+use crate::devices;
+use crate::encoders::VideoCodec;
+use crate::gl_context;
+use crate::logos;
+use crate::output::{file, hls, whip, Outputs};
+use crate::overlay_bridge;
+use crate::pipeline_object::PipelineObject;
+use crate::rtp;
+use crate::scenes;
+use crate::session_log::{EventKind, SessionLogWeak};
+use crate::settings::{
+    AudioFilterSettings, AudioInputSettings, AudioMix, BackgroundKind, CameraBackend,
+    CameraDecoding, CameraFormat, ChromaKeySettings, LayerSettings, LocalRecording, Logo,
+    MonitorSettings, OutputMode, RtspCameraSettings, ScreenSource, Settings,
+};
+use crate::stream_health::{ConnectionState, StreamStats};
+use crate::templates;
+use crate::ticker;
+use crate::toasts;
+use crate::uploader::UploaderWeak;
 use crate::utils;
+use crate::validation;
+
+// How far the overlay got with the last markup or web page given to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayState {
+    Loading,
+    Loaded,
+    Failed,
+}
+
+impl From<bool> for OverlayState {
+    // Whether the markup or page could be handed to wpesrc at all
+    fn from(started: bool) -> Self {
+        if started {
+            OverlayState::Loading
+        } else {
+            OverlayState::Failed
+        }
+    }
+}
 
 // Our refcounted pipeline struct for containing all the media state we have to carry around.
 #[derive(Clone)]
@@ -23,19 +69,113 @@ impl ops::Deref for Pipeline {
     type Target = PipelineInner;
 
     fn deref(&self) -> &PipelineInner {
-        &*self.0
+        &self.0
     }
 }
 
 pub struct PipelineInner {
     pipeline: gst::Pipeline,
-    tee: gst::Element,
     sink: gst::Element,
     wpesrc: gst::Element,
-    recording_bin: RefCell<Option<gst::Bin>>,
-    recording_audio_pad: RefCell<Option<gst::Pad>>,
-    recording_video_pad: RefCell<Option<gst::Pad>>,
+    outputs: Outputs,
+    // Network outputs of the running recording by name, reconnected when they fail
+    network_outputs: RefCell<HashMap<String, NetworkOutput>>,
+    // Branch playing the program audio locally and the output device it plays on, while
+    // monitoring
+    monitor: RefCell<Option<(gst::Element, Option<String>)>>,
+    // Display name of the camera in use, None for the default device of the backend
+    camera_device: RefCell<Option<String>>,
+    // Backend setting the local camera was opened with
+    camera_backend: Cell<CameraBackend>,
+    // Launch description of the camera decoder currently in place
+    camera_decoder: RefCell<String>,
+    // Settings of the network camera in use, None for local devices
+    camera_rtsp: RefCell<Option<RtspCameraSettings>>,
+    // Whether test sources replace the camera and the microphone
+    test_sources: Cell<bool>,
+    // Tries to connect a lost network camera again
+    camera_retry_timeout: RefCell<Option<glib::SourceId>>,
+    // Watches cameras being plugged and unplugged
+    camera_monitor: gst::DeviceMonitor,
+    // Device node of the camera while it is unplugged and the no signal picture is shown
+    camera_lost: RefCell<Option<String>>,
     audio_vumeter: AudioVuMeterWeak,
+    uploader: UploaderWeak,
+    session_log: SessionLogWeak,
+    // Loudest channel of the last program level message, in dB
+    audio_peak: Cell<Option<f64>>,
+    // Only loaded once WebKit reported the whole page loaded
+    overlay_state: Cell<OverlayState>,
+    // Web page wpesrc shows instead of the edited markup
+    overlay_url: RefCell<Option<String>>,
+    // Defined in the variables tab or set by the rundown, available as {name} in the overlay HTML
+    overlay_variables: RefCell<HashMap<String, String>>,
+    // Built-in and imported images and fonts, available as {name} too
+    asset_variables: RefCell<HashMap<String, String>>,
+    // Logos shown on top of the overlay markup
+    logos_html: RefCell<String>,
+    // Counters for the debug HUD, the first two are updated from the streaming threads
+    frame_count: Arc<AtomicU64>,
+    output_bytes: Arc<AtomicU64>,
+    // Video frames that went into the encoder of the stream output, updated from a streaming
+    // thread
+    encoded_frames: Arc<AtomicU64>,
+    // Smoothed delay in nanoseconds between capturing a frame and it reaching the preview sink or
+    // leaving the encoder of the stream, 0 while unknown. Updated from the streaming threads
+    preview_latency: Arc<AtomicU64>,
+    encode_latency: Arc<AtomicU64>,
+    // Last number of dropped frames reported by each element
+    dropped_frames: RefCell<HashMap<String, u64>>,
+    // File the next frame is written to, the snapshot branch only lets frames through while set
+    pending_snapshot: RefCell<Option<PathBuf>>,
+    // Name of the scene placing the camera and the overlay in the mixer
+    scene: RefCell<String>,
+    // While in standby all mixer layers are hidden, whatever the scene says
+    standby: Cell<bool>,
+    // While paused the slate covers the program and the audio is muted, the outputs keep running
+    paused: Cell<bool>,
+    // Launch description of the running screen capture, if any
+    screen_description: RefCell<Option<String>>,
+    // The video clip played as a mixer layer, if any
+    media: RefCell<Option<MediaLayer>>,
+    // Tells messages of the current media layer apart from those of previous ones
+    media_generation: Cell<u32>,
+    // Mute button of the operator, independent of standby and pause
+    muted: Cell<bool>,
+    // Pads of the program audio mixer by input name, see audio_mixer
+    audio_inputs: RefCell<HashMap<String, gst::Pad>>,
+    // Animates the mixer pads while switching scenes
+    transition_timeout: RefCell<Option<glib::SourceId>>,
+    // GObject view of the pipeline with its properties and signals
+    object: PipelineObject,
+    // Samples output_bytes once per second while recording to update the bitrate property
+    bitrate_timeout: RefCell<Option<glib::SourceId>>,
+    bitrate_sample: Cell<u64>,
+    // Running time the outputs were started at, None while not recording
+    recording_started: Cell<Option<i64>>,
+    // Set once an output failed for good during the current recording
+    output_failed: Cell<bool>,
+}
+
+// What is needed to attach a network output again after it failed
+struct NetworkOutput {
+    description: String,
+    audio_tees: Vec<gst::Element>,
+    // Failed reconnection attempts in a row
+    attempts: u32,
+    attached_at: Instant,
+    retry_timeout: Option<glib::SourceId>,
+}
+
+// State of the media layer
+struct MediaLayer {
+    location: PathBuf,
+    looping: bool,
+    generation: u32,
+    // Branches that reached the end of the file
+    eos_count: u32,
+    // Running time the layer was paused at
+    paused_at: Option<i64>,
 }
 
 // Weak reference to our pipeline struct
@@ -50,41 +190,66 @@ impl PipelineWeak {
     }
 }
 
-fn update_overlay(wpesrc: &gst::Element, html_buffer: &str, css_buffer: &str) {
-    const IGALIA_LOGO: &[u8] = include_bytes!("../data/igalia-logo.png");
-    let igalia_logo = format!("data:image/png;base64,{}", base64::encode(IGALIA_LOGO));
-    let igalia_logo_str = igalia_logo.as_str();
-
-    const GST_LOGO: &[u8] = include_bytes!("../data/gst-logo.svg");
-    let gst_logo = format!("data:image/svg+xml;base64,{}", base64::encode(GST_LOGO));
-    let gst_logo_str = gst_logo.as_str();
-
-    let mut vars = HashMap::new();
-    vars.insert("css_buffer".to_string(), &css_buffer);
-    vars.insert("igalia_logo".to_string(), &igalia_logo_str);
-    vars.insert("gst_logo".to_string(), &gst_logo_str);
-
-    let data = &strfmt(&html_buffer, &vars).unwrap();
+// Returns whether the overlay could be handed over to wpesrc
+fn update_overlay(
+    wpesrc: &gst::Element,
+    html_buffer: &str,
+    css_buffer: &str,
+    assets: &HashMap<String, String>,
+    variables: &HashMap<String, String>,
+    logos_html: &str,
+) -> bool {
+    // User-defined variables take precedence over assets with the same name
+    let mut vars = assets
+        .iter()
+        .chain(variables)
+        .map(|(name, value)| (name.clone(), value.as_str()))
+        .collect::<HashMap<_, _>>();
+    vars.insert("css_buffer".to_string(), css_buffer);
+
+    // Variables that are not defined yet are left in place, so they show up in the overlay.
+    // Only malformed braces make the markup fail to render
+    let mut data = match strfmt_map(html_buffer, &|mut fmt: Formatter| match vars.get(fmt.key) {
+        Some(value) => fmt.str(value),
+        None => fmt.skip(),
+    }) {
+        Ok(data) => data,
+        Err(_) => return false,
+    };
+    // Browsers put whatever follows the markup into its body
+    data.push_str(logos_html);
     let bytes = glib::Bytes::from(&data.as_bytes());
-    wpesrc.emit("load-bytes", &[&bytes]).unwrap();
+    wpesrc.emit("load-bytes", &[&bytes]).is_ok()
 }
 
 impl Pipeline {
-    pub fn new(audio_vumeter: AudioVuMeterWeak) -> Result<Self, Box<dyn error::Error>> {
+    pub fn new(
+        audio_vumeter: AudioVuMeterWeak,
+        uploader: UploaderWeak,
+        session_log: SessionLogWeak,
+    ) -> Result<Self, Box<dyn error::Error>> {
         let settings = utils::load_settings();
 
-        let (width, height) = match settings.video_resolution {
-            VideoResolution::V480P => (640, 480),
-            VideoResolution::V720P => (1280, 720),
-            VideoResolution::V1080P => (1920, 1080),
+        let (width, height) = output_size(&settings);
+
+        // Without window the program is only counted, not shown
+        let preview_sink = if cli::is_headless() {
+            "fakesink name=sink sync=1"
+        } else {
+            "gtkglsink enable-last-sample=0 name=sink"
         };
 
         let pipeline = gst::parse_launch(&format!(
-            "glvideomixerelement name=mixer sink_1::zorder=0 sink_1::height={height} sink_1::width={width} \
-             ! tee name=tee ! queue ! gtkglsink enable-last-sample=0 name=sink \
-             autoaudiosrc ! tee name=audio-tee ! queue ! level ! fakesink sync=1 \
-             wpesrc name=wpesrc draw-background=0 ! capsfilter name=wpecaps caps=\"video/x-raw(memory:GLMemory),width={width},height={height},pixel-aspect-ratio=(fraction)1/1\" ! glcolorconvert ! queue ! mixer. \
-             v4l2src name=videosrc ! capsfilter name=camcaps caps=\"image/jpeg,width={width},height={height},framerate=30/1\" ! decodebin ! queue ! glupload ! glcolorconvert ! queue ! mixer.", width=width, height=height)
+            "glvideomixerelement name=mixer background=black sink_0::zorder=2 sink_1::zorder=1 sink_1::height={height} sink_1::width={width} \
+             ! capsfilter name=mixcaps caps=\"{mix_caps}\" ! tee name=tee ! queue ! {preview_sink} \
+             tee. ! valve name=snapshot-valve drop=1 ! queue ! gldownload ! videoconvert ! gdkpixbufsink name=snapshot-sink post-messages=1 async=0 sync=0 \
+             audioconvert name=audio-convert ! level name=input-level ! audioresample ! audio/x-raw,rate=48000,channels=2 ! queue max-size-buffers=0 max-size-time=2000000000 ! audio-mixer.sink_0 \
+             audiomixer name=audio-mixer ! audio/x-raw,rate=48000,channels=2 ! volume name=volume ! audioconvert ! capsfilter name=audio-denoise-caps caps=\"audio/x-raw,format=S16LE,layout=interleaved\" ! identity name=audio-denoise ! audioconvert ! \
+             audiodynamic name=audio-gate mode=expander ! audiodynamic name=audio-compressor mode=compressor characteristics=soft-knee ! equalizer-3bands name=audio-eq ! tee name=audio-tee ! queue ! level name=program-level ! fakesink sync=1 \
+             wpesrc name=wpesrc draw-background=0 ! capsfilter name=wpecaps caps=\"video/x-raw(memory:GLMemory),width={width},height={height},pixel-aspect-ratio=(fraction)1/1\" ! glcolorconvert ! glshader name=overlaycrop ! queue ! mixer. \
+             capsfilter name=camcaps ! decodebin name=camdecode ! queue name=camqueue ! glupload ! glcolorconvert ! glvideoflip name=camflip ! glshader name=camkey ! glshader name=camcrop ! camselect.sink_0 \
+             input-selector name=camselect sync-streams=0 ! queue max-size-time=2000000000 ! mixer. \
+             videotestsrc name=nosignal is-live=1 pattern=smpte ! video/x-raw,width=320,height=180,framerate=30/1 ! glupload ! glcolorconvert ! camselect.sink_1", width=width, height=height, mix_caps=mix_caps(&settings), preview_sink=preview_sink)
         )?;
 
         // Upcast to a gst::Pipeline as the above function could've also returned an arbitrary
@@ -102,21 +267,196 @@ impl Pipeline {
         let sink = pipeline.get_by_name("sink").expect("No sink found");
         let wpesrc = pipeline.get_by_name("wpesrc").expect("No wpesrc found");
 
-        let css_buffer = include_str!("../data/style.css").to_string();
-        let html_buffer = include_str!("../data/index.html").to_string();
-        update_overlay(&wpesrc, &html_buffer, &css_buffer);
+        // The mixer, the overlay and all sinks added later render with the GL context of the
+        // preview, also after being rebuilt
+        gl_context::share(&pipeline, Some(&sink).filter(|_| !cli::is_headless()));
+
+        // The overlay page can post events back to us, the URL to post to is available as
+        // {bridge_url} in the overlay HTML
+        let (bridge_url, bridge_receiver) = match settings.overlay_bridge_port {
+            0 => (None, None),
+            port => match overlay_bridge::start(port) {
+                Ok((url, receiver)) => (Some(url), Some(receiver)),
+                Err(err) => {
+                    utils::show_error_dialog(
+                        false,
+                        format!(
+                            "Failed to listen for overlay events on port {}: {}",
+                            port, err
+                        )
+                        .as_str(),
+                    );
+                    (None, None)
+                }
+            },
+        };
+
+        let mut overlay_variables = settings
+            .overlay_variables
+            .iter()
+            .map(|variable| (variable.name.clone(), variable.value.clone()))
+            .collect::<HashMap<_, _>>();
+        overlay_variables.insert(
+            ticker::VARIABLE.to_string(),
+            ticker::items_html(&settings.ticker_items),
+        );
+        if let Some(url) = bridge_url {
+            overlay_variables
+                .entry("bridge_url".to_string())
+                .or_insert(url);
+        }
+        let asset_variables = assets::variables(settings.inline_assets);
+        let logos_html = logos::overlay_html(&settings.logos);
+        let overlay_url = settings.active_overlay_url().map(String::from);
+        let overlay_started = match overlay_url {
+            Some(ref url) => {
+                wpesrc.set_property("location", url)?;
+                true
+            }
+            None => {
+                let (html_buffer, css_buffer) = templates::load_current();
+                update_overlay(
+                    &wpesrc,
+                    &html_buffer,
+                    &css_buffer,
+                    &asset_variables,
+                    &overlay_variables,
+                    &logos_html,
+                )
+            }
+        };
+
+        let outputs = Outputs::new(&pipeline, &tee);
+
+        // The microphone is linked in the launch description above, further inputs are added
+        // and removed at runtime
+        let mut audio_inputs = HashMap::new();
+        audio_inputs.insert(
+            audio_mixer::MICROPHONE.to_string(),
+            pipeline
+                .get_by_name("audio-mixer")
+                .and_then(|audio_mixer| audio_mixer.get_static_pad("sink_0"))
+                .expect("No microphone pad on the audio mixer"),
+        );
+
+        let camera_monitor = gst::DeviceMonitor::new();
+        camera_monitor.add_filter(Some("Video/Source"), None);
 
         let pipeline = Pipeline(Rc::new(PipelineInner {
             pipeline,
-            tee,
             sink,
             wpesrc,
             audio_vumeter,
-            recording_bin: RefCell::new(None),
-            recording_audio_pad: RefCell::new(None),
-            recording_video_pad: RefCell::new(None),
+            uploader,
+            session_log,
+            audio_peak: Cell::new(None),
+            overlay_state: Cell::new(OverlayState::from(overlay_started)),
+            overlay_url: RefCell::new(overlay_url),
+            overlay_variables: RefCell::new(overlay_variables),
+            asset_variables: RefCell::new(asset_variables),
+            logos_html: RefCell::new(logos_html),
+            frame_count: Arc::new(AtomicU64::new(0)),
+            output_bytes: Arc::new(AtomicU64::new(0)),
+            encoded_frames: Arc::new(AtomicU64::new(0)),
+            preview_latency: Arc::new(AtomicU64::new(0)),
+            encode_latency: Arc::new(AtomicU64::new(0)),
+            dropped_frames: RefCell::new(HashMap::new()),
+            pending_snapshot: RefCell::new(None),
+            scene: RefCell::new(scenes::DEFAULT_SCENE.to_string()),
+            standby: Cell::new(false),
+            paused: Cell::new(false),
+            screen_description: RefCell::new(None),
+            media: RefCell::new(None),
+            media_generation: Cell::new(0),
+            muted: Cell::new(false),
+            audio_inputs: RefCell::new(audio_inputs),
+            transition_timeout: RefCell::new(None),
+            object: PipelineObject::new(),
+            bitrate_timeout: RefCell::new(None),
+            bitrate_sample: Cell::new(0),
+            recording_started: Cell::new(None),
+            output_failed: Cell::new(false),
+            outputs,
+            network_outputs: RefCell::new(HashMap::new()),
+            monitor: RefCell::new(None),
+            camera_device: RefCell::new(None),
+            camera_backend: Cell::new(settings.camera_backend),
+            camera_decoder: RefCell::new("decodebin".to_string()),
+            camera_rtsp: RefCell::new(None),
+            test_sources: Cell::new(false),
+            camera_retry_timeout: RefCell::new(None),
+            camera_monitor,
+            camera_lost: RefCell::new(None),
         }));
 
+        if settings.test_sources {
+            pipeline.set_test_sources()?;
+        } else {
+            if settings.rtsp_camera.enabled {
+                pipeline.set_rtsp_camera(&settings.rtsp_camera)?;
+            } else {
+                pipeline.set_camera_device(settings.camera_device.as_deref())?;
+            }
+            pipeline.set_audio_device(settings.audio_device.as_deref())?;
+        }
+        pipeline.update_camera_caps(&settings, width, height);
+        pipeline.set_secondary_audio_device(settings.secondary_audio_device.as_deref())?;
+        pipeline.set_av_offset(settings.av_offset);
+        pipeline.update_audio_filters(&settings.audio_filters);
+        pipeline.update_chroma_key(&settings.chroma_key);
+        pipeline.update_crop(&settings);
+        pipeline.set_audio_input_volume(
+            audio_mixer::MICROPHONE,
+            settings
+                .audio_inputs
+                .get(audio_mixer::MICROPHONE)
+                .cloned()
+                .unwrap_or_default(),
+        );
+        if settings.desktop_audio {
+            if let Err(err) = pipeline.set_desktop_audio(true) {
+                utils::show_error_dialog(
+                    false,
+                    format!("Failed to capture the desktop audio: {}", err).as_str(),
+                );
+            }
+        }
+        pipeline.update_background(&settings, width, height)?;
+        if let Err(err) = pipeline.update_screen_capture(&settings) {
+            utils::show_error_dialog(
+                false,
+                format!("Failed to capture the screen: {}", err).as_str(),
+            );
+        }
+        pipeline.update_camera_orientation(&settings, width, height);
+
+        // Count the frames shown in the preview
+        let frame_count = pipeline.frame_count.clone();
+        pipeline
+            .sink
+            .get_static_pad("sink")
+            .expect("Sink had no sink pad")
+            .add_probe(gst::PadProbeType::BUFFER, move |_, _| {
+                frame_count.fetch_add(1, AtomicOrdering::Relaxed);
+                gst::PadProbeReturn::Ok
+            });
+        add_latency_probe(
+            &pipeline
+                .sink
+                .get_static_pad("sink")
+                .expect("Sink had no sink pad"),
+            pipeline.preview_latency.clone(),
+        );
+
+        if let Some(receiver) = bridge_receiver {
+            let pipeline_weak = pipeline.downgrade();
+            receiver.attach(None, move |event| {
+                let pipeline = upgrade_weak!(pipeline_weak, glib::Continue(false));
+                pipeline.object.emit_overlay_event(&event.to_string());
+                glib::Continue(true)
+            });
+        }
+
         // Install a message handler on the pipeline's bus to catch errors
         let bus = pipeline.pipeline.get_bus().expect("Pipeline had no bus");
 
@@ -137,43 +477,81 @@ impl Pipeline {
         })
         .expect("Unable to add bus watch");
 
+        // Same for the camera monitor, which tells us about cameras coming and going
+        let pipeline_weak = pipeline.downgrade();
+        pipeline
+            .camera_monitor
+            .get_bus()
+            .add_watch_local(move |_bus, msg| {
+                let pipeline = upgrade_weak!(pipeline_weak, glib::Continue(false));
+
+                pipeline.on_camera_monitor_message(msg);
+
+                glib::Continue(true)
+            })
+            .expect("Unable to add camera monitor bus watch");
+
+        // Without device providers we can't notice cameras coming back but unplugging them still
+        // shows the no signal picture
+        let _ = pipeline.camera_monitor.start();
+
         Ok(pipeline)
     }
 
     pub fn refresh(&self) {
         let settings = utils::load_settings();
 
-        let (width, height) = match settings.video_resolution {
-            VideoResolution::V480P => (640, 480),
-            VideoResolution::V720P => (1280, 720),
-            VideoResolution::V1080P => (1920, 1080),
-        };
+        let (width, height) = output_size(&settings);
+
+        if let Err(err) = self.update_camera(&settings) {
+            utils::show_error_dialog(
+                false,
+                format!("Failed to change the camera: {}", err).as_str(),
+            );
+        }
+
+        self.update_camera_caps(&settings, width, height);
 
-        let cam_caps_filter = self
-            .pipeline
-            .get_by_name("camcaps")
-            .expect("No webcam capsfilter found");
-        let mixer = self.pipeline.get_by_name("mixer").expect("No mixer found");
         let wpecaps_filter = self
             .pipeline
             .get_by_name("wpecaps")
             .expect("No wpe capsfilter found");
 
-        cam_caps_filter.set_property_from_str(
-            "caps",
-            &format!(
-                "image/jpeg,width={width},height={height},framerate=30/1",
-                width = width,
-                height = height
-            ),
-        );
         wpecaps_filter.set_property_from_str("caps", &format!("video/x-raw(memory:GLMemory),width={width},height={height},pixel-aspect-ratio=(fraction)1/1", width=width, height=height));
 
-        if let Some(pad) = mixer.get_static_pad("sink_1") {
-            pad.set_property("width", &width)
-                .expect("No width pad property");
-            pad.set_property("height", &height)
-                .expect("No height pad property");
+        let mixcaps_filter = self
+            .pipeline
+            .get_by_name("mixcaps")
+            .expect("No mixer capsfilter found");
+        mixcaps_filter.set_property_from_str("caps", &mix_caps(&settings));
+
+        if let Err(err) = self.update_screen_capture(&settings) {
+            utils::show_error_dialog(
+                false,
+                format!("Failed to capture the screen: {}", err).as_str(),
+            );
+        }
+        self.update_camera_orientation(&settings, width, height);
+        self.set_av_offset(settings.av_offset);
+        self.update_audio_filters(&settings.audio_filters);
+        self.update_chroma_key(&settings.chroma_key);
+        self.update_crop(&settings);
+
+        if let Err(err) = self.update_background(&settings, width, height) {
+            utils::show_error_dialog(
+                false,
+                format!("Failed to update mixer background: {}", err).as_str(),
+            );
+        }
+
+        // Rebuild the slate for the new settings
+        if self.paused.get() {
+            if let Err(err) = self.add_slate(&settings, width, height) {
+                utils::show_error_dialog(
+                    false,
+                    format!("Failed to update the slate: {}", err).as_str(),
+                );
+            }
         }
 
         self.pipeline.set_state(gst::State::Paused).unwrap();
@@ -184,346 +562,3246 @@ impl Pipeline {
         self.pipeline.set_state(gst::State::Playing).unwrap();
     }
 
-    // Downgrade to a weak reference
-    pub fn downgrade(&self) -> PipelineWeak {
-        PipelineWeak(Rc::downgrade(&self.0))
+    // Identifies the camera for the per-camera settings: the device node of v4l2 cameras or the
+    // node path of PipeWire ones
+    pub fn camera_id(&self) -> String {
+        let videosrc = match self.pipeline.get_by_name("videosrc") {
+            Some(videosrc) => videosrc,
+            None => return String::new(),
+        };
+
+        ["device", "path", "location"]
+            .iter()
+            .filter(|property| videosrc.find_property(**property).is_some())
+            .filter_map(|property| videosrc.get_property(*property).ok())
+            .filter_map(|value| value.get::<String>().ok().flatten())
+            .next()
+            .unwrap_or_default()
     }
 
-    pub fn get_widget(&self) -> gtk::Widget {
-        // Get the GTK video sink and retrieve the video display widget from it
-        let widget_value = self
-            .sink
-            .get_property("widget")
-            .expect("Sink had no widget property");
+    // Replace the camera by the video source device with the given display name, or the default
+    // device of the camera backend if None
+    pub fn set_camera_device(
+        &self,
+        display_name: Option<&str>,
+    ) -> Result<(), Box<dyn error::Error>> {
+        let camcaps = self
+            .pipeline
+            .get_by_name("camcaps")
+            .expect("No webcam capsfilter found");
 
-        widget_value
-            .get::<gtk::Widget>()
-            .expect("Sink's widget propery was of the wrong type")
-            .unwrap()
-    }
+        let videosrc =
+            devices::create_camera_source(display_name, self.camera_backend.get(), "videosrc")
+                .map_err(|err| format!("Failed to create camera source: {}", err))?;
+        self.replace_camera_source(&videosrc)?;
+        videosrc.link(&camcaps)?;
+        videosrc.sync_state_with_parent()?;
 
-    pub fn start(&self) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
-        // This has no effect if called multiple times
-        self.pipeline.set_state(gst::State::Playing)
-    }
+        self.camera_device
+            .replace(display_name.map(|name| name.to_string()));
+        self.camera_rtsp.replace(None);
+        self.test_sources.set(false);
 
-    pub fn stop(&self) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
-        // This has no effect if called multiple times
-        self.pipeline.set_state(gst::State::Null)
+        Ok(())
     }
 
-    // Start recording to the configured location
-    pub fn start_recording(&self) -> Result<(), Box<dyn error::Error>> {
-        let settings = utils::load_settings();
+    // Replace the camera and the microphone by a test pattern and a ticking tone, to run without
+    // any devices
+    fn set_test_sources(&self) -> Result<(), Box<dyn error::Error>> {
+        let camcaps = self
+            .pipeline
+            .get_by_name("camcaps")
+            .expect("No webcam capsfilter found");
+
+        let videosrc = gst::ElementFactory::make("videotestsrc", Some("videosrc"))
+            .map_err(|err| format!("Failed to create test camera source: {}", err))?;
+        videosrc.set_property("is-live", &true)?;
+        // Moving, so frozen or dropped frames are noticed
+        videosrc.set_property_from_str("pattern", "ball");
+        self.replace_camera_source(&videosrc)?;
+        videosrc.link(&camcaps)?;
+        videosrc.sync_state_with_parent()?;
+
+        let audiosrc = gst::ElementFactory::make("audiotestsrc", Some("audiosrc"))
+            .map_err(|err| format!("Failed to create test audio source: {}", err))?;
+        audiosrc.set_property("is-live", &true)?;
+        audiosrc.set_property_from_str("wave", "ticks");
+        self.replace_audio_source(&audiosrc)?;
+
+        self.camera_device.replace(None);
+        self.camera_rtsp.replace(None);
+        self.test_sources.set(true);
+
+        Ok(())
+    }
 
-        if settings.rtmp_location.is_none() {
-            return Err("Please set the RTMP end-point URL in the settings".into());
+    // Replace the camera by the network camera
+    fn set_rtsp_camera(&self, rtsp: &RtspCameraSettings) -> Result<(), Box<dyn error::Error>> {
+        let url = rtsp
+            .url
+            .as_deref()
+            .ok_or("No network camera URL configured")?;
+
+        let videosrc = gst::ElementFactory::make("rtspsrc", Some("videosrc"))
+            .map_err(|err| format!("Failed to create network camera source: {}", err))?;
+        videosrc.set_property("location", &url)?;
+        videosrc.set_property("latency", &rtsp.latency)?;
+        if let Some(ref username) = rtsp.username {
+            videosrc.set_property("user-id", username)?;
+        }
+        if let Some(ref password) = rtsp.password {
+            videosrc.set_property("user-pw", password)?;
         }
-        let bin_description = &format!(
-            "queue name=video-queue ! gldownload ! videoconvert ! {h264_encoder} ! \
-             flvmux streamable=1 name=mux ! rtmpsink enable-last-sample=0 location=\"{location}\" \
-             queue name=audio-queue ! fdkaacenc bitrate=128000 ! mux.",
-            location = settings.rtmp_location.unwrap(),
-            h264_encoder = settings.h264_encoder
-        );
 
-        let bin = gst::parse_bin_from_description(bin_description, false)
-            .map_err(|err| format!("Failed to create recording pipeline: {}", err))?;
-        bin.set_name("recording-bin")
-            .map_err(|err| format!("Failed to set recording bin name: {}", err))?;
-
-        let video_queue = bin
-            .get_by_name("video-queue")
-            .expect("No video-queue found");
-        let audio_queue = bin
-            .get_by_name("audio-queue")
-            .expect("No audio-queue found");
-        let audio_tee = self
+        // The RTP streams of the camera only appear once connected. The video one is depayloaded
+        // and decoded by the decodebin after the capsfilter
+        let camcaps = self
             .pipeline
-            .get_by_name("audio-tee")
-            .expect("No audio-tee found");
+            .get_by_name("camcaps")
+            .expect("No webcam capsfilter found");
+        let camcaps_weak = camcaps.downgrade();
+        videosrc.connect_pad_added(move |_, srcpad| {
+            let camcaps = upgrade_weak!(camcaps_weak);
+            let sinkpad = camcaps
+                .get_static_pad("sink")
+                .expect("capsfilter has no sink pad");
+            if sinkpad.is_linked() {
+                return;
+            }
 
-        // Add the bin to the pipeline. This would only fail if there was
-        // already a bin with the same name, which we ensured can't happen
-        self.pipeline
-            .add(&bin)
-            .expect("Failed to add recording bin");
+            let caps = srcpad.query_caps(None);
+            let is_video = caps
+                .as_ref()
+                .and_then(|caps| caps.get_structure(0))
+                .and_then(|s| s.get::<&str>("media").ok().flatten().map(|m| m == "video"))
+                .unwrap_or(false);
+            if is_video {
+                let _ = srcpad.link(&sinkpad);
+            }
+        });
 
-        // Get our tee element by name, request a new source pad from it and then link that to our
-        // recording bin to actually start receiving data
-        let srcpad = self
-            .tee
-            .get_request_pad("src_%u")
-            .expect("Failed to request new pad from tee");
-        let sinkpad = video_queue
-            .get_static_pad("sink")
-            .expect("Failed to get sink pad from recording bin");
-
-        *self.recording_video_pad.borrow_mut() = Some(srcpad.clone());
-        if let Ok(video_ghost_pad) = gst::GhostPad::new(Some("video_sink"), &sinkpad) {
-            bin.add_pad(&video_ghost_pad).unwrap();
-            // If linking fails, we just undo what we did above
-            if let Err(err) = srcpad.link(&video_ghost_pad) {
-                // This might fail but we don't care anymore: we're in an error path
-                let _ = self.pipeline.remove(&bin);
-                let _ = bin.set_state(gst::State::Null);
-
-                return Err(
-                    format!("Failed to link recording bin video branch: {}", err)
-                        .as_str()
-                        .into(),
-                );
+        self.replace_camera_source(&videosrc)?;
+        videosrc.sync_state_with_parent()?;
+
+        self.camera_rtsp.replace(Some(rtsp.clone()));
+        self.test_sources.set(false);
+        self.camera_device.replace(None);
+
+        Ok(())
+    }
+
+    // Use the camera configured in the settings, a network camera or a local device
+    fn update_camera(&self, settings: &Settings) -> Result<(), Box<dyn error::Error>> {
+        if settings.test_sources {
+            if !self.test_sources.get() {
+                self.set_test_sources()?;
             }
+            return Ok(());
         }
 
-        let audio_srcpad = audio_tee
-            .get_request_pad("src_%u")
-            .expect("Failed to request new pad from audio-tee");
-        let queue_sinkpad = audio_queue
-            .get_static_pad("sink")
-            .expect("Failed to get sink pad from queue");
-
-        *self.recording_audio_pad.borrow_mut() = Some(audio_srcpad.clone());
-        if let Ok(audio_ghost_pad) = gst::GhostPad::new(Some("audio_sink"), &queue_sinkpad) {
-            bin.add_pad(&audio_ghost_pad).unwrap();
-            // If linking fails, we just undo what we did above
-            if let Err(err) = audio_srcpad.link(&audio_ghost_pad) {
-                // This might fail but we don't care anymore: we're in an error path
-                let _ = self.pipeline.remove(&bin);
-                let _ = bin.set_state(gst::State::Null);
-
-                return Err(
-                    format!("Failed to link recording bin audio branch: {}", err)
-                        .as_str()
-                        .into(),
-                );
+        // The test tone took the place of the microphone too
+        let leaving_test_sources = self.test_sources.get();
+        if settings.rtsp_camera.enabled {
+            if leaving_test_sources
+                || self.camera_rtsp.borrow().as_ref() != Some(&settings.rtsp_camera)
+            {
+                self.set_rtsp_camera(&settings.rtsp_camera)?;
             }
+        } else if leaving_test_sources
+            || self.camera_rtsp.borrow().is_some()
+            || settings.camera_device != *self.camera_device.borrow()
+            || settings.camera_backend != self.camera_backend.get()
+        {
+            self.camera_backend.set(settings.camera_backend);
+            self.set_camera_device(settings.camera_device.as_deref())?;
+        }
+        if leaving_test_sources {
+            self.set_audio_device(settings.audio_device.as_deref())?;
+        }
+
+        Ok(())
+    }
+
+    // Shut down the old camera source and add the new one in its place, which still has to be
+    // linked and started
+    fn replace_camera_source(&self, videosrc: &gst::Element) -> Result<(), Box<dyn error::Error>> {
+        if let Some(source_id) = self.camera_retry_timeout.borrow_mut().take() {
+            glib::source_remove(source_id);
         }
 
-        bin.set_state(gst::State::Playing)
-            .map_err(|_err| "Failed to start recording")?;
+        if let Some(old_videosrc) = self.pipeline.get_by_name("videosrc") {
+            let _ = old_videosrc.set_state(gst::State::Null);
+            self.pipeline.remove(&old_videosrc)?;
+        }
+        self.pipeline.add(videosrc)?;
 
-        *self.recording_bin.borrow_mut() = Some(bin);
+        self.camera_lost.replace(None);
+        self.select_camera_input("sink_0");
 
         Ok(())
     }
 
-    // Stop recording if any recording was currently ongoing
-    pub fn stop_recording(&self) {
-        // Get our recording bin, if it does not exist then nothing has to be stopped actually.
-        // This shouldn't really happen
-        let bin = match self.recording_bin.borrow_mut().take() {
-            None => return,
-            Some(bin) => bin,
-        };
+    // Switch the camera layer between the camera (sink_0) and the no signal picture (sink_1)
+    fn select_camera_input(&self, pad_name: &str) {
+        let camselect = self
+            .pipeline
+            .get_by_name("camselect")
+            .expect("No camera selector found");
+        let pad = camselect
+            .get_static_pad(pad_name)
+            .expect("Camera selector has no such pad");
+        camselect
+            .set_property("active-pad", &pad)
+            .expect("Failed to select camera input");
+    }
 
-        let recordind_audio_srcpad = match self.recording_audio_pad.borrow_mut().take() {
-            None => return,
-            Some(bin) => bin,
-        };
-        let recordind_video_srcpad = match self.recording_video_pad.borrow_mut().take() {
-            None => return,
-            Some(bin) => bin,
+    // Take the failed source out of the program unless it is part of the core pipeline. Returns
+    // false for errors that can't be recovered from
+    fn on_source_failed(&self, src: &gst::Object, text: &str) -> bool {
+        let source = match ancestor_of(src, |name| {
+            name == "audiosrc"
+                || name == "secondary-audio-bin"
+                || name == "media-bin"
+                || name.starts_with("audio-input-")
+        }) {
+            Some(source) => source,
+            None => return false,
         };
+        // Follow-up errors of sources that were already dropped
+        if self
+            .pipeline
+            .get_by_name(&source.get_name())
+            .map(|e| e.upcast::<gst::Object>())
+            != Some(source.clone())
+        {
+            return true;
+        }
 
-        let video_queue = bin
-            .get_by_name("video-queue")
-            .expect("No video-queue found");
-        let audio_queue = bin
-            .get_by_name("audio-queue")
-            .expect("No audio-queue found");
-
-        let sinkpad = video_queue
-            .get_static_pad("sink")
-            .expect("Failed to get video sink pad from recording bin");
-
-        // Once the tee source pad is idle and we wouldn't interfere with any data flow, unlink the
-        // tee and the recording bin and remove/finalize the recording bin
-        //
-        // The closure below might be called directly from the main UI thread here or at a later
-        // time from a GStreamer streaming thread
-        let pipeline_weak = self.pipeline.downgrade();
-        recordind_video_srcpad.add_probe(gst::PadProbeType::IDLE, move |srcpad, _| {
-            // Get the parent of the tee source pad, i.e. the tee itself
-            if let Some(parent) = srcpad.get_parent() {
-                if let Ok(tee) = parent.downcast::<gst::Element>() {
-                    let _ = srcpad.unlink(&sinkpad);
-                    tee.release_request_pad(srcpad);
-
-                    let pipeline = upgrade_weak!(pipeline_weak, gst::PadProbeReturn::Remove);
-                    pipeline.call_async(move |pipeline| {
-                        let bin = match pipeline.get_by_name("recording-bin") {
-                            Some(bin) => bin,
-                            None => return,
-                        };
-                        let pbin = pipeline.clone().upcast::<gst::Bin>();
-                        // Ignore if the bin was not in the pipeline anymore for whatever
-                        // reason. It's not a problem
-                        let _ = pbin.remove(&bin);
-
-                        if let Err(err) = bin.set_state(gst::State::Null) {
-                            let bus = pbin.get_bus().expect("Pipeline has no bus");
-                            let _ = bus.post(&Self::create_application_warning_message(
-                                format!("Failed to stop recording: {}", err).as_str(),
-                            ));
-                        }
-                    });
+        let name = source.get_name();
+        let (result, message) = if name == "audiosrc" {
+            // Keep the audio mixer running with silence until another microphone is selected
+            let result = gst::ElementFactory::make("audiotestsrc", Some("audiosrc"))
+                .map_err(|err| err.into())
+                .and_then(|audiosrc| {
+                    audiosrc.set_property("is-live", &true)?;
+                    audiosrc.set_property_from_str("wave", "silence");
+                    self.replace_audio_source(&audiosrc)
+                });
+            (result, "The microphone failed and was muted")
+        } else if name == "secondary-audio-bin" {
+            (
+                self.set_secondary_audio_device(None),
+                "The secondary audio device failed and was removed",
+            )
+        } else if name == "media-bin" {
+            self.stop_media();
+            (Ok(()), "The media clip failed and was stopped")
+        } else {
+            let input = name.trim_start_matches("audio-input-");
+            (
+                self.remove_audio_input(input),
+                "An audio input failed and was removed",
+            )
+        };
 
-                    // Don't block the pad but remove the probe to let everything
-                    // continue as normal
-                    return gst::PadProbeReturn::Remove;
-                }
-            }
-            gst::PadProbeReturn::Ok
-        });
+        if let Err(err) = result {
+            utils::show_error_dialog(true, format!("{}. {}", text, err).as_str());
+            return true;
+        }
+        if let Some(session_log) = self.session_log.upgrade() {
+            session_log.log(EventKind::Warning, message);
+        }
+        utils::show_error_dialog(false, format!("{}. {}", message, text).as_str());
+        true
+    }
 
-        let audio_sinkpad = audio_queue
-            .get_static_pad("sink")
-            .expect("Failed to get audio sink pad from recording bin");
-
-        let pipeline_weak = self.pipeline.downgrade();
-        recordind_audio_srcpad.add_probe(gst::PadProbeType::IDLE, move |srcpad, _| {
-            // Get the parent of the tee source pad, i.e. the tee itself
-            if let Some(parent) = srcpad.get_parent() {
-                if let Ok(tee) = parent.downcast::<gst::Element>() {
-                    let _ = srcpad.unlink(&audio_sinkpad);
-                    tee.release_request_pad(srcpad);
-
-                    let pipeline = upgrade_weak!(pipeline_weak, gst::PadProbeReturn::Remove);
-                    pipeline.call_async(move |pipeline| {
-                        let bin = match pipeline.get_by_name("recording-bin") {
-                            Some(bin) => bin,
-                            None => return,
-                        };
+    // Show the no signal picture instead of the camera that went away and drop its source, it is
+    // created again once the camera is back
+    fn on_camera_lost(&self) {
+        if self.camera_lost.borrow().is_some() {
+            return;
+        }
+        self.camera_lost.replace(Some(self.camera_id()));
+        self.select_camera_input("sink_1");
 
-                        let pbin = pipeline.clone().upcast::<gst::Bin>();
-                        // Ignore if the bin was not in the pipeline anymore for whatever
-                        // reason. It's not a problem
-                        let _ = pbin.remove(&bin);
+        if let Some(videosrc) = self.pipeline.get_by_name("videosrc") {
+            let _ = videosrc.set_state(gst::State::Null);
+            let _ = self.pipeline.remove(&videosrc);
+        }
 
-                        if let Err(err) = bin.set_state(gst::State::Null) {
-                            let bus = pbin.get_bus().expect("Pipeline has no bus");
-                            let _ = bus.post(&Self::create_application_warning_message(
-                                format!("Failed to stop recording: {}", err).as_str(),
-                            ));
-                        }
-                    });
+        if let Some(session_log) = self.session_log.upgrade() {
+            session_log.log(EventKind::Warning, "Camera disconnected");
+        }
 
-                    // Don't block the pad but remove the probe to let everything
-                    // continue as normal
-                    return gst::PadProbeReturn::Remove;
+        // Network cameras don't show up in the device monitor, so try again from time to time
+        let rtsp = match *self.camera_rtsp.borrow() {
+            Some(ref rtsp) => rtsp.clone(),
+            None => return,
+        };
+        let pipeline_weak = self.downgrade();
+        let source_id = glib::timeout_add_seconds_local(CAMERA_RETRY_INTERVAL, move || {
+            let pipeline = upgrade_weak!(pipeline_weak, glib::Continue(false));
+            pipeline.camera_retry_timeout.borrow_mut().take();
+            if let Err(err) = pipeline.set_rtsp_camera(&rtsp) {
+                if let Some(session_log) = pipeline.session_log.upgrade() {
+                    session_log.log(
+                        EventKind::Warning,
+                        &format!("Failed to reconnect the camera: {}", err),
+                    );
                 }
             }
-            gst::PadProbeReturn::Ok
+            glib::Continue(false)
         });
+        self.camera_retry_timeout.replace(Some(source_id));
     }
 
-    pub fn update_overlay(&self, html_buffer: &str, css_buffer: &str) {
-        update_overlay(&self.wpesrc, html_buffer, css_buffer);
-    }
-
-    // Here we handle all message we get from the GStreamer pipeline. These are notifications sent
-    // from GStreamer, including errors that happend at runtime.
-    //
-    // This is always called from the main application thread by construction.
-    fn on_pipeline_message(&self, msg: &gst::MessageRef) {
+    fn on_camera_monitor_message(&self, msg: &gst::MessageRef) {
         use gst::MessageView;
 
-        // A message can contain various kinds of information but
-        // here we are only interested in errors so far
         match msg.view() {
-            MessageView::Error(err) => {
-                utils::show_error_dialog(
-                    true,
-                    format!(
-                        "Error from {:?}: {} ({:?})",
-                        err.get_src().map(|s| s.get_path_string()),
-                        err.get_error(),
-                        err.get_debug()
-                    )
-                    .as_str(),
-                );
+            MessageView::DeviceRemoved(removed) => {
+                let device = removed.get_device();
+                let camera_id = self.camera_id();
+                if devices::matches(&device, self.camera_device.borrow().as_deref(), &camera_id) {
+                    self.on_camera_lost();
+                }
             }
-            MessageView::Application(msg) => match msg.get_structure() {
-                // Here we can send ourselves messages from any thread and show them to the user in
-                // the UI in case something goes wrong
-                Some(s) if s.get_name() == "warning" => {
-                    let text = s
-                        .get::<&str>("text")
-                        .expect("Warning message without text")
-                        .unwrap();
-                    utils::show_error_dialog(false, text);
+            MessageView::DeviceAdded(added) => {
+                let device = added.get_device();
+                let camera_device = self.camera_device.borrow().clone();
+                let returned = match *self.camera_lost.borrow() {
+                    Some(ref camera_id) => {
+                        devices::matches(&device, camera_device.as_deref(), camera_id)
+                    }
+                    None => false,
+                };
+                if !returned {
+                    return;
                 }
-                _ => (),
-            },
-            MessageView::Element(msg) => {
-                if let Some(structure) = msg.get_structure() {
-                    if structure.get_name() == "level" {
-                        let rms = structure
-                            .get::<glib::ValueArray>("rms")
-                            .expect("level message without RMS value")
-                            .unwrap();
-                        let rms_values = rms
-                            .iter()
-                            .map(|v| v.get_some::<f64>().unwrap())
-                            .collect::<Vec<_>>();
-
-                        let peak = structure
-                            .get::<glib::ValueArray>("peak")
-                            .expect("level message without Peak value")
-                            .unwrap();
-                        let peak_values = peak
-                            .iter()
-                            .map(|v| v.get_some::<f64>().unwrap())
-                            .collect::<Vec<_>>();
-
-                        let decay = structure
-                            .get::<glib::ValueArray>("decay")
-                            .expect("level message without Decay value")
-                            .unwrap();
-                        let decay_values = decay
-                            .iter()
-                            .map(|v| v.get_some::<f64>().unwrap())
-                            .collect::<Vec<_>>();
 
-                        let audio_vumeter = &self.audio_vumeter;
-                        let mut vumeter = upgrade_weak!(audio_vumeter);
-                        vumeter.update(&rms_values, &peak_values, &decay_values);
+                match self.set_camera_device(camera_device.as_deref()) {
+                    Ok(()) => {
+                        if let Some(session_log) = self.session_log.upgrade() {
+                            session_log.log(EventKind::Reconnect, "Camera reconnected");
+                        }
                     }
-                }
-            }
-            MessageView::StateChanged(state_changed) => {
-                if let Some(element) = msg.get_src() {
-                    if element == self.pipeline {
-                        let bin_ref = element.downcast_ref::<gst::Bin>().unwrap();
-                        let filename = format!(
-                            "gst-wpe-broadcast-demo-{:#?}_to_{:#?}",
-                            state_changed.get_old(),
-                            state_changed.get_current()
-                        );
-                        bin_ref.debug_to_dot_file_with_ts(gst::DebugGraphDetails::all(), filename);
+                    Err(err) => {
+                        if let Some(session_log) = self.session_log.upgrade() {
+                            session_log.log(
+                                EventKind::Warning,
+                                &format!("Failed to reconnect the camera: {}", err),
+                            );
+                        }
                     }
                 }
             }
-            MessageView::AsyncDone(_) => {
-                if let Some(element) = msg.get_src() {
-                    let bin_ref = element.downcast_ref::<gst::Bin>().unwrap();
-                    bin_ref.debug_to_dot_file_with_ts(
-                        gst::DebugGraphDetails::all(),
-                        "gst-wpe-broadcast-demo-async-done",
-                    );
-                }
-            }
             _ => (),
-        };
+        }
     }
 
-    fn create_application_warning_message(text: &str) -> gst::Message {
-        gst::Message::new_application(
-            gst::Structure::builder("warning")
-                .field("text", &text)
-                .build(),
-        )
-        .build()
+    // Ask the camera for the configured format, size and framerate. If it can't deliver that
+    // combination, the framerate and then the format are given up in that order
+    fn update_camera_caps(&self, settings: &Settings, width: i32, height: i32) {
+        let camcaps = self
+            .pipeline
+            .get_by_name("camcaps")
+            .expect("No webcam capsfilter found");
+
+        // The test pattern is produced in whatever format is asked for
+        if self.test_sources.get() {
+            camcaps.set_property_from_str(
+                "caps",
+                &format!(
+                    "{},width={},height={},framerate={}/1",
+                    CameraFormat::Yuy2.caps(),
+                    width,
+                    height,
+                    settings.camera_framerate
+                ),
+            );
+            self.update_camera_decoder(settings, Some(CameraFormat::Yuy2));
+            return;
+        }
+
+        // Whatever the network camera sends is negotiated when connecting
+        if self.camera_rtsp.borrow().is_some() {
+            camcaps
+                .set_property("caps", &gst::Caps::new_any())
+                .expect("No caps property on capsfilter");
+            self.update_camera_decoder(settings, None);
+            return;
+        }
+
+        // Zero-copy capture needs a raw format, so that one is asked for first then
+        let zero_copy = settings.zero_copy_cameras.contains(&self.camera_id());
+        let preferred = if zero_copy {
+            CameraFormat::Yuy2
+        } else {
+            settings.camera_format
+        };
+
+        let candidates = std::iter::once(preferred)
+            .chain(
+                CameraFormat::ALL
+                    .iter()
+                    .cloned()
+                    .filter(|format| *format != preferred),
+            )
+            .flat_map(|format| {
+                vec![
+                    (
+                        format,
+                        format!(
+                            "{},width={},height={},framerate={}/1",
+                            format.caps(),
+                            width,
+                            height,
+                            settings.camera_framerate
+                        ),
+                    ),
+                    (
+                        format,
+                        format!("{},width={},height={}", format.caps(), width, height),
+                    ),
+                ]
+            })
+            // Sizes the camera doesn't have at all, e.g. vertical ones, are left to the mixer to
+            // scale to
+            .chain(std::iter::once((preferred, preferred.caps().to_string())))
+            .collect::<Vec<_>>();
+
+        // Without a device we can't know what the camera supports and just try the preferred caps
+        let supported = devices::find_device(
+            "Video/Source",
+            self.camera_device.borrow().as_deref(),
+            &self.camera_id(),
+        )
+        .and_then(|device| device.get_caps());
+        let (format, caps) = match supported {
+            Some(supported) => candidates
+                .iter()
+                .find(|(_, candidate)| {
+                    candidate
+                        .parse::<gst::Caps>()
+                        .map(|caps| caps.can_intersect(&supported))
+                        .unwrap_or(false)
+                })
+                .unwrap_or(&candidates[0]),
+            None => &candidates[0],
+        };
+
+        if *caps != candidates[0].1 {
+            if let Some(session_log) = self.session_log.upgrade() {
+                session_log.log(
+                    EventKind::Warning,
+                    &format!("Camera can't deliver {}, using {}", candidates[0].1, caps),
+                );
+            }
+        }
+
+        camcaps.set_property_from_str("caps", caps);
+        self.update_camera_io_mode(zero_copy && *format == CameraFormat::Yuy2);
+        self.update_camera_decoder(settings, Some(*format));
+    }
+
+    // Let a v4l2 camera hand its buffers to glupload as DMABuf instead of having them copied.
+    // The mode can only be changed while the source is stopped
+    fn update_camera_io_mode(&self, dmabuf: bool) {
+        let videosrc = match self.pipeline.get_by_name("videosrc") {
+            Some(videosrc)
+                if videosrc
+                    .get_factory()
+                    .is_some_and(|factory| factory.get_name() == "v4l2src") =>
+            {
+                videosrc
+            }
+            _ => return,
+        };
+
+        let io_mode = if dmabuf { "dmabuf" } else { "auto" };
+        let current = videosrc
+            .get_property("io-mode")
+            .ok()
+            .and_then(|value| glib::EnumValue::from_value(&value))
+            .map(|value| value.get_nick().to_string());
+        if current.as_deref() == Some(io_mode) {
+            return;
+        }
+
+        let _ = videosrc.set_state(gst::State::Null);
+        videosrc.set_property_from_str("io-mode", io_mode);
+        if let Err(err) = videosrc.sync_state_with_parent() {
+            if let Some(session_log) = self.session_log.upgrade() {
+                session_log.log(
+                    EventKind::Warning,
+                    &format!(
+                        "Failed to restart the camera for {} capture: {}",
+                        io_mode, err
+                    ),
+                );
+            }
+        }
+    }
+
+    // Put the decoder for the camera format in place, None for network cameras whose streams
+    // still have to be depayloaded. Replaced only if the decoder changes
+    fn update_camera_decoder(&self, settings: &Settings, format: Option<CameraFormat>) {
+        let (description, fallback) = camera_decoder_description(format, settings.camera_decoding);
+        if *self.camera_decoder.borrow() == description {
+            return;
+        }
+
+        if fallback {
+            if let Some(session_log) = self.session_log.upgrade() {
+                session_log.log(
+                    EventKind::Warning,
+                    "No hardware decoder for the camera is installed, decoding in software",
+                );
+            }
+        }
+
+        if let Err(err) = self.replace_camera_decoder(&description) {
+            utils::show_error_dialog(
+                false,
+                format!("Failed to set up the camera decoder: {}", err).as_str(),
+            );
+            return;
+        }
+        self.camera_decoder.replace(description);
+    }
+
+    fn replace_camera_decoder(&self, description: &str) -> Result<(), Box<dyn error::Error>> {
+        let camcaps = self
+            .pipeline
+            .get_by_name("camcaps")
+            .expect("No webcam capsfilter found");
+        let camqueue = self
+            .pipeline
+            .get_by_name("camqueue")
+            .expect("No camera queue found");
+
+        // decodebin only gets its source pad once it knows what to decode, the others are linked
+        // right away
+        let decoder = if description == "decodebin" {
+            let decoder = gst::ElementFactory::make("decodebin", Some("camdecode"))?;
+            let camqueue_weak = camqueue.downgrade();
+            decoder.connect_pad_added(move |_, srcpad| {
+                let camqueue = upgrade_weak!(camqueue_weak);
+                if let Some(sinkpad) = camqueue.get_static_pad("sink") {
+                    if !sinkpad.is_linked() {
+                        let _ = srcpad.link(&sinkpad);
+                    }
+                }
+            });
+            decoder
+        } else {
+            let decoder = gst::parse_bin_from_description(description, true)?;
+            decoder.set_name("camdecode")?;
+            decoder.upcast()
+        };
+
+        if let Some(old_decoder) = self.pipeline.get_by_name("camdecode") {
+            let _ = old_decoder.set_state(gst::State::Null);
+            self.pipeline.remove(&old_decoder)?;
+        }
+        self.pipeline.add(&decoder)?;
+        camcaps.link(&decoder)?;
+        if description != "decodebin" {
+            decoder.link(&camqueue)?;
+        }
+        decoder.sync_state_with_parent()?;
+
+        Ok(())
+    }
+
+    pub fn refresh_camera_orientation(&self) {
+        let settings = utils::load_settings();
+        let (width, height) = output_size(&settings);
+        self.update_camera_orientation(&settings, width, height);
+    }
+
+    // Rotate and mirror the camera as configured for it, then place it as the current scene says
+    fn update_camera_orientation(&self, settings: &Settings, width: i32, height: i32) {
+        let orientation = settings.camera_orientation(&self.camera_id());
+
+        let camflip = self
+            .pipeline
+            .get_by_name("camflip")
+            .expect("No camflip found");
+        camflip.set_property_from_str("video-direction", orientation.video_direction());
+
+        self.apply_scene(settings, width, height, 0);
+    }
+
+    // Place the camera and the overlay as the current scene describes. Sideways cameras are scaled
+    // down to fit their area and centered instead of being stretched
+    fn apply_scene(&self, settings: &Settings, width: i32, height: i32, transition: u32) {
+        let scene = scenes::find(&self.scene.borrow())
+            .or_else(|| scenes::find(scenes::DEFAULT_SCENE))
+            .expect("No default scene");
+        let sideways = settings.camera_orientation(&self.camera_id()).is_sideways();
+        let camera = if scene.name == scenes::PICTURE_IN_PICTURE {
+            settings.pip.layer()
+        } else {
+            scene.camera
+        };
+
+        if let Some(source_id) = self.transition_timeout.borrow_mut().take() {
+            glib::source_remove(source_id);
+        }
+
+        let mixer = self.pipeline.get_by_name("mixer").expect("No mixer found");
+        let mut targets = Vec::new();
+        // The screen capture is placed as configured, whatever the scene
+        let uncropped = LayerSettings::default();
+        for (pad, layer, adjustment, is_camera) in [
+            (
+                mixer.get_static_pad("sink_0"),
+                scene.overlay,
+                &settings.overlay_layer,
+                false,
+            ),
+            (
+                mixer.get_static_pad("sink_1"),
+                camera,
+                &settings.camera_layer,
+                true,
+            ),
+            (
+                self.screen_pad(),
+                settings.screen.layer(),
+                &uncropped,
+                false,
+            ),
+        ] {
+            let pad = match pad {
+                Some(pad) => pad,
+                None => continue,
+            };
+
+            let (mut xpos, ypos, mut pad_width, pad_height) = layer.rect(width, height);
+            if is_camera && sideways {
+                // Keep the aspect ratio of the rotated picture and center it in its area
+                let rotated_width = pad_height * height / width;
+                xpos += (pad_width - rotated_width) / 2;
+                pad_width = rotated_width;
+            }
+            let alpha = if self.standby.get() {
+                0.0
+            } else {
+                layer.alpha * adjustment.opacity.clamp(0.0, 1.0)
+            };
+
+            // The crop shader stretches what is left over the whole layer, so the layer shrinks
+            // by the cropped parts to keep the rest in place
+            let (left, top, right, bottom) = adjustment.crop();
+            let (xpos, ypos, pad_width, pad_height) = (
+                f64::from(xpos) + left * f64::from(pad_width),
+                f64::from(ypos) + top * f64::from(pad_height),
+                f64::from(pad_width) * (1.0 - left - right),
+                f64::from(pad_height) * (1.0 - top - bottom),
+            );
+
+            // The stacking order can't be animated, so it changes right away
+            pad.set_property("zorder", &layer.zorder)
+                .expect("No zorder pad property");
+
+            let target = PadLayout {
+                xpos,
+                ypos,
+                width: pad_width,
+                height: pad_height,
+                alpha,
+            };
+            targets.push((pad.clone(), PadLayout::get(&pad), target));
+        }
+
+        if transition == 0 {
+            for (pad, _, target) in &targets {
+                target.set(pad);
+            }
+            return;
+        }
+
+        // Move all layers from where they are now to their place in the new scene, easing in and
+        // out over the transition time
+        let start = Instant::now();
+        let duration = Duration::from_millis(u64::from(transition));
+        let pipeline_weak = self.downgrade();
+        let source_id = glib::timeout_add_local(TRANSITION_FRAME_INTERVAL, move || {
+            let pipeline = upgrade_weak!(pipeline_weak, glib::Continue(false));
+            let t = (start.elapsed().as_secs_f64() / duration.as_secs_f64()).min(1.0);
+            let eased = t * t * (3.0 - 2.0 * t);
+            for (pad, from, to) in &targets {
+                from.interpolate(to, eased).set(pad);
+            }
+
+            if t < 1.0 {
+                glib::Continue(true)
+            } else {
+                pipeline.transition_timeout.borrow_mut().take();
+                glib::Continue(false)
+            }
+        });
+        self.transition_timeout.replace(Some(source_id));
+    }
+
+    // Switch to another composition of the camera and the overlay
+    pub fn switch_scene(&self, name: &str) -> Result<(), String> {
+        if scenes::find(name).is_none() {
+            return Err(format!("Unknown scene '{}'", name));
+        }
+        self.scene.replace(name.to_string());
+
+        let settings = utils::load_settings();
+        let (width, height) = output_size(&settings);
+        self.apply_scene(&settings, width, height, settings.scene_transition);
+
+        Ok(())
+    }
+
+    pub fn get_scene(&self) -> String {
+        self.scene.borrow().clone()
+    }
+
+    // Move the camera of the picture-in-picture scene to where the settings say right away, e.g.
+    // while it is dragged around in the preview
+    pub fn place_pip(&self, settings: &Settings) {
+        if *self.scene.borrow() != scenes::PICTURE_IN_PICTURE {
+            return;
+        }
+        let (width, height) = output_size(settings);
+        self.apply_scene(settings, width, height, 0);
+    }
+
+    // Rebuild the layer below the camera and the overlay. Plain black and checker board are drawn
+    // by the mixer itself, colors and images get their own branch at the bottom of the mixer
+    fn update_background(
+        &self,
+        settings: &Settings,
+        width: i32,
+        height: i32,
+    ) -> Result<(), Box<dyn error::Error>> {
+        let mixer = self.pipeline.get_by_name("mixer").expect("No mixer found");
+
+        self.remove_mixer_bin("background-bin")?;
+
+        let background = &settings.background;
+        let bin = match background.kind {
+            BackgroundKind::Black => None,
+            BackgroundKind::Checker => {
+                mixer.set_property_from_str("background", "checker");
+                return Ok(());
+            }
+            BackgroundKind::Color => Some(gst::parse_bin_from_description(
+                &format!(
+                    "videotestsrc is-live=1 pattern=solid-color foreground-color={color} ! \
+                     video/x-raw,width={width},height={height},framerate={framerate}/1 ! glupload ! glcolorconvert",
+                    color = 0xff00_0000 | background.rgb(),
+                    width = width,
+                    height = height,
+                    framerate = settings.video_framerate
+                ),
+                true,
+            )
+            .map_err(|err| format!("Failed to create background: {}", err))?),
+            BackgroundKind::Image => match background.image {
+                Some(ref image) => Some(image_bin(image, width, height, settings.video_framerate)?),
+                None => None,
+            },
+        };
+
+        mixer.set_property_from_str("background", "black");
+        let bin = match bin {
+            Some(bin) => bin,
+            None => return Ok(()),
+        };
+        bin.set_name("background-bin")
+            .map_err(|err| format!("Failed to set background bin name: {}", err))?;
+        self.pipeline.add(&bin)?;
+
+        let srcpad = bin
+            .get_static_pad("src")
+            .expect("Failed to get src pad from background bin");
+        let sinkpad = mixer
+            .get_request_pad("sink_%u")
+            .expect("Failed to request new pad from mixer");
+        sinkpad.set_property("zorder", &0u32)?;
+        sinkpad.set_property("width", &width)?;
+        sinkpad.set_property("height", &height)?;
+
+        srcpad.link(&sinkpad)?;
+        bin.sync_state_with_parent()?;
+
+        Ok(())
+    }
+
+    // Remove a bin feeding a request pad of the mixer, if there is one
+    fn remove_mixer_bin(&self, name: &str) -> Result<(), Box<dyn error::Error>> {
+        let mixer = self.pipeline.get_by_name("mixer").expect("No mixer found");
+
+        if let Some(bin) = self.pipeline.get_by_name(name) {
+            let _ = bin.set_state(gst::State::Null);
+            if let Some(peer) = bin.get_static_pad("src").and_then(|p| p.get_peer()) {
+                mixer.release_request_pad(&peer);
+            }
+            self.pipeline.remove(&bin)?;
+        }
+
+        Ok(())
+    }
+
+    // Put the "be right back" slate on top of everything else in the mixer, either the configured
+    // image or a plain card with a caption
+    fn add_slate(
+        &self,
+        settings: &Settings,
+        width: i32,
+        height: i32,
+    ) -> Result<(), Box<dyn error::Error>> {
+        self.remove_mixer_bin("slate-bin")?;
+
+        let bin = match settings.slate_image {
+            Some(ref image) => image_bin(image, width, height, settings.video_framerate)?,
+            None => gst::parse_bin_from_description(
+                &format!(
+                    "videotestsrc is-live=1 pattern=solid-color foreground-color=0xff202020 ! \
+                     video/x-raw,width={width},height={height},framerate={framerate}/1 ! \
+                     textoverlay text=\"Be right back\" font-desc=\"Sans Bold 48\" valignment=center halignment=center ! \
+                     glupload ! glcolorconvert",
+                    width = width,
+                    height = height,
+                    framerate = settings.video_framerate
+                ),
+                true,
+            )
+            .map_err(|err| format!("Failed to create slate: {}", err))?,
+        };
+        bin.set_name("slate-bin")
+            .map_err(|err| format!("Failed to set slate bin name: {}", err))?;
+        self.pipeline.add(&bin)?;
+
+        let mixer = self.pipeline.get_by_name("mixer").expect("No mixer found");
+        let srcpad = bin
+            .get_static_pad("src")
+            .expect("Failed to get src pad from slate bin");
+        let sinkpad = mixer
+            .get_request_pad("sink_%u")
+            .expect("Failed to request new pad from mixer");
+        sinkpad.set_property("zorder", &10u32)?;
+        sinkpad.set_property("width", &width)?;
+        sinkpad.set_property("height", &height)?;
+
+        srcpad.link(&sinkpad)?;
+        bin.sync_state_with_parent()?;
+
+        Ok(())
+    }
+
+    // Add, replace or remove the screen capture layer as configured. It is placed by
+    // apply_scene()
+    fn update_screen_capture(&self, settings: &Settings) -> Result<(), Box<dyn error::Error>> {
+        let screen = &settings.screen;
+        let description = if !screen.enabled {
+            None
+        } else {
+            let source = match screen.source {
+                ScreenSource::X11 => "ximagesrc use-damage=0".to_string(),
+                ScreenSource::PipeWire => match screen.pipewire_path {
+                    Some(ref path) => format!("pipewiresrc path={}", path),
+                    None => "pipewiresrc".to_string(),
+                },
+            };
+            Some(format!(
+                "{} ! videorate ! video/x-raw,framerate={}/1 ! videoconvert ! queue ! \
+                 glupload ! glcolorconvert",
+                source, settings.video_framerate
+            ))
+        };
+
+        // Don't restart the capture if only the layout changed
+        if description == *self.screen_description.borrow() {
+            return Ok(());
+        }
+        self.remove_mixer_bin("screen-bin")?;
+        self.screen_description.replace(None);
+
+        let description = match description {
+            Some(description) => description,
+            None => return Ok(()),
+        };
+
+        let bin = gst::parse_bin_from_description(&description, true)
+            .map_err(|err| format!("Failed to create screen capture: {}", err))?;
+        bin.set_name("screen-bin")
+            .map_err(|err| format!("Failed to set screen capture bin name: {}", err))?;
+        self.pipeline.add(&bin)?;
+
+        let mixer = self.pipeline.get_by_name("mixer").expect("No mixer found");
+        let srcpad = bin
+            .get_static_pad("src")
+            .expect("Failed to get src pad from screen capture bin");
+        let sinkpad = mixer
+            .get_request_pad("sink_%u")
+            .expect("Failed to request new pad from mixer");
+
+        srcpad.link(&sinkpad)?;
+        bin.sync_state_with_parent()?;
+
+        self.screen_description.replace(Some(description));
+
+        Ok(())
+    }
+
+    // The mixer pad of the screen capture layer, if enabled
+    fn screen_pad(&self) -> Option<gst::Pad> {
+        self.pipeline
+            .get_by_name("screen-bin")
+            .and_then(|bin| bin.get_static_pad("src"))
+            .and_then(|srcpad| srcpad.get_peer())
+    }
+
+    // Cover the program with the slate and mute the audio while the outputs keep streaming, so
+    // viewers stay connected
+    pub fn pause_streaming(&self) -> Result<(), Box<dyn error::Error>> {
+        let settings = utils::load_settings();
+        let (width, height) = output_size(&settings);
+        self.add_slate(&settings, width, height)?;
+
+        self.paused.set(true);
+        self.update_mute();
+
+        Ok(())
+    }
+
+    pub fn resume_streaming(&self) {
+        if let Err(err) = self.remove_mixer_bin("slate-bin") {
+            utils::show_error_dialog(
+                false,
+                format!("Failed to remove the slate: {}", err).as_str(),
+            );
+        }
+
+        self.paused.set(false);
+        self.update_mute();
+    }
+
+    pub fn set_muted(&self, muted: bool) {
+        self.muted.set(muted);
+        self.update_mute();
+    }
+
+    // The audio is muted by the operator or while in standby or paused
+    fn update_mute(&self) {
+        let volume = self
+            .pipeline
+            .get_by_name("volume")
+            .expect("No volume found");
+        volume
+            .set_property(
+                "mute",
+                &(self.muted.get() || self.standby.get() || self.paused.get()),
+            )
+            .expect("No mute property on volume");
+    }
+
+    // Play the program audio on the local speakers or headphones, so the operator hears what goes
+    // out, mute included. It is held back to play in sync with the preview
+    pub fn set_monitoring(
+        &self,
+        enabled: bool,
+        settings: &MonitorSettings,
+    ) -> Result<(), Box<dyn error::Error>> {
+        // Only another output device needs a new branch, the rest is changed while playing
+        let device = self
+            .monitor
+            .borrow()
+            .as_ref()
+            .map(|(_, device)| device.clone());
+        if enabled && device.as_ref() == Some(&settings.device) {
+            self.update_monitor(settings);
+            return Ok(());
+        }
+        if let Some((bin, _)) = self.monitor.borrow_mut().take() {
+            remove_tee_branch(bin);
+        }
+        if !enabled {
+            return Ok(());
+        }
+
+        let audio_tee = self
+            .pipeline
+            .get_by_name("audio-tee")
+            .expect("No audio-tee found");
+
+        // The queue holds the audio while it is delayed
+        let bin = gst::parse_bin_from_description(
+            "queue name=monitor-queue max-size-buffers=0 max-size-bytes=0 ! audioconvert ! \
+             audioresample ! volume name=monitor-volume",
+            false,
+        )
+        .map_err(|err| format!("Failed to create audio monitor: {}", err))?;
+        let sink = devices::create_source(
+            "Audio/Sink",
+            settings.device.as_deref(),
+            "autoaudiosink",
+            "monitor-sink",
+        )
+        .map_err(|err| format!("Failed to create audio monitor output: {}", err))?;
+        bin.add(&sink)?;
+        bin.get_by_name("monitor-volume")
+            .expect("No monitor-volume found")
+            .link(&sink)?;
+        let target = bin
+            .get_by_name("monitor-queue")
+            .and_then(|queue| queue.get_static_pad("sink"))
+            .expect("No monitor-queue sink pad");
+        let ghost_pad = gst::GhostPad::new(Some("sink"), &target)
+            .map_err(|err| format!("Failed to create audio monitor pad: {}", err))?;
+        bin.add_pad(&ghost_pad)?;
+
+        self.add_tee_branch(&audio_tee, &bin)
+            .map_err(|err| format!("Failed to link audio monitor: {}", err))?;
+        self.monitor.replace(Some((
+            bin.clone().upcast::<gst::Element>(),
+            settings.device.clone(),
+        )));
+        self.update_monitor(settings);
+
+        Ok(())
+    }
+
+    pub fn is_monitoring(&self) -> bool {
+        self.monitor.borrow().is_some()
+    }
+
+    // Apply the volume and delay to the running monitor
+    fn update_monitor(&self, settings: &MonitorSettings) {
+        let bin = match *self.monitor.borrow() {
+            Some((ref bin, _)) => bin
+                .clone()
+                .downcast::<gst::Bin>()
+                .expect("Monitor not a bin"),
+            None => return,
+        };
+
+        if let Some(volume) = bin.get_by_name("monitor-volume") {
+            volume
+                .set_property("volume", &settings.volume)
+                .expect("No volume property on volume");
+        }
+
+        // The measured latency is only known once the preview showed some frames
+        let delay = settings
+            .match_preview
+            .then(|| self.get_preview_latency())
+            .flatten()
+            .unwrap_or_else(|| Duration::from_millis(u64::from(settings.delay)));
+        let delay = gst::ClockTime::from_nseconds(delay.as_nanos() as u64);
+        // Shifted like the audio inputs, the queue keeps the audio meanwhile
+        if let Some(queue) = bin.get_by_name("monitor-queue") {
+            queue
+                .set_property("max-size-time", &(delay + gst::SECOND).nseconds().unwrap())
+                .expect("No max-size-time property on queue");
+        }
+        if let Some(pad) = bin.get_static_pad("sink") {
+            pad.set_offset(delay.nseconds().unwrap() as i64);
+        }
+    }
+
+    // Show the program on another video sink, e.g. fullscreen on a second monitor for stage
+    // displays. Returns the widget of the sink
+    pub fn add_projector(&self) -> Result<gtk::Widget, Box<dyn error::Error>> {
+        if let Some(bin) = self.pipeline.get_by_name("projector-bin") {
+            remove_tee_branch(bin);
+        }
+
+        // A slow projector drops frames instead of holding up the program
+        let bin = gst::parse_bin_from_description(
+            "queue leaky=downstream max-size-buffers=2 ! \
+             gtkglsink name=projector-sink enable-last-sample=0",
+            true,
+        )
+        .map_err(|err| format!("Failed to create projector: {}", err))?;
+        bin.set_name("projector-bin")
+            .map_err(|err| format!("Failed to set projector bin name: {}", err))?;
+        let widget = bin
+            .get_by_name("projector-sink")
+            .expect("No projector-sink found")
+            .get_property("widget")?
+            .get::<gtk::Widget>()?
+            .ok_or("Projector sink has no widget")?;
+
+        let tee = self.pipeline.get_by_name("tee").expect("No tee found");
+        self.add_tee_branch(&tee, &bin)
+            .map_err(|err| format!("Failed to link projector: {}", err))?;
+
+        Ok(widget)
+    }
+
+    pub fn remove_projector(&self) {
+        if let Some(bin) = self.pipeline.get_by_name("projector-bin") {
+            remove_tee_branch(bin);
+        }
+    }
+
+    // Add the bin to the pipeline and feed it from a new pad of the tee
+    fn add_tee_branch(&self, tee: &gst::Element, bin: &gst::Bin) -> Result<(), String> {
+        self.pipeline.add(bin).map_err(|err| err.to_string())?;
+
+        let srcpad = tee
+            .get_request_pad("src_%u")
+            .expect("Failed to request new pad from tee");
+        let sinkpad = bin.get_static_pad("sink").expect("Bin has no sink pad");
+        if let Err(err) = srcpad.link(&sinkpad) {
+            tee.release_request_pad(&srcpad);
+            let _ = self.pipeline.remove(bin);
+            return Err(err.to_string());
+        }
+        bin.sync_state_with_parent()
+            .map_err(|err| err.to_string())?;
+
+        Ok(())
+    }
+
+    // Replace the audio source by the device with the given display name, or the automatic
+    // source if None. Only the source element is rebuilt, the rest of the pipeline keeps running
+    pub fn set_audio_device(
+        &self,
+        display_name: Option<&str>,
+    ) -> Result<(), Box<dyn error::Error>> {
+        let audiosrc =
+            devices::create_source("Audio/Source", display_name, "autoaudiosrc", "audiosrc")
+                .map_err(|err| format!("Failed to create audio source: {}", err))?;
+        self.replace_audio_source(&audiosrc)
+    }
+
+    // Shut down the old microphone source and start the new one in its place
+    fn replace_audio_source(&self, audiosrc: &gst::Element) -> Result<(), Box<dyn error::Error>> {
+        let audio_convert = self
+            .pipeline
+            .get_by_name("audio-convert")
+            .expect("No audio-convert found");
+
+        if let Some(old_audiosrc) = self.pipeline.get_by_name("audiosrc") {
+            let _ = old_audiosrc.set_state(gst::State::Null);
+            old_audiosrc.unlink(&audio_convert);
+            self.pipeline.remove(&old_audiosrc)?;
+        }
+
+        self.pipeline.add(audiosrc)?;
+        audiosrc.link(&audio_convert)?;
+        audiosrc.sync_state_with_parent()?;
+
+        Ok(())
+    }
+
+    // Mix the bin's src pad into the program audio as the input with the given name, replacing a
+    // previous input of that name. Non-live inputs like files start playing right away
+    fn add_audio_input(
+        &self,
+        name: &str,
+        bin: gst::Bin,
+        live: bool,
+    ) -> Result<(), Box<dyn error::Error>> {
+        self.remove_audio_input(name)?;
+
+        bin.set_name(&format!("audio-input-{}", name))
+            .map_err(|err| format!("Failed to set audio input bin name: {}", err))?;
+        self.pipeline.add(&bin)?;
+
+        let srcpad = bin
+            .get_static_pad("src")
+            .expect("Failed to get src pad from audio input bin");
+
+        // The timestamps of files start at zero, shift them to the current running time so the
+        // mixer doesn't consider them late
+        if !live {
+            srcpad.set_offset(self.get_running_time());
+        }
+
+        if let Err(err) = self.link_audio_input(name, &srcpad) {
+            let _ = self.pipeline.remove(&bin);
+            return Err(err.into());
+        }
+
+        bin.sync_state_with_parent()?;
+
+        Ok(())
+    }
+
+    // Link the pad to a new pad of the program audio mixer, registered under the input name
+    fn link_audio_input(&self, name: &str, srcpad: &gst::Pad) -> Result<(), String> {
+        let audio_mixer = self
+            .pipeline
+            .get_by_name("audio-mixer")
+            .expect("No audio-mixer found");
+        let sinkpad = audio_mixer
+            .get_request_pad("sink_%u")
+            .expect("Failed to request new pad from audiomixer");
+
+        if let Err(err) = srcpad.link(&sinkpad) {
+            audio_mixer.release_request_pad(&sinkpad);
+            return Err(format!("Failed to link audio input: {}", err));
+        }
+        let settings = utils::load_settings();
+        sinkpad.set_offset(av_offsets(settings.av_offset).0);
+        self.audio_inputs
+            .borrow_mut()
+            .insert(name.to_string(), sinkpad);
+        self.set_audio_input_volume(
+            name,
+            settings.audio_inputs.get(name).cloned().unwrap_or_default(),
+        );
+
+        Ok(())
+    }
+
+    fn unlink_audio_input(&self, name: &str) {
+        if let Some(sinkpad) = self.audio_inputs.borrow_mut().remove(name) {
+            let audio_mixer = self
+                .pipeline
+                .get_by_name("audio-mixer")
+                .expect("No audio-mixer found");
+            audio_mixer.release_request_pad(&sinkpad);
+        }
+    }
+
+    // Shift all audio inputs against the camera to make up for devices capturing with different
+    // delays. Only ever delays one side, as data shifted into the past arrives late at the mixers.
+    // The queues in front of the mixers hold the delayed data meanwhile
+    pub fn set_av_offset(&self, offset_ms: i32) {
+        let (audio_offset, camera_offset) = av_offsets(offset_ms);
+        for pad in self.audio_inputs.borrow().values() {
+            pad.set_offset(audio_offset);
+        }
+        // The camera and its no-signal replacement both come in through the input selector
+        if let Some(pad) = self
+            .pipeline
+            .get_by_name("mixer")
+            .and_then(|mixer| mixer.get_static_pad("sink_1"))
+        {
+            pad.set_offset(camera_offset);
+        }
+    }
+
+    // Make the backdrop of the camera transparent, or pass the camera through untouched
+    pub fn update_chroma_key(&self, chroma_key: &ChromaKeySettings) {
+        let camkey = self
+            .pipeline
+            .get_by_name("camkey")
+            .expect("No camkey found");
+
+        let fragment = if chroma_key.enabled {
+            CHROMA_KEY_SHADER
+        } else {
+            PASSTHROUGH_SHADER
+        };
+        let current = camkey
+            .get_property("fragment")
+            .ok()
+            .and_then(|value| value.get::<String>().ok().flatten());
+        if current.as_deref() != Some(fragment) {
+            let _ = camkey.set_property("fragment", &fragment);
+        }
+
+        let rgb = chroma_key.rgb();
+        let channel = |shift: u32| ((rgb >> shift) & 0xff) as f32 / 255.0;
+        let uniforms = gst::Structure::builder("uniforms")
+            .field("key_r", &channel(16))
+            .field("key_g", &channel(8))
+            .field("key_b", &channel(0))
+            .field("threshold", &(chroma_key.threshold as f32))
+            .field("smoothing", &(chroma_key.smoothing.max(0.001) as f32))
+            .build();
+        let _ = camkey.set_property("uniforms", &uniforms);
+    }
+
+    // Change the opacity and cropping of the camera and the overlay right away
+    pub fn update_layers(&self, settings: &Settings) {
+        self.update_crop(settings);
+        let (width, height) = output_size(settings);
+        self.apply_scene(settings, width, height, 0);
+    }
+
+    // Cut off the edges of the camera and the overlay in their crop shaders, apply_scene() then
+    // shrinks their layers accordingly
+    fn update_crop(&self, settings: &Settings) {
+        for (name, layer) in &[
+            ("camcrop", &settings.camera_layer),
+            ("overlaycrop", &settings.overlay_layer),
+        ] {
+            let shader = self
+                .pipeline
+                .get_by_name(name)
+                .unwrap_or_else(|| panic!("No {} found", name));
+            let current = shader
+                .get_property("fragment")
+                .ok()
+                .and_then(|value| value.get::<String>().ok().flatten());
+            if current.as_deref() != Some(CROP_SHADER) {
+                let _ = shader.set_property("fragment", &CROP_SHADER);
+            }
+
+            let (left, top, right, bottom) = layer.crop();
+            let uniforms = gst::Structure::builder("uniforms")
+                .field("left", &(left as f32))
+                .field("top", &(top as f32))
+                .field("right", &(right as f32))
+                .field("bottom", &(bottom as f32))
+                .build();
+            let _ = shader.set_property("uniforms", &uniforms);
+        }
+    }
+
+    // Configure the processing of the program audio, while it keeps running
+    pub fn update_audio_filters(&self, filters: &AudioFilterSettings) {
+        // audiodynamic scales the level beyond the threshold by the ratio, 1 leaves it as it is.
+        // Expanding steeply below the threshold mutes the audio there
+        let gate = self
+            .pipeline
+            .get_by_name("audio-gate")
+            .expect("No audio-gate found");
+        let gate_ratio: f32 = if filters.gate { 10.0 } else { 1.0 };
+        let _ = gate.set_property("ratio", &gate_ratio);
+        let _ = gate.set_property("threshold", &db_to_amplitude(filters.gate_threshold));
+
+        let compressor = self
+            .pipeline
+            .get_by_name("audio-compressor")
+            .expect("No audio-compressor found");
+        let compressor_ratio = if filters.compressor {
+            (1.0 / filters.compressor_ratio.max(1.0)) as f32
+        } else {
+            1.0
+        };
+        let _ = compressor.set_property("ratio", &compressor_ratio);
+        let _ =
+            compressor.set_property("threshold", &db_to_amplitude(filters.compressor_threshold));
+
+        // The equalizer passes the audio through untouched with all gains at 0
+        let equalizer = self
+            .pipeline
+            .get_by_name("audio-eq")
+            .expect("No audio-eq found");
+        for (band, gain) in ["band0", "band1", "band2"].iter().zip(&[
+            filters.eq_low,
+            filters.eq_mid,
+            filters.eq_high,
+        ]) {
+            let gain = if filters.equalizer { *gain } else { 0.0 };
+            let _ = equalizer.set_property(*band, &gain);
+        }
+
+        if let Err(err) = self.update_noise_suppression(filters.noise_suppression) {
+            utils::show_error_dialog(
+                false,
+                format!("Failed to change the noise suppression: {}", err).as_str(),
+            );
+        }
+    }
+
+    // webrtcdsp only reads its settings when starting, so it is swapped with a placeholder instead
+    // of being reconfigured
+    fn update_noise_suppression(&self, enabled: bool) -> Result<(), Box<dyn error::Error>> {
+        let enabled = enabled && has_noise_suppression();
+        let current = self
+            .pipeline
+            .get_by_name("audio-denoise")
+            .expect("No audio-denoise found");
+        let running = current
+            .get_factory()
+            .is_some_and(|factory| factory.get_name() == "webrtcdsp");
+        if enabled == running {
+            return Ok(());
+        }
+
+        let denoise = if enabled {
+            let denoise = gst::ElementFactory::make("webrtcdsp", None)?;
+            // Without echo cancellation no webrtcechoprobe is needed
+            denoise.set_property("echo-cancel", &false)?;
+            denoise.set_property("gain-control", &false)?;
+            denoise.set_property_from_str("noise-suppression-level", "high");
+            denoise
+        } else {
+            gst::ElementFactory::make("identity", None)?
+        };
+        denoise.set_name("audio-denoise")?;
+
+        let caps = self
+            .pipeline
+            .get_by_name("audio-denoise-caps")
+            .expect("No audio-denoise-caps found");
+        let srcpad = caps
+            .get_static_pad("src")
+            .expect("Failed to get src pad from audio-denoise-caps");
+
+        // Relink once no data is flowing and shut the previous element down from a separate
+        // thread as this might be called from a streaming thread
+        let pipeline = self.pipeline.clone();
+        srcpad.add_probe(gst::PadProbeType::IDLE, move |_, _| {
+            let next = current
+                .get_static_pad("src")
+                .and_then(|pad| pad.get_peer())
+                .and_then(|pad| pad.get_parent_element());
+            if let Some(next) = next {
+                caps.unlink(&current);
+                current.unlink(&next);
+                let _ = pipeline.remove(&current);
+
+                if pipeline.add(&denoise).is_ok() {
+                    let _ = caps.link(&denoise);
+                    let _ = denoise.link(&next);
+                    let _ = denoise.sync_state_with_parent();
+                }
+
+                let current = current.clone();
+                pipeline.call_async(move |_| {
+                    let _ = current.set_state(gst::State::Null);
+                });
+            }
+            gst::PadProbeReturn::Remove
+        });
+
+        Ok(())
+    }
+
+    // Current running time of the pipeline in nanoseconds, the offset needed for non-live
+    // sources starting at zero
+    fn get_running_time(&self) -> i64 {
+        self.pipeline
+            .get_clock()
+            .and_then(|clock| (clock.get_time() - self.pipeline.get_base_time()).nseconds())
+            .unwrap_or(0) as i64
+    }
+
+    fn remove_audio_input(&self, name: &str) -> Result<(), Box<dyn error::Error>> {
+        let bin = match self.pipeline.get_by_name(&format!("audio-input-{}", name)) {
+            Some(bin) => bin,
+            None => return Ok(()),
+        };
+
+        let _ = bin.set_state(gst::State::Null);
+        self.unlink_audio_input(name);
+        self.pipeline.remove(&bin)?;
+
+        Ok(())
+    }
+
+    pub fn set_audio_input_volume(&self, name: &str, input_settings: AudioInputSettings) {
+        if let Some(pad) = self.audio_inputs.borrow().get(name) {
+            pad.set_property("volume", &input_settings.volume)
+                .expect("No volume pad property");
+            pad.set_property("mute", &input_settings.muted)
+                .expect("No mute pad property");
+        }
+    }
+
+    // Mix what is played on this machine into the program, captured from the monitor of the
+    // default PulseAudio or PipeWire output
+    pub fn set_desktop_audio(&self, enabled: bool) -> Result<(), Box<dyn error::Error>> {
+        if !enabled {
+            return self.remove_audio_input(audio_mixer::DESKTOP);
+        }
+
+        let bin = gst::parse_bin_from_description(
+            "pulsesrc device=@DEFAULT_MONITOR@ ! audioconvert ! audioresample ! \
+             audio/x-raw,rate=48000,channels=2 ! queue max-size-buffers=0 max-size-time=2000000000",
+            true,
+        )
+        .map_err(|err| format!("Failed to create desktop audio input: {}", err))?;
+
+        self.add_audio_input(audio_mixer::DESKTOP, bin, true)
+    }
+
+    // Play the audio of the file into the program from its start, or stop it if None
+    pub fn set_media_audio(&self, location: Option<&Path>) -> Result<(), Box<dyn error::Error>> {
+        let location = match location {
+            Some(location) => location,
+            None => return self.remove_audio_input(audio_mixer::MEDIA),
+        };
+
+        let bin = gst::parse_bin_from_description(
+            "uridecodebin name=media-decode ! audioconvert ! audioresample ! \
+             audio/x-raw,rate=48000,channels=2",
+            true,
+        )
+        .map_err(|err| format!("Failed to create media audio input: {}", err))?;
+        let uri = glib::filename_to_uri(location, None)
+            .map_err(|err| format!("Invalid media file '{}': {}", location.display(), err))?;
+        bin.get_by_name("media-decode")
+            .expect("No media-decode found")
+            .set_property("uri", &uri)?;
+
+        self.add_audio_input(audio_mixer::MEDIA, bin, false)
+    }
+
+    // Play a video file on top of the program, e.g. a stinger, pre-roll or ad bump. Its audio goes
+    // into the program audio mixer. The layer is removed once the file ended unless it loops
+    pub fn play_media(&self, location: &Path, looping: bool) -> Result<(), Box<dyn error::Error>> {
+        // Replace the previous clip without going through the stopped state
+        self.remove_media_bin();
+
+        let uri = glib::filename_to_uri(location, None)
+            .map_err(|err| format!("Invalid media file '{}': {}", location.display(), err))?;
+
+        // Both branches start with a converter so uridecodebin's pads are linked by media type
+        let bin = gst::parse_bin_from_description(
+            "uridecodebin name=media-decode \
+             media-decode. ! videoconvert ! queue ! glupload ! glcolorconvert name=media-video \
+             media-decode. ! audioconvert ! audioresample ! audio/x-raw,rate=48000,channels=2 ! \
+             queue name=media-audio",
+            false,
+        )
+        .map_err(|err| format!("Failed to create media layer: {}", err))?;
+        bin.set_name("media-bin")
+            .map_err(|err| format!("Failed to set media bin name: {}", err))?;
+        bin.get_by_name("media-decode")
+            .expect("No media-decode found")
+            .set_property("uri", &uri)?;
+
+        let generation = self.media_generation.get().wrapping_add(1);
+        self.media_generation.set(generation);
+
+        let running_time = self.get_running_time();
+        let mut ghost_pads = Vec::new();
+        for (name, element) in &[("video_src", "media-video"), ("audio_src", "media-audio")] {
+            let target = bin
+                .get_by_name(element)
+                .and_then(|element| element.get_static_pad("src"))
+                .expect("No media branch src pad found");
+            let ghost_pad = gst::GhostPad::new(Some(name), &target)
+                .map_err(|err| format!("Failed to create ghost pad: {}", err))?;
+            bin.add_pad(&ghost_pad)?;
+
+            // Files start at zero, shift them to now so the mixers don't drop them as late
+            ghost_pad.set_offset(running_time);
+
+            // Keep EOS away from the mixers, which would otherwise consider the pads done for
+            // good, and let the main thread decide whether to loop or remove the layer
+            let pipeline_weak = self.pipeline.downgrade();
+            ghost_pad.add_probe(gst::PadProbeType::EVENT_DOWNSTREAM, move |_, info| {
+                match info.data {
+                    Some(gst::PadProbeData::Event(ref event))
+                        if event.get_type() == gst::EventType::Eos => {}
+                    _ => return gst::PadProbeReturn::Ok,
+                }
+
+                if let Some(pipeline) = pipeline_weak.upgrade() {
+                    let bus = pipeline.get_bus().expect("Pipeline has no bus");
+                    let _ = bus.post(
+                        &gst::Message::new_application(
+                            gst::Structure::builder("media-eos")
+                                .field("generation", &generation)
+                                .build(),
+                        )
+                        .build(),
+                    );
+                }
+
+                gst::PadProbeReturn::Drop
+            });
+
+            ghost_pads.push(ghost_pad);
+        }
+
+        self.pipeline.add(&bin)?;
+
+        let settings = utils::load_settings();
+        let (width, height) = output_size(&settings);
+        let mixer = self.pipeline.get_by_name("mixer").expect("No mixer found");
+        let sinkpad = mixer
+            .get_request_pad("sink_%u")
+            .expect("Failed to request new pad from mixer");
+        sinkpad.set_property("zorder", &5u32)?;
+        sinkpad.set_property("width", &width)?;
+        sinkpad.set_property("height", &height)?;
+        if self.standby.get() {
+            sinkpad.set_property("alpha", &0.0f64)?;
+        }
+
+        let result = ghost_pads[0]
+            .link(&sinkpad)
+            .map(|_| ())
+            .map_err(|err| format!("Failed to link media layer: {}", err))
+            .and_then(|_| self.link_audio_input(audio_mixer::CLIP, ghost_pads[1].upcast_ref()));
+        if let Err(err) = result {
+            mixer.release_request_pad(&sinkpad);
+            let _ = self.pipeline.remove(&bin);
+            return Err(err.into());
+        }
+
+        self.media.replace(Some(MediaLayer {
+            location: location.to_path_buf(),
+            looping,
+            generation,
+            eos_count: 0,
+            paused_at: None,
+        }));
+        bin.sync_state_with_parent()?;
+        self.object.set_media_playing(true);
+
+        Ok(())
+    }
+
+    // Pause or resume the media layer, its last frame stays visible while paused
+    pub fn pause_media(&self, paused: bool) {
+        let bin = match self.pipeline.get_by_name("media-bin") {
+            Some(bin) => bin,
+            None => return,
+        };
+        let mut media = self.media.borrow_mut();
+        let media = match *media {
+            Some(ref mut media) => media,
+            None => return,
+        };
+
+        let running_time = self.get_running_time();
+        match (paused, media.paused_at) {
+            (true, None) => {
+                media.paused_at = Some(running_time);
+                let _ = bin.set_state(gst::State::Paused);
+            }
+            (false, Some(paused_at)) => {
+                // Continue where we left off instead of catching up with the pipeline
+                media.paused_at = None;
+                for pad in bin.get_src_pads() {
+                    pad.set_offset(pad.get_offset() + running_time - paused_at);
+                }
+                let _ = bin.sync_state_with_parent();
+            }
+            _ => (),
+        }
+    }
+
+    pub fn is_media_playing(&self) -> bool {
+        self.media.borrow().is_some()
+    }
+
+    pub fn set_media_looping(&self, looping: bool) {
+        if let Some(ref mut media) = *self.media.borrow_mut() {
+            media.looping = looping;
+        }
+    }
+
+    pub fn stop_media(&self) {
+        self.remove_media_bin();
+        self.media.replace(None);
+        self.object.set_media_playing(false);
+    }
+
+    fn remove_media_bin(&self) {
+        let bin = match self.pipeline.get_by_name("media-bin") {
+            Some(bin) => bin,
+            None => return,
+        };
+
+        let _ = bin.set_state(gst::State::Null);
+        if let Some(peer) = bin.get_static_pad("video_src").and_then(|p| p.get_peer()) {
+            let mixer = self.pipeline.get_by_name("mixer").expect("No mixer found");
+            mixer.release_request_pad(&peer);
+        }
+        self.unlink_audio_input(audio_mixer::CLIP);
+        let _ = self.pipeline.remove(&bin);
+    }
+
+    // Called for the EOS of each branch of the media layer. Once all branches that got any data
+    // are done, the file is played again or the layer removed
+    fn on_media_eos(&self, generation: u32) {
+        let location = {
+            let mut media = self.media.borrow_mut();
+            let media = match *media {
+                Some(ref mut media) if media.generation == generation => media,
+                _ => return,
+            };
+            media.eos_count += 1;
+
+            let bin = match self.pipeline.get_by_name("media-bin") {
+                Some(bin) => bin,
+                None => return,
+            };
+            let branches = bin
+                .get_src_pads()
+                .iter()
+                .filter(|pad| pad.get_current_caps().is_some())
+                .count();
+            if (media.eos_count as usize) < branches {
+                return;
+            }
+
+            if media.looping {
+                Some(media.location.clone())
+            } else {
+                None
+            }
+        };
+
+        match location {
+            Some(location) => {
+                if let Err(err) = self.play_media(&location, true) {
+                    self.stop_media();
+                    utils::show_error_dialog(
+                        false,
+                        format!("Failed to loop the media file: {}", err).as_str(),
+                    );
+                }
+            }
+            None => self.stop_media(),
+        }
+    }
+
+    // Replace the second language or commentary input by the device with the given display name,
+    // or remove it if None. Its tee can be linked into outputs as a separate audio track
+    pub fn set_secondary_audio_device(
+        &self,
+        display_name: Option<&str>,
+    ) -> Result<(), Box<dyn error::Error>> {
+        if let Some(old_bin) = self.pipeline.get_by_name("secondary-audio-bin") {
+            let _ = old_bin.set_state(gst::State::Null);
+            self.pipeline.remove(&old_bin)?;
+        }
+
+        let display_name = match display_name {
+            Some(display_name) => display_name,
+            None => return Ok(()),
+        };
+
+        let bin = gst::parse_bin_from_description(
+            "audioconvert name=secondary-audio-convert ! audioresample ! audio/x-raw,rate=48000 ! \
+             tee name=secondary-audio-tee ! queue ! fakesink sync=1",
+            false,
+        )
+        .map_err(|err| format!("Failed to create secondary audio input: {}", err))?;
+        bin.set_name("secondary-audio-bin")
+            .map_err(|err| format!("Failed to set secondary audio bin name: {}", err))?;
+
+        let audiosrc = devices::create_source(
+            "Audio/Source",
+            Some(display_name),
+            "autoaudiosrc",
+            "secondary-audiosrc",
+        )
+        .map_err(|err| format!("Failed to create secondary audio source: {}", err))?;
+        let audio_convert = bin
+            .get_by_name("secondary-audio-convert")
+            .expect("No secondary-audio-convert found");
+
+        bin.add(&audiosrc)?;
+        audiosrc.link(&audio_convert)?;
+
+        self.pipeline.add(&bin)?;
+        bin.sync_state_with_parent()?;
+
+        Ok(())
+    }
+
+    // The tees of the audio tracks that go into the RTMP stream according to the settings.
+    // Falls back to the primary track if no secondary input is configured
+    fn rtmp_audio_tees(&self, settings: &Settings) -> Vec<gst::Element> {
+        let primary = self
+            .pipeline
+            .get_by_name("audio-tee")
+            .expect("No audio-tee found");
+        let secondary = self.pipeline.get_by_name("secondary-audio-tee");
+
+        match (settings.rtmp_audio_mix, secondary) {
+            (AudioMix::Secondary, Some(secondary)) => vec![secondary],
+            (AudioMix::Both, Some(secondary)) => vec![primary, secondary],
+            _ => vec![primary],
+        }
+    }
+
+    pub fn get_debug_stats(&self) -> DebugStats {
+        DebugStats {
+            frames: self.frame_count.load(AtomicOrdering::Relaxed),
+            output_bytes: self.output_bytes.load(AtomicOrdering::Relaxed),
+            dropped_frames: self.dropped_frames.borrow().values().sum(),
+            audio_peak: self.audio_peak.get(),
+            preview_latency: self.get_preview_latency(),
+            encode_latency: latency_from_nanos(&self.encode_latency),
+        }
+    }
+
+    // Latency the pipeline configured on its sinks to keep all live sources in sync
+    fn get_configured_latency(&self) -> Option<Duration> {
+        let mut query = gst::Query::new_latency();
+        if !self.pipeline.query(&mut query) {
+            return None;
+        }
+        let (live, min, _) = query.get_result();
+        min.nseconds().filter(|_| live).map(Duration::from_nanos)
+    }
+
+    // Frames are only shown once the configured latency passed, but arrive at the sink later still
+    // if any element is too slow
+    fn get_preview_latency(&self) -> Option<Duration> {
+        match (
+            latency_from_nanos(&self.preview_latency),
+            self.get_configured_latency(),
+        ) {
+            (Some(measured), Some(configured)) => Some(measured.max(configured)),
+            (measured, configured) => measured.or(configured),
+        }
+    }
+
+    // How long the outputs have been running, None while not recording
+    pub fn get_recording_duration(&self) -> Option<Duration> {
+        let started = self.recording_started.get()?;
+        let elapsed = self.get_running_time().saturating_sub(started).max(0);
+        Some(Duration::from_nanos(elapsed as u64))
+    }
+
+    // Whether an output failed during the current recording or is waiting to be reconnected
+    pub fn is_output_failing(&self) -> bool {
+        self.output_failed.get()
+            || self
+                .network_outputs
+                .borrow()
+                .values()
+                .any(|output| output.retry_timeout.is_some())
+    }
+
+    pub fn get_stream_stats(&self) -> StreamStats {
+        let (connection, connected_since) = match self.network_outputs.borrow().get("stream") {
+            Some(output) if output.retry_timeout.is_some() => (ConnectionState::Reconnecting, None),
+            Some(output) => (ConnectionState::Connected, Some(output.attached_at)),
+            None => (ConnectionState::Offline, None),
+        };
+
+        let buffer_fill = self
+            .outputs
+            .get_elements_by_name("video-queue")
+            .iter()
+            .filter_map(|queue| {
+                let level = queue.get_property("current-level-buffers").ok()?;
+                let max = queue.get_property("max-size-buffers").ok()?;
+                match (level.get_some::<u32>().ok()?, max.get_some::<u32>().ok()?) {
+                    (level, max) if max > 0 => Some(f64::from(level) / f64::from(max)),
+                    _ => None,
+                }
+            })
+            .fold(None, |fullest: Option<f64>, fill| {
+                Some(fullest.map_or(fill, |fullest| fullest.max(fill)))
+            });
+
+        StreamStats {
+            encoded_frames: self.encoded_frames.load(AtomicOrdering::Relaxed),
+            output_bytes: self.output_bytes.load(AtomicOrdering::Relaxed),
+            dropped_frames: self.dropped_frames.borrow().values().sum(),
+            buffer_fill,
+            connection,
+            connected_since,
+            preview_latency: self.get_preview_latency(),
+            encode_latency: latency_from_nanos(&self.encode_latency),
+        }
+    }
+
+    // Run the given JavaScript in the overlay page. Only newer versions of wpesrc support this,
+    // with older ones this does nothing
+    pub fn run_javascript(&self, script: &str) {
+        let _ = self.wpesrc.emit("run-javascript", &[&script]);
+    }
+
+    // Dispatch a message to the overlay page as overlay_bridge::MESSAGE_EVENT. Events of the page
+    // come back through the overlay-event signal
+    pub fn send_overlay_message(&self, message: &serde_json::Value) {
+        self.run_javascript(&overlay_bridge::message_script(message));
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> PipelineWeak {
+        PipelineWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> gtk::Widget {
+        // There is no preview in headless mode, the window isn't shown anyway
+        if cli::is_headless() {
+            return gtk::Label::new(Some("No preview in headless mode")).upcast();
+        }
+
+        // Get the GTK video sink and retrieve the video display widget from it
+        let widget_value = self
+            .sink
+            .get_property("widget")
+            .expect("Sink had no widget property");
+
+        widget_value
+            .get::<gtk::Widget>()
+            .expect("Sink's widget propery was of the wrong type")
+            .unwrap()
+    }
+
+    pub fn start(&self) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
+        // This has no effect if called multiple times
+        self.pipeline.set_state(gst::State::Playing)
+    }
+
+    pub fn stop(&self) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
+        // This has no effect if called multiple times
+        self.pipeline.set_state(gst::State::Null)
+    }
+
+    // Start sending the program to the configured outputs: the stream, the additional RTMP
+    // end-points and the local copy
+    pub fn start_recording(&self) -> Result<(), Box<dyn error::Error>> {
+        let settings = utils::load_settings();
+
+        if let Some(problem) = validation::validate(&settings).into_iter().next() {
+            return Err(problem.message.into());
+        }
+        // Which codecs the containers and payloaders of the outputs can carry
+        let renditions = settings.active_renditions();
+        let lan_preview = settings.lan_preview_port != 0;
+        let extra_rtmp = !settings.extra_rtmp_locations.is_empty() || !renditions.is_empty();
+        let video_codec = settings.video_codec();
+        let video_codec_supported = match video_codec {
+            VideoCodec::H264 => true,
+            VideoCodec::H265 => {
+                (settings.output_mode == OutputMode::Srt
+                    || settings.output_mode == OutputMode::Whip)
+                    && !extra_rtmp
+                    && !lan_preview
+            }
+            VideoCodec::Vp9 => {
+                settings.output_mode == OutputMode::Whip
+                    && !extra_rtmp
+                    && !lan_preview
+                    && settings.local_recording != LocalRecording::Mp4
+            }
+        };
+        if !video_codec_supported {
+            return Err(format!(
+                "{} video can't be sent to the configured outputs, please choose an H.264 encoder",
+                video_codec.label()
+            )
+            .into());
+        }
+        let flv = settings.output_mode == OutputMode::Rtmp || extra_rtmp;
+        if !settings.audio_codec.is_aac()
+            && (flv || lan_preview || settings.output_mode == OutputMode::Rtp)
+        {
+            return Err(
+                "RTMP, RTP and the LAN preview can only carry AAC audio, please choose an AAC \
+                 encoder"
+                    .into(),
+            );
+        }
+
+        // FLV only carries a single audio track, so multiple inputs get mixed down. The same mix
+        // is used for all other outputs to keep them identical
+        let audio_tees = self.rtmp_audio_tees(&settings);
+        let mixdown = if audio_tees.len() > 1 {
+            "audiomixer name=audio-mix ! "
+        } else {
+            ""
+        };
+
+        let stream_description = match settings.output_mode {
+            OutputMode::Rtp => rtp::bin_description(
+                &settings.rtp,
+                &settings.output_scale_description(),
+                &settings.video_encoder_description(),
+                &settings.audio_encoder_description(),
+                settings.bind_address.as_deref(),
+            ),
+            OutputMode::Whip => whip::bin_description(
+                &settings.whip,
+                &settings.output_scale_description(),
+                &settings.video_encoder_description(),
+                settings.video_codec(),
+                mixdown,
+            ),
+            OutputMode::Srt => muxed_bin_description(
+                &settings,
+                &srt_mux_description(&settings),
+                &format!(
+                    "srtsink name=srtsink uri=\"{uri}\" latency={latency} wait-for-connection=0{bind}",
+                    uri = settings.srt.uri.as_deref().unwrap_or_default(),
+                    latency = settings.srt.latency,
+                    bind = settings
+                        .bind_address
+                        .as_ref()
+                        .map(|address| format!(" localaddress={}", address))
+                        .unwrap_or_default(),
+                ),
+                mixdown,
+            ),
+            OutputMode::Rtmp => rtmp_bin_description(
+                &settings,
+                &settings.rtmp_url().unwrap_or_default(),
+                mixdown,
+            ),
+        };
+
+        let mut descriptions = vec![("stream".to_string(), stream_description)];
+        for (i, location) in settings.extra_rtmp_urls().iter().enumerate() {
+            descriptions.push((
+                format!("rtmp-{}", i + 1),
+                rtmp_bin_description(&settings, location, mixdown),
+            ));
+        }
+        // Each rendition of the simulcast ladder is scaled and encoded on its own
+        for (i, rendition) in renditions.iter().enumerate() {
+            if let Some(ref location) = rendition.location {
+                descriptions.push((
+                    format!("rendition-{}", i + 1),
+                    rtmp_bin_description(&settings.for_rendition(rendition), location, mixdown),
+                ));
+            }
+        }
+        if let Some(description) = file::bin_description(&settings, mixdown) {
+            descriptions.push(("file".to_string(), description));
+        }
+        if lan_preview {
+            descriptions.push(("hls".to_string(), hls::bin_description(&settings, mixdown)));
+        }
+
+        for (name, description) in descriptions {
+            if let Err(err) = self.attach_output(&settings, &name, &description, &audio_tees) {
+                self.outputs.detach_all();
+                self.network_outputs.borrow_mut().clear();
+                return Err(err);
+            }
+
+            // Local outputs are not reconnected
+            if name != "file" && name != "hls" {
+                self.network_outputs.borrow_mut().insert(
+                    name,
+                    NetworkOutput {
+                        description,
+                        audio_tees: audio_tees.clone(),
+                        attempts: 0,
+                        attached_at: Instant::now(),
+                        retry_timeout: None,
+                    },
+                );
+            }
+        }
+
+        self.object.set_recording(true);
+        self.recording_started.set(Some(self.get_running_time()));
+        self.output_failed.set(false);
+        self.bitrate_sample
+            .set(self.output_bytes.load(AtomicOrdering::Relaxed));
+        let pipeline_weak = self.downgrade();
+        let source_id = glib::timeout_add_seconds_local(1, move || {
+            let pipeline = upgrade_weak!(pipeline_weak, glib::Continue(false));
+            let output_bytes = pipeline.output_bytes.load(AtomicOrdering::Relaxed);
+            let previous = pipeline.bitrate_sample.replace(output_bytes);
+            pipeline
+                .object
+                .set_bitrate(output_bytes.saturating_sub(previous) * 8);
+            glib::Continue(true)
+        });
+        if let Some(old_source_id) = self.bitrate_timeout.replace(Some(source_id)) {
+            glib::source_remove(old_source_id);
+        }
+
+        Ok(())
+    }
+
+    // Create the bin of one output from its launch description and attach it to the tees
+    fn attach_output(
+        &self,
+        settings: &Settings,
+        name: &str,
+        description: &str,
+        audio_tees: &[gst::Element],
+    ) -> Result<(), Box<dyn error::Error>> {
+        let bin = gst::parse_bin_from_description(description, false)
+            .map_err(|err| format!("Failed to create {} output: {}", name, err))?;
+
+        if bin.get_by_name("rtpbin").is_some() {
+            rtp::write_sdp_when_negotiated(&bin, &settings.rtp);
+        }
+
+        if bin.get_by_name("whipsink").is_some() {
+            whip::configure(&bin, &settings.whip)?;
+        }
+
+        // The additional RTMP locations carry their own credentials, if any
+        if name == "stream" {
+            if let Some(rtmpsink) = bin.get_by_name("rtmpsink") {
+                configure_rtmp_login(&rtmpsink, settings)?;
+            }
+        }
+
+        if let (Some(srtsink), Some(passphrase)) =
+            (bin.get_by_name("srtsink"), settings.srt.passphrase.as_ref())
+        {
+            srtsink
+                .set_property("passphrase", passphrase)
+                .map_err(|err| format!("Failed to set SRT passphrase: {}", err))?;
+        }
+
+        if bin.get_by_name("hls-sink").is_some() {
+            hls::prepare()?;
+        }
+
+        if bin.get_by_name("file-sink").is_some() {
+            file::configure(&bin, settings)?;
+        } else {
+            // Count the encoded bytes after the muxer or payloaders of everything that is sent
+            for element in &["mux", "video-pay", "audio-pay"] {
+                if let Some(pad) = bin
+                    .get_by_name(element)
+                    .and_then(|e| e.get_static_pad("src"))
+                {
+                    let output_bytes = self.output_bytes.clone();
+                    pad.add_probe(gst::PadProbeType::BUFFER, move |_, info| {
+                        if let Some(gst::PadProbeData::Buffer(ref buffer)) = info.data {
+                            output_bytes
+                                .fetch_add(buffer.get_size() as u64, AtomicOrdering::Relaxed);
+                        }
+                        gst::PadProbeReturn::Ok
+                    });
+                }
+            }
+        }
+
+        // Count the frames encoded for the stream, the encoder is whatever the settings configure
+        if name == "stream" {
+            let encoder = bin.iterate_recurse().into_iter().flatten().find(|element| {
+                element
+                    .get_factory()
+                    .and_then(|factory| factory.get_metadata(&gst::ELEMENT_METADATA_KLASS))
+                    .is_some_and(|klass| klass.contains("Encoder/Video"))
+            });
+            if let Some(pad) = encoder
+                .as_ref()
+                .and_then(|encoder| encoder.get_static_pad("sink"))
+            {
+                let encoded_frames = self.encoded_frames.clone();
+                pad.add_probe(gst::PadProbeType::BUFFER, move |_, _| {
+                    encoded_frames.fetch_add(1, AtomicOrdering::Relaxed);
+                    gst::PadProbeReturn::Ok
+                });
+            }
+            // Encoders keep the timestamps of the raw frames, so the delay at their source pad
+            // includes the time spent encoding
+            if let Some(pad) = encoder.and_then(|encoder| encoder.get_static_pad("src")) {
+                add_latency_probe(&pad, self.encode_latency.clone());
+            }
+        }
+
+        self.outputs.attach(name, bin, audio_tees)
+    }
+
+    // Stop all outputs if any were running
+    pub fn stop_recording(&self) {
+        self.reset_recording();
+        self.outputs.detach_all();
+    }
+
+    // Stop recording but let every output finish first, e.g. when quitting. The files are
+    // finalized and the streams ended once is_finishing() returns false
+    pub fn finish_recording(&self) {
+        self.reset_recording();
+        self.outputs.finish_all();
+    }
+
+    pub fn is_finishing(&self) -> bool {
+        self.outputs.is_finishing()
+    }
+
+    fn reset_recording(&self) {
+        if let Some(source_id) = self.bitrate_timeout.borrow_mut().take() {
+            glib::source_remove(source_id);
+        }
+        self.object.set_bitrate(0);
+        self.object.set_recording(false);
+        self.recording_started.set(None);
+        self.encode_latency.store(0, AtomicOrdering::Relaxed);
+
+        for (_, output) in self.network_outputs.borrow_mut().drain() {
+            if let Some(source_id) = output.retry_timeout {
+                glib::source_remove(source_id);
+            }
+        }
+    }
+
+    // Take down the failed network output and try to connect it again later, waiting longer after
+    // every attempt that failed again. Returns false if the output is not reconnected
+    fn schedule_reconnect(&self, name: &str) -> bool {
+        let delay = {
+            let mut network_outputs = self.network_outputs.borrow_mut();
+            let output = match network_outputs.get_mut(name) {
+                Some(output) => output,
+                None => return false,
+            };
+            if output.retry_timeout.is_some() {
+                return true;
+            }
+
+            if output.attached_at.elapsed() >= OUTPUT_RETRY_RESET {
+                output.attempts = 0;
+            }
+            let delay = OUTPUT_RETRY_MIN_INTERVAL
+                .checked_shl(output.attempts)
+                .unwrap_or(OUTPUT_RETRY_MAX_INTERVAL)
+                .min(OUTPUT_RETRY_MAX_INTERVAL);
+            output.attempts += 1;
+            delay
+        };
+
+        self.outputs.detach(name);
+
+        let pipeline_weak = self.downgrade();
+        let name_clone = name.to_string();
+        let source_id = glib::timeout_add_seconds_local(delay, move || {
+            let pipeline = upgrade_weak!(pipeline_weak, glib::Continue(false));
+            pipeline.reconnect_output(&name_clone);
+            glib::Continue(false)
+        });
+        if let Some(output) = self.network_outputs.borrow_mut().get_mut(name) {
+            output.retry_timeout = Some(source_id);
+        }
+
+        if let Some(session_log) = self.session_log.upgrade() {
+            session_log.log(
+                EventKind::Warning,
+                &format!(
+                    "The {} output failed, reconnecting in {} seconds",
+                    name, delay
+                ),
+            );
+        }
+
+        true
+    }
+
+    fn reconnect_output(&self, name: &str) {
+        let (description, audio_tees) = {
+            let mut network_outputs = self.network_outputs.borrow_mut();
+            let output = match network_outputs.get_mut(name) {
+                Some(output) => output,
+                None => return,
+            };
+            output.retry_timeout = None;
+            output.attached_at = Instant::now();
+            (output.description.clone(), output.audio_tees.clone())
+        };
+
+        let settings = utils::load_settings();
+        match self.attach_output(&settings, name, &description, &audio_tees) {
+            Ok(()) => {
+                if let Some(session_log) = self.session_log.upgrade() {
+                    session_log.log(
+                        EventKind::Reconnect,
+                        &format!("The {} output was reconnected", name),
+                    );
+                }
+                self.object.emit_reconnected();
+            }
+            Err(err) => {
+                if let Some(session_log) = self.session_log.upgrade() {
+                    session_log.log(
+                        EventKind::Warning,
+                        &format!("Failed to reconnect the {} output: {}", name, err),
+                    );
+                }
+                self.schedule_reconnect(name);
+            }
+        }
+    }
+
+    // Panic mode: hide all mixer layers so only the black background is left, mute the audio and
+    // throw away whatever is still waiting in the delay buffer
+    pub fn set_standby(&self, standby: bool) {
+        self.standby.set(standby);
+
+        let mixer = self.pipeline.get_by_name("mixer").expect("No mixer found");
+        let alpha = if standby { 0.0f64 } else { 1.0f64 };
+        for pad in mixer.get_sink_pads() {
+            pad.set_property("alpha", &alpha)
+                .expect("No alpha pad property");
+        }
+
+        // Layers hidden by the scene stay hidden when coming back
+        if !standby {
+            let settings = utils::load_settings();
+            let (width, height) = output_size(&settings);
+            self.apply_scene(&settings, width, height, 0);
+        }
+
+        self.update_mute();
+
+        if standby {
+            self.flush_delay_buffer();
+        }
+    }
+
+    fn flush_delay_buffer(&self) {
+        for delay_queue in self.outputs.get_elements_by_name("delay-queue") {
+            flush_delay_queue(&delay_queue);
+        }
+    }
+
+    // Fill level of the broadcast delay buffer between 0 and 1, if recording with a delay
+    pub fn get_delay_fill(&self) -> Option<f64> {
+        // All outputs are delayed the same, so any of them will do
+        let delay_queue = self
+            .outputs
+            .get_elements_by_name("delay-queue")
+            .into_iter()
+            .next()?;
+
+        let level = delay_queue
+            .get_property("current-level-time")
+            .ok()?
+            .get_some::<u64>()
+            .ok()?;
+        let threshold = delay_queue
+            .get_property("min-threshold-time")
+            .ok()?
+            .get_some::<u64>()
+            .ok()?;
+
+        if threshold == 0 {
+            return None;
+        }
+
+        Some(num::clamp(level as f64 / threshold as f64, 0.0, 1.0))
+    }
+
+    // Ignored while a web page is loaded as overlay
+    pub fn update_overlay(&self, html_buffer: &str, css_buffer: &str) {
+        if self.overlay_url.borrow().is_some() {
+            return;
+        }
+        self.overlay_state.set(OverlayState::from(update_overlay(
+            &self.wpesrc,
+            html_buffer,
+            css_buffer,
+            &self.asset_variables.borrow(),
+            &self.overlay_variables.borrow(),
+            &self.logos_html.borrow(),
+        )));
+    }
+
+    // Load the given web page as overlay, or go back to the markup with the next overlay update.
+    // Loading the same page again reloads it
+    pub fn set_overlay_url(&self, url: Option<&str>) {
+        self.overlay_url.replace(url.map(String::from));
+        if let Some(url) = url {
+            self.overlay_state.set(OverlayState::from(
+                self.wpesrc.set_property("location", &url).is_ok(),
+            ));
+        }
+    }
+
+    // Takes effect with the next overlay update. The page is told right away, so it can update
+    // itself without being reloaded
+    pub fn set_overlay_variable(&self, name: &str, value: &str) {
+        self.overlay_variables
+            .borrow_mut()
+            .insert(name.to_string(), value.to_string());
+        self.send_overlay_message(&serde_json::json!({
+            "type": "variable",
+            "name": name,
+            "value": value,
+        }));
+    }
+
+    pub fn remove_overlay_variable(&self, name: &str) {
+        self.overlay_variables.borrow_mut().remove(name);
+    }
+
+    // Takes effect with the next overlay update
+    pub fn set_logos(&self, logos: &[Logo]) {
+        self.logos_html.replace(logos::overlay_html(logos));
+    }
+
+    // Read the imported assets again, takes effect with the next overlay update
+    pub fn refresh_assets(&self) {
+        let settings = utils::load_settings();
+        self.asset_variables
+            .replace(assets::variables(settings.inline_assets));
+    }
+
+    pub fn is_recording(&self) -> bool {
+        !self.outputs.is_empty()
+    }
+
+    // Partial files of the recordings in progress, which must not be repaired
+    pub fn get_recording_paths(&self) -> Vec<PathBuf> {
+        self.outputs.get_recording_paths()
+    }
+
+    // Write the next program frame to a timestamped PNG file in the snapshots folder, the
+    // snapshot-saved signal tells where
+    pub fn take_snapshot(&self) -> Result<(), String> {
+        let directory = utils::get_snapshots_directory(&utils::load_settings());
+        fs::create_dir_all(&directory).map_err(|err| {
+            format!(
+                "Failed to create snapshots folder '{}': {}",
+                directory.display(),
+                err
+            )
+        })?;
+
+        let path = directory.join(format!(
+            "snapshot-{}.png",
+            glib::DateTime::new_now_local()
+                .format("%Y-%m-%d-%H%M%S")
+                .map(|t| t.to_string())
+                .unwrap_or_default(),
+        ));
+        self.pending_snapshot.replace(Some(path));
+
+        let valve = self
+            .pipeline
+            .get_by_name("snapshot-valve")
+            .expect("No snapshot valve found");
+        valve
+            .set_property("drop", &false)
+            .map_err(|err| err.to_string())
+    }
+
+    fn on_snapshot(&self, pixbuf: &gdk_pixbuf::Pixbuf) {
+        let path = match self.pending_snapshot.borrow_mut().take() {
+            Some(path) => path,
+            None => return,
+        };
+        if let Some(valve) = self.pipeline.get_by_name("snapshot-valve") {
+            let _ = valve.set_property("drop", &true);
+        }
+
+        match pixbuf.savev(&path, "png", &[]) {
+            Ok(()) => self.object.emit_snapshot_saved(&path.to_string_lossy()),
+            Err(err) => utils::show_error_dialog(
+                false,
+                format!("Failed to save snapshot '{}': {}", path.display(), err).as_str(),
+            ),
+        }
+    }
+
+    pub fn get_overlay_state(&self) -> OverlayState {
+        self.overlay_state.get()
+    }
+
+    // Whether the camera source is up and running
+    pub fn is_camera_running(&self) -> bool {
+        match self.pipeline.get_by_name("videosrc") {
+            Some(videosrc) => {
+                let (_, current, _) = videosrc.get_state(gst::ClockTime::from_seconds(0));
+                current == gst::State::Playing
+            }
+            None => false,
+        }
+    }
+
+    // GObject exposing the state, bitrate and recording properties and the pipeline's signals
+    pub fn get_object(&self) -> &PipelineObject {
+        &self.object
+    }
+
+    // Loudest channel of the most recent audio level measurement, in dB
+    pub fn get_audio_peak(&self) -> Option<f64> {
+        self.audio_peak.get()
+    }
+
+    // Here we handle all message we get from the GStreamer pipeline. These are notifications sent
+    // from GStreamer, including errors that happend at runtime.
+    //
+    // This is always called from the main application thread by construction.
+    fn on_pipeline_message(&self, msg: &gst::MessageRef) {
+        use gst::MessageView;
+
+        // A message can contain various kinds of information but
+        // here we are only interested in errors so far
+        match msg.view() {
+            MessageView::Error(err) => {
+                let text = format!(
+                    "Error from {:?}: {} ({:?})",
+                    err.get_src().map(|s| s.get_path_string()),
+                    err.get_error(),
+                    err.get_debug()
+                );
+                if let Some(session_log) = self.session_log.upgrade() {
+                    session_log.log(EventKind::Error, &text);
+                }
+                self.object.emit_error(&text);
+
+                // A page failing to load leaves the overlay empty, the program keeps going
+                if msg
+                    .get_src()
+                    .is_some_and(|src| src == self.wpesrc.clone().upcast::<gst::Object>())
+                {
+                    self.overlay_state.set(OverlayState::Failed);
+                    utils::show_error_dialog(
+                        false,
+                        format!("The overlay failed to load. {}", text).as_str(),
+                    );
+                    return;
+                }
+
+                // An unplugged camera is replaced by the no signal picture until it comes back.
+                // Errors of camera sources we already dropped don't matter anymore
+                if let Some(src) = msg.get_src().and_then(|src| camera_source_of(&src)) {
+                    let is_current = self
+                        .pipeline
+                        .get_by_name("videosrc")
+                        .is_some_and(|videosrc| videosrc.upcast::<gst::Object>() == src);
+                    if is_current {
+                        self.on_camera_lost();
+                    }
+                    return;
+                }
+
+                // Failing audio sources and clips are dropped, the program keeps going without them
+                if let Some(src) = msg.get_src() {
+                    if self.on_source_failed(&src, &text) {
+                        return;
+                    }
+                }
+
+                // A failing output only takes itself down, the other outputs keep running.
+                // Network outputs are connected again after a while, so a short outage of the
+                // network or the server doesn't end the broadcast
+                let output = msg.get_src().and_then(|src| self.outputs.find_output(&src));
+                if let Some(output) = output {
+                    if self.schedule_reconnect(&output) {
+                        return;
+                    }
+                    self.output_failed.set(true);
+
+                    self.outputs.detach(&output);
+                    let reconnecting = self
+                        .network_outputs
+                        .borrow()
+                        .values()
+                        .any(|output| output.retry_timeout.is_some());
+                    if self.outputs.is_empty() && !reconnecting {
+                        self.stop_recording();
+                    }
+                    utils::show_error_dialog(
+                        false,
+                        format!("The {} output was stopped. {}", output, text).as_str(),
+                    );
+                    return;
+                }
+
+                // Follow-up errors of an output that was already taken down
+                if msg
+                    .get_src()
+                    .is_some_and(|src| self.outputs.is_detached(&src))
+                {
+                    return;
+                }
+
+                utils::show_error_dialog(true, text.as_str());
+            }
+            MessageView::Application(msg) => match msg.get_structure() {
+                // Here we can send ourselves messages from any thread and show them to the user in
+                // the UI in case something goes wrong
+                Some(s) if s.get_name() == "warning" => {
+                    let text = s
+                        .get::<&str>("text")
+                        .expect("Warning message without text")
+                        .unwrap();
+                    // Transient, so not worth interrupting the operator with a dialog
+                    toasts::show_warning(text);
+                    if let Some(session_log) = self.session_log.upgrade() {
+                        session_log.log(EventKind::Warning, text);
+                    }
+                }
+                Some(s) if s.get_name() == "media-eos" => {
+                    let generation = s
+                        .get_some::<u32>("generation")
+                        .expect("Media EOS message without generation");
+                    self.on_media_eos(generation);
+                }
+                // Posted once a recording file was finalized and can be handed over to the
+                // uploader
+                Some(s) if s.get_name() == "recording-finalized" => {
+                    let location = s
+                        .get::<&str>("location")
+                        .expect("Finalized recording message without location")
+                        .unwrap();
+                    let uploader = &self.uploader;
+                    let uploader = upgrade_weak!(uploader);
+                    uploader.upload(std::path::Path::new(location));
+                }
+                _ => (),
+            },
+            // Elements keep going after warnings, so they only end up in the log
+            MessageView::Warning(warning) => {
+                log::warn!(
+                    "Warning from {:?}: {} ({:?})",
+                    warning.get_src().map(|s| s.get_path_string()),
+                    warning.get_error(),
+                    warning.get_debug()
+                );
+            }
+            MessageView::Element(msg) => {
+                if let Some(structure) = msg.get_structure() {
+                    if structure.get_name() == "pixbuf" {
+                        if let Ok(Some(pixbuf)) = structure.get::<gdk_pixbuf::Pixbuf>("pixbuf") {
+                            self.on_snapshot(&pixbuf);
+                        }
+                    } else if structure.get_name() == "wpe-stats" {
+                        // Posted by wpesrc while WebKit loads the page, in percent. Failures
+                        // arrive as errors instead
+                        let progress = structure
+                            .get_some::<f64>("estimated-load-progress")
+                            .unwrap_or(0.0);
+                        if progress >= 100.0 && self.overlay_state.get() == OverlayState::Loading {
+                            self.overlay_state.set(OverlayState::Loaded);
+                        }
+                    } else if structure.get_name() == "level" {
+                        let level = msg
+                            .get_src()
+                            .and_then(|src| src.downcast::<gst::Element>().ok());
+                        // The microphone before any processing, or what is encoded
+                        let source = match level.as_ref().map(|level| level.get_name()) {
+                            Some(ref name) if name == "input-level" => LevelSource::Input,
+                            _ => LevelSource::Program,
+                        };
+
+                        let rms = structure
+                            .get::<glib::ValueArray>("rms")
+                            .expect("level message without RMS value")
+                            .unwrap();
+                        let rms_values = rms
+                            .iter()
+                            .map(|v| v.get_some::<f64>().unwrap())
+                            .collect::<Vec<_>>();
+
+                        let peak = structure
+                            .get::<glib::ValueArray>("peak")
+                            .expect("level message without Peak value")
+                            .unwrap();
+                        let peak_values = peak
+                            .iter()
+                            .map(|v| v.get_some::<f64>().unwrap())
+                            .collect::<Vec<_>>();
+
+                        let decay = structure
+                            .get::<glib::ValueArray>("decay")
+                            .expect("level message without Decay value")
+                            .unwrap();
+                        let decay_values = decay
+                            .iter()
+                            .map(|v| v.get_some::<f64>().unwrap())
+                            .collect::<Vec<_>>();
+
+                        if source == LevelSource::Program {
+                            let audio_peak = peak_values
+                                .iter()
+                                .cloned()
+                                .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+                            self.audio_peak.set(audio_peak);
+                            if let Some(audio_peak) = audio_peak {
+                                self.object.emit_level_updated(audio_peak);
+                            }
+                        }
+
+                        let audio_vumeter = &self.audio_vumeter;
+                        let mut vumeter = upgrade_weak!(audio_vumeter);
+                        // Name the channels as negotiated, their number is only known from the
+                        // caps the level element got
+                        if let Some(caps) = level
+                            .and_then(|level| level.get_static_pad("sink"))
+                            .and_then(|pad| pad.get_current_caps())
+                        {
+                            vumeter.set_channels(source, audio_vumeter::channel_labels(&caps));
+                        }
+                        vumeter.update(source, &rms_values, &peak_values, &decay_values);
+                    }
+                }
+            }
+            MessageView::Qos(qos) => {
+                if let (
+                    Some(src),
+                    gst::GenericFormattedValue::Buffers(gst::format::Buffers(Some(dropped))),
+                ) = (msg.get_src(), qos.get_stats().1)
+                {
+                    self.dropped_frames
+                        .borrow_mut()
+                        .insert(src.get_path_string().to_string(), dropped);
+                }
+            }
+            MessageView::StateChanged(state_changed) => {
+                if let Some(element) = msg.get_src() {
+                    if element == self.pipeline {
+                        self.object.set_state(
+                            &format!("{:?}", state_changed.get_current()).to_lowercase(),
+                        );
+
+                        let bin_ref = element.downcast_ref::<gst::Bin>().unwrap();
+                        let filename = format!(
+                            "gst-wpe-broadcast-demo-{:#?}_to_{:#?}",
+                            state_changed.get_old(),
+                            state_changed.get_current()
+                        );
+                        bin_ref.debug_to_dot_file_with_ts(gst::DebugGraphDetails::all(), filename);
+                    }
+                }
+            }
+            MessageView::AsyncDone(_) => {
+                if let Some(element) = msg.get_src() {
+                    let bin_ref = element.downcast_ref::<gst::Bin>().unwrap();
+                    bin_ref.debug_to_dot_file_with_ts(
+                        gst::DebugGraphDetails::all(),
+                        "gst-wpe-broadcast-demo-async-done",
+                    );
+                }
+            }
+            _ => (),
+        };
+    }
+}
+
+// Shaders of the camkey glshader. The chroma key compares the blue and red difference of each
+// pixel with the key color, so shadows on the backdrop are keyed out as well
+const PASSTHROUGH_SHADER: &str = "#version 100
+#ifdef GL_ES
+precision mediump float;
+#endif
+varying vec2 v_texcoord;
+uniform sampler2D tex;
+void main () {
+  gl_FragColor = texture2D (tex, v_texcoord);
+}
+";
+
+const CHROMA_KEY_SHADER: &str = "#version 100
+#ifdef GL_ES
+precision mediump float;
+#endif
+varying vec2 v_texcoord;
+uniform sampler2D tex;
+uniform float key_r;
+uniform float key_g;
+uniform float key_b;
+uniform float threshold;
+uniform float smoothing;
+vec2 chroma (vec3 rgb) {
+  return vec2 (-0.169 * rgb.r - 0.331 * rgb.g + 0.5 * rgb.b,
+               0.5 * rgb.r - 0.419 * rgb.g - 0.081 * rgb.b);
+}
+void main () {
+  vec4 color = texture2D (tex, v_texcoord);
+  float difference = distance (chroma (color.rgb), chroma (vec3 (key_r, key_g, key_b)));
+  float alpha = smoothstep (threshold, threshold + smoothing, difference);
+  gl_FragColor = vec4 (color.rgb, color.a * alpha);
+}
+";
+
+// Shader of the camcrop and overlaycrop glshaders, stretching the part within the crop edges over
+// the whole picture
+const CROP_SHADER: &str = "#version 100
+#ifdef GL_ES
+precision mediump float;
+#endif
+varying vec2 v_texcoord;
+uniform sampler2D tex;
+uniform float left;
+uniform float top;
+uniform float right;
+uniform float bottom;
+void main () {
+  vec2 coord = vec2 (left, top) + v_texcoord * vec2 (1.0 - left - right, 1.0 - top - bottom);
+  gl_FragColor = texture2D (tex, coord);
+}
+";
+
+// Milliseconds between two steps of a scene transition, about one per frame
+const TRANSITION_FRAME_INTERVAL: u32 = 16;
+
+// Seconds between two attempts to connect a network camera again
+const CAMERA_RETRY_INTERVAL: u32 = 5;
+
+// Seconds before reconnecting a failed network output, doubled after every failed attempt
+const OUTPUT_RETRY_MIN_INTERVAL: u32 = 1;
+const OUTPUT_RETRY_MAX_INTERVAL: u32 = 60;
+// A reconnected output that runs this long without errors starts over with the shortest interval
+const OUTPUT_RETRY_RESET: Duration = Duration::from_secs(30);
+
+// Animated properties of a mixer pad
+#[derive(Debug, Clone, Copy)]
+struct PadLayout {
+    xpos: f64,
+    ypos: f64,
+    width: f64,
+    height: f64,
+    alpha: f64,
+}
+
+impl PadLayout {
+    fn get(pad: &gst::Pad) -> Self {
+        let int = |name| {
+            pad.get_property(name)
+                .ok()
+                .and_then(|value| value.get_some::<i32>().ok())
+                .map(f64::from)
+                .unwrap_or(0.0)
+        };
+
+        PadLayout {
+            xpos: int("xpos"),
+            ypos: int("ypos"),
+            width: int("width"),
+            height: int("height"),
+            alpha: pad
+                .get_property("alpha")
+                .ok()
+                .and_then(|value| value.get_some::<f64>().ok())
+                .unwrap_or(1.0),
+        }
+    }
+
+    fn set(&self, pad: &gst::Pad) {
+        for (name, value) in &[
+            ("xpos", self.xpos),
+            ("ypos", self.ypos),
+            ("width", self.width),
+            ("height", self.height),
+        ] {
+            pad.set_property(*name, &(value.round() as i32))
+                .expect("No position pad property");
+        }
+        pad.set_property("alpha", &self.alpha)
+            .expect("No alpha pad property");
+    }
+
+    fn interpolate(&self, to: &PadLayout, t: f64) -> PadLayout {
+        let mix = |from: f64, to: f64| from + (to - from) * t;
+        PadLayout {
+            xpos: mix(self.xpos, to.xpos),
+            ypos: mix(self.ypos, to.ypos),
+            width: mix(self.width, to.width),
+            height: mix(self.height, to.height),
+            alpha: mix(self.alpha, to.alpha),
+        }
+    }
+}
+
+// The first of the elements that is installed
+fn find_installed<'a>(factories: &[&'a str]) -> Option<&'a str> {
+    factories
+        .iter()
+        .find(|factory| gst::ElementFactory::find(factory).is_some())
+        .copied()
+}
+
+// Launch description decoding what the camera delivers in the given format, and whether a
+// hardware decoder was asked for but none is installed
+fn camera_decoder_description(
+    format: Option<CameraFormat>,
+    decoding: CameraDecoding,
+) -> (String, bool) {
+    let (parser, hardware, software): (&str, &[&str], &str) = match format {
+        // Network cameras send RTP which has to be depayloaded first
+        None => return ("decodebin".to_string(), false),
+        Some(CameraFormat::Yuy2) => return ("identity".to_string(), false),
+        Some(CameraFormat::Mjpeg) => ("jpegparse", &["vaapijpegdec", "v4l2jpegdec"], "jpegdec"),
+        Some(CameraFormat::H264) => ("h264parse", &["vaapih264dec", "v4l2h264dec"], "avdec_h264"),
+    };
+
+    let hardware = match decoding {
+        CameraDecoding::Software => None,
+        _ => find_installed(hardware),
+    };
+    let fallback = decoding == CameraDecoding::Hardware && hardware.is_none();
+    let decoder = hardware.unwrap_or(software);
+
+    // The parsers are optional, but some hardware decoders want complete frames
+    let description = match find_installed(&[parser]) {
+        Some(parser) => format!("{} ! {}", parser, decoder),
+        None => decoder.to_string(),
+    };
+    (description, fallback)
+}
+
+pub fn output_size(settings: &Settings) -> (i32, i32) {
+    settings.video_resolution.size()
+}
+
+// Caps the mixer produces the program in, whatever the sizes and rates of its inputs
+fn mix_caps(settings: &Settings) -> String {
+    let (width, height) = output_size(settings);
+    format!(
+        "video/x-raw(memory:GLMemory),width={},height={},framerate={}/1",
+        width, height, settings.video_framerate
+    )
+}
+
+// Remove a branch added with add_tee_branch. It's unlinked once no data is flowing and shut down
+// from a separate thread as this might be called from a streaming thread
+fn remove_tee_branch(bin: gst::Element) {
+    let srcpad = match bin
+        .get_static_pad("sink")
+        .and_then(|sinkpad| sinkpad.get_peer())
+    {
+        Some(srcpad) => srcpad,
+        None => return,
+    };
+
+    srcpad.add_probe(gst::PadProbeType::IDLE, move |srcpad, _| {
+        if let Some(tee) = srcpad.get_parent_element() {
+            if let Some(peer) = srcpad.get_peer() {
+                let _ = srcpad.unlink(&peer);
+            }
+            tee.release_request_pad(srcpad);
+
+            let bin = bin.clone();
+            if let Some(pipeline) = tee
+                .get_parent()
+                .and_then(|parent| parent.downcast::<gst::Pipeline>().ok())
+            {
+                pipeline.call_async(move |pipeline| {
+                    let _ = pipeline.remove(&bin);
+                    let _ = bin.set_state(gst::State::Null);
+                });
+            }
+        }
+        gst::PadProbeReturn::Remove
+    });
+}
+
+// The camera source the object is part of, e.g. one of the elements inside rtspsrc
+fn camera_source_of(object: &gst::Object) -> Option<gst::Object> {
+    ancestor_of(object, |name| name == "videosrc")
+}
+
+// The object itself or the first of its parents whose name matches
+fn ancestor_of<F: Fn(&str) -> bool>(object: &gst::Object, matches: F) -> Option<gst::Object> {
+    let mut object = Some(object.clone());
+    while let Some(current) = object {
+        if matches(&current.get_name()) {
+            return Some(current);
+        }
+        object = current.get_parent();
+    }
+    None
+}
+
+pub fn has_noise_suppression() -> bool {
+    gst::ElementFactory::find("webrtcdsp").is_some()
+}
+
+// Linear amplitude between 0 and 1 of a level in dBFS
+fn db_to_amplitude(db: f64) -> f32 {
+    10f64.powf(db / 20.0) as f32
+}
+
+// Pad offsets in nanoseconds of the audio inputs and the camera for the A/V offset setting
+fn av_offsets(offset_ms: i32) -> (i64, i64) {
+    let offset = i64::from(offset_ms) * 1_000_000;
+    if offset >= 0 {
+        (offset, 0)
+    } else {
+        (0, -offset)
+    }
+}
+
+// Throw away everything waiting in the delay queue
+fn flush_delay_queue(delay_queue: &gst::Element) {
+    let sinkpad = delay_queue
+        .get_static_pad("sink")
+        .expect("Failed to get sink pad from delay queue");
+
+    // Flushing drops the segment, so send the current one again afterwards for the data that
+    // keeps coming from the muxer
+    let segment = sinkpad.get_sticky_event(gst::EventType::Segment, 0);
+    sinkpad.send_event(gst::Event::new_flush_start().build());
+    sinkpad.send_event(gst::Event::new_flush_stop(false).build());
+    if let Some(segment) = segment {
+        sinkpad.send_event(segment);
+    }
+}
+
+// Measure how long ago the frames passing the pad were captured. Live sources timestamp their
+// buffers with the running time of the capture, which the mixer and encoders keep
+fn add_latency_probe(pad: &gst::Pad, latency: Arc<AtomicU64>) {
+    pad.add_probe(gst::PadProbeType::BUFFER, move |pad, info| {
+        let pts = match info.data {
+            Some(gst::PadProbeData::Buffer(ref buffer)) => buffer.get_pts(),
+            _ => return gst::PadProbeReturn::Ok,
+        };
+        let element = match pad.get_parent_element() {
+            Some(element) => element,
+            None => return gst::PadProbeReturn::Ok,
+        };
+        let now = element
+            .get_clock()
+            .map(|clock| clock.get_time() - element.get_base_time());
+        if let (Some(now), Some(pts)) = (now.and_then(|now| now.nseconds()), pts.nseconds()) {
+            let sample = now.saturating_sub(pts).max(1);
+            // Smooth out the jitter of single frames
+            let previous = latency.load(AtomicOrdering::Relaxed);
+            let smoothed = if previous == 0 {
+                sample
+            } else {
+                (previous * 7 + sample) / 8
+            };
+            latency.store(smoothed, AtomicOrdering::Relaxed);
+        }
+        gst::PadProbeReturn::Ok
+    });
+}
+
+fn latency_from_nanos(latency: &AtomicU64) -> Option<Duration> {
+    match latency.load(AtomicOrdering::Relaxed) {
+        0 => None,
+        nanos => Some(Duration::from_nanos(nanos)),
+    }
+}
+
+// Launch description of an output muxing H.264 and AAC into a single stream, which is held back
+// by the broadcast delay
+fn muxed_bin_description(settings: &Settings, mux: &str, sink: &str, mixdown: &str) -> String {
+    // The delay queue only lets data through once it holds the configured amount of time
+    let delay = if settings.broadcast_delay > 0 {
+        format!(
+            "queue name=delay-queue max-size-buffers=0 max-size-bytes=0 max-size-time=0 \
+             min-threshold-time={} ! ",
+            gst::ClockTime::from_seconds(u64::from(settings.broadcast_delay))
+                .nseconds()
+                .unwrap()
+        )
+    } else {
+        String::new()
+    };
+
+    format!(
+        "queue name=video-queue ! {scale}gldownload ! videoconvert ! {h264_encoder} ! \
+         {mux} ! {delay}{sink} \
+         {mixdown}queue name=audio-queue ! {audio_encoder} ! mux.",
+        scale = settings.output_scale_description(),
+        h264_encoder = settings.video_encoder_description(),
+        audio_encoder = settings.audio_encoder_description(),
+        mux = mux,
+        delay = delay,
+        sink = sink,
+        mixdown = mixdown,
+    )
+}
+
+fn srt_mux_description(settings: &Settings) -> String {
+    format!(
+        "{} ! mpegtsmux name=mux alignment=7",
+        settings.video_codec().parser().unwrap_or("identity")
+    )
+}
+
+// rtmp2sink is preferred as it supports RTMPS and authentication. librtmp's rtmpsink is used if
+// it's not installed, or for plain RTMP through a SOCKS proxy which only librtmp supports
+fn use_rtmp2sink(settings: &Settings, location: &str) -> bool {
+    gst::ElementFactory::find("rtmp2sink").is_some()
+        && (settings.rtmp_socks_proxy().is_none() || location.starts_with("rtmps://"))
+}
+
+fn rtmp_bin_description(settings: &Settings, location: &str, mixdown: &str) -> String {
+    let sink = if use_rtmp2sink(settings, location) {
+        // rtmp2sink takes the plain URL without librtmp options
+        format!(
+            "rtmp2sink name=rtmpsink enable-last-sample=0 location=\"{}\"",
+            location.split_whitespace().next().unwrap_or_default()
+        )
+    } else {
+        format!(
+            "rtmpsink name=rtmpsink enable-last-sample=0 location=\"{}\"",
+            settings.rtmp_location_with_options(location)
+        )
+    };
+
+    muxed_bin_description(settings, "flvmux streamable=1 name=mux", &sink, mixdown)
+}
+
+// librtmp options are separated by spaces, which have to be escaped in their values
+fn escape_librtmp_option(value: &str) -> String {
+    value.replace('\\', "\\5c").replace(' ', "\\20")
+}
+
+// Still image scaled to the output size, for the background and the slate. The file is set on
+// filesrc instead of going through the launch description, which would need quoting
+fn image_bin(
+    image: &str,
+    width: i32,
+    height: i32,
+    framerate: u32,
+) -> Result<gst::Bin, Box<dyn error::Error>> {
+    let bin = gst::parse_bin_from_description(
+        &format!(
+            "decodebin name=image-decode ! imagefreeze ! videoconvert ! videoscale ! \
+             video/x-raw,width={width},height={height},pixel-aspect-ratio=1/1,framerate={framerate}/1 ! \
+             glupload ! glcolorconvert name=image-convert",
+            width = width,
+            height = height,
+            framerate = framerate
+        ),
+        false,
+    )
+    .map_err(|err| format!("Failed to create image source: {}", err))?;
+
+    let filesrc = gst::ElementFactory::make("filesrc", Some("image-src"))?;
+    filesrc
+        .set_property("location", &image)
+        .map_err(|err| format!("Failed to set image location: {}", err))?;
+    bin.add(&filesrc)?;
+    filesrc.link(
+        &bin.get_by_name("image-decode")
+            .expect("No image-decode found"),
+    )?;
+
+    let target = bin
+        .get_by_name("image-convert")
+        .and_then(|convert| convert.get_static_pad("src"))
+        .expect("No image-convert src pad");
+    let ghost_pad = gst::GhostPad::new(Some("src"), &target)
+        .map_err(|err| format!("Failed to create image source pad: {}", err))?;
+    bin.add_pad(&ghost_pad)?;
+
+    Ok(bin)
+}
+
+// Set the configured login on the RTMP sink of the stream. The credentials are set on the element
+// instead of going through the launch description, which would need quoting
+fn configure_rtmp_login(sink: &gst::Element, settings: &Settings) -> Result<(), String> {
+    let username = match settings.rtmp_username.as_deref() {
+        Some(username) => username,
+        None => return Ok(()),
+    };
+    let password = settings.rtmp_password.as_deref().unwrap_or_default();
+
+    let is_rtmp2sink = sink
+        .get_factory()
+        .is_some_and(|factory| factory.get_name() == "rtmp2sink");
+    if is_rtmp2sink {
+        sink.set_property("username", &username)
+            .and_then(|_| sink.set_property("password", &password))
+            .map_err(|err| format!("Failed to set the RTMP login: {}", err))
+    } else {
+        // librtmp takes the login for publishing as options of the location
+        let location = sink
+            .get_property("location")
+            .ok()
+            .and_then(|location| location.get::<String>().ok().flatten())
+            .unwrap_or_default();
+        sink.set_property(
+            "location",
+            &format!(
+                "{} pubUser={} pubPasswd={}",
+                location,
+                escape_librtmp_option(username),
+                escape_librtmp_option(password)
+            ),
+        )
+        .map_err(|err| format!("Failed to set the RTMP login: {}", err))
+    }
+}
+
+// Encode a few seconds of test pictures and tones with the configured encoders and muxer into a
+// fakesink, in a throwaway pipeline. Missing elements and negotiation errors show up here
+// instead of when going live. The callback is called from the main thread once done
+pub fn test_encoders<F: FnOnce(Result<(), String>) + 'static>(settings: &Settings, callback: F) {
+    if settings.output_mode == OutputMode::Rtmp {
+        match settings.rtmp_location.as_deref() {
+            Some(location) => {
+                if let Err(err) = validation::check_rtmp_url(location) {
+                    return callback(Err(err));
+                }
+            }
+            None => return callback(Err("No RTMP end-point URL configured".to_string())),
+        }
+    }
+
+    // RTP and WHIP don't use a muxer, the encoders are tested with Matroska which takes all
+    // codecs
+    let mux = match settings.output_mode {
+        OutputMode::Rtmp => "flvmux streamable=1 name=mux".to_string(),
+        OutputMode::Srt => srt_mux_description(settings),
+        OutputMode::Rtp | OutputMode::Whip => "matroskamux name=mux".to_string(),
+    };
+    let (width, height) = output_size(settings);
+    let description = format!(
+        "videotestsrc num-buffers=90 ! video/x-raw,width={width},height={height},framerate={framerate}/1 ! \
+         glupload ! {output} \
+         audiotestsrc num-buffers=150 ! audio/x-raw,rate=48000,channels=2 ! audio-queue.",
+        width = width,
+        height = height,
+        framerate = settings.video_framerate,
+        output = muxed_bin_description(settings, &mux, "fakesink sync=0", ""),
+    );
+
+    let pipeline = match gst::parse_launch(&description) {
+        Ok(pipeline) => pipeline,
+        Err(err) => return callback(Err(format!("Failed to create the encoders: {}", err))),
+    };
+    if let Some(pipeline) = pipeline.downcast_ref::<gst::Pipeline>() {
+        gl_context::share(pipeline, None);
+    }
+    if let Err(err) = pipeline.set_state(gst::State::Playing) {
+        let _ = pipeline.set_state(gst::State::Null);
+        return callback(Err(format!("Failed to start the encoders: {}", err)));
+    }
+
+    // Whichever comes first of the end of the test data, an error or the timeout finishes the
+    // test
+    let test = Rc::new(RefCell::new(EncoderTest {
+        pipeline: pipeline.clone(),
+        callback: Some(callback),
+        bus_watch: None,
+        timeout: None,
+    }));
+
+    let test_clone = test.clone();
+    let timeout = glib::timeout_add_seconds_local(10, move || {
+        let mut test = test_clone.borrow_mut();
+        test.timeout = None;
+        test.finish(Err(
+            "The encoders didn't finish within 10 seconds".to_string()
+        ));
+        glib::Continue(false)
+    });
+    test.borrow_mut().timeout = Some(timeout);
+
+    let test_clone = test.clone();
+    let bus = pipeline.get_bus().expect("Pipeline without bus");
+    let bus_watch = bus
+        .add_watch_local(move |_, msg| {
+            use gst::MessageView;
+
+            let result = match msg.view() {
+                MessageView::Eos(..) => Ok(()),
+                MessageView::Error(err) => Err(format!(
+                    "Error from {:?}: {} ({:?})",
+                    err.get_src().map(|s| s.get_path_string()),
+                    err.get_error(),
+                    err.get_debug()
+                )),
+                _ => return glib::Continue(true),
+            };
+
+            let mut test = test_clone.borrow_mut();
+            test.bus_watch = None;
+            test.finish(result);
+            glib::Continue(false)
+        })
+        .expect("Failed to add bus watch");
+    test.borrow_mut().bus_watch = Some(bus_watch);
+}
+
+// State of a running test_encoders()
+struct EncoderTest<F: FnOnce(Result<(), String>)> {
+    pipeline: gst::Element,
+    callback: Option<F>,
+    bus_watch: Option<glib::SourceId>,
+    timeout: Option<glib::SourceId>,
+}
+
+impl<F: FnOnce(Result<(), String>)> EncoderTest<F> {
+    // Shut down the pipeline and report the result, the sources still pending are removed
+    fn finish(&mut self, result: Result<(), String>) {
+        let _ = self.pipeline.set_state(gst::State::Null);
+        for source_id in self.bus_watch.take().into_iter().chain(self.timeout.take()) {
+            glib::source_remove(source_id);
+        }
+        if let Some(callback) = self.callback.take() {
+            callback(result);
+        }
     }
 }
@@ -0,0 +1,198 @@
+use glib::subclass;
+use glib::subclass::prelude::*;
+use glib::translate::*;
+use glib::{self, prelude::*};
+
+use std::cell::{Cell, RefCell};
+
+// Properties of the pipeline object, all of them read-only and kept up to date by the pipeline
+static PROPERTIES: [subclass::Property; 4] = [
+    subclass::Property("state", |name| {
+        glib::ParamSpec::string(
+            name,
+            "State",
+            "Current state of the pipeline: null, ready, paused or playing",
+            Some("null"),
+            glib::ParamFlags::READABLE,
+        )
+    }),
+    subclass::Property("bitrate", |name| {
+        glib::ParamSpec::uint64(
+            name,
+            "Bitrate",
+            "Bitrate of the stream sent to the outputs, in bits per second",
+            0,
+            u64::MAX,
+            0,
+            glib::ParamFlags::READABLE,
+        )
+    }),
+    subclass::Property("recording", |name| {
+        glib::ParamSpec::boolean(
+            name,
+            "Recording",
+            "Whether the pipeline is currently streaming or recording",
+            false,
+            glib::ParamFlags::READABLE,
+        )
+    }),
+    subclass::Property("media-playing", |name| {
+        glib::ParamSpec::boolean(
+            name,
+            "Media playing",
+            "Whether a media file layer is loaded, paused or not",
+            false,
+            glib::ParamFlags::READABLE,
+        )
+    }),
+];
+
+// Instance state of the GObject
+pub struct PipelineObjectPrivate {
+    state: RefCell<String>,
+    bitrate: Cell<u64>,
+    recording: Cell<bool>,
+    media_playing: Cell<bool>,
+}
+
+impl ObjectSubclass for PipelineObjectPrivate {
+    const NAME: &'static str = "GstWpeBroadcastPipeline";
+    type ParentType = glib::Object;
+    type Instance = subclass::simple::InstanceStruct<Self>;
+    type Class = subclass::simple::ClassStruct<Self>;
+
+    glib_object_subclass!();
+
+    fn class_init(klass: &mut subclass::simple::ClassStruct<Self>) {
+        klass.install_properties(&PROPERTIES);
+
+        // Peak of the loudest audio channel in dB
+        klass.add_signal(
+            "level-updated",
+            glib::SignalFlags::RUN_LAST,
+            &[f64::static_type()],
+            glib::Type::Unit,
+        );
+        // Human readable error message
+        klass.add_signal(
+            "error",
+            glib::SignalFlags::RUN_LAST,
+            &[String::static_type()],
+            glib::Type::Unit,
+        );
+        // Emitted after an output got its connection back
+        klass.add_signal(
+            "reconnected",
+            glib::SignalFlags::RUN_LAST,
+            &[],
+            glib::Type::Unit,
+        );
+        // JSON event posted by the overlay page
+        klass.add_signal(
+            "overlay-event",
+            glib::SignalFlags::RUN_LAST,
+            &[String::static_type()],
+            glib::Type::Unit,
+        );
+        // Path of the PNG file a snapshot of the program was written to
+        klass.add_signal(
+            "snapshot-saved",
+            glib::SignalFlags::RUN_LAST,
+            &[String::static_type()],
+            glib::Type::Unit,
+        );
+    }
+
+    fn new() -> Self {
+        PipelineObjectPrivate {
+            state: RefCell::new("null".to_string()),
+            bitrate: Cell::new(0),
+            recording: Cell::new(false),
+            media_playing: Cell::new(false),
+        }
+    }
+}
+
+impl ObjectImpl for PipelineObjectPrivate {
+    glib_object_impl!();
+
+    fn get_property(&self, _obj: &glib::Object, id: usize) -> Result<glib::Value, ()> {
+        match PROPERTIES[id] {
+            subclass::Property("state", ..) => Ok(self.state.borrow().to_value()),
+            subclass::Property("bitrate", ..) => Ok(self.bitrate.get().to_value()),
+            subclass::Property("recording", ..) => Ok(self.recording.get().to_value()),
+            subclass::Property("media-playing", ..) => Ok(self.media_playing.get().to_value()),
+            _ => Err(()),
+        }
+    }
+}
+
+glib_wrapper! {
+    // GObject interface of the pipeline, for consumers like GtkBuilder, introspection-based
+    // bindings and remote control that can't use the Rust API directly
+    pub struct PipelineObject(
+        Object<subclass::simple::InstanceStruct<PipelineObjectPrivate>,
+        subclass::simple::ClassStruct<PipelineObjectPrivate>, PipelineObjectClass>);
+
+    match fn {
+        get_type => || PipelineObjectPrivate::get_type().to_glib(),
+    }
+}
+
+impl PipelineObject {
+    pub fn new() -> Self {
+        glib::Object::new(Self::static_type(), &[])
+            .expect("Failed to create pipeline object")
+            .downcast()
+            .expect("Created pipeline object is of wrong type")
+    }
+
+    fn get_private(&self) -> &PipelineObjectPrivate {
+        PipelineObjectPrivate::from_instance(self)
+    }
+
+    pub fn set_state(&self, state: &str) {
+        if *self.get_private().state.borrow() != state {
+            self.get_private().state.replace(state.to_string());
+            self.notify("state");
+        }
+    }
+
+    pub fn set_bitrate(&self, bitrate: u64) {
+        if self.get_private().bitrate.replace(bitrate) != bitrate {
+            self.notify("bitrate");
+        }
+    }
+
+    pub fn set_recording(&self, recording: bool) {
+        if self.get_private().recording.replace(recording) != recording {
+            self.notify("recording");
+        }
+    }
+
+    pub fn set_media_playing(&self, media_playing: bool) {
+        if self.get_private().media_playing.replace(media_playing) != media_playing {
+            self.notify("media-playing");
+        }
+    }
+
+    pub fn emit_level_updated(&self, peak: f64) {
+        let _ = self.emit("level-updated", &[&peak]);
+    }
+
+    pub fn emit_error(&self, message: &str) {
+        let _ = self.emit("error", &[&message]);
+    }
+
+    pub fn emit_reconnected(&self) {
+        let _ = self.emit("reconnected", &[]);
+    }
+
+    pub fn emit_overlay_event(&self, event: &str) {
+        let _ = self.emit("overlay-event", &[&event]);
+    }
+
+    pub fn emit_snapshot_saved(&self, path: &str) {
+        let _ = self.emit("snapshot-saved", &[&path]);
+    }
+}
@@ -0,0 +1,142 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::keyring::{self, Secret};
+use crate::settings::Settings;
+use crate::templates;
+use crate::utils;
+use crate::APPLICATION_NAME;
+
+// Profile kept in the original settings file, which always exists
+pub const DEFAULT_PROFILE: &str = "Default";
+
+// Selected profile, read from ACTIVE_PROFILE_FILE on first use
+static ACTIVE_PROFILE: Mutex<Option<String>> = Mutex::new(None);
+
+const ACTIVE_PROFILE_FILE: &str = "profile";
+
+fn get_config_directory() -> PathBuf {
+    let mut path = glib::get_user_config_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push(APPLICATION_NAME);
+    path
+}
+
+// Directory of the settings files of all but the default profile
+fn get_profiles_directory() -> PathBuf {
+    get_config_directory().join("profiles")
+}
+
+// Settings file of the given profile
+pub fn get_settings_file_path(name: &str) -> PathBuf {
+    if name == DEFAULT_PROFILE {
+        get_config_directory().join("settings.toml")
+    } else {
+        get_profiles_directory().join(format!("{}.toml", templates::directory_name(name)))
+    }
+}
+
+// Name of the selected profile, whose settings are loaded and saved
+pub fn active() -> String {
+    let mut active = ACTIVE_PROFILE.lock().unwrap();
+    active
+        .get_or_insert_with(|| {
+            fs::read_to_string(get_config_directory().join(ACTIVE_PROFILE_FILE))
+                .ok()
+                .map(|name| name.trim().to_string())
+                .filter(|name| exists(name))
+                .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+        })
+        .clone()
+}
+
+// Switch to the profile, remembering it for the next start unless only selected for this run
+pub fn set_active(name: &str, remember: bool) -> io::Result<()> {
+    ACTIVE_PROFILE.lock().unwrap().replace(name.to_string());
+    if remember {
+        fs::create_dir_all(get_config_directory())?;
+        fs::write(get_config_directory().join(ACTIVE_PROFILE_FILE), name)?;
+    }
+    Ok(())
+}
+
+pub fn exists(name: &str) -> bool {
+    name == DEFAULT_PROFILE || get_settings_file_path(name).is_file()
+}
+
+// The default profile followed by the others, sorted
+pub fn list_names() -> Vec<String> {
+    let mut names = fs::read_dir(get_profiles_directory())
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+                .filter_map(|path| path.file_stem().map(|n| n.to_string_lossy().into_owned()))
+                .filter(|name| name != DEFAULT_PROFILE)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    names.sort();
+
+    let mut all = vec![DEFAULT_PROFILE.to_string()];
+    all.extend(names);
+    all
+}
+
+// Create a profile from the current settings and switch to it. Returns its name as listed
+pub fn create(name: &str) -> io::Result<String> {
+    let name = templates::directory_name(name.trim());
+    if name.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Profiles need a name",
+        ));
+    }
+    if exists(&name) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("There is a profile called {}", name),
+        ));
+    }
+
+    // Copy the stored settings, those loaded could contain overrides from the command line
+    let current = get_settings_file_path(&active());
+    let secrets = Secret::ALL
+        .iter()
+        .map(|&secret| (secret, keyring::lookup(&active(), secret)))
+        .collect::<Vec<_>>();
+    fs::create_dir_all(get_profiles_directory())?;
+    if current.is_file() {
+        fs::copy(current, get_settings_file_path(&name))?;
+        set_active(&name, true)?;
+    } else {
+        set_active(&name, true)?;
+        utils::save_settings(&Settings::default());
+    }
+    // Without keyring the secrets are still taken over until the application quits
+    for (secret, value) in secrets.into_iter().filter(|(_, value)| value.is_some()) {
+        let _ = keyring::store(&name, secret, value.as_deref());
+    }
+    Ok(name)
+}
+
+// Remove the profile, switching to the default profile if it was selected
+pub fn remove(name: &str) -> io::Result<()> {
+    if name == DEFAULT_PROFILE {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "The default profile can't be removed",
+        ));
+    }
+
+    fs::remove_file(get_settings_file_path(name))?;
+    if active() == name {
+        set_active(DEFAULT_PROFILE, true)?;
+    }
+    // The profile is gone even if the keyring is not available
+    for &secret in Secret::ALL {
+        let _ = keyring::store(name, secret, None);
+    }
+    Ok(())
+}
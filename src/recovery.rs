@@ -0,0 +1,180 @@
+use gst::{self, prelude::*};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use crate::utils;
+
+// Recordings are written with this extra extension and renamed once the muxer finalized them, so
+// anything still carrying it after the application stopped was interrupted
+pub const PARTIAL_EXTENSION: &str = "part";
+
+// MP4 recordings additionally keep their moov atom in this file while being written
+pub const MOOV_RECOVERY_EXTENSION: &str = "mrf";
+
+// Path a recording is written to while in progress
+pub fn partial_path(path: &Path) -> PathBuf {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".");
+    partial.push(PARTIAL_EXTENSION);
+    PathBuf::from(partial)
+}
+
+// Path of the moov recovery file for the given in-progress MP4 recording
+pub fn moov_recovery_path(partial: &Path) -> PathBuf {
+    partial.with_extension(MOOV_RECOVERY_EXTENSION)
+}
+
+// All interrupted recordings in the given directory
+pub fn find_interrupted_recordings(directory: &Path) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut recordings = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file() && path.extension().and_then(|e| e.to_str()) == Some(PARTIAL_EXTENSION)
+        })
+        .collect::<Vec<_>>();
+    recordings.sort();
+    recordings
+}
+
+// Run the given pipeline until EOS or an error
+fn run_to_eos(pipeline: &gst::Pipeline) -> Result<(), String> {
+    pipeline
+        .set_state(gst::State::Playing)
+        .map_err(|err| format!("Failed to start: {}", err))?;
+
+    let bus = pipeline.get_bus().expect("Pipeline had no bus");
+    let result = match bus.timed_pop_filtered(
+        gst::CLOCK_TIME_NONE,
+        &[gst::MessageType::Eos, gst::MessageType::Error],
+    ) {
+        Some(msg) => match msg.view() {
+            gst::MessageView::Error(err) => {
+                Err(format!("{} ({:?})", err.get_error(), err.get_debug()))
+            }
+            _ => Ok(()),
+        },
+        None => Err("Pipeline stopped unexpectedly".to_string()),
+    };
+
+    let _ = pipeline.set_state(gst::State::Null);
+    result
+}
+
+// Demux the streams of the input file and mux them again into a new, finalized container
+fn remux(input: &Path, output: &Path, muxer: &str) -> Result<(), String> {
+    let pipeline = gst::parse_launch(&format!(
+        "filesrc name=src ! parsebin name=parse {muxer} name=mux ! filesink name=sink",
+        muxer = muxer
+    ))
+    .map_err(|err| format!("Failed to create remux pipeline: {}", err))?
+    .downcast::<gst::Pipeline>()
+    .expect("Couldn't downcast pipeline");
+
+    let src = pipeline.get_by_name("src").expect("No src found");
+    let parse = pipeline.get_by_name("parse").expect("No parsebin found");
+    let mux = pipeline.get_by_name("mux").expect("No mux found");
+    let sink = pipeline.get_by_name("sink").expect("No sink found");
+
+    src.set_property("location", &input.to_string_lossy().as_ref())
+        .expect("No location property on filesrc");
+    sink.set_property("location", &output.to_string_lossy().as_ref())
+        .expect("No location property on filesink");
+
+    // Link every elementary stream found in the file to a new pad of the muxer
+    parse.connect_pad_added(move |_, pad| {
+        if let Some(sinkpad) = mux.get_compatible_pad(pad, None) {
+            let _ = pad.link(&sinkpad);
+        }
+    });
+
+    run_to_eos(&pipeline)
+}
+
+// Rebuild the index of an interrupted MP4 recording from its moov recovery file
+fn recover_mp4(input: &Path, output: &Path) -> Result<(), String> {
+    let recovery_file = moov_recovery_path(input);
+    if !recovery_file.exists() {
+        return Err("no moov recovery data was written, the file cannot be salvaged".to_string());
+    }
+
+    let recover = gst::ElementFactory::make("qtmoovrecover", None)
+        .map_err(|_| "qtmoovrecover element is missing".to_string())?;
+    recover
+        .set_property("recovery-input", &recovery_file.to_string_lossy().as_ref())
+        .expect("No recovery-input property on qtmoovrecover");
+    recover
+        .set_property("broken-input", &input.to_string_lossy().as_ref())
+        .expect("No broken-input property on qtmoovrecover");
+    recover
+        .set_property("fixed-output", &output.to_string_lossy().as_ref())
+        .expect("No fixed-output property on qtmoovrecover");
+
+    let pipeline = recover
+        .downcast::<gst::Pipeline>()
+        .expect("qtmoovrecover is not a pipeline");
+    run_to_eos(&pipeline)?;
+
+    let _ = fs::remove_file(&recovery_file);
+    Ok(())
+}
+
+// Repair one interrupted recording, returning the path of the playable file
+fn repair(partial: &Path) -> Result<PathBuf, String> {
+    // Strip the partial extension to get the name the recording should have had
+    let output = partial.with_extension("");
+    let extension = output
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "mp4" | "mov" => recover_mp4(partial, &output)?,
+        "mkv" | "webm" => remux(partial, &output, "matroskamux")?,
+        "flv" => remux(partial, &output, "flvmux")?,
+        "ts" => remux(partial, &output, "mpegtsmux")?,
+        _ => return Err(format!("unknown container format '{}'", extension)),
+    }
+
+    let _ = fs::remove_file(partial);
+    Ok(output)
+}
+
+// Scan the recordings directory for interrupted recordings in a separate thread, remux them into
+// playable files and report what was salvaged once done. The files in use by recordings still in
+// progress are left alone
+pub fn repair_recordings(in_use: Vec<PathBuf>) {
+    let settings = utils::load_settings();
+    let directory = utils::get_recordings_directory(&settings);
+
+    let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+    thread::spawn(move || {
+        let report = find_interrupted_recordings(&directory)
+            .iter()
+            .filter(|partial| !in_use.contains(partial))
+            .map(|partial| match repair(partial) {
+                Ok(output) => format!("Salvaged {}", output.display()),
+                Err(err) => format!("Failed to repair {}: {}", partial.display(), err),
+            })
+            .collect::<Vec<_>>();
+        let _ = sender.send((directory, report));
+    });
+
+    receiver.attach(None, |(directory, report)| {
+        if report.is_empty() {
+            utils::show_info_dialog(
+                format!("No interrupted recordings found in {}", directory.display()).as_str(),
+            );
+        } else {
+            utils::show_info_dialog(report.join("\n").as_str());
+        }
+        glib::Continue(false)
+    });
+}
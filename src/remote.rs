@@ -0,0 +1,442 @@
+use gio::prelude::*;
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use crate::app::{Action, RecordState};
+use crate::scenes;
+
+// Appended to the key of the WebSocket handshake before hashing it, see RFC 6455
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xa;
+
+// Requests and messages larger than this are refused, commands are supposed to be small
+const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Names of the actions clients can trigger, as listed by GET /actions
+const ACTIONS: &[&str] = &[
+    "record",
+    "update_overlay",
+    "scene",
+    "audio_input",
+    "panic",
+    "pause_stream",
+    "mute_audio",
+    "monitor_audio",
+    "debug_hud",
+    "marker",
+    "snapshot",
+    "rundown_next",
+    "rundown_run",
+    "settings",
+];
+
+// Stateful actions that are toggled when no value is given
+const TOGGLES: &[&str] = &[
+    "record",
+    "panic",
+    "pause_stream",
+    "mute_audio",
+    "monitor_audio",
+    "debug_hud",
+    "rundown_run",
+];
+
+// What a remote client asked for
+pub enum Command {
+    Trigger(Action),
+    // Activates the stateful action of that name without parameter, which toggles its state
+    Toggle(&'static str),
+}
+
+impl Command {
+    // Parse a message like {"action": "scene", "value": "camera"}
+    fn parse(message: &serde_json::Value) -> Result<Command, String> {
+        let name = message
+            .get("action")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| "Missing action".to_string())?;
+        let value = message.get("value").filter(|value| !value.is_null());
+
+        if value.is_none() {
+            if let Some(toggle) = TOGGLES.iter().find(|toggle| **toggle == name) {
+                return Ok(Command::Toggle(toggle));
+            }
+        }
+
+        let bool_value = || {
+            value
+                .and_then(serde_json::Value::as_bool)
+                .ok_or_else(|| format!("{} expects true or false as value", name))
+        };
+        let string_value = || {
+            value
+                .and_then(serde_json::Value::as_str)
+                .map(|value| value.to_string())
+                .ok_or_else(|| format!("{} expects a string as value", name))
+        };
+
+        let action = match name {
+            "record" => Action::Record(RecordState::from(bool_value()?)),
+            "update_overlay" => Action::UpdateOverlay,
+            // Scenes are either given by name or by their number, counted from 1
+            "scene" => match value.and_then(serde_json::Value::as_i64) {
+                Some(number) if number >= 1 && number as usize <= scenes::SCENES.len() => {
+                    Action::SwitchScene(number as i32)
+                }
+                Some(number) => return Err(format!("There is no scene {}", number)),
+                None => {
+                    let scene = string_value()?;
+                    if !scenes::SCENES.iter().any(|s| s.name == scene) {
+                        return Err(format!("There is no scene {}", scene));
+                    }
+                    Action::Scene(scene)
+                }
+            },
+            "audio_input" => Action::AudioInput(string_value()?),
+            "panic" => Action::Panic(bool_value()?),
+            "pause_stream" => Action::PauseStream(bool_value()?),
+            "mute_audio" => Action::MuteAudio(bool_value()?),
+            "monitor_audio" => Action::MonitorAudio(bool_value()?),
+            "debug_hud" => Action::DebugHud(bool_value()?),
+            "marker" => Action::Marker,
+            "snapshot" => Action::Snapshot,
+            "rundown_next" => Action::RundownNext,
+            "rundown_run" => Action::RundownRun(bool_value()?),
+            "settings" => Action::Settings,
+            _ => return Err(format!("Unknown action {}", name)),
+        };
+
+        Ok(Command::Trigger(action))
+    }
+
+    pub fn apply(self, application: &gtk::Application) {
+        match self {
+            Command::Trigger(action) => action.trigger(application),
+            Command::Toggle(name) => application.activate_action(name, None),
+        }
+    }
+}
+
+// Listen on the given port for remote control clients. Without a token only local clients can
+// connect, otherwise clients on any interface that present it. Returns a receiver for the
+// commands to be attached to the main context
+//
+// Commands are posted as JSON to http://<host>:<port>/actions or sent as WebSocket text messages
+// to ws://<host>:<port>/ws, which answers each of them with {"ok": true} or an error. Web pages
+// from other origins are turned away, otherwise any site open in a local browser could take over
+// the broadcast
+pub fn start(port: u16, token: Option<String>) -> io::Result<glib::Receiver<Command>> {
+    let address = if token.is_some() {
+        Ipv4Addr::UNSPECIFIED
+    } else {
+        Ipv4Addr::LOCALHOST
+    };
+    let listener = TcpListener::bind((address, port))?;
+
+    let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => continue,
+            };
+            // WebSocket clients stay connected, so each client gets its own thread
+            let sender = sender.clone();
+            let token = token.clone();
+            thread::spawn(move || {
+                let _ = handle_connection(stream, port, token.as_deref(), &sender);
+            });
+        }
+    });
+
+    Ok(receiver)
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    port: u16,
+    token: Option<&str>,
+    sender: &glib::Sender<Command>,
+) -> io::Result<()> {
+    // Don't let a stalled client hold on to its thread forever
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default().to_string();
+
+    let mut path_and_query = target.splitn(2, '?');
+    let path = path_and_query.next().unwrap_or_default();
+    // Browsers can't set headers on WebSocket connections, so the token can be a parameter too
+    let mut client_token = path_and_query.next().and_then(|query| {
+        query
+            .split('&')
+            .find_map(|parameter| parameter.strip_prefix("token="))
+            .map(|token| token.to_string())
+    });
+
+    let mut content_length = 0;
+    let mut content_type = None;
+    let mut websocket_key = None;
+    let mut origin = None;
+    let mut host = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        let mut header = line.splitn(2, ':');
+        if let (Some(name), Some(value)) = (header.next(), header.next()) {
+            let name = name.trim();
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse().unwrap_or(0);
+            } else if name.eq_ignore_ascii_case("content-type") {
+                content_type = Some(value.to_string());
+            } else if name.eq_ignore_ascii_case("origin") {
+                origin = Some(value.to_string());
+            } else if name.eq_ignore_ascii_case("host") {
+                host = Some(value.to_string());
+            } else if name.eq_ignore_ascii_case("sec-websocket-key") {
+                websocket_key = Some(value.to_string());
+            } else if name.eq_ignore_ascii_case("authorization") {
+                if let Some(bearer) = value.strip_prefix("Bearer ") {
+                    client_token = Some(bearer.trim().to_string());
+                }
+            }
+        }
+    }
+
+    // Browsers always tell where a request or WebSocket comes from, other clients don't send an
+    // origin at all
+    if let Some(origin) = origin {
+        if !is_own_origin(&origin, port, host.as_deref(), token.is_some()) {
+            return respond(&mut stream, "403 Forbidden", None);
+        }
+    }
+
+    if let Some(token) = token {
+        let client_token = client_token.unwrap_or_default();
+        if !constant_time_eq(client_token.as_bytes(), token.as_bytes()) {
+            return respond(&mut stream, "401 Unauthorized", None);
+        }
+    }
+
+    match (method.as_str(), path, websocket_key) {
+        ("GET", "/ws", Some(key)) => serve_websocket(stream, reader, &key, sender),
+        ("GET", "/actions", _) => respond(
+            &mut stream,
+            "200 OK",
+            Some(&serde_json::json!({ "actions": ACTIONS })),
+        ),
+        ("POST", "/actions", _) if content_length > MAX_MESSAGE_SIZE => {
+            respond(&mut stream, "413 Payload Too Large", None)
+        }
+        // Plain form posts don't need a preflight, so a page could send them to us without ever
+        // being asked about its origin
+        ("POST", "/actions", _)
+            if !content_type
+                .is_some_and(|content_type| content_type.starts_with("application/json")) =>
+        {
+            respond(&mut stream, "415 Unsupported Media Type", None)
+        }
+        ("POST", "/actions", _) => {
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body)?;
+            match dispatch(&body, sender)? {
+                Ok(()) => respond(&mut stream, "204 No Content", None),
+                Err(err) => respond(
+                    &mut stream,
+                    "400 Bad Request",
+                    Some(&serde_json::json!({ "error": err })),
+                ),
+            }
+        }
+        _ => respond(&mut stream, "404 Not Found", None),
+    }
+}
+
+// Whether a browser request comes from a page served on this port of the local host or, with a
+// token protecting the server, from the address the client connected to
+fn is_own_origin(origin: &str, port: u16, host: Option<&str>, has_token: bool) -> bool {
+    let authority = match origin.strip_prefix("http://") {
+        Some(authority) => authority,
+        None => return false,
+    };
+
+    ["localhost", "127.0.0.1", "[::1]"]
+        .iter()
+        .any(|name| authority == format!("{}:{}", name, port))
+        || (has_token && host == Some(authority))
+}
+
+// Compare without bailing out at the first difference, which would tell how much of a guessed
+// token was right by how long it took
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter()
+        .zip(b)
+        .fold(0, |difference, (a, b)| difference | (a ^ b))
+        == 0
+}
+
+// Hand the command in the message to the main context. Fails if the application is gone, the
+// inner result tells whether the message was valid
+fn dispatch(message: &[u8], sender: &glib::Sender<Command>) -> io::Result<Result<(), String>> {
+    let command = match serde_json::from_slice(message)
+        .map_err(|err| format!("Invalid JSON: {}", err))
+        .and_then(|message| Command::parse(&message))
+    {
+        Ok(command) => command,
+        Err(err) => return Ok(Err(err)),
+    };
+
+    sender
+        .send(command)
+        .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "Application is shutting down"))?;
+    Ok(Ok(()))
+}
+
+fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    body: Option<&serde_json::Value>,
+) -> io::Result<()> {
+    let body = body.map(|body| body.to_string()).unwrap_or_default();
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n\
+         {}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+fn serve_websocket(
+    mut stream: TcpStream,
+    mut reader: BufReader<TcpStream>,
+    key: &str,
+    sender: &glib::Sender<Command>,
+) -> io::Result<()> {
+    let mut checksum = glib::Checksum::new(glib::ChecksumType::Sha1);
+    checksum.update(format!("{}{}", key, WEBSOCKET_GUID).as_bytes());
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\n\
+         Upgrade: websocket\r\n\
+         Connection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {}\r\n\r\n",
+        base64::encode(&checksum.get_digest())
+    )?;
+
+    // Clients are idle most of the time
+    stream.set_read_timeout(None)?;
+
+    let mut message = Vec::new();
+    loop {
+        let (fin, opcode, payload) = read_frame(&mut reader)?;
+        match opcode {
+            OPCODE_TEXT | OPCODE_BINARY | OPCODE_CONTINUATION => {
+                message.extend_from_slice(&payload);
+                if message.len() > MAX_MESSAGE_SIZE {
+                    return write_frame(&mut stream, OPCODE_CLOSE, &[]);
+                }
+                if !fin {
+                    continue;
+                }
+
+                let reply = match dispatch(&message, sender)? {
+                    Ok(()) => serde_json::json!({ "ok": true }),
+                    Err(err) => serde_json::json!({ "ok": false, "error": err }),
+                };
+                message.clear();
+                write_frame(&mut stream, OPCODE_TEXT, reply.to_string().as_bytes())?;
+            }
+            OPCODE_CLOSE => return write_frame(&mut stream, OPCODE_CLOSE, &payload),
+            OPCODE_PING => write_frame(&mut stream, OPCODE_PONG, &payload)?,
+            _ => (),
+        }
+    }
+}
+
+// Read one frame, returning whether it is the final one of its message, its opcode and its
+// unmasked payload
+fn read_frame(reader: &mut impl Read) -> io::Result<(bool, u8, Vec<u8>)> {
+    let mut header = [0; 2];
+    reader.read_exact(&mut header)?;
+    let fin = header[0] & 0x80 != 0;
+    let opcode = header[0] & 0x0f;
+    let masked = header[1] & 0x80 != 0;
+
+    let length = match header[1] & 0x7f {
+        126 => {
+            let mut length = [0; 2];
+            reader.read_exact(&mut length)?;
+            u64::from(u16::from_be_bytes(length))
+        }
+        127 => {
+            let mut length = [0; 8];
+            reader.read_exact(&mut length)?;
+            u64::from_be_bytes(length)
+        }
+        length => u64::from(length),
+    };
+    if length > MAX_MESSAGE_SIZE as u64 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "WebSocket frame too large",
+        ));
+    }
+
+    let mut mask = [0; 4];
+    if masked {
+        reader.read_exact(&mut mask)?;
+    }
+
+    let mut payload = vec![0; length as usize];
+    reader.read_exact(&mut payload)?;
+    if masked {
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+    }
+
+    Ok((fin, opcode, payload))
+}
+
+// Write an unfragmented frame, servers don't mask their frames
+fn write_frame(stream: &mut TcpStream, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        length if length < 126 => frame.push(length as u8),
+        length if length <= 0xffff => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    stream.write_all(&frame)
+}
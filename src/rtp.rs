@@ -0,0 +1,154 @@
+use gst::{self, prelude::*};
+
+use std::fs;
+
+use crate::settings::RtpSettings;
+use crate::APPLICATION_NAME;
+
+// Payload types of the two RTP sessions
+const VIDEO_PAYLOAD: u32 = 96;
+const AUDIO_PAYLOAD: u32 = 97;
+
+// Caps fields that are already covered by the m= and a=rtpmap lines, or that are only meaningful
+// to the sender
+const NON_FMTP_FIELDS: &[&str] = &[
+    "media",
+    "payload",
+    "clock-rate",
+    "encoding-name",
+    "encoding-params",
+    "ssrc",
+    "timestamp-offset",
+    "seqnum-offset",
+    "a-framerate",
+];
+
+// Launch description of the RTP output bin, with one rtpbin session for video and one for audio.
+// RTP goes to the configured ports, RTCP to the port right above each of them
+pub fn bin_description(
+    settings: &RtpSettings,
+    scale: &str,
+    h264_encoder: &str,
+    audio_encoder: &str,
+    bind_address: Option<&str>,
+) -> String {
+    let bind = match bind_address {
+        Some(address) => format!(" bind-address={}", address),
+        None => String::new(),
+    };
+
+    format!(
+        "rtpbin name=rtpbin \
+         queue name=video-queue ! {scale}gldownload ! videoconvert ! {h264_encoder} ! \
+         rtph264pay name=video-pay config-interval=-1 pt={video_payload} ! rtpbin.send_rtp_sink_0 \
+         rtpbin.send_rtp_src_0 ! udpsink host={host} port={video_port}{bind} \
+         rtpbin.send_rtcp_src_0 ! udpsink host={host} port={video_rtcp_port}{bind} sync=0 async=0 \
+         queue name=audio-queue ! {audio_encoder} ! \
+         rtpmp4gpay name=audio-pay pt={audio_payload} ! rtpbin.send_rtp_sink_1 \
+         rtpbin.send_rtp_src_1 ! udpsink host={host} port={audio_port}{bind} \
+         rtpbin.send_rtcp_src_1 ! udpsink host={host} port={audio_rtcp_port}{bind} sync=0 async=0",
+        scale = scale,
+        h264_encoder = h264_encoder,
+        audio_encoder = audio_encoder,
+        host = settings.host,
+        video_port = settings.video_port,
+        video_rtcp_port = settings.video_port + 1,
+        audio_port = settings.audio_port,
+        audio_rtcp_port = settings.audio_port + 1,
+        video_payload = VIDEO_PAYLOAD,
+        audio_payload = AUDIO_PAYLOAD,
+        bind = bind,
+    )
+}
+
+// SDP media description for the RTP caps negotiated on a payloader
+fn sdp_media(caps: &gst::Caps, port: u16) -> Option<String> {
+    let s = caps.get_structure(0)?;
+    let media = s.get::<String>("media").ok()??;
+    let payload = s.get_some::<i32>("payload").ok()?;
+    let clock_rate = s.get_some::<i32>("clock-rate").ok()?;
+    let encoding_name = s.get::<String>("encoding-name").ok()??;
+
+    let mut lines = vec![
+        format!("m={} {} RTP/AVP {}", media, port, payload),
+        match s.get::<String>("encoding-params").ok().flatten() {
+            Some(params) => format!(
+                "a=rtpmap:{} {}/{}/{}",
+                payload, encoding_name, clock_rate, params
+            ),
+            None => format!("a=rtpmap:{} {}/{}", payload, encoding_name, clock_rate),
+        },
+    ];
+
+    let fmtp = s
+        .iter()
+        .filter(|(name, _)| !NON_FMTP_FIELDS.contains(name))
+        .filter_map(|(name, value)| {
+            value
+                .get::<String>()
+                .ok()
+                .flatten()
+                .map(|v| format!("{}={}", name, v))
+        })
+        .collect::<Vec<_>>();
+    if !fmtp.is_empty() {
+        lines.push(format!("a=fmtp:{} {}", payload, fmtp.join(";")));
+    }
+
+    Some(lines.join("\r\n"))
+}
+
+fn write_sdp(
+    settings: &RtpSettings,
+    path: &str,
+    video_pad: &gst::Pad,
+    audio_pad: &gst::Pad,
+) -> Option<()> {
+    let video = sdp_media(&video_pad.get_current_caps()?, settings.video_port)?;
+    let audio = sdp_media(&audio_pad.get_current_caps()?, settings.audio_port)?;
+
+    let sdp = format!(
+        "v=0\r\n\
+         o=- 0 0 IN IP4 127.0.0.1\r\n\
+         s={name}\r\n\
+         c=IN IP4 {host}\r\n\
+         t=0 0\r\n\
+         {video}\r\n\
+         {audio}\r\n",
+        name = APPLICATION_NAME,
+        host = settings.host,
+        video = video,
+        audio = audio
+    );
+
+    fs::write(path, sdp).ok()
+}
+
+// Write the SDP describing the streams of the given RTP output bin to the configured file, once
+// both payloaders know their caps
+pub fn write_sdp_when_negotiated(bin: &gst::Bin, settings: &RtpSettings) {
+    let path = match settings.sdp_file {
+        Some(ref path) => path.clone(),
+        None => return,
+    };
+
+    let video_pad = bin
+        .get_by_name("video-pay")
+        .and_then(|pay| pay.get_static_pad("src"))
+        .expect("No video payloader found");
+    let audio_pad = bin
+        .get_by_name("audio-pay")
+        .and_then(|pay| pay.get_static_pad("src"))
+        .expect("No audio payloader found");
+
+    for pad in &[&video_pad, &audio_pad] {
+        let settings = settings.clone();
+        let path = path.clone();
+        let video_pad = video_pad.clone();
+        let audio_pad = audio_pad.clone();
+        pad.connect_notify(Some("caps"), move |_, _| {
+            // Does nothing until the caps of both pads are known
+            let _ = write_sdp(&settings, &path, &video_pad, &audio_pad);
+        });
+    }
+}
@@ -0,0 +1,419 @@
+use gio::{self, prelude::*};
+use gtk::{self, prelude::*};
+
+use std::cell::{Cell, RefCell};
+use std::fs;
+use std::ops;
+use std::path::PathBuf;
+use std::rc::{Rc, Weak};
+
+use serde::{Deserialize, Serialize};
+
+use crate::scenes;
+use crate::templates;
+use crate::utils;
+use crate::APPLICATION_NAME;
+
+// One step of a show
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Cue {
+    // Load the overlay template with the given name from the local library
+    LoadOverlay { template: String },
+    // Switch to the scene with the given name, see scenes::SCENES
+    SwitchScene { scene: String },
+    // Set a variable that can be referenced as {name} from the overlay HTML
+    SetVariable { name: String, value: String },
+    // Continue with the next cue after the given number of seconds
+    Wait { seconds: u32 },
+    StartStream,
+    StopStream,
+}
+
+impl Cue {
+    fn describe(&self) -> String {
+        match self {
+            Cue::LoadOverlay { template } => format!("Load overlay \"{}\"", template),
+            Cue::SwitchScene { scene } => format!("Switch to scene \"{}\"", scene),
+            Cue::SetVariable { name, value } => format!("Set {} to \"{}\"", name, value),
+            Cue::Wait { seconds } => format!("Wait {} seconds", seconds),
+            Cue::StartStream => "Start stream".to_string(),
+            Cue::StopStream => "Stop stream".to_string(),
+        }
+    }
+}
+
+// Cue types offered in the editor, with the label of their parameter if any
+const CUE_TYPES: &[(&str, &str, Option<&str>)] = &[
+    ("load-overlay", "Load overlay", Some("Template")),
+    ("switch-scene", "Switch scene", Some("Scene")),
+    ("set-variable", "Set variable", Some("name=value")),
+    ("wait", "Wait", Some("Seconds")),
+    ("start-stream", "Start stream", None),
+    ("stop-stream", "Stop stream", None),
+];
+
+// Build a cue from the type and parameter entered in the editor
+fn parse_cue(cue_type: &str, parameter: &str) -> Result<Cue, String> {
+    match cue_type {
+        "load-overlay" if !parameter.is_empty() => Ok(Cue::LoadOverlay {
+            template: parameter.to_string(),
+        }),
+        "load-overlay" => Err("Please enter the name of the template".to_string()),
+        "switch-scene" => match scenes::find(parameter.trim()) {
+            Some(scene) => Ok(Cue::SwitchScene {
+                scene: scene.name.to_string(),
+            }),
+            None => Err(format!(
+                "Please enter one of the scenes {}",
+                scenes::SCENES
+                    .iter()
+                    .map(|scene| scene.name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        },
+        "set-variable" => {
+            let mut parts = parameter.splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(name), Some(value)) if !name.trim().is_empty() => Ok(Cue::SetVariable {
+                    name: name.trim().to_string(),
+                    value: value.to_string(),
+                }),
+                _ => Err("Please enter the variable as name=value".to_string()),
+            }
+        }
+        "wait" => parameter
+            .trim()
+            .parse::<u32>()
+            .map(|seconds| Cue::Wait { seconds })
+            .map_err(|_| "Please enter the number of seconds to wait".to_string()),
+        "start-stream" => Ok(Cue::StartStream),
+        "stop-stream" => Ok(Cue::StopStream),
+        _ => Err(format!("Unknown cue type {}", cue_type)),
+    }
+}
+
+fn get_rundown_file_path() -> PathBuf {
+    let mut path = glib::get_user_config_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push(APPLICATION_NAME);
+    path.push("rundown.json");
+    path
+}
+
+fn load_cues() -> Vec<Cue> {
+    fs::read(get_rundown_file_path())
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default()
+}
+
+fn save_cues(cues: &[Cue]) {
+    let path = get_rundown_file_path();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Ok(data) = serde_json::to_vec_pretty(cues) {
+        let _ = fs::write(path, data);
+    }
+}
+
+type CueExecutor = Box<dyn Fn(&Cue)>;
+
+// Our refcounted rundown struct, stepping through the cues of a show either one by one or
+// automatically
+#[derive(Clone)]
+pub struct Rundown(Rc<RundownInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for Rundown {
+    type Target = RundownInner;
+
+    fn deref(&self) -> &RundownInner {
+        &self.0
+    }
+}
+
+pub struct RundownInner {
+    cues: RefCell<Vec<Cue>>,
+    // Index of the cue that is executed next
+    position: Cell<usize>,
+    automatic: Cell<bool>,
+    wait_timeout: RefCell<Option<glib::SourceId>>,
+    // Executes a cue, provided by the application
+    execute: RefCell<Option<CueExecutor>>,
+    // List of the editor window, if open
+    list_box: RefCell<Option<gtk::ListBox>>,
+}
+
+// Weak reference to our rundown struct
+pub struct RundownWeak(Weak<RundownInner>);
+impl RundownWeak {
+    pub fn upgrade(&self) -> Option<Rundown> {
+        self.0.upgrade().map(Rundown)
+    }
+}
+
+impl Rundown {
+    pub fn new() -> Self {
+        Rundown(Rc::new(RundownInner {
+            cues: RefCell::new(load_cues()),
+            position: Cell::new(0),
+            automatic: Cell::new(false),
+            wait_timeout: RefCell::new(None),
+            execute: RefCell::new(None),
+            list_box: RefCell::new(None),
+        }))
+    }
+
+    pub fn set_executor<F: Fn(&Cue) + 'static>(&self, execute: F) {
+        self.execute.replace(Some(Box::new(execute)));
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> RundownWeak {
+        RundownWeak(Rc::downgrade(&self.0))
+    }
+
+    // Execute the current cue and move on. Waits only hold back automatic runs, stepping manually
+    // skips them
+    pub fn next(&self) {
+        self.cancel_wait();
+
+        let cue = match self.cues.borrow().get(self.position.get()) {
+            Some(cue) => cue.clone(),
+            None => {
+                self.set_automatic(false);
+                return;
+            }
+        };
+        self.set_position(self.position.get() + 1);
+
+        match cue {
+            Cue::Wait { seconds } if self.automatic.get() => {
+                let rundown_weak = self.downgrade();
+                let source_id = glib::timeout_add_seconds_local(seconds, move || {
+                    let rundown = upgrade_weak!(rundown_weak, glib::Continue(false));
+                    rundown.wait_timeout.borrow_mut().take();
+                    rundown.next();
+                    glib::Continue(false)
+                });
+                self.wait_timeout.replace(Some(source_id));
+            }
+            Cue::Wait { .. } => (),
+            cue => {
+                if let Some(ref execute) = *self.execute.borrow() {
+                    execute(&cue);
+                }
+                if self.automatic.get() {
+                    self.next();
+                }
+            }
+        }
+    }
+
+    // Run all remaining cues one after another, or stop doing so
+    pub fn set_automatic(&self, automatic: bool) {
+        if self.automatic.replace(automatic) == automatic {
+            return;
+        }
+
+        // Keep the state of the action that is bound to the run button in sync
+        if let Some(action) = gio::Application::get_default()
+            .and_then(|app| app.lookup_action("rundown_run"))
+            .and_then(|action| action.downcast::<gio::SimpleAction>().ok())
+        {
+            action.set_state(&automatic.to_variant());
+        }
+
+        if automatic {
+            self.next();
+        } else {
+            self.cancel_wait();
+        }
+    }
+
+    fn cancel_wait(&self) {
+        if let Some(source_id) = self.wait_timeout.borrow_mut().take() {
+            glib::source_remove(source_id);
+        }
+    }
+
+    fn set_position(&self, position: usize) {
+        self.position.set(position);
+        self.update_list();
+    }
+
+    // Rebuild the list of the editor, highlighting the next cue
+    fn update_list(&self) {
+        let list_box = match *self.list_box.borrow() {
+            Some(ref list_box) => list_box.clone(),
+            None => return,
+        };
+
+        for child in list_box.get_children() {
+            list_box.remove(&child);
+        }
+        for (i, cue) in self.cues.borrow().iter().enumerate() {
+            let marker = if i == self.position.get() { "▶" } else { " " };
+            let label = gtk::Label::new(Some(&format!("{} {}. {}", marker, i + 1, cue.describe())));
+            label.set_halign(gtk::Align::Start);
+            label.show();
+            list_box.insert(&label, -1);
+        }
+    }
+
+    fn edit<F: FnOnce(&mut Vec<Cue>)>(&self, f: F) {
+        f(&mut self.cues.borrow_mut());
+        save_cues(&self.cues.borrow());
+
+        let len = self.cues.borrow().len();
+        if self.position.get() > len {
+            self.position.set(len);
+        }
+        self.update_list();
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        self.list_box
+            .borrow()
+            .as_ref()
+            .and_then(|list_box| list_box.get_selected_row())
+            .map(|row| row.get_index() as usize)
+    }
+
+    // Show the editor window for the cues
+    pub fn show_editor(&self, application: &gtk::Application) {
+        let window = gtk::Window::new(gtk::WindowType::Toplevel);
+        window.set_title("Rundown");
+        window.set_transient_for(application.get_active_window().as_ref());
+        window.set_default_size(400, 400);
+        window.set_border_width(10);
+
+        let list_box = gtk::ListBox::new();
+        let scrolled_window = gtk::ScrolledWindow::new(gtk::NONE_ADJUSTMENT, gtk::NONE_ADJUSTMENT);
+        scrolled_window.add(&list_box);
+
+        let cue_type = gtk::ComboBoxText::new();
+        for (id, label, _) in CUE_TYPES {
+            cue_type.append(Some(id), label);
+        }
+        cue_type.set_active(Some(0));
+
+        let parameter = gtk::ComboBoxText::new_with_entry();
+        for name in templates::list_names() {
+            parameter.append_text(&name);
+        }
+        parameter.set_hexpand(true);
+
+        let add_button = gtk::Button::new_with_mnemonic("_Add");
+        let add_box = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        add_box.pack_start(&cue_type, false, false, 0);
+        add_box.pack_start(&parameter, true, true, 0);
+        add_box.pack_start(&add_button, false, false, 0);
+
+        let up_button =
+            gtk::Button::new_from_icon_name(Some("go-up-symbolic"), gtk::IconSize::Button);
+        up_button.set_tooltip_text(Some("Move up"));
+        let down_button =
+            gtk::Button::new_from_icon_name(Some("go-down-symbolic"), gtk::IconSize::Button);
+        down_button.set_tooltip_text(Some("Move down"));
+        let remove_button =
+            gtk::Button::new_from_icon_name(Some("list-remove-symbolic"), gtk::IconSize::Button);
+        remove_button.set_tooltip_text(Some("Remove"));
+        let rewind_button = gtk::Button::new_with_mnemonic("_Rewind");
+        let next_button = gtk::Button::new_with_mnemonic("_Next");
+        next_button
+            .clone()
+            .upcast::<gtk::Actionable>()
+            .set_action_name(Some("app.rundown_next"));
+        let run_button = gtk::ToggleButton::new_with_mnemonic("R_un automatically");
+        run_button
+            .clone()
+            .upcast::<gtk::Actionable>()
+            .set_action_name(Some("app.rundown_run"));
+
+        let control_box = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        control_box.pack_start(&up_button, false, false, 0);
+        control_box.pack_start(&down_button, false, false, 0);
+        control_box.pack_start(&remove_button, false, false, 0);
+        control_box.pack_end(&run_button, false, false, 0);
+        control_box.pack_end(&next_button, false, false, 0);
+        control_box.pack_end(&rewind_button, false, false, 0);
+
+        let vbox = gtk::Box::new(gtk::Orientation::Vertical, 8);
+        vbox.pack_start(&scrolled_window, true, true, 0);
+        vbox.pack_start(&add_box, false, false, 0);
+        vbox.pack_start(&control_box, false, false, 0);
+        window.add(&vbox);
+
+        self.list_box.replace(Some(list_box));
+        self.update_list();
+
+        let rundown_weak = self.downgrade();
+        add_button.connect_clicked(move |_| {
+            let rundown = upgrade_weak!(rundown_weak);
+            let id = cue_type
+                .get_active_id()
+                .map(|id| id.to_string())
+                .unwrap_or_default();
+            let text = parameter
+                .get_active_text()
+                .map(|t| t.to_string())
+                .unwrap_or_default();
+
+            match parse_cue(&id, &text) {
+                Ok(cue) => {
+                    let index = rundown.selected_index();
+                    rundown.edit(|cues| match index {
+                        Some(index) => cues.insert(index + 1, cue),
+                        None => cues.push(cue),
+                    });
+                }
+                Err(err) => utils::show_error_dialog(false, &err),
+            }
+        });
+
+        let rundown_weak = self.downgrade();
+        up_button.connect_clicked(move |_| {
+            let rundown = upgrade_weak!(rundown_weak);
+            if let Some(index) = rundown.selected_index().filter(|i| *i > 0) {
+                rundown.edit(|cues| cues.swap(index - 1, index));
+            }
+        });
+
+        let rundown_weak = self.downgrade();
+        down_button.connect_clicked(move |_| {
+            let rundown = upgrade_weak!(rundown_weak);
+            let len = rundown.cues.borrow().len();
+            if let Some(index) = rundown.selected_index().filter(|i| i + 1 < len) {
+                rundown.edit(|cues| cues.swap(index, index + 1));
+            }
+        });
+
+        let rundown_weak = self.downgrade();
+        remove_button.connect_clicked(move |_| {
+            let rundown = upgrade_weak!(rundown_weak);
+            if let Some(index) = rundown.selected_index() {
+                rundown.edit(|cues| {
+                    cues.remove(index);
+                });
+            }
+        });
+
+        let rundown_weak = self.downgrade();
+        rewind_button.connect_clicked(move |_| {
+            let rundown = upgrade_weak!(rundown_weak);
+            rundown.cancel_wait();
+            rundown.set_position(0);
+        });
+
+        let rundown_weak = self.downgrade();
+        window.connect_destroy(move |_| {
+            let rundown = upgrade_weak!(rundown_weak);
+            rundown.list_box.replace(None);
+        });
+
+        window.show_all();
+    }
+}
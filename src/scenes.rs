@@ -0,0 +1,95 @@
+// Named compositions of the camera and the overlay page
+//
+// Each scene places both layers in the mixer. Positions and sizes are given as fractions of the
+// output size so the scenes work with every resolution.
+
+// Placement of one mixer layer
+//
+// Layers are stacked by zorder: the camera at 1, the screen capture at 2 and the overlay at 3, so
+// the screen capture can be shown on top of the camera and below the overlay page
+#[derive(Debug, Clone, Copy)]
+pub struct Layer {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub alpha: f64,
+    pub zorder: u32,
+}
+
+impl Layer {
+    // Position and size in pixels for the given output size
+    pub fn rect(&self, width: i32, height: i32) -> (i32, i32, i32, i32) {
+        (
+            (self.x * f64::from(width)).round() as i32,
+            (self.y * f64::from(height)).round() as i32,
+            (self.width * f64::from(width)).round() as i32,
+            (self.height * f64::from(height)).round() as i32,
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct Scene {
+    pub name: &'static str,
+    pub label: &'static str,
+    pub camera: Layer,
+    pub overlay: Layer,
+}
+
+const FULL: Layer = Layer {
+    x: 0.0,
+    y: 0.0,
+    width: 1.0,
+    height: 1.0,
+    alpha: 1.0,
+    zorder: 1,
+};
+
+const HIDDEN: Layer = Layer { alpha: 0.0, ..FULL };
+
+pub const DEFAULT_SCENE: &str = "camera-overlay";
+// Its camera is placed as the settings say instead of the layer given here
+pub const PICTURE_IN_PICTURE: &str = "picture-in-picture";
+
+pub const SCENES: &[Scene] = &[
+    Scene {
+        name: "camera-overlay",
+        label: "Camera and overlay",
+        camera: FULL,
+        overlay: Layer { zorder: 3, ..FULL },
+    },
+    Scene {
+        name: "camera",
+        label: "Camera fullscreen",
+        camera: FULL,
+        overlay: Layer {
+            zorder: 3,
+            ..HIDDEN
+        },
+    },
+    Scene {
+        name: "overlay",
+        label: "Overlay only",
+        camera: HIDDEN,
+        overlay: Layer { zorder: 3, ..FULL },
+    },
+    // Small camera in the bottom right corner on top of the overlay page
+    Scene {
+        name: PICTURE_IN_PICTURE,
+        label: "Picture-in-picture",
+        camera: Layer {
+            x: 0.64,
+            y: 0.64,
+            width: 0.32,
+            height: 0.32,
+            alpha: 1.0,
+            zorder: 4,
+        },
+        overlay: Layer { zorder: 3, ..FULL },
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Scene> {
+    SCENES.iter().find(|scene| scene.name == name)
+}
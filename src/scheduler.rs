@@ -0,0 +1,189 @@
+use gio::prelude::*;
+
+use std::cell::{Cell, RefCell};
+use std::ops;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+use crate::app::{Action, RecordState};
+use crate::overlay_widgets::countdown::CountdownWeak;
+use crate::session_log::{EventKind, SessionLogWeak};
+use crate::settings::ScheduleSettings;
+use crate::utils;
+
+// Microseconds, as returned by glib::DateTime::difference()
+const MICROSECONDS_PER_SECOND: i64 = 1_000_000;
+// Without end time, a start is only caught up on for this many seconds, so an old schedule
+// doesn't take the application live on its next start
+const CATCH_UP: i64 = 600;
+
+fn days_in_month(year: i32, month: i32) -> i32 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Parse a local date and time given as YYYY-MM-DD HH:MM
+pub fn parse_date_time(text: &str) -> Option<glib::DateTime> {
+    let (date, time) = text.trim().split_once(char::is_whitespace)?;
+    let mut date = date.splitn(3, '-').map(|part| part.parse::<i32>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    let (hour, minute) = time.trim().split_once(':')?;
+    let (hour, minute) = (hour.parse::<i32>().ok()?, minute.parse::<i32>().ok()?);
+
+    // glib doesn't like invalid dates
+    if !(1..=9999).contains(&year)
+        || !(1..=12).contains(&month)
+        || day < 1
+        || day > days_in_month(year, month)
+        || !(0..24).contains(&hour)
+        || !(0..60).contains(&minute)
+    {
+        return None;
+    }
+    Some(glib::DateTime::new_local(
+        year, month, day, hour, minute, 0.0,
+    ))
+}
+
+// Seconds from now until the given time, negative once it passed
+fn seconds_until(time: &glib::DateTime) -> i64 {
+    time.difference(&glib::DateTime::new_now_local()) / MICROSECONDS_PER_SECOND
+}
+
+// Our refcounted scheduler, going live and off air at the configured times. It drives the record
+// action like a click on the record button would, so everything else behaves the same
+#[derive(Clone)]
+pub struct Scheduler(Rc<SchedulerInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for Scheduler {
+    type Target = SchedulerInner;
+
+    fn deref(&self) -> &SchedulerInner {
+        &self.0
+    }
+}
+
+pub struct SchedulerInner {
+    application: glib::WeakRef<gtk::Application>,
+    countdown: CountdownWeak,
+    session_log: SessionLogWeak,
+    timeout: RefCell<Option<glib::SourceId>>,
+    // Each of them only happens once per schedule, so stopping or starting by hand in between
+    // is not undone
+    countdown_shown: Cell<bool>,
+    started: Cell<bool>,
+    stopped: Cell<bool>,
+}
+
+pub struct SchedulerWeak(Weak<SchedulerInner>);
+impl SchedulerWeak {
+    pub fn upgrade(&self) -> Option<Scheduler> {
+        self.0.upgrade().map(Scheduler)
+    }
+}
+
+impl Scheduler {
+    pub fn new(
+        application: &gtk::Application,
+        countdown: CountdownWeak,
+        session_log: SessionLogWeak,
+    ) -> Self {
+        Scheduler(Rc::new(SchedulerInner {
+            application: application.downgrade(),
+            countdown,
+            session_log,
+            timeout: RefCell::new(None),
+            countdown_shown: Cell::new(false),
+            started: Cell::new(false),
+            stopped: Cell::new(false),
+        }))
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> SchedulerWeak {
+        SchedulerWeak(Rc::downgrade(&self.0))
+    }
+
+    // Start over with the stored schedule, or stop checking if it is disabled
+    pub fn refresh(&self) {
+        if let Some(source_id) = self.timeout.borrow_mut().take() {
+            glib::source_remove(source_id);
+        }
+        self.countdown_shown.set(false);
+        self.started.set(false);
+        self.stopped.set(false);
+
+        let schedule = utils::load_settings().schedule;
+        if !schedule.enabled || parse_date_time(&schedule.start).is_none() {
+            return;
+        }
+
+        let scheduler_weak = self.downgrade();
+        let source_id = glib::timeout_add_seconds_local(1, move || {
+            let scheduler = upgrade_weak!(scheduler_weak, glib::Continue(false));
+            scheduler.tick(&utils::load_settings().schedule);
+            glib::Continue(true)
+        });
+        self.timeout.replace(Some(source_id));
+    }
+
+    fn tick(&self, schedule: &ScheduleSettings) {
+        let application = match self.application.upgrade() {
+            Some(application) => application,
+            None => return,
+        };
+        let start = match parse_date_time(&schedule.start) {
+            Some(start) => seconds_until(&start),
+            None => return,
+        };
+        let end = schedule.end.as_deref().and_then(parse_date_time);
+        let end = end.as_ref().map(seconds_until);
+        let over = match end {
+            Some(end) => end <= 0,
+            None => start < -CATCH_UP,
+        };
+
+        if start > 0 {
+            let lead = i64::from(schedule.countdown) * 60;
+            if start <= lead && !self.countdown_shown.replace(true) {
+                if let Some(countdown) = self.countdown.upgrade() {
+                    countdown.run_for(Duration::from_secs(start as u64));
+                }
+            }
+            return;
+        }
+
+        // Also catches up if the application was started after the start time
+        if !over && !self.started.replace(true) {
+            if let Some(countdown) = self.countdown.upgrade() {
+                if self.countdown_shown.get() {
+                    countdown.reset();
+                }
+            }
+            self.log(EventKind::Marker, "Going live as scheduled");
+            Action::Record(RecordState::Recording).trigger(&application);
+        }
+
+        if end.is_some() && over && !self.stopped.replace(true) && is_live(&application) {
+            self.log(EventKind::Marker, "Stopping as scheduled");
+            Action::Record(RecordState::Idle).trigger(&application);
+        }
+    }
+
+    fn log(&self, kind: EventKind, message: &str) {
+        if let Some(session_log) = self.session_log.upgrade() {
+            session_log.log(kind, message);
+        }
+    }
+}
+
+fn is_live(application: &gtk::Application) -> bool {
+    application
+        .get_action_state("record")
+        .is_some_and(|state| RecordState::from(&state) == RecordState::Recording)
+}
@@ -0,0 +1,167 @@
+use gtk::{self, prelude::*};
+
+use std::cell::RefCell;
+use std::fs::{self, File};
+use std::io::Write;
+use std::ops;
+use std::path::PathBuf;
+use std::rc::{Rc, Weak};
+
+use serde::{Deserialize, Serialize};
+
+use crate::APPLICATION_NAME;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EventKind {
+    Start,
+    Stop,
+    SceneSwitch,
+    Reconnect,
+    Warning,
+    Error,
+    Marker,
+}
+
+impl EventKind {
+    fn label(self) -> &'static str {
+        match self {
+            EventKind::Start => "Start",
+            EventKind::Stop => "Stop",
+            EventKind::SceneSwitch => "Scene",
+            EventKind::Reconnect => "Reconnect",
+            EventKind::Warning => "Warning",
+            EventKind::Error => "Error",
+            EventKind::Marker => "Marker",
+        }
+    }
+}
+
+// One entry of the session timeline, written as one JSON object per line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    // ISO 8601 local time
+    pub time: String,
+    pub kind: EventKind,
+    pub message: String,
+}
+
+// Our refcounted session log, keeping the timeline of everything that happened since the
+// application was started both in a file and in the Session panel
+#[derive(Clone)]
+pub struct SessionLog(Rc<SessionLogInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for SessionLog {
+    type Target = SessionLogInner;
+
+    fn deref(&self) -> &SessionLogInner {
+        &self.0
+    }
+}
+
+pub struct SessionLogInner {
+    expander: gtk::Expander,
+    list_box: gtk::ListBox,
+    file: RefCell<Option<File>>,
+}
+
+// Weak reference to our session log struct
+pub struct SessionLogWeak(Weak<SessionLogInner>);
+impl SessionLogWeak {
+    pub fn upgrade(&self) -> Option<SessionLog> {
+        self.0.upgrade().map(SessionLog)
+    }
+}
+
+// Directory the session files are written to
+pub fn get_sessions_directory() -> PathBuf {
+    let mut path = glib::get_user_data_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push(APPLICATION_NAME);
+    path.push("sessions");
+    path
+}
+
+fn now() -> String {
+    glib::DateTime::new_now_local()
+        .format("%FT%T%z")
+        .map(|t| t.to_string())
+        .unwrap_or_default()
+}
+
+impl SessionLog {
+    pub fn new() -> Self {
+        let list_box = gtk::ListBox::new();
+        list_box.set_selection_mode(gtk::SelectionMode::None);
+
+        let scrolled_window = gtk::ScrolledWindow::new(gtk::NONE_ADJUSTMENT, gtk::NONE_ADJUSTMENT);
+        scrolled_window.set_size_request(-1, 150);
+        scrolled_window.add(&list_box);
+
+        let marker_button = gtk::Button::new_with_mnemonic("Add _marker");
+        marker_button
+            .clone()
+            .upcast::<gtk::Actionable>()
+            .set_action_name(Some("app.marker"));
+        marker_button.set_halign(gtk::Align::Start);
+
+        let vbox = gtk::Box::new(gtk::Orientation::Vertical, 4);
+        vbox.pack_start(&scrolled_window, true, true, 0);
+        vbox.pack_start(&marker_button, false, false, 0);
+
+        let expander = gtk::Expander::new(Some("Session"));
+        expander.add(&vbox);
+
+        // A failure to create the file only loses the file, the panel keeps working
+        let directory = get_sessions_directory();
+        let file = fs::create_dir_all(&directory)
+            .and_then(|_| {
+                let name = now().replace(':', "-");
+                File::create(directory.join(format!("session-{}.jsonl", name)))
+            })
+            .ok();
+
+        SessionLog(Rc::new(SessionLogInner {
+            expander,
+            list_box,
+            file: RefCell::new(file),
+        }))
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> SessionLogWeak {
+        SessionLogWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> &gtk::Expander {
+        &self.expander
+    }
+
+    // Add an event to the timeline
+    pub fn log(&self, kind: EventKind, message: &str) {
+        let event = Event {
+            time: now(),
+            kind,
+            message: message.to_string(),
+        };
+
+        if let Some(ref mut file) = *self.file.borrow_mut() {
+            if let Ok(line) = serde_json::to_string(&event) {
+                let _ = writeln!(file, "{}", line);
+            }
+        }
+
+        let time = event.time.get(11..19).unwrap_or(&event.time);
+        let label = gtk::Label::new(Some(&format!(
+            "{}  {:<9}  {}",
+            time,
+            kind.label(),
+            event.message
+        )));
+        label.set_halign(gtk::Align::Start);
+        label.set_selectable(true);
+        label.get_style_context().add_class("monospace");
+        label.show();
+        self.list_box.insert(&label, -1);
+    }
+}
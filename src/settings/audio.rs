@@ -0,0 +1,250 @@
+use gtk::{self, prelude::*};
+
+use crate::app::App;
+use crate::devices;
+
+use std::rc::Rc;
+
+use super::{
+    entry_text, page_grid, AudioMix, MeterOrientation, MonitorSettings, Settings, VuMeterSettings,
+};
+
+// Additional audio inputs, the level meter and the monitoring of the program audio
+pub struct AudioPage {
+    grid: gtk::Grid,
+    secondary_audio_device: gtk::ComboBoxText,
+    rtmp_audio_mix: gtk::ComboBoxText,
+    av_offset: gtk::SpinButton,
+    vu_meter_orientation: gtk::ComboBoxText,
+    vu_meter_min_db: gtk::SpinButton,
+    vu_meter_ticks: gtk::Entry,
+    monitor_device: gtk::ComboBoxText,
+    monitor_volume: gtk::SpinButton,
+    monitor_delay: gtk::SpinButton,
+    monitor_match_preview: gtk::CheckButton,
+}
+
+// Tick levels from a list separated by commas or spaces, ignoring anything that isn't a number
+fn parse_ticks(text: &str) -> Vec<i32> {
+    text.split([',', ' '])
+        .filter_map(|tick| tick.trim().parse().ok())
+        .collect()
+}
+
+impl AudioPage {
+    pub fn new(settings: &Settings) -> Self {
+        let grid = page_grid();
+
+        let secondary_audio_label = gtk::Label::new(Some("Second language or commentary"));
+        let secondary_audio_device = gtk::ComboBoxText::new();
+        secondary_audio_device.append(Some(""), "None");
+        for name in devices::list_device_names("Audio/Source") {
+            secondary_audio_device.append(Some(&name), &name);
+        }
+        let secondary_audio_name = settings.secondary_audio_device.clone().unwrap_or_default();
+        if !secondary_audio_device.set_active_id(Some(&secondary_audio_name)) {
+            // The device is gone, keep it selectable so the setting isn't lost
+            secondary_audio_device.append(Some(&secondary_audio_name), &secondary_audio_name);
+            secondary_audio_device.set_active_id(Some(&secondary_audio_name));
+        }
+        secondary_audio_device.set_hexpand(true);
+
+        secondary_audio_label.set_halign(gtk::Align::Start);
+
+        grid.attach(&secondary_audio_label, 0, 0, 1, 1);
+        grid.attach(&secondary_audio_device, 1, 0, 3, 1);
+
+        let rtmp_audio_mix_label = gtk::Label::new(Some("RTMP audio"));
+        let rtmp_audio_mix = gtk::ComboBoxText::new();
+        rtmp_audio_mix.append(Some(AudioMix::Primary.id()), "Microphone only");
+        rtmp_audio_mix.append(Some(AudioMix::Secondary.id()), "Second input only");
+        rtmp_audio_mix.append(Some(AudioMix::Both.id()), "Mix of both");
+        rtmp_audio_mix.set_active_id(Some(settings.rtmp_audio_mix.id()));
+
+        rtmp_audio_mix_label.set_halign(gtk::Align::Start);
+
+        grid.attach(&rtmp_audio_mix_label, 0, 1, 1, 1);
+        grid.attach(&rtmp_audio_mix, 1, 1, 3, 1);
+
+        let av_offset_label = gtk::Label::new(Some("Audio delay (ms)"));
+        let av_offset = gtk::SpinButton::new_with_range(-1000.0, 1000.0, 10.0);
+        av_offset.set_value(f64::from(settings.av_offset));
+        av_offset.set_tooltip_text(Some(
+            "Delay the audio to match a slow camera, or the camera with a negative value",
+        ));
+
+        av_offset_label.set_halign(gtk::Align::Start);
+
+        grid.attach(&av_offset_label, 0, 2, 1, 1);
+        grid.attach(&av_offset, 1, 2, 3, 1);
+
+        let vu_meter_orientation_label = gtk::Label::new(Some("Level meter"));
+        let vu_meter_orientation = gtk::ComboBoxText::new();
+        vu_meter_orientation.append(Some(MeterOrientation::Vertical.id()), "Next to the preview");
+        vu_meter_orientation.append(Some(MeterOrientation::Horizontal.id()), "Below the preview");
+        vu_meter_orientation.set_active_id(Some(settings.vu_meter.orientation.id()));
+
+        let vu_meter_min_db_label = gtk::Label::new(Some("Level meter range (dB)"));
+        let vu_meter_min_db = gtk::SpinButton::new_with_range(-96.0, -12.0, 6.0);
+        vu_meter_min_db.set_value(f64::from(settings.vu_meter.min_db));
+
+        let vu_meter_ticks_label = gtk::Label::new(Some("Level meter marks (dB)"));
+        let vu_meter_ticks = gtk::Entry::new();
+        vu_meter_ticks.set_text(
+            &settings
+                .vu_meter
+                .ticks
+                .iter()
+                .map(|tick| tick.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+        vu_meter_ticks.set_placeholder_text(Some("-40, -20, -10, -5"));
+
+        vu_meter_orientation_label.set_halign(gtk::Align::Start);
+        vu_meter_min_db_label.set_halign(gtk::Align::Start);
+        vu_meter_ticks_label.set_halign(gtk::Align::Start);
+
+        grid.attach(&vu_meter_orientation_label, 0, 3, 1, 1);
+        grid.attach(&vu_meter_orientation, 1, 3, 3, 1);
+        grid.attach(&vu_meter_min_db_label, 0, 4, 1, 1);
+        grid.attach(&vu_meter_min_db, 1, 4, 3, 1);
+        grid.attach(&vu_meter_ticks_label, 0, 5, 1, 1);
+        grid.attach(&vu_meter_ticks, 1, 5, 3, 1);
+
+        let monitor_device_label = gtk::Label::new(Some("Monitor output"));
+        let monitor_device = gtk::ComboBoxText::new();
+        monitor_device.append(Some(""), "Default output");
+        for name in devices::list_device_names("Audio/Sink") {
+            monitor_device.append(Some(&name), &name);
+        }
+        let monitor_name = settings.monitor.device.clone().unwrap_or_default();
+        if !monitor_device.set_active_id(Some(&monitor_name)) {
+            // The device is gone, keep it selectable so the setting isn't lost
+            monitor_device.append(Some(&monitor_name), &monitor_name);
+            monitor_device.set_active_id(Some(&monitor_name));
+        }
+
+        let monitor_volume_label = gtk::Label::new(Some("Monitor volume (%)"));
+        let monitor_volume = gtk::SpinButton::new_with_range(0.0, 200.0, 5.0);
+        monitor_volume.set_value(settings.monitor.volume * 100.0);
+
+        let monitor_delay_label = gtk::Label::new(Some("Monitor delay (ms)"));
+        let monitor_delay = gtk::SpinButton::new_with_range(0.0, 2000.0, 10.0);
+        monitor_delay.set_value(f64::from(settings.monitor.delay));
+        monitor_delay.set_tooltip_text(Some(
+            "Hold the monitored audio back so it plays in sync with the preview",
+        ));
+        let monitor_match_preview =
+            gtk::CheckButton::new_with_label("Match the measured preview latency");
+        monitor_match_preview.set_active(settings.monitor.match_preview);
+        monitor_match_preview.set_tooltip_text(Some(
+            "Use the latency of the preview measured when monitoring starts, the delay above \
+             until it is known",
+        ));
+
+        monitor_device_label.set_halign(gtk::Align::Start);
+        monitor_volume_label.set_halign(gtk::Align::Start);
+        monitor_delay_label.set_halign(gtk::Align::Start);
+
+        grid.attach(&monitor_device_label, 0, 6, 1, 1);
+        grid.attach(&monitor_device, 1, 6, 3, 1);
+        grid.attach(&monitor_volume_label, 0, 7, 1, 1);
+        grid.attach(&monitor_volume, 1, 7, 3, 1);
+        grid.attach(&monitor_delay_label, 0, 8, 1, 1);
+        grid.attach(&monitor_delay, 1, 8, 3, 1);
+        grid.attach(&monitor_match_preview, 1, 9, 3, 1);
+
+        AudioPage {
+            grid,
+            secondary_audio_device,
+            rtmp_audio_mix,
+            av_offset,
+            vu_meter_orientation,
+            vu_meter_min_db,
+            vu_meter_ticks,
+            monitor_device,
+            monitor_volume,
+            monitor_delay,
+            monitor_match_preview,
+        }
+    }
+
+    pub fn get_widget(&self) -> &gtk::Grid {
+        &self.grid
+    }
+
+    // Take the values of the page into the settings
+    pub fn update_settings(&self, settings: &mut Settings) {
+        settings.secondary_audio_device = self
+            .secondary_audio_device
+            .get_active_id()
+            .filter(|id| !id.is_empty())
+            .map(|id| id.to_string());
+        settings.rtmp_audio_mix = AudioMix::from(self.rtmp_audio_mix.get_active_id());
+        settings.av_offset = self.av_offset.get_value_as_int();
+        settings.vu_meter = VuMeterSettings {
+            orientation: MeterOrientation::from(self.vu_meter_orientation.get_active_id()),
+            min_db: self.vu_meter_min_db.get_value_as_int(),
+            ticks: parse_ticks(&entry_text(&self.vu_meter_ticks).unwrap_or_default()),
+        };
+        settings.monitor = MonitorSettings {
+            device: self
+                .monitor_device
+                .get_active_id()
+                .filter(|id| !id.is_empty())
+                .map(|id| id.to_string()),
+            volume: self.monitor_volume.get_value() / 100.0,
+            delay: self.monitor_delay.get_value_as_int() as u32,
+            match_preview: self.monitor_match_preview.get_active(),
+        };
+    }
+
+    // Save the settings with the given function whenever something on the page was changed and
+    // apply what changed right away. The mix of the RTMP audio only applies from the next time
+    // going live
+    pub fn connect_changed<F: Fn() + 'static>(&self, app: &App, save: F) {
+        let save = Rc::new(save);
+        let changed = |apply: fn(&App)| {
+            let save = save.clone();
+            let weak_app = app.downgrade();
+            move || {
+                save();
+                let app = upgrade_weak!(weak_app);
+                apply(&app);
+            }
+        };
+
+        let secondary_audio_changed = changed(App::refresh_secondary_audio);
+        self.secondary_audio_device
+            .connect_changed(move |_| secondary_audio_changed());
+
+        let av_offset_changed = changed(App::refresh_av_offset);
+        self.av_offset
+            .connect_value_changed(move |_| av_offset_changed());
+
+        let orientation_changed = changed(App::refresh_appearance);
+        self.vu_meter_orientation
+            .connect_changed(move |_| orientation_changed());
+        let min_db_changed = changed(App::refresh_appearance);
+        self.vu_meter_min_db
+            .connect_value_changed(move |_| min_db_changed());
+        let ticks_changed = changed(App::refresh_appearance);
+        self.vu_meter_ticks
+            .connect_property_text_notify(move |_| ticks_changed());
+
+        // Applied right away while monitoring
+        let device_changed = changed(App::refresh_monitor);
+        self.monitor_device
+            .connect_changed(move |_| device_changed());
+        for spin in &[&self.monitor_volume, &self.monitor_delay] {
+            let monitor_changed = changed(App::refresh_monitor);
+            spin.connect_value_changed(move |_| monitor_changed());
+        }
+        let match_preview_changed = changed(App::refresh_monitor);
+        self.monitor_match_preview
+            .connect_toggled(move |_| match_preview_changed());
+
+        self.rtmp_audio_mix.connect_changed(move |_| save());
+    }
+}
@@ -0,0 +1,152 @@
+use gtk::{self, prelude::*};
+
+use crate::app::App;
+use crate::pipeline;
+
+use std::rc::Rc;
+
+use super::{page_grid, AudioFilterSettings, Settings};
+
+// Processing of the program audio, applied while running
+pub struct AudioFiltersPage {
+    grid: gtk::Grid,
+    noise_suppression: gtk::CheckButton,
+    gate: gtk::CheckButton,
+    gate_threshold: gtk::SpinButton,
+    compressor: gtk::CheckButton,
+    compressor_threshold: gtk::SpinButton,
+    compressor_ratio: gtk::SpinButton,
+    equalizer: gtk::CheckButton,
+    eq_low: gtk::SpinButton,
+    eq_mid: gtk::SpinButton,
+    eq_high: gtk::SpinButton,
+}
+
+impl AudioFiltersPage {
+    pub fn new(settings: &Settings) -> Self {
+        let grid = page_grid();
+
+        let filters = &settings.audio_filters;
+        let noise_suppression = gtk::CheckButton::new_with_label("Suppress background noise");
+        noise_suppression.set_active(filters.noise_suppression);
+        if !pipeline::has_noise_suppression() {
+            noise_suppression.set_sensitive(false);
+            noise_suppression.set_tooltip_text(Some(
+                "Needs the webrtcdsp element from the GStreamer bad plugins",
+            ));
+        }
+        grid.attach(&noise_suppression, 0, 0, 4, 1);
+
+        let gate = gtk::CheckButton::new_with_label("Noise gate");
+        gate.set_active(filters.gate);
+        let gate_threshold_label = gtk::Label::new(Some("Gate threshold (dB)"));
+        let gate_threshold = gtk::SpinButton::new_with_range(-90.0, 0.0, 1.0);
+        gate_threshold.set_value(filters.gate_threshold);
+
+        let compressor = gtk::CheckButton::new_with_label("Compressor");
+        compressor.set_active(filters.compressor);
+        let compressor_threshold_label = gtk::Label::new(Some("Compressor threshold (dB)"));
+        let compressor_threshold = gtk::SpinButton::new_with_range(-60.0, 0.0, 1.0);
+        compressor_threshold.set_value(filters.compressor_threshold);
+        let compressor_ratio_label = gtk::Label::new(Some("Compressor ratio (x:1)"));
+        let compressor_ratio = gtk::SpinButton::new_with_range(1.0, 20.0, 0.5);
+        compressor_ratio.set_value(filters.compressor_ratio);
+
+        let equalizer = gtk::CheckButton::new_with_label("Equalizer");
+        equalizer.set_active(filters.equalizer);
+        let eq_low_label = gtk::Label::new(Some("Low (dB)"));
+        let eq_low = gtk::SpinButton::new_with_range(-24.0, 12.0, 0.5);
+        eq_low.set_value(filters.eq_low);
+        let eq_mid_label = gtk::Label::new(Some("Mid (dB)"));
+        let eq_mid = gtk::SpinButton::new_with_range(-24.0, 12.0, 0.5);
+        eq_mid.set_value(filters.eq_mid);
+        let eq_high_label = gtk::Label::new(Some("High (dB)"));
+        let eq_high = gtk::SpinButton::new_with_range(-24.0, 12.0, 0.5);
+        eq_high.set_value(filters.eq_high);
+
+        grid.attach(&gate, 0, 1, 4, 1);
+        grid.attach(&compressor, 0, 3, 4, 1);
+        grid.attach(&equalizer, 0, 6, 4, 1);
+        for (row, label, spin) in &[
+            (2, &gate_threshold_label, &gate_threshold),
+            (4, &compressor_threshold_label, &compressor_threshold),
+            (5, &compressor_ratio_label, &compressor_ratio),
+            (7, &eq_low_label, &eq_low),
+            (8, &eq_mid_label, &eq_mid),
+            (9, &eq_high_label, &eq_high),
+        ] {
+            label.set_halign(gtk::Align::Start);
+            label.set_margin_start(24);
+            spin.set_digits(1);
+            grid.attach(*label, 0, *row, 1, 1);
+            grid.attach(*spin, 1, *row, 3, 1);
+        }
+
+        AudioFiltersPage {
+            grid,
+            noise_suppression,
+            gate,
+            gate_threshold,
+            compressor,
+            compressor_threshold,
+            compressor_ratio,
+            equalizer,
+            eq_low,
+            eq_mid,
+            eq_high,
+        }
+    }
+
+    pub fn get_widget(&self) -> &gtk::Grid {
+        &self.grid
+    }
+
+    // Take the values of the page into the settings
+    pub fn update_settings(&self, settings: &mut Settings) {
+        settings.audio_filters = AudioFilterSettings {
+            noise_suppression: self.noise_suppression.get_active(),
+            gate: self.gate.get_active(),
+            gate_threshold: self.gate_threshold.get_value(),
+            compressor: self.compressor.get_active(),
+            compressor_threshold: self.compressor_threshold.get_value(),
+            compressor_ratio: self.compressor_ratio.get_value(),
+            equalizer: self.equalizer.get_active(),
+            eq_low: self.eq_low.get_value(),
+            eq_mid: self.eq_mid.get_value(),
+            eq_high: self.eq_high.get_value(),
+        };
+    }
+
+    // Save the settings with the given function whenever something on the page was changed and
+    // apply them to the program audio right away
+    pub fn connect_changed<F: Fn() + 'static>(&self, app: &App, save: F) {
+        let weak_app = app.downgrade();
+        let changed = Rc::new(move || {
+            save();
+            let app = upgrade_weak!(weak_app);
+            app.refresh_audio_filters();
+        });
+
+        for check in &[
+            &self.noise_suppression,
+            &self.gate,
+            &self.compressor,
+            &self.equalizer,
+        ] {
+            let changed = changed.clone();
+            check.connect_toggled(move |_| changed());
+        }
+
+        for spin in &[
+            &self.gate_threshold,
+            &self.compressor_threshold,
+            &self.compressor_ratio,
+            &self.eq_low,
+            &self.eq_mid,
+            &self.eq_high,
+        ] {
+            let changed = changed.clone();
+            spin.connect_value_changed(move |_| changed());
+        }
+    }
+}
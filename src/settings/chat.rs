@@ -0,0 +1,149 @@
+use gtk::{self, prelude::*};
+
+use crate::app::App;
+use crate::keyring::Secret;
+
+use std::rc::Rc;
+
+use super::{entry_text, page_grid, ChatProvider, ChatSettings, Settings};
+
+// Live chat of the stream
+pub struct ChatPage {
+    grid: gtk::Grid,
+    enabled: gtk::CheckButton,
+    provider: gtk::ComboBoxText,
+    twitch_channel: gtk::Entry,
+    youtube_api_key: gtk::Entry,
+    youtube_live_chat_id: gtk::Entry,
+    show_in_overlay: gtk::CheckButton,
+}
+
+impl ChatPage {
+    pub fn new(settings: &Settings) -> Self {
+        let grid = page_grid();
+
+        let enabled = gtk::CheckButton::new_with_label("Show the live chat");
+        enabled.set_active(settings.chat.enabled);
+        grid.attach(&enabled, 0, 0, 4, 1);
+
+        let provider_label = gtk::Label::new(Some("Service"));
+        let provider = gtk::ComboBoxText::new();
+        provider.append(Some(ChatProvider::Twitch.id()), "Twitch");
+        provider.append(Some(ChatProvider::YouTube.id()), "YouTube");
+        provider.set_active_id(Some(settings.chat.provider.id()));
+
+        let twitch_channel_label = gtk::Label::new(Some("Twitch channel"));
+        let twitch_channel = gtk::Entry::new();
+        if let Some(ref channel) = settings.chat.twitch_channel {
+            twitch_channel.set_text(channel);
+        }
+        twitch_channel.set_hexpand(true);
+
+        let youtube_api_key_label = gtk::Label::new(Some("YouTube API key"));
+        let youtube_api_key = gtk::Entry::new();
+        if let Some(ref api_key) = settings.chat.youtube_api_key {
+            youtube_api_key.set_text(api_key);
+        }
+        youtube_api_key.set_visibility(false);
+
+        let youtube_live_chat_id_label = gtk::Label::new(Some("YouTube live chat ID"));
+        let youtube_live_chat_id = gtk::Entry::new();
+        if let Some(ref live_chat_id) = settings.chat.youtube_live_chat_id {
+            youtube_live_chat_id.set_text(live_chat_id);
+        }
+
+        for (row, (label, widget)) in [
+            (&provider_label, provider.clone().upcast::<gtk::Widget>()),
+            (&twitch_channel_label, twitch_channel.clone().upcast()),
+            (&youtube_api_key_label, youtube_api_key.clone().upcast()),
+            (
+                &youtube_live_chat_id_label,
+                youtube_live_chat_id.clone().upcast(),
+            ),
+        ]
+        .iter()
+        .enumerate()
+        {
+            label.set_halign(gtk::Align::Start);
+            grid.attach(*label, 0, row as i32 + 1, 1, 1);
+            grid.attach(widget, 1, row as i32 + 1, 3, 1);
+        }
+
+        let show_in_overlay = gtk::CheckButton::new_with_label("Show the messages in the overlay");
+        show_in_overlay.set_active(settings.chat.show_in_overlay);
+        grid.attach(&show_in_overlay, 0, 5, 4, 1);
+
+        ChatPage {
+            grid,
+            enabled,
+            provider,
+            twitch_channel,
+            youtube_api_key,
+            youtube_live_chat_id,
+            show_in_overlay,
+        }
+    }
+
+    pub fn get_widget(&self) -> &gtk::Grid {
+        &self.grid
+    }
+
+    // Take the values of the page into the settings
+    pub fn update_settings(&self, settings: &mut Settings) {
+        settings.chat = ChatSettings {
+            enabled: self.enabled.get_active(),
+            provider: ChatProvider::from(self.provider.get_active_id()),
+            twitch_channel: entry_text(&self.twitch_channel),
+            youtube_api_key: entry_text(&self.youtube_api_key),
+            youtube_live_chat_id: entry_text(&self.youtube_live_chat_id),
+            show_in_overlay: self.show_in_overlay.get_active(),
+        };
+    }
+
+    // Entries of the page whose values are kept in the keyring
+    pub fn get_secret_entries(&self) -> Vec<(Secret, &gtk::Entry)> {
+        vec![(Secret::YouTubeApiKey, &self.youtube_api_key)]
+    }
+
+    // Save the settings with the given function whenever something on the page was changed and
+    // connect to the chat again with them
+    pub fn connect_changed<F: Fn() + 'static>(&self, app: &App, save: F) {
+        let save = Rc::new(save);
+
+        // The chat is only connected again once the settings are complete, not on every keystroke
+        for entry in &[
+            &self.twitch_channel,
+            &self.youtube_api_key,
+            &self.youtube_live_chat_id,
+        ] {
+            let save = save.clone();
+            entry.connect_property_text_notify(move |_| save());
+
+            let weak_app = app.downgrade();
+            entry.connect_activate(move |_| {
+                let app = upgrade_weak!(weak_app);
+                app.refresh_chat();
+            });
+
+            let weak_app = app.downgrade();
+            entry.connect_focus_out_event(move |_, _| {
+                let app = upgrade_weak!(weak_app, Inhibit(false));
+                app.refresh_chat();
+                Inhibit(false)
+            });
+        }
+
+        let weak_app = app.downgrade();
+        let changed = Rc::new(move || {
+            save();
+            let app = upgrade_weak!(weak_app);
+            app.refresh_chat();
+        });
+
+        for check in &[&self.enabled, &self.show_in_overlay] {
+            let changed = changed.clone();
+            check.connect_toggled(move |_| changed());
+        }
+        self.provider.connect_changed(move |_| changed());
+    }
+}
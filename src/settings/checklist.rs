@@ -0,0 +1,96 @@
+use gtk::{self, prelude::*};
+
+use std::rc::Rc;
+
+use super::{page_grid, CheckSeverity, ChecklistSettings, Settings};
+
+// Labels of the go-live checks as shown in the settings dialog
+const CHECKS: &[&str] = &[
+    "Camera",
+    "Audio levels",
+    "RTMP end-point",
+    "Disk space",
+    "Web-page overlay",
+];
+
+// Go-live checklist settings
+pub struct ChecklistPage {
+    grid: gtk::Grid,
+    enabled: gtk::CheckButton,
+    // One severity combobox per check, in the order of CHECKS
+    severities: Vec<gtk::ComboBoxText>,
+}
+
+impl ChecklistPage {
+    pub fn new(settings: &Settings) -> Self {
+        let grid = page_grid();
+
+        let enabled = gtk::CheckButton::new_with_label("Show checklist before going live");
+        enabled.set_active(settings.checklist.enabled);
+        grid.attach(&enabled, 0, 0, 4, 1);
+
+        let mut severities = Vec::new();
+        for (row, (label, severity)) in CHECKS
+            .iter()
+            .zip(&[
+                settings.checklist.camera,
+                settings.checklist.audio,
+                settings.checklist.rtmp,
+                settings.checklist.disk_space,
+                settings.checklist.overlay,
+            ])
+            .enumerate()
+        {
+            let label = gtk::Label::new(Some(*label));
+            let combo = gtk::ComboBoxText::new();
+            combo.append(Some(CheckSeverity::Off.id()), "Off");
+            combo.append(Some(CheckSeverity::Warning.id()), "Warning");
+            combo.append(Some(CheckSeverity::Blocker.id()), "Blocker");
+            combo.set_active_id(Some(severity.id()));
+            combo.set_hexpand(true);
+
+            label.set_halign(gtk::Align::Start);
+
+            grid.attach(&label, 0, row as i32 + 1, 1, 1);
+            grid.attach(&combo, 1, row as i32 + 1, 3, 1);
+            severities.push(combo);
+        }
+
+        ChecklistPage {
+            grid,
+            enabled,
+            severities,
+        }
+    }
+
+    pub fn get_widget(&self) -> &gtk::Grid {
+        &self.grid
+    }
+
+    // Take the values of the page into the settings
+    pub fn update_settings(&self, settings: &mut Settings) {
+        let severity = |i: usize| CheckSeverity::from(self.severities[i].get_active_id());
+        settings.checklist = ChecklistSettings {
+            enabled: self.enabled.get_active(),
+            camera: severity(0),
+            audio: severity(1),
+            rtmp: severity(2),
+            disk_space: severity(3),
+            overlay: severity(4),
+        };
+    }
+
+    // Call the function whenever something on the page was changed. The checks only run when
+    // going live
+    pub fn connect_changed<F: Fn() + 'static>(&self, changed: F) {
+        let changed = Rc::new(changed);
+
+        let changed_clone = changed.clone();
+        self.enabled.connect_toggled(move |_| changed_clone());
+
+        for combo in &self.severities {
+            let changed = changed.clone();
+            combo.connect_changed(move |_| changed());
+        }
+    }
+}
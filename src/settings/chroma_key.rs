@@ -0,0 +1,98 @@
+use gtk::{self, prelude::*};
+
+use crate::app::App;
+
+use std::rc::Rc;
+
+use super::{hex_to_rgba, page_grid, rgba_to_hex, ChromaKeySettings, Settings};
+
+// Chroma keying of the camera
+pub struct ChromaKeyPage {
+    grid: gtk::Grid,
+    enabled: gtk::CheckButton,
+    color: gtk::ColorButton,
+    threshold: gtk::SpinButton,
+    smoothing: gtk::SpinButton,
+}
+
+impl ChromaKeyPage {
+    pub fn new(settings: &Settings) -> Self {
+        let grid = page_grid();
+
+        let enabled = gtk::CheckButton::new_with_label("Key out the backdrop behind the camera");
+        enabled.set_active(settings.chroma_key.enabled);
+        grid.attach(&enabled, 0, 0, 4, 1);
+
+        let color_label = gtk::Label::new(Some("Key color"));
+        let color = gtk::ColorButton::new_with_rgba(&hex_to_rgba(settings.chroma_key.rgb()));
+        let threshold_label = gtk::Label::new(Some("Threshold"));
+        let threshold = gtk::SpinButton::new_with_range(0.0, 1.0, 0.01);
+        threshold.set_digits(2);
+        threshold.set_value(settings.chroma_key.threshold);
+        threshold.set_tooltip_text(Some(
+            "Raise it until the backdrop is gone, colors closer to the key color become transparent",
+        ));
+        let smoothing_label = gtk::Label::new(Some("Smoothing"));
+        let smoothing = gtk::SpinButton::new_with_range(0.0, 1.0, 0.01);
+        smoothing.set_digits(2);
+        smoothing.set_value(settings.chroma_key.smoothing);
+        smoothing.set_tooltip_text(Some("Softens the edges of the presenter"));
+
+        for (row, (label, widget)) in [
+            (&color_label, color.clone().upcast::<gtk::Widget>()),
+            (&threshold_label, threshold.clone().upcast()),
+            (&smoothing_label, smoothing.clone().upcast()),
+        ]
+        .iter()
+        .enumerate()
+        {
+            label.set_halign(gtk::Align::Start);
+            grid.attach(*label, 0, row as i32 + 1, 1, 1);
+            grid.attach(widget, 1, row as i32 + 1, 3, 1);
+        }
+
+        ChromaKeyPage {
+            grid,
+            enabled,
+            color,
+            threshold,
+            smoothing,
+        }
+    }
+
+    pub fn get_widget(&self) -> &gtk::Grid {
+        &self.grid
+    }
+
+    // Take the values of the page into the settings
+    pub fn update_settings(&self, settings: &mut Settings) {
+        settings.chroma_key = ChromaKeySettings {
+            enabled: self.enabled.get_active(),
+            color: rgba_to_hex(&self.color.get_rgba()),
+            threshold: self.threshold.get_value(),
+            smoothing: self.smoothing.get_value(),
+        };
+    }
+
+    // Save the settings with the given function whenever something on the page was changed and
+    // apply them to the camera right away
+    pub fn connect_changed<F: Fn() + 'static>(&self, app: &App, save: F) {
+        let weak_app = app.downgrade();
+        let changed = Rc::new(move || {
+            save();
+            let app = upgrade_weak!(weak_app);
+            app.refresh_chroma_key();
+        });
+
+        let changed_clone = changed.clone();
+        self.enabled.connect_toggled(move |_| changed_clone());
+
+        let changed_clone = changed.clone();
+        self.color.connect_color_set(move |_| changed_clone());
+
+        for spin in &[&self.threshold, &self.smoothing] {
+            let changed = changed.clone();
+            spin.connect_value_changed(move |_| changed());
+        }
+    }
+}
@@ -0,0 +1,4417 @@
+use gtk::{self, prelude::*};
+
+use crate::app::App;
+use crate::devices;
+use crate::encoders;
+use crate::keyring::{self, Secret};
+use crate::midi::MidiPage;
+use crate::pipeline;
+use crate::profiles;
+use crate::scenes;
+use crate::templates;
+use crate::utils;
+use crate::validation::{self, Field};
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::create_dir_all;
+use std::net::IpAddr;
+use std::ops;
+use std::rc::{Rc, Weak};
+use std::str;
+
+use serde::{Deserialize, Serialize};
+
+mod audio;
+mod audio_filters;
+mod chat;
+mod checklist;
+mod chroma_key;
+mod rotation;
+mod rtp;
+mod rtsp_camera;
+mod schedule;
+mod screen;
+mod simulcast;
+mod srt;
+mod upload;
+mod whip;
+
+use self::audio::AudioPage;
+use self::audio_filters::AudioFiltersPage;
+use self::chat::ChatPage;
+use self::checklist::ChecklistPage;
+use self::chroma_key::ChromaKeyPage;
+use self::rotation::RotationPage;
+use self::rtp::RtpPage;
+use self::rtsp_camera::RtspCameraPage;
+use self::schedule::SchedulePage;
+use self::screen::ScreenPage;
+use self::simulcast::SimulcastPage;
+use self::srt::SrtPage;
+use self::upload::UploadPage;
+use self::whip::WhipPage;
+
+// Size of the program in pixels, any even size so vertical video for mobile platforms works too
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VideoResolution {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Default for VideoResolution {
+    fn default() -> Self {
+        VideoResolution::new(1280, 720)
+    }
+}
+
+impl VideoResolution {
+    // Limits of what the mixer and the common encoders handle
+    pub const MIN_SIZE: i32 = 64;
+    pub const MAX_SIZE: i32 = 7680;
+
+    // Offered in the settings, landscape first
+    pub const PRESETS: &'static [(i32, i32)] = &[
+        (640, 480),
+        (1280, 720),
+        (1920, 1080),
+        (2560, 1440),
+        (3840, 2160),
+        (720, 1280),
+        (1080, 1920),
+    ];
+
+    pub const fn new(width: i32, height: i32) -> Self {
+        VideoResolution { width, height }
+    }
+
+    // Width and height in pixels
+    pub fn size(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+}
+
+impl fmt::Display for VideoResolution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+// Parses "WIDTHxHEIGHT" as well as the names of the old presets like "720p"
+impl str::FromStr for VideoResolution {
+    type Err = std::string::String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        let resolution = match s.as_str() {
+            "480p" => VideoResolution::new(640, 480),
+            "720p" => VideoResolution::new(1280, 720),
+            "1080p" => VideoResolution::new(1920, 1080),
+            "1440p" => VideoResolution::new(2560, 1440),
+            "2160p" | "4k" => VideoResolution::new(3840, 2160),
+            _ => {
+                let (width, height) = s
+                    .split_once(['x', '×'])
+                    .and_then(|(width, height)| {
+                        Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+                    })
+                    .ok_or_else(|| format!("'{}' is not a resolution like 1920x1080", s))?;
+                VideoResolution::new(width, height)
+            }
+        };
+
+        let range = VideoResolution::MIN_SIZE..=VideoResolution::MAX_SIZE;
+        if !range.contains(&resolution.width) || !range.contains(&resolution.height) {
+            return Err(format!(
+                "Width and height have to be between {} and {} pixels",
+                VideoResolution::MIN_SIZE,
+                VideoResolution::MAX_SIZE
+            ));
+        }
+        // Encoders subsample the colors by two in both directions
+        if resolution.width % 2 != 0 || resolution.height % 2 != 0 {
+            return Err("Width and height have to be even".to_string());
+        }
+        Ok(resolution)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum UploadDestination {
+    #[default]
+    Disabled,
+    S3,
+    Sftp,
+    YouTube,
+}
+
+// Convenience for converting from the ids in the combobox
+impl From<Option<glib::GString>> for UploadDestination {
+    fn from(s: Option<glib::GString>) -> Self {
+        match s.as_ref().map(|s| s.as_str()) {
+            Some("s3") => UploadDestination::S3,
+            Some("sftp") => UploadDestination::Sftp,
+            Some("youtube") => UploadDestination::YouTube,
+            _ => UploadDestination::Disabled,
+        }
+    }
+}
+
+impl UploadDestination {
+    fn id(self) -> &'static str {
+        match self {
+            UploadDestination::Disabled => "disabled",
+            UploadDestination::S3 => "s3",
+            UploadDestination::Sftp => "sftp",
+            UploadDestination::YouTube => "youtube",
+        }
+    }
+}
+
+// Where finished recordings are uploaded to
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct UploadSettings {
+    pub destination: UploadDestination,
+    // Bucket URL for S3, or sftp:// directory URL
+    pub url: Option<std::string::String>,
+    // Only used for S3
+    pub region: Option<std::string::String>,
+    // Access key for S3, user name for SFTP
+    pub username: Option<std::string::String>,
+    // Secret key for S3, password for SFTP, OAuth access token for YouTube. Kept in the keyring
+    // instead of the settings file, filled in when loading the settings
+    #[serde(skip)]
+    pub secret: Option<std::string::String>,
+}
+
+// How a failed go-live check is treated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CheckSeverity {
+    Off,
+    Warning,
+    Blocker,
+}
+
+impl From<Option<glib::GString>> for CheckSeverity {
+    fn from(s: Option<glib::GString>) -> Self {
+        match s.as_ref().map(|s| s.as_str()) {
+            Some("warning") => CheckSeverity::Warning,
+            Some("blocker") => CheckSeverity::Blocker,
+            _ => CheckSeverity::Off,
+        }
+    }
+}
+
+impl CheckSeverity {
+    fn id(self) -> &'static str {
+        match self {
+            CheckSeverity::Off => "off",
+            CheckSeverity::Warning => "warning",
+            CheckSeverity::Blocker => "blocker",
+        }
+    }
+}
+
+// Pre-flight checks run before going live
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct ChecklistSettings {
+    pub enabled: bool,
+    pub camera: CheckSeverity,
+    pub audio: CheckSeverity,
+    pub rtmp: CheckSeverity,
+    pub disk_space: CheckSeverity,
+    pub overlay: CheckSeverity,
+}
+
+impl Default for ChecklistSettings {
+    fn default() -> Self {
+        ChecklistSettings {
+            enabled: false,
+            camera: CheckSeverity::Blocker,
+            audio: CheckSeverity::Warning,
+            rtmp: CheckSeverity::Blocker,
+            disk_space: CheckSeverity::Warning,
+            overlay: CheckSeverity::Warning,
+        }
+    }
+}
+
+// Where the stream is sent to when going live
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum OutputMode {
+    #[default]
+    Rtmp,
+    Rtp,
+    Srt,
+    Whip,
+}
+
+impl From<Option<glib::GString>> for OutputMode {
+    fn from(s: Option<glib::GString>) -> Self {
+        match s.as_ref().map(|s| s.as_str()) {
+            Some("rtp") => OutputMode::Rtp,
+            Some("srt") => OutputMode::Srt,
+            Some("whip") => OutputMode::Whip,
+            _ => OutputMode::Rtmp,
+        }
+    }
+}
+
+impl OutputMode {
+    fn id(self) -> &'static str {
+        match self {
+            OutputMode::Rtmp => "rtmp",
+            OutputMode::Rtp => "rtp",
+            OutputMode::Srt => "srt",
+            OutputMode::Whip => "whip",
+        }
+    }
+}
+
+// File format of the per-second statistics written while live
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum StatsLogFormat {
+    #[default]
+    Off,
+    Csv,
+    // One JSON object per line
+    Json,
+}
+
+impl From<Option<glib::GString>> for StatsLogFormat {
+    fn from(s: Option<glib::GString>) -> Self {
+        match s.as_ref().map(|s| s.as_str()) {
+            Some("csv") => StatsLogFormat::Csv,
+            Some("json") => StatsLogFormat::Json,
+            _ => StatsLogFormat::Off,
+        }
+    }
+}
+
+impl StatsLogFormat {
+    fn id(self) -> &'static str {
+        match self {
+            StatsLogFormat::Off => "off",
+            StatsLogFormat::Csv => "csv",
+            StatsLogFormat::Json => "json",
+        }
+    }
+
+    pub fn extension(self) -> Option<&'static str> {
+        match self {
+            StatsLogFormat::Off => None,
+            StatsLogFormat::Csv => Some("csv"),
+            StatsLogFormat::Json => Some("jsonl"),
+        }
+    }
+}
+
+// Container of the local copy written next to the RTMP stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum LocalRecording {
+    #[default]
+    Off,
+    Mp4,
+    Matroska,
+}
+
+impl From<Option<glib::GString>> for LocalRecording {
+    fn from(s: Option<glib::GString>) -> Self {
+        match s.as_ref().map(|s| s.as_str()) {
+            Some("mp4") => LocalRecording::Mp4,
+            Some("mkv") => LocalRecording::Matroska,
+            _ => LocalRecording::Off,
+        }
+    }
+}
+
+impl LocalRecording {
+    fn id(self) -> &'static str {
+        match self {
+            LocalRecording::Off => "off",
+            LocalRecording::Mp4 => "mp4",
+            LocalRecording::Matroska => "mkv",
+        }
+    }
+
+    // File extension and muxer of the container, None if no local copy is written
+    pub fn extension(self) -> Option<&'static str> {
+        match self {
+            LocalRecording::Off => None,
+            LocalRecording::Mp4 => Some("mp4"),
+            LocalRecording::Matroska => Some("mkv"),
+        }
+    }
+
+    pub fn muxer(self) -> &'static str {
+        match self {
+            LocalRecording::Mp4 => "mp4mux",
+            _ => "matroskamux",
+        }
+    }
+}
+
+// Audio encoder of the outputs. FLV and the RTP payloader only carry AAC, Opus is meant for SRT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum AudioCodec {
+    #[default]
+    FdkAac,
+    AvAac,
+    VoAac,
+    Opus,
+}
+
+impl From<Option<glib::GString>> for AudioCodec {
+    fn from(s: Option<glib::GString>) -> Self {
+        match s.as_ref().map(|s| s.as_str()) {
+            Some("avaac") => AudioCodec::AvAac,
+            Some("voaac") => AudioCodec::VoAac,
+            Some("opus") => AudioCodec::Opus,
+            _ => AudioCodec::FdkAac,
+        }
+    }
+}
+
+impl AudioCodec {
+    pub const ALL: [AudioCodec; 4] = [
+        AudioCodec::FdkAac,
+        AudioCodec::AvAac,
+        AudioCodec::VoAac,
+        AudioCodec::Opus,
+    ];
+
+    fn id(self) -> &'static str {
+        match self {
+            AudioCodec::FdkAac => "fdkaac",
+            AudioCodec::AvAac => "avaac",
+            AudioCodec::VoAac => "voaac",
+            AudioCodec::Opus => "opus",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            AudioCodec::FdkAac => "AAC (Fraunhofer FDK)",
+            AudioCodec::AvAac => "AAC (FFmpeg)",
+            AudioCodec::VoAac => "AAC (VisualOn)",
+            AudioCodec::Opus => "Opus",
+        }
+    }
+
+    // Name of the GStreamer element factory
+    pub fn factory(self) -> &'static str {
+        match self {
+            AudioCodec::FdkAac => "fdkaacenc",
+            AudioCodec::AvAac => "avenc_aac",
+            AudioCodec::VoAac => "voaacenc",
+            AudioCodec::Opus => "opusenc",
+        }
+    }
+
+    pub fn is_aac(self) -> bool {
+        self != AudioCodec::Opus
+    }
+
+    // Launch fragment encoding raw audio at the given bitrate in kbit/s
+    pub fn launch_description(self, bitrate: u32) -> std::string::String {
+        let parser = if self.is_aac() {
+            "aacparse"
+        } else {
+            "opusparse"
+        };
+        format!(
+            "audioconvert ! {} bitrate={} ! {}",
+            self.factory(),
+            bitrate * 1000,
+            parser
+        )
+    }
+}
+
+// What the camera is asked to deliver. MJPEG is what most webcams can do at high resolutions,
+// raw YUY2 avoids decoding but is often limited to low framerates over USB 2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum CameraFormat {
+    #[default]
+    Mjpeg,
+    Yuy2,
+    H264,
+}
+
+impl From<Option<glib::GString>> for CameraFormat {
+    fn from(s: Option<glib::GString>) -> Self {
+        match s.as_ref().map(|s| s.as_str()) {
+            Some("yuy2") => CameraFormat::Yuy2,
+            Some("h264") => CameraFormat::H264,
+            _ => CameraFormat::Mjpeg,
+        }
+    }
+}
+
+impl CameraFormat {
+    pub const ALL: &'static [CameraFormat] =
+        &[CameraFormat::Mjpeg, CameraFormat::Yuy2, CameraFormat::H264];
+
+    fn id(self) -> &'static str {
+        match self {
+            CameraFormat::Mjpeg => "mjpeg",
+            CameraFormat::Yuy2 => "yuy2",
+            CameraFormat::H264 => "h264",
+        }
+    }
+
+    // Media type and format fields of the caps
+    pub fn caps(self) -> &'static str {
+        match self {
+            CameraFormat::Mjpeg => "image/jpeg",
+            CameraFormat::Yuy2 => "video/x-raw,format=YUY2",
+            CameraFormat::H264 => "video/x-h264",
+        }
+    }
+}
+
+// How the pictures of the camera are decoded. Hardware decoders take most of the load off the
+// CPU at 1080p30, software decoding works everywhere
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum CameraDecoding {
+    // A hardware decoder if one is installed, the software one otherwise
+    #[default]
+    Auto,
+    // A hardware decoder, warning if none is installed
+    Hardware,
+    Software,
+}
+
+impl From<Option<glib::GString>> for CameraDecoding {
+    fn from(s: Option<glib::GString>) -> Self {
+        match s.as_ref().map(|s| s.as_str()) {
+            Some("hardware") => CameraDecoding::Hardware,
+            Some("software") => CameraDecoding::Software,
+            _ => CameraDecoding::Auto,
+        }
+    }
+}
+
+impl CameraDecoding {
+    fn id(self) -> &'static str {
+        match self {
+            CameraDecoding::Auto => "auto",
+            CameraDecoding::Hardware => "hardware",
+            CameraDecoding::Software => "software",
+        }
+    }
+}
+
+// How local cameras are captured. Inside a sandbox like Flatpak or where the access to the device
+// nodes is mediated, cameras are only reachable through PipeWire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum CameraBackend {
+    // PipeWire when sandboxed or no V4L2 device can be opened, V4L2 otherwise
+    #[default]
+    Auto,
+    V4l2,
+    PipeWire,
+}
+
+impl From<Option<glib::GString>> for CameraBackend {
+    fn from(s: Option<glib::GString>) -> Self {
+        match s.as_ref().map(|s| s.as_str()) {
+            Some("v4l2") => CameraBackend::V4l2,
+            Some("pipewire") => CameraBackend::PipeWire,
+            _ => CameraBackend::Auto,
+        }
+    }
+}
+
+impl CameraBackend {
+    fn id(self) -> &'static str {
+        match self {
+            CameraBackend::Auto => "auto",
+            CameraBackend::V4l2 => "v4l2",
+            CameraBackend::PipeWire => "pipewire",
+        }
+    }
+}
+
+// Gain and mute of one input of the program audio mixer
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct AudioInputSettings {
+    // Linear, 1.0 leaves the input as it is
+    pub volume: f64,
+    pub muted: bool,
+}
+
+impl Default for AudioInputSettings {
+    fn default() -> Self {
+        AudioInputSettings {
+            volume: 1.0,
+            muted: false,
+        }
+    }
+}
+
+// Processing of the program audio before it is encoded. Disabled filters leave the audio as it is
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct AudioFilterSettings {
+    // Needs webrtcdsp
+    pub noise_suppression: bool,
+    pub gate: bool,
+    // Level below which the audio is muted, in dBFS
+    pub gate_threshold: f64,
+    pub compressor: bool,
+    // Level above which the audio is compressed, in dBFS
+    pub compressor_threshold: f64,
+    // Input to output change above the threshold, e.g. 4 for 4:1
+    pub compressor_ratio: f64,
+    pub equalizer: bool,
+    // Gains of the low, mid and high band in dB
+    pub eq_low: f64,
+    pub eq_mid: f64,
+    pub eq_high: f64,
+}
+
+impl Default for AudioFilterSettings {
+    fn default() -> Self {
+        AudioFilterSettings {
+            noise_suppression: false,
+            gate: false,
+            gate_threshold: -50.0,
+            compressor: false,
+            compressor_threshold: -20.0,
+            compressor_ratio: 4.0,
+            equalizer: false,
+            eq_low: 0.0,
+            eq_mid: 0.0,
+            eq_high: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ScreenSource {
+    #[default]
+    X11,
+    PipeWire,
+}
+
+impl From<Option<glib::GString>> for ScreenSource {
+    fn from(s: Option<glib::GString>) -> Self {
+        match s.as_ref().map(|s| s.as_str()) {
+            Some("pipewire") => ScreenSource::PipeWire,
+            _ => ScreenSource::X11,
+        }
+    }
+}
+
+impl ScreenSource {
+    fn id(self) -> &'static str {
+        match self {
+            ScreenSource::X11 => "x11",
+            ScreenSource::PipeWire => "pipewire",
+        }
+    }
+}
+
+// Desktop capture shown as another mixer layer, e.g. for slides or a terminal
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct ScreenSettings {
+    pub enabled: bool,
+    pub source: ScreenSource,
+    // Node of the PipeWire stream to capture, the default one if None
+    pub pipewire_path: Option<std::string::String>,
+    // Position and size as fractions of the output size
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Default for ScreenSettings {
+    fn default() -> Self {
+        ScreenSettings {
+            enabled: false,
+            source: ScreenSource::default(),
+            pipewire_path: None,
+            x: 0.0,
+            y: 0.0,
+            width: 1.0,
+            height: 1.0,
+        }
+    }
+}
+
+impl ScreenSettings {
+    // Placement in the mixer, between the camera and the overlay
+    pub fn layer(&self) -> scenes::Layer {
+        scenes::Layer {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+            alpha: 1.0,
+            zorder: 2,
+        }
+    }
+}
+
+// Placement of the camera in the picture-in-picture scene as fractions of the output size, dragged
+// around in the preview. The camera keeps the aspect ratio of the output
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct PipSettings {
+    pub x: f64,
+    pub y: f64,
+    pub size: f64,
+}
+
+impl Default for PipSettings {
+    fn default() -> Self {
+        PipSettings {
+            x: 0.64,
+            y: 0.64,
+            size: 0.32,
+        }
+    }
+}
+
+// Distance of the corner presets to the edges of the output
+const PIP_MARGIN: f64 = 0.04;
+// Cameras dropped closer than this to a corner preset snap to it
+const PIP_SNAP_DISTANCE: f64 = 0.05;
+
+impl PipSettings {
+    // On top of the overlay
+    pub fn layer(&self) -> scenes::Layer {
+        scenes::Layer {
+            x: self.x,
+            y: self.y,
+            width: self.size,
+            height: self.size,
+            alpha: 1.0,
+            zorder: 4,
+        }
+    }
+
+    // Same size in one of the corners
+    pub fn at_corner(self, right: bool, bottom: bool) -> Self {
+        let position = |far: bool| {
+            if far {
+                1.0 - PIP_MARGIN - self.size
+            } else {
+                PIP_MARGIN
+            }
+        };
+        PipSettings {
+            x: position(right),
+            y: position(bottom),
+            ..self
+        }
+    }
+
+    // Moved to the closest corner preset along each axis it is close to
+    pub fn snapped(self) -> Self {
+        let snap = |position: f64| {
+            [PIP_MARGIN, 1.0 - PIP_MARGIN - self.size]
+                .iter()
+                .copied()
+                .find(|preset| (position - preset).abs() < PIP_SNAP_DISTANCE)
+                .unwrap_or(position)
+        };
+        PipSettings {
+            x: snap(self.x),
+            y: snap(self.y),
+            ..self
+        }
+    }
+}
+
+// Look of the lower third, each one is a CSS class of lower_third::STYLESHEET
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum LowerThirdStyle {
+    #[default]
+    Classic,
+    Modern,
+    Minimal,
+}
+
+impl From<Option<glib::GString>> for LowerThirdStyle {
+    fn from(s: Option<glib::GString>) -> Self {
+        match s.as_ref().map(|s| s.as_str()) {
+            Some("modern") => LowerThirdStyle::Modern,
+            Some("minimal") => LowerThirdStyle::Minimal,
+            _ => LowerThirdStyle::Classic,
+        }
+    }
+}
+
+impl LowerThirdStyle {
+    pub const ALL: &'static [LowerThirdStyle] = &[
+        LowerThirdStyle::Classic,
+        LowerThirdStyle::Modern,
+        LowerThirdStyle::Minimal,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            LowerThirdStyle::Classic => "classic",
+            LowerThirdStyle::Modern => "modern",
+            LowerThirdStyle::Minimal => "minimal",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LowerThirdStyle::Classic => "Classic bar",
+            LowerThirdStyle::Modern => "Modern accent",
+            LowerThirdStyle::Minimal => "Minimal text",
+        }
+    }
+}
+
+// Last contents of the lower third editor
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct LowerThirdSettings {
+    pub name: std::string::String,
+    pub title: std::string::String,
+    pub style: LowerThirdStyle,
+    // Seconds the lower third stays on screen, 0 to keep it until it is hidden
+    pub duration: u32,
+}
+
+impl Default for LowerThirdSettings {
+    fn default() -> Self {
+        LowerThirdSettings {
+            name: std::string::String::new(),
+            title: std::string::String::new(),
+            style: LowerThirdStyle::default(),
+            duration: 8,
+        }
+    }
+}
+
+// Corner of the overlay a logo is shown in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum LogoPosition {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl From<Option<glib::GString>> for LogoPosition {
+    fn from(s: Option<glib::GString>) -> Self {
+        match s.as_ref().map(|s| s.as_str()) {
+            Some("top-left") => LogoPosition::TopLeft,
+            Some("bottom-left") => LogoPosition::BottomLeft,
+            Some("bottom-right") => LogoPosition::BottomRight,
+            _ => LogoPosition::TopRight,
+        }
+    }
+}
+
+impl LogoPosition {
+    pub const ALL: &'static [LogoPosition] = &[
+        LogoPosition::TopLeft,
+        LogoPosition::TopRight,
+        LogoPosition::BottomLeft,
+        LogoPosition::BottomRight,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            LogoPosition::TopLeft => "top-left",
+            LogoPosition::TopRight => "top-right",
+            LogoPosition::BottomLeft => "bottom-left",
+            LogoPosition::BottomRight => "bottom-right",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LogoPosition::TopLeft => "Top left",
+            LogoPosition::TopRight => "Top right",
+            LogoPosition::BottomLeft => "Bottom left",
+            LogoPosition::BottomRight => "Bottom right",
+        }
+    }
+}
+
+// Image shown on top of the overlay markup, e.g. a watermark
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct Logo {
+    pub path: Option<std::string::String>,
+    pub position: LogoPosition,
+    // Width in percent of the overlay width
+    pub size: u32,
+}
+
+impl Default for Logo {
+    fn default() -> Self {
+        Logo {
+            path: None,
+            position: LogoPosition::default(),
+            size: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ChatProvider {
+    #[default]
+    Twitch,
+    YouTube,
+}
+
+impl From<Option<glib::GString>> for ChatProvider {
+    fn from(s: Option<glib::GString>) -> Self {
+        match s.as_ref().map(|s| s.as_str()) {
+            Some("youtube") => ChatProvider::YouTube,
+            _ => ChatProvider::Twitch,
+        }
+    }
+}
+
+impl ChatProvider {
+    fn id(self) -> &'static str {
+        match self {
+            ChatProvider::Twitch => "twitch",
+            ChatProvider::YouTube => "youtube",
+        }
+    }
+}
+
+// Live chat shown next to the program and optionally in the overlay
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct ChatSettings {
+    pub enabled: bool,
+    pub provider: ChatProvider,
+    // Joined anonymously, so only reading the chat is possible
+    pub twitch_channel: Option<std::string::String>,
+    // Kept in the keyring instead of the settings file, filled in when loading the settings
+    #[serde(skip)]
+    pub youtube_api_key: Option<std::string::String>,
+    // liveChatId of the broadcast, see the liveBroadcasts API
+    pub youtube_live_chat_id: Option<std::string::String>,
+    pub show_in_overlay: bool,
+}
+
+impl Default for ChatSettings {
+    fn default() -> Self {
+        ChatSettings {
+            enabled: false,
+            provider: ChatProvider::default(),
+            twitch_channel: None,
+            youtube_api_key: None,
+            youtube_live_chat_id: None,
+            show_in_overlay: true,
+        }
+    }
+}
+
+// Kind of MIDI message a control of a controller sends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MidiControlKind {
+    Note,
+    Controller,
+}
+
+// Button, fader or knob of a MIDI controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MidiControl {
+    pub kind: MidiControlKind,
+    // 0-based, shown 1-based as in the manuals of the controllers
+    pub channel: u8,
+    // Note or controller number
+    pub number: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MidiMapping {
+    pub control: MidiControl,
+    // Id of what the control drives, see midi::targets()
+    pub target: std::string::String,
+}
+
+// MIDI controller driving actions and audio gains
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct MidiSettings {
+    // Raw MIDI device, e.g. /dev/snd/midiC1D0
+    pub device: Option<std::string::String>,
+    pub mappings: Vec<MidiMapping>,
+}
+
+// Last configuration of the countdown overlay widget
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct CountdownSettings {
+    // Shown above the remaining time, e.g. "Starting soon"
+    pub title: std::string::String,
+    // Count down to target_time instead of for duration seconds
+    pub until_time: bool,
+    pub duration: u32,
+    // Local time of day as HH:MM
+    pub target_time: std::string::String,
+}
+
+impl Default for CountdownSettings {
+    fn default() -> Self {
+        CountdownSettings {
+            title: "Starting soon".to_string(),
+            until_time: false,
+            duration: 300,
+            target_time: "20:00".to_string(),
+        }
+    }
+}
+
+// User-defined value available as {name} in the overlay HTML
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct OverlayVariable {
+    pub name: std::string::String,
+    pub value: std::string::String,
+}
+
+// Network camera used instead of a local device
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct RtspCameraSettings {
+    pub enabled: bool,
+    // e.g. rtsp://192.168.1.20:554/stream1
+    pub url: Option<std::string::String>,
+    // Jitterbuffer latency in milliseconds
+    pub latency: u32,
+    pub username: Option<std::string::String>,
+    // Kept in the keyring instead of the settings file, filled in when loading the settings
+    #[serde(skip)]
+    pub password: Option<std::string::String>,
+}
+
+impl Default for RtspCameraSettings {
+    fn default() -> Self {
+        RtspCameraSettings {
+            enabled: false,
+            url: None,
+            latency: 200,
+            username: None,
+            password: None,
+        }
+    }
+}
+
+// SRT ingest, sent as MPEG-TS
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct SrtSettings {
+    // e.g. srt://ingest.example.com:9000?streamid=key, caller mode unless set in the URI
+    pub uri: Option<std::string::String>,
+    // Receiver latency in milliseconds
+    pub latency: u32,
+    // Kept in the keyring instead of the settings file, filled in when loading the settings
+    #[serde(skip)]
+    pub passphrase: Option<std::string::String>,
+}
+
+impl Default for SrtSettings {
+    fn default() -> Self {
+        SrtSettings {
+            uri: None,
+            latency: 125,
+            passphrase: None,
+        }
+    }
+}
+
+// WebRTC publishing to a WHIP endpoint of an SFU
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct WhipSettings {
+    pub endpoint: Option<std::string::String>,
+    // Sent as bearer token with the offer. Kept in the keyring instead of the settings file,
+    // filled in when loading the settings
+    #[serde(skip)]
+    pub auth_token: Option<std::string::String>,
+}
+
+// Lower quality copy of the program for a simulcast ladder, sent to its own RTMP end-point
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Rendition {
+    pub resolution: VideoResolution,
+    // In kbit/s, only used with an encoder family
+    pub video_bitrate: u32,
+    // Not sent without
+    pub location: Option<std::string::String>,
+}
+
+impl Rendition {
+    fn new(width: i32, height: i32, video_bitrate: u32) -> Self {
+        Rendition {
+            resolution: VideoResolution::new(width, height),
+            video_bitrate,
+            location: None,
+        }
+    }
+}
+
+// Renditions encoded from the program next to the stream, each one with its own encoder
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct SimulcastSettings {
+    pub enabled: bool,
+    pub renditions: Vec<Rendition>,
+}
+
+impl SimulcastSettings {
+    pub const MAX_RENDITIONS: usize = 3;
+}
+
+impl Default for SimulcastSettings {
+    fn default() -> Self {
+        SimulcastSettings {
+            enabled: false,
+            renditions: vec![
+                Rendition::new(1280, 720, 3000),
+                Rendition::new(854, 480, 1200),
+                Rendition::new(640, 360, 800),
+            ],
+        }
+    }
+}
+
+// RTP contribution output, RTCP is sent to the port above each RTP port
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct RtpSettings {
+    pub host: std::string::String,
+    pub video_port: u16,
+    pub audio_port: u16,
+    // Where to write an SDP file describing the streams for the receiver, if anywhere
+    pub sdp_file: Option<std::string::String>,
+}
+
+impl Default for RtpSettings {
+    fn default() -> Self {
+        RtpSettings {
+            host: "127.0.0.1".to_string(),
+            video_port: 5000,
+            audio_port: 5002,
+            sdp_file: None,
+        }
+    }
+}
+
+// Which audio inputs are mixed down into outputs that carry a single track, like RTMP
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum AudioMix {
+    #[default]
+    Primary,
+    Secondary,
+    Both,
+}
+
+impl From<Option<glib::GString>> for AudioMix {
+    fn from(s: Option<glib::GString>) -> Self {
+        match s.as_ref().map(|s| s.as_str()) {
+            Some("secondary") => AudioMix::Secondary,
+            Some("both") => AudioMix::Both,
+            _ => AudioMix::Primary,
+        }
+    }
+}
+
+impl AudioMix {
+    fn id(self) -> &'static str {
+        match self {
+            AudioMix::Primary => "primary",
+            AudioMix::Secondary => "secondary",
+            AudioMix::Both => "both",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum MeterOrientation {
+    // Next to the preview
+    #[default]
+    Vertical,
+    // In a bar below the preview
+    Horizontal,
+}
+
+impl From<Option<glib::GString>> for MeterOrientation {
+    fn from(s: Option<glib::GString>) -> Self {
+        match s.as_ref().map(|s| s.as_str()) {
+            Some("horizontal") => MeterOrientation::Horizontal,
+            _ => MeterOrientation::Vertical,
+        }
+    }
+}
+
+impl MeterOrientation {
+    fn id(self) -> &'static str {
+        match self {
+            MeterOrientation::Vertical => "vertical",
+            MeterOrientation::Horizontal => "horizontal",
+        }
+    }
+}
+
+// Layout and scale of the audio level meter
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct VuMeterSettings {
+    pub orientation: MeterOrientation,
+    // Lowest level shown in dBFS, the scale always ends at 0
+    pub min_db: i32,
+    // Levels labeled on the scale in dBFS
+    pub ticks: Vec<i32>,
+}
+
+impl Default for VuMeterSettings {
+    fn default() -> Self {
+        VuMeterSettings {
+            orientation: MeterOrientation::Vertical,
+            min_db: -60,
+            ticks: vec![-40, -20, -10, -5, -4, -3, -2, -1],
+        }
+    }
+}
+
+// Local playback of the program audio for the operator
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct MonitorSettings {
+    // Display name of the output device, None for the default one
+    pub device: Option<std::string::String>,
+    // Linear gain, 1.0 plays the program as it is
+    pub volume: f64,
+    // Milliseconds the audio is held back so it plays in sync with the preview
+    pub delay: u32,
+    // Hold the audio back by the measured latency of the preview instead, when known
+    pub match_preview: bool,
+}
+
+impl Default for MonitorSettings {
+    fn default() -> Self {
+        MonitorSettings {
+            device: None,
+            volume: 1.0,
+            delay: 0,
+            match_preview: true,
+        }
+    }
+}
+
+// What is shown where neither the camera nor the overlay cover the mixer output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BackgroundKind {
+    Black,
+    Checker,
+    Color,
+    Image,
+}
+
+impl From<Option<glib::GString>> for BackgroundKind {
+    fn from(s: Option<glib::GString>) -> Self {
+        match s.as_ref().map(|s| s.as_str()) {
+            Some("checker") => BackgroundKind::Checker,
+            Some("color") => BackgroundKind::Color,
+            Some("image") => BackgroundKind::Image,
+            _ => BackgroundKind::Black,
+        }
+    }
+}
+
+impl BackgroundKind {
+    fn id(self) -> &'static str {
+        match self {
+            BackgroundKind::Black => "black",
+            BackgroundKind::Checker => "checker",
+            BackgroundKind::Color => "color",
+            BackgroundKind::Image => "image",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct BackgroundSettings {
+    pub kind: BackgroundKind,
+    // As #rrggbb
+    pub color: std::string::String,
+    pub image: Option<std::string::String>,
+}
+
+impl Default for BackgroundSettings {
+    fn default() -> Self {
+        BackgroundSettings {
+            kind: BackgroundKind::Black,
+            color: "#000000".to_string(),
+            image: None,
+        }
+    }
+}
+
+impl BackgroundSettings {
+    // The background color as 0xRRGGBB
+    pub fn rgb(&self) -> u32 {
+        hex_to_rgb(&self.color)
+    }
+}
+
+// Keying out a colored backdrop behind the presenter, so the layers below the camera show through
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct ChromaKeySettings {
+    pub enabled: bool,
+    // As #rrggbb
+    pub color: std::string::String,
+    // Distance in chroma to the key color below which the camera is transparent, from 0 to 1
+    pub threshold: f64,
+    // Width of the soft edge above the threshold
+    pub smoothing: f64,
+}
+
+impl Default for ChromaKeySettings {
+    fn default() -> Self {
+        ChromaKeySettings {
+            enabled: false,
+            color: "#00ff00".to_string(),
+            threshold: 0.15,
+            smoothing: 0.05,
+        }
+    }
+}
+
+impl ChromaKeySettings {
+    // The key color as 0xRRGGBB
+    pub fn rgb(&self) -> u32 {
+        hex_to_rgb(&self.color)
+    }
+}
+
+// Opacity and cropping of a mixer layer, on top of the placement by the scene
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct LayerSettings {
+    // From 0 to 1, multiplied with the alpha of the scene
+    pub opacity: f64,
+    // Parts cut off at each edge as fractions of the layer size
+    pub crop_left: f64,
+    pub crop_top: f64,
+    pub crop_right: f64,
+    pub crop_bottom: f64,
+}
+
+impl Default for LayerSettings {
+    fn default() -> Self {
+        LayerSettings {
+            opacity: 1.0,
+            crop_left: 0.0,
+            crop_top: 0.0,
+            crop_right: 0.0,
+            crop_bottom: 0.0,
+        }
+    }
+}
+
+// Something of the layer is always left over
+const MAX_CROP: f64 = 0.45;
+
+impl LayerSettings {
+    // Left, top, right and bottom crop, each limited so the layer never vanishes
+    pub fn crop(&self) -> (f64, f64, f64, f64) {
+        let limit = |crop: f64| crop.clamp(0.0, MAX_CROP);
+        (
+            limit(self.crop_left),
+            limit(self.crop_top),
+            limit(self.crop_right),
+            limit(self.crop_bottom),
+        )
+    }
+}
+
+fn hex_to_rgb(color: &str) -> u32 {
+    u32::from_str_radix(color.trim_start_matches('#'), 16).unwrap_or(0)
+}
+
+fn rgba_to_hex(rgba: &gdk::RGBA) -> std::string::String {
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        (rgba.red * 255.0) as u8,
+        (rgba.green * 255.0) as u8,
+        (rgba.blue * 255.0) as u8
+    )
+}
+
+fn hex_to_rgba(rgb: u32) -> gdk::RGBA {
+    gdk::RGBA {
+        red: f64::from((rgb >> 16) & 0xff) / 255.0,
+        green: f64::from((rgb >> 8) & 0xff) / 255.0,
+        blue: f64::from(rgb & 0xff) / 255.0,
+        alpha: 1.0,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum CameraRotation {
+    #[default]
+    None,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+}
+
+impl From<Option<glib::GString>> for CameraRotation {
+    fn from(s: Option<glib::GString>) -> Self {
+        match s.as_ref().map(|s| s.as_str()) {
+            Some("90") => CameraRotation::Rotate90,
+            Some("180") => CameraRotation::Rotate180,
+            Some("270") => CameraRotation::Rotate270,
+            _ => CameraRotation::None,
+        }
+    }
+}
+
+impl CameraRotation {
+    fn id(self) -> &'static str {
+        match self {
+            CameraRotation::None => "0",
+            CameraRotation::Rotate90 => "90",
+            CameraRotation::Rotate180 => "180",
+            CameraRotation::Rotate270 => "270",
+        }
+    }
+}
+
+// How a camera is mounted, rotations are clockwise and mirroring is applied afterwards
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct CameraOrientation {
+    pub rotation: CameraRotation,
+    pub mirror: bool,
+}
+
+impl CameraOrientation {
+    // Nick of the matching GstVideoOrientationMethod, as used by glvideoflip
+    pub fn video_direction(self) -> &'static str {
+        match (self.rotation, self.mirror) {
+            (CameraRotation::None, false) => "identity",
+            (CameraRotation::Rotate90, false) => "90r",
+            (CameraRotation::Rotate180, false) => "180",
+            (CameraRotation::Rotate270, false) => "90l",
+            (CameraRotation::None, true) => "horiz",
+            (CameraRotation::Rotate90, true) => "ul-lr",
+            (CameraRotation::Rotate180, true) => "vert",
+            (CameraRotation::Rotate270, true) => "ur-ll",
+        }
+    }
+
+    // Whether width and height of the image get swapped
+    pub fn is_sideways(self) -> bool {
+        self.rotation == CameraRotation::Rotate90 || self.rotation == CameraRotation::Rotate270
+    }
+}
+
+fn default_disk_warning_threshold() -> u32 {
+    5 * 1024
+}
+
+fn default_disk_stop_threshold() -> u32 {
+    512
+}
+
+// Unattended rotation through a set of overlay templates and scenes
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct RotationSettings {
+    pub enabled: bool,
+    // Seconds each step stays on air
+    pub interval: u32,
+    // Names of the templates from the library, in rotation order
+    pub templates: Vec<std::string::String>,
+    // Names of the scenes, switched in step with the templates
+    pub scenes: Vec<std::string::String>,
+}
+
+// Going live and off air automatically at set times
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct ScheduleSettings {
+    pub enabled: bool,
+    // Local time as YYYY-MM-DD HH:MM
+    pub start: std::string::String,
+    // Same format, None to keep streaming until stopped by hand
+    pub end: Option<std::string::String>,
+    // Minutes the countdown runs in the overlay before the start, 0 for none
+    pub countdown: u32,
+}
+
+impl Default for ScheduleSettings {
+    fn default() -> Self {
+        ScheduleSettings {
+            enabled: false,
+            start: std::string::String::new(),
+            end: None,
+            countdown: 5,
+        }
+    }
+}
+
+impl Default for RotationSettings {
+    fn default() -> Self {
+        RotationSettings {
+            enabled: false,
+            interval: 30,
+            templates: Vec::new(),
+            scenes: Vec::new(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_scene_transition() -> u32 {
+    300
+}
+
+fn default_camera_framerate() -> u32 {
+    30
+}
+
+fn default_video_framerate() -> u32 {
+    30
+}
+
+fn default_audio_bitrate() -> u32 {
+    128
+}
+
+fn default_ticker_items() -> Vec<std::string::String> {
+    vec![
+        "Audio support coming soon to GstWPE!".to_string(),
+        "GStreamer 1.16.1 has been released!".to_string(),
+        "This demo was show-cased at the GStreamer conference".to_string(),
+        "and at ELC-E! Both took place in Lyon, France".to_string(),
+    ]
+}
+
+fn default_overlay_bridge_port() -> u16 {
+    9101
+}
+
+// The second monitor, the first one usually shows the main window
+fn default_projector_monitor() -> i32 {
+    1
+}
+
+fn default_video_bitrate() -> u32 {
+    4000
+}
+
+fn default_keyframe_interval() -> u32 {
+    60
+}
+
+fn default_video_profile() -> Option<std::string::String> {
+    Some("main".to_string())
+}
+
+fn default_ui_scale() -> f64 {
+    1.0
+}
+
+// Values read from a settings file on their way to the current version
+struct MigratingSettings {
+    values: serde_json::Map<std::string::String, serde_json::Value>,
+    // The values as read, kept as backup of the file without the secrets moved into the keyring
+    backup: serde_json::Map<std::string::String, serde_json::Value>,
+    // What the user has to know about, e.g. secrets that could not be moved
+    problems: Vec<std::string::String>,
+}
+
+impl MigratingSettings {
+    // Store a secret found in the stored values in the keyring of the active profile, telling the
+    // user if that failed
+    fn store_secret(&mut self, secret: Secret, value: &str) -> bool {
+        match keyring::store(&profiles::active(), secret, Some(value)) {
+            Ok(()) => true,
+            Err(err) => {
+                self.problems.push(format!(
+                    "The {} could not be moved into the keyring: {}",
+                    secret.label(),
+                    err
+                ));
+                false
+            }
+        }
+    }
+
+    // Replace a value that contained a secret in the stored values and the backup
+    fn replace(&mut self, name: &str, value: serde_json::Value) {
+        self.values.insert(name.to_string(), value.clone());
+        self.backup.insert(name.to_string(), value);
+    }
+
+    // Move the secret stored under the given name of a table, or of the settings themselves,
+    // into the keyring and out of the stored values and the backup
+    fn move_secret(&mut self, secret: Secret, table: Option<&str>, name: &str) {
+        let value = table_mut(&mut self.values, table)
+            .and_then(|values| values.get(name))
+            .and_then(|value| value.as_str())
+            .filter(|value| !value.is_empty())
+            .map(|value| value.to_string());
+        if let Some(value) = value {
+            if self.store_secret(secret, &value) {
+                for values in &mut [&mut self.values, &mut self.backup] {
+                    if let Some(values) = table_mut(values, table) {
+                        values.remove(name);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// The values of the table with the given name, or all values without name
+fn table_mut<'a>(
+    values: &'a mut serde_json::Map<std::string::String, serde_json::Value>,
+    table: Option<&str>,
+) -> Option<&'a mut serde_json::Map<std::string::String, serde_json::Value>> {
+    match table {
+        Some(table) => values.get_mut(table)?.as_object_mut(),
+        None => Some(values),
+    }
+}
+
+// Step bringing the stored values of one settings version to the next one
+type Migration = fn(&mut MigratingSettings);
+
+// Migrations indexed by the version they start from. Append a step whenever stored values have
+// to be renamed or converted, plain new fields only need a serde default
+const MIGRATIONS: &[Migration] = &[
+    // Files from before the settings were versioned, the format itself did not change
+    |_| (),
+    // The secrets moved into the keyring, the stream keys from the end of the RTMP URLs. They stay
+    // where they were if there is no keyring. The stream keys of the further RTMP end-points are
+    // matched to them by their order, so they only move if all of them have one
+    |migrating| {
+        let location = migrating
+            .values
+            .get("rtmp_location")
+            .and_then(|location| location.as_str())
+            .map(|location| location.to_string());
+        if let Some((server, key)) = location.as_deref().and_then(split_stream_key) {
+            if migrating.store_secret(Secret::StreamKey, &key) {
+                migrating.replace("rtmp_location", server.into());
+            }
+        }
+
+        migrating.move_secret(Secret::RtspPassword, Some("rtsp_camera"), "password");
+        migrating.move_secret(Secret::SrtPassphrase, Some("srt"), "passphrase");
+        migrating.move_secret(Secret::WhipToken, Some("whip"), "auth_token");
+        migrating.move_secret(Secret::YouTubeApiKey, Some("chat"), "youtube_api_key");
+        migrating.move_secret(Secret::RemoteToken, None, "remote_token");
+
+        let locations = migrating
+            .values
+            .get("extra_rtmp_locations")
+            .and_then(|locations| locations.as_array())
+            .and_then(|locations| {
+                locations
+                    .iter()
+                    .map(|location| location.as_str().and_then(split_stream_key))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .filter(|locations| !locations.is_empty());
+        if let Some(locations) = locations {
+            let (servers, keys): (Vec<_>, Vec<_>) = locations.into_iter().unzip();
+            if migrating.store_secret(Secret::ExtraStreamKeys, &keys.join(" ")) {
+                migrating.replace("extra_rtmp_locations", servers.into());
+            }
+        }
+    },
+    // Resolutions were one of three presets stored by name, now they are any width and height
+    |migrating| {
+        let values = &mut migrating.values;
+        for name in &["video_resolution", "output_resolution"] {
+            let preset = match values.get(*name).and_then(|value| value.as_str()) {
+                Some(preset) => preset.to_string(),
+                None => continue,
+            };
+            let (width, height) = match preset.as_str() {
+                "V480P" => (640, 480),
+                "V1080P" => (1920, 1080),
+                _ => (1280, 720),
+            };
+            values.insert(
+                name.to_string(),
+                serde_json::json!({ "width": width, "height": height }),
+            );
+        }
+    },
+];
+
+// Split an RTMP URL into the server URL with the application and the stream key, which is the
+// last part of the path if there are at least two. librtmp options stay with the server URL
+fn split_stream_key(location: &str) -> Option<(std::string::String, std::string::String)> {
+    let (url, options) = location
+        .trim()
+        .split_once(char::is_whitespace)
+        .unwrap_or((location.trim(), ""));
+    let host_start = url.find("://")? + 3;
+    let path_start = host_start + url[host_start..].find('/')?;
+    let (application, key) = url[path_start + 1..]
+        .trim_end_matches('/')
+        .rsplit_once('/')?;
+    if application.is_empty() || key.is_empty() {
+        return None;
+    }
+
+    let server = format!("{}/{}", &url[..path_start], application);
+    let server = if options.is_empty() {
+        server
+    } else {
+        format!("{} {}", server, options.trim())
+    };
+    Some((server, key.to_string()))
+}
+
+// Append the stream key to the server URL of an RTMP location, in front of the librtmp options
+fn with_stream_key(location: &str, key: Option<&str>) -> std::string::String {
+    let location = location.trim();
+    let key = match key {
+        Some(key) => key,
+        None => return location.to_string(),
+    };
+
+    let (url, options) = location
+        .split_once(char::is_whitespace)
+        .unwrap_or((location, ""));
+    let url = format!("{}/{}", url.trim_end_matches('/'), key);
+    if options.is_empty() {
+        url
+    } else {
+        format!("{} {}", url, options.trim())
+    }
+}
+
+// Version of the settings written by this build
+pub const SETTINGS_VERSION: u32 = MIGRATIONS.len() as u32;
+
+// Settings read from a file, possibly from an older version
+pub struct StoredSettings {
+    pub settings: Settings,
+    // Whether the file has to be written again to be in the current format
+    pub migrated: bool,
+    // Stored values that could not be used and were reset to their defaults
+    pub reset: Vec<std::string::String>,
+    // What else went wrong while migrating
+    pub problems: Vec<std::string::String>,
+    // The values as read to keep as backup when migrating, without the secrets that are in the
+    // keyring now
+    pub backup: serde_json::Value,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Settings {
+    // Format version of the stored settings, files without one are from version 0
+    #[serde(default)]
+    pub version: u32,
+    // RTMP server URL including the application, without the stream key
+    pub rtmp_location: Option<std::string::String>,
+    // Kept in the keyring instead of the settings file, filled in when loading the settings
+    #[serde(skip)]
+    pub rtmp_stream_key: Option<std::string::String>,
+    // Login for servers requiring authentication, the password is kept in the keyring
+    #[serde(default)]
+    pub rtmp_username: Option<std::string::String>,
+    #[serde(skip)]
+    pub rtmp_password: Option<std::string::String>,
+    pub h264_encoder: std::string::String,
+    // Frames per second of the program and all outputs
+    #[serde(default = "default_video_framerate")]
+    pub video_framerate: u32,
+    // Factory name of the selected encoder family, encoders::AUTO for the best available one or
+    // None for the custom h264_encoder chain
+    #[serde(default)]
+    pub encoder: Option<std::string::String>,
+    // In kbit/s, only used with an encoder family
+    #[serde(default = "default_video_bitrate")]
+    pub video_bitrate: u32,
+    // Frames between two keyframes, also set on known encoders of the custom chain that don't set
+    // it themselves
+    #[serde(default = "default_keyframe_interval")]
+    pub keyframe_interval: u32,
+    // Profile of the encoded video, None to let the encoder decide
+    #[serde(default = "default_video_profile")]
+    pub video_profile: Option<std::string::String>,
+    // Seconds the stream is held back before being sent, 0 to disable
+    #[serde(default)]
+    pub broadcast_delay: u32,
+    // Display name of the camera, None for the default device of the camera backend
+    #[serde(default)]
+    pub camera_device: Option<std::string::String>,
+    #[serde(default)]
+    pub camera_backend: CameraBackend,
+    // Preferred camera format and framerate, others are used if the camera can't deliver these
+    #[serde(default)]
+    pub camera_format: CameraFormat,
+    #[serde(default = "default_camera_framerate")]
+    pub camera_framerate: u32,
+    #[serde(default)]
+    pub camera_decoding: CameraDecoding,
+    #[serde(default)]
+    pub audio_codec: AudioCodec,
+    // In kbit/s
+    #[serde(default = "default_audio_bitrate")]
+    pub audio_bitrate: u32,
+    // Display name of the microphone, None for the automatically selected one
+    #[serde(default)]
+    pub audio_device: Option<std::string::String>,
+    // Display name of the second language or commentary input, None if not used
+    #[serde(default)]
+    pub secondary_audio_device: Option<std::string::String>,
+    // Mix the audio played on this machine into the program
+    #[serde(default)]
+    pub desktop_audio: bool,
+    #[serde(default)]
+    pub rtmp_audio_mix: AudioMix,
+    // Milliseconds the audio is delayed against the video, negative values delay the camera
+    // instead
+    #[serde(default)]
+    pub av_offset: i32,
+    #[serde(default)]
+    pub output_mode: OutputMode,
+    // Also write the program to a file in the recordings folder, whatever the output
+    #[serde(default)]
+    pub local_recording: LocalRecording,
+    // Local address outgoing connections are made from, None to let the routing table decide
+    #[serde(default)]
+    pub bind_address: Option<std::string::String>,
+    // Proxy URL, e.g. socks5://host:1080 or http://host:3128
+    #[serde(default)]
+    pub proxy: Option<std::string::String>,
+    // Index of the remote overlay template gallery
+    #[serde(default)]
+    pub template_gallery_url: Option<std::string::String>,
+    // Load this web page as overlay instead of the edited markup, the URL is kept when switching
+    // back to the markup
+    #[serde(default)]
+    pub overlay_from_url: bool,
+    #[serde(default)]
+    pub overlay_url: Option<std::string::String>,
+    // Update the overlay shortly after the markup was edited, without pressing the update button
+    #[serde(default)]
+    pub overlay_live_update: bool,
+    // Embed the imported assets into the overlay instead of referring to their files
+    #[serde(default = "default_true")]
+    pub inline_assets: bool,
+    // Local port the overlay page posts its events to, 0 to disable
+    #[serde(default = "default_overlay_bridge_port")]
+    pub overlay_bridge_port: u16,
+    // Port of the remote control server for stream decks and companion software, 0 to disable
+    #[serde(default)]
+    pub remote_port: u16,
+    // Token remote clients have to present. Without one, only local clients are accepted. Kept in
+    // the keyring, filled in when loading the settings
+    #[serde(skip)]
+    pub remote_token: Option<std::string::String>,
+    // Port serving the program as HLS to browsers on the local network while live, 0 to disable
+    #[serde(default)]
+    pub lan_preview_port: u16,
+    // Overlay template loaded when switching to the profile of these settings
+    #[serde(default)]
+    pub profile_template: Option<std::string::String>,
+    // Free space in MiB on the recordings filesystem below which a warning is shown, and
+    // below which the recording is stopped before the disk fills up
+    #[serde(default = "default_disk_warning_threshold")]
+    pub disk_warning_threshold: u32,
+    #[serde(default = "default_disk_stop_threshold")]
+    pub disk_stop_threshold: u32,
+    // Scale factor applied to text and controls
+    #[serde(default = "default_ui_scale")]
+    pub ui_scale: f64,
+    #[serde(default)]
+    pub high_contrast: bool,
+    // Hide the debug HUD while live so it doesn't end up in the stream
+    #[serde(default = "default_true")]
+    pub debug_hud_preview_only: bool,
+    // None means the default directory, see utils::get_recordings_directory()
+    #[serde(default)]
+    pub recordings_directory: Option<std::string::String>,
+    // None means the default directory, see utils::get_snapshots_directory()
+    #[serde(default)]
+    pub snapshots_directory: Option<std::string::String>,
+    #[serde(default)]
+    pub stats_log_format: StatsLogFormat,
+    // None means the default directory, see utils::get_stats_directory()
+    #[serde(default)]
+    pub stats_directory: Option<std::string::String>,
+    // Milliseconds scene switches are animated for, 0 to cut
+    #[serde(default = "default_scene_transition")]
+    pub scene_transition: u32,
+    // Image shown instead of the program while the stream is paused, None for a plain slate
+    #[serde(default)]
+    pub slate_image: Option<std::string::String>,
+    // Further RTMP end-points the program is streamed to at the same time, e.g. a second platform,
+    // without their stream keys
+    #[serde(default)]
+    pub extra_rtmp_locations: Vec<std::string::String>,
+    // Stream keys of the further end-points in the same order, kept in the keyring
+    #[serde(skip)]
+    pub extra_rtmp_stream_keys: Vec<std::string::String>,
+    // Scrolling through the ticker of the overlay, in this order
+    #[serde(default = "default_ticker_items")]
+    pub ticker_items: Vec<std::string::String>,
+    // Sections of nested settings come after all plain values, TOML can't have values after them
+    pub video_resolution: VideoResolution,
+    // Resolution the outputs are encoded at, None for the one of the mix
+    #[serde(default)]
+    pub output_resolution: Option<VideoResolution>,
+    #[serde(default)]
+    pub vu_meter: VuMeterSettings,
+    #[serde(default)]
+    pub monitor: MonitorSettings,
+    #[serde(default)]
+    pub audio_filters: AudioFilterSettings,
+    // Shown in the corners of the overlay, whatever the markup is
+    #[serde(default)]
+    pub logos: Vec<Logo>,
+    #[serde(default)]
+    pub encoder_options: HashMap<std::string::String, std::string::String>,
+    #[serde(default)]
+    pub rtp: RtpSettings,
+    #[serde(default)]
+    pub srt: SrtSettings,
+    #[serde(default)]
+    pub whip: WhipSettings,
+    #[serde(default)]
+    pub simulcast: SimulcastSettings,
+    #[serde(default)]
+    pub screen: ScreenSettings,
+    #[serde(default)]
+    pub pip: PipSettings,
+    #[serde(default)]
+    pub rtsp_camera: RtspCameraSettings,
+    #[serde(default)]
+    pub upload: UploadSettings,
+    #[serde(default)]
+    pub checklist: ChecklistSettings,
+    #[serde(default)]
+    pub background: BackgroundSettings,
+    #[serde(default)]
+    pub chroma_key: ChromaKeySettings,
+    #[serde(default)]
+    pub camera_layer: LayerSettings,
+    #[serde(default)]
+    pub overlay_layer: LayerSettings,
+    // Keyed by the input names in audio_mixer
+    #[serde(default)]
+    pub audio_inputs: HashMap<std::string::String, AudioInputSettings>,
+    // Keyed by the device the camera is captured from
+    #[serde(default)]
+    pub camera_orientations: HashMap<std::string::String, CameraOrientation>,
+    // Devices of the cameras captured as raw DMABuf straight into the GL mixer
+    #[serde(default)]
+    pub zero_copy_cameras: Vec<std::string::String>,
+    // Capture test patterns and a tone instead of the camera and microphone, e.g. on machines
+    // without them
+    #[serde(default)]
+    pub test_sources: bool,
+    // Number of the monitor the projector is shown fullscreen on
+    #[serde(default = "default_projector_monitor")]
+    pub projector_monitor: i32,
+    #[serde(default)]
+    pub rotation: RotationSettings,
+    #[serde(default)]
+    pub schedule: ScheduleSettings,
+    #[serde(default)]
+    pub lower_third: LowerThirdSettings,
+    #[serde(default)]
+    pub countdown: CountdownSettings,
+    #[serde(default)]
+    pub chat: ChatSettings,
+    #[serde(default)]
+    pub midi: MidiSettings,
+    // In the order they are listed in the variables tab
+    #[serde(default)]
+    pub overlay_variables: Vec<OverlayVariable>,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            version: SETTINGS_VERSION,
+            rtmp_location: None,
+            rtmp_stream_key: None,
+            rtmp_username: None,
+            rtmp_password: None,
+            h264_encoder: "video/x-raw,format=NV12 ! vaapih264enc bitrate=20000 keyframe-period=60 ! video/x-h264,profile=main".to_string(),
+            video_framerate: default_video_framerate(),
+            encoder: Some(encoders::AUTO.to_string()),
+            video_bitrate: default_video_bitrate(),
+            keyframe_interval: default_keyframe_interval(),
+            video_profile: default_video_profile(),
+            broadcast_delay: 0,
+            audio_codec: AudioCodec::default(),
+            audio_bitrate: default_audio_bitrate(),
+            audio_device: None,
+            secondary_audio_device: None,
+            camera_device: None,
+            camera_backend: CameraBackend::default(),
+            camera_format: CameraFormat::default(),
+            camera_framerate: default_camera_framerate(),
+            camera_decoding: CameraDecoding::default(),
+            desktop_audio: false,
+            rtmp_audio_mix: AudioMix::default(),
+            av_offset: 0,
+            output_mode: OutputMode::default(),
+            local_recording: LocalRecording::default(),
+            bind_address: None,
+            proxy: None,
+            template_gallery_url: None,
+            overlay_from_url: false,
+            overlay_url: None,
+            overlay_live_update: false,
+            inline_assets: true,
+            overlay_bridge_port: default_overlay_bridge_port(),
+            remote_port: 0,
+            remote_token: None,
+            lan_preview_port: 0,
+            profile_template: None,
+            disk_warning_threshold: default_disk_warning_threshold(),
+            disk_stop_threshold: default_disk_stop_threshold(),
+            ui_scale: default_ui_scale(),
+            high_contrast: false,
+            debug_hud_preview_only: true,
+            recordings_directory: None,
+            snapshots_directory: None,
+            stats_log_format: StatsLogFormat::default(),
+            stats_directory: None,
+            extra_rtmp_locations: Vec::new(),
+            extra_rtmp_stream_keys: Vec::new(),
+            ticker_items: default_ticker_items(),
+            video_resolution: VideoResolution::default(),
+            output_resolution: None,
+            vu_meter: VuMeterSettings::default(),
+            monitor: MonitorSettings::default(),
+            audio_filters: AudioFilterSettings::default(),
+            logos: Vec::new(),
+            scene_transition: default_scene_transition(),
+            slate_image: None,
+            encoder_options: HashMap::new(),
+            rtp: RtpSettings::default(),
+            srt: SrtSettings::default(),
+            whip: WhipSettings::default(),
+            simulcast: SimulcastSettings::default(),
+            screen: ScreenSettings::default(),
+            pip: PipSettings::default(),
+            rtsp_camera: RtspCameraSettings::default(),
+            upload: UploadSettings::default(),
+            checklist: ChecklistSettings::default(),
+            background: BackgroundSettings::default(),
+            chroma_key: ChromaKeySettings::default(),
+            camera_layer: LayerSettings::default(),
+            overlay_layer: LayerSettings::default(),
+            audio_inputs: HashMap::new(),
+            camera_orientations: HashMap::new(),
+            zero_copy_cameras: Vec::new(),
+            test_sources: false,
+            projector_monitor: default_projector_monitor(),
+            rotation: RotationSettings::default(),
+            schedule: ScheduleSettings::default(),
+            lower_third: LowerThirdSettings::default(),
+            countdown: CountdownSettings::default(),
+            chat: ChatSettings::default(),
+            midi: MidiSettings::default(),
+            overlay_variables: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    // Migrate the values read from a settings file to the current version. Values that still
+    // can't be read afterwards, e.g. from hand-edited files or newer versions, are reset to their
+    // defaults instead of failing all settings
+    pub fn from_stored(stored: serde_json::Value) -> StoredSettings {
+        let values = match stored {
+            serde_json::Value::Object(values) => values,
+            _ => serde_json::Map::new(),
+        };
+        let mut migrating = MigratingSettings {
+            backup: values.clone(),
+            values,
+            problems: Vec::new(),
+        };
+
+        let version = migrating
+            .values
+            .get("version")
+            .and_then(|version| version.as_u64())
+            .unwrap_or(0) as usize;
+        for migration in MIGRATIONS.iter().skip(version) {
+            migration(&mut migrating);
+        }
+        let MigratingSettings {
+            mut values,
+            backup,
+            problems,
+        } = migrating;
+        let backup = serde_json::Value::Object(backup);
+        let migrated = version < MIGRATIONS.len();
+        if migrated {
+            values.insert("version".to_string(), SETTINGS_VERSION.into());
+        }
+
+        if let Ok(settings) = serde_json::from_value(serde_json::Value::Object(values.clone())) {
+            return StoredSettings {
+                settings,
+                migrated,
+                reset: Vec::new(),
+                problems,
+                backup,
+            };
+        }
+
+        // Take over the stored values one by one on top of the defaults, skipping those that
+        // make the settings unreadable
+        let mut merged = match serde_json::to_value(Settings::default()) {
+            Ok(serde_json::Value::Object(defaults)) => defaults,
+            _ => serde_json::Map::new(),
+        };
+        let mut reset = Vec::new();
+        for (name, value) in values {
+            let default = merged.insert(name.clone(), value);
+            if serde_json::from_value::<Settings>(serde_json::Value::Object(merged.clone()))
+                .is_err()
+            {
+                match default {
+                    Some(default) => merged.insert(name.clone(), default),
+                    None => merged.remove(&name),
+                };
+                reset.push(name);
+            }
+        }
+
+        StoredSettings {
+            settings: serde_json::from_value(serde_json::Value::Object(merged)).unwrap_or_default(),
+            migrated: true,
+            reset,
+            problems,
+            backup,
+        }
+    }
+
+    // The RTMP URL to stream to, the server URL with the stream key appended in front of the
+    // librtmp options
+    pub fn rtmp_url(&self) -> Option<std::string::String> {
+        let location = self.rtmp_location.as_deref()?;
+        Some(with_stream_key(location, self.rtmp_stream_key.as_deref()))
+    }
+
+    // The URLs of the further RTMP end-points, with the stream key of the same position
+    pub fn extra_rtmp_urls(&self) -> Vec<std::string::String> {
+        self.extra_rtmp_locations
+            .iter()
+            .enumerate()
+            .map(|(i, location)| {
+                with_stream_key(
+                    location,
+                    self.extra_rtmp_stream_keys.get(i).map(|key| key.as_str()),
+                )
+            })
+            .collect()
+    }
+
+    // The selected encoder family, None for the custom chain or if no encoder was found for
+    // the automatic selection
+    pub fn encoder_family(&self) -> Option<&'static encoders::EncoderFamily> {
+        match self.encoder.as_deref() {
+            Some(encoders::AUTO) => encoders::best_family(),
+            Some(factory) => encoders::find_family(factory),
+            None => None,
+        }
+    }
+
+    // Launch fragment of the configured video encoder, either generated from the selected
+    // encoder family or the custom chain
+    pub fn video_encoder_description(&self) -> std::string::String {
+        match self.encoder_family() {
+            Some(family) => family.launch_description(
+                self.video_bitrate,
+                self.keyframe_interval,
+                self.video_profile.as_deref(),
+                &self.encoder_options,
+            ),
+            None => encoders::with_keyframe_interval(&self.h264_encoder, self.keyframe_interval),
+        }
+    }
+
+    // Renditions of the simulcast ladder that are sent when going live
+    pub fn active_renditions(&self) -> Vec<&Rendition> {
+        if !self.simulcast.enabled {
+            return Vec::new();
+        }
+        self.simulcast
+            .renditions
+            .iter()
+            .filter(|rendition| rendition.location.is_some())
+            .collect()
+    }
+
+    // The settings the outputs of the rendition are created with
+    pub fn for_rendition(&self, rendition: &Rendition) -> Settings {
+        Settings {
+            output_resolution: Some(rendition.resolution),
+            video_bitrate: rendition.video_bitrate,
+            ..self.clone()
+        }
+    }
+
+    // Launch fragment scaling the mix to the output resolution in front of the download from GL
+    // memory, empty if both are the same
+    pub fn output_scale_description(&self) -> std::string::String {
+        match self.output_resolution {
+            Some(ref resolution) if *resolution != self.video_resolution => {
+                let (width, height) = resolution.size();
+                format!(
+                    "glcolorscale ! video/x-raw(memory:GLMemory),width={},height={} ! ",
+                    width, height
+                )
+            }
+            _ => std::string::String::new(),
+        }
+    }
+
+    // The custom chain is expected to produce H.264
+    pub fn video_codec(&self) -> encoders::VideoCodec {
+        self.encoder_family()
+            .map(|family| family.codec)
+            .unwrap_or(encoders::VideoCodec::H264)
+    }
+
+    pub fn audio_encoder_description(&self) -> std::string::String {
+        self.audio_codec.launch_description(self.audio_bitrate)
+    }
+
+    // The web page loaded as overlay, None if the edited markup is used
+    pub fn active_overlay_url(&self) -> Option<&str> {
+        self.overlay_url
+            .as_deref()
+            .filter(|url| self.overlay_from_url && !url.is_empty())
+    }
+
+    // Address of the configured proxy if it's a SOCKS proxy, the only kind librtmp supports
+    pub fn rtmp_socks_proxy(&self) -> Option<&str> {
+        self.proxy.as_ref().and_then(|proxy| {
+            let mut parts = proxy.splitn(2, "://");
+            match (parts.next(), parts.next()) {
+                (Some(scheme), Some(address)) if scheme.starts_with("socks") => {
+                    Some(address.trim_end_matches('/'))
+                }
+                _ => None,
+            }
+        })
+    }
+
+    // The given RTMP URL with the librtmp options for the configured proxy. librtmp only supports
+    // SOCKS proxies, so others are not used for RTMP
+    pub fn rtmp_location_with_options(&self, location: &str) -> std::string::String {
+        match self.rtmp_socks_proxy() {
+            Some(address) => format!("{} socks={}", location, address),
+            None => location.to_string(),
+        }
+    }
+
+    pub fn camera_orientation(&self, camera: &str) -> CameraOrientation {
+        self.camera_orientations
+            .get(camera)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+// Rate control of the selected encoder family, rebuilt whenever another family is selected
+struct EncoderControls {
+    bitrate: gtk::SpinButton,
+    keyframe_interval: gtk::SpinButton,
+    profile: gtk::ComboBoxText,
+    // The custom chain only takes the keyframe interval, it sets everything else itself
+    custom: bool,
+}
+
+// Our refcounted settings struct for containing all the widgets we have to carry around.
+//
+// This represents our settings dialog.
+#[derive(Clone)]
+struct SettingsDialog(Rc<SettingsDialogInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for SettingsDialog {
+    type Target = SettingsDialogInner;
+
+    fn deref(&self) -> &SettingsDialogInner {
+        &self.0
+    }
+}
+
+// Weak reference to our settings dialog struct
+//
+// Weak references are important to prevent reference cycles. Reference cycles are cases where
+// struct A references directly or indirectly struct B, and struct B references struct A again
+// while both are using reference counting.
+struct SettingsDialogWeak(Weak<SettingsDialogInner>);
+
+impl SettingsDialogWeak {
+    // Upgrade to a strong reference if it still exists
+    pub fn upgrade(&self) -> Option<SettingsDialog> {
+        self.0.upgrade().map(SettingsDialog)
+    }
+}
+
+struct SettingsDialogInner {
+    rtmp_location: gtk::Entry,
+    rtmp_stream_key: gtk::Entry,
+    rtmp_username: gtk::Entry,
+    rtmp_password: gtk::Entry,
+    h264_encoder: gtk::Entry,
+    video_resolution: gtk::ComboBoxText,
+    video_framerate: gtk::ComboBoxText,
+    output_resolution: gtk::ComboBoxText,
+    encoder: gtk::ComboBoxText,
+    encoder_options: gtk::Grid,
+    encoder_option_combos: RefCell<Vec<(&'static str, gtk::ComboBoxText)>>,
+    encoder_controls: RefCell<Option<EncoderControls>>,
+    recordings_directory: gtk::FileChooserButton,
+    snapshots_directory: gtk::FileChooserButton,
+    stats_log_format: gtk::ComboBoxText,
+    stats_directory: gtk::FileChooserButton,
+    broadcast_delay: gtk::SpinButton,
+    background_kind: gtk::ComboBoxText,
+    background_color: gtk::ColorButton,
+    background_image: gtk::FileChooserButton,
+    // Device of the camera the orientation settings apply to
+    camera: std::string::String,
+    camera_rotation: gtk::ComboBoxText,
+    camera_mirror: gtk::CheckButton,
+    camera_device: gtk::ComboBoxText,
+    camera_backend: gtk::ComboBoxText,
+    camera_format: gtk::ComboBoxText,
+    camera_framerate: gtk::ComboBoxText,
+    camera_decoding: gtk::ComboBoxText,
+    zero_copy: gtk::CheckButton,
+    test_sources: gtk::CheckButton,
+    projector_monitor: gtk::ComboBoxText,
+    audio_codec: gtk::ComboBoxText,
+    audio_bitrate: gtk::SpinButton,
+    overlay_bridge_port: gtk::SpinButton,
+    remote_port: gtk::SpinButton,
+    lan_preview_port: gtk::SpinButton,
+    remote_token: gtk::Entry,
+    profile_template: gtk::ComboBoxText,
+    output_mode: gtk::ComboBoxText,
+    local_recording: gtk::ComboBoxText,
+    extra_rtmp_locations: gtk::TextView,
+    extra_rtmp_stream_keys: gtk::Entry,
+    scene_transition: gtk::SpinButton,
+    slate_image: gtk::FileChooserButton,
+    bind_address: gtk::Entry,
+    proxy: gtk::Entry,
+    disk_warning_threshold: gtk::SpinButton,
+    disk_stop_threshold: gtk::SpinButton,
+    // Problems with the settings that would keep streaming from starting
+    problems: gtk::Label,
+    ui_scale: gtk::SpinButton,
+    high_contrast: gtk::CheckButton,
+    debug_hud_preview_only: gtk::CheckButton,
+    rtp_page: RtpPage,
+    srt_page: SrtPage,
+    whip_page: WhipPage,
+    simulcast_page: SimulcastPage,
+    rtsp_camera_page: RtspCameraPage,
+    screen_page: ScreenPage,
+    chroma_key_page: ChromaKeyPage,
+    audio_page: AudioPage,
+    audio_filters_page: AudioFiltersPage,
+    upload_page: UploadPage,
+    rotation_page: RotationPage,
+    schedule_page: SchedulePage,
+    checklist_page: ChecklistPage,
+    chat_page: ChatPage,
+    // Saves the MIDI settings itself
+    #[allow(dead_code)]
+    midi_page: MidiPage,
+}
+
+// Grid laid out like all pages of the settings dialog
+fn page_grid() -> gtk::Grid {
+    let grid = gtk::Grid::new();
+    grid.set_column_spacing(4);
+    grid.set_row_spacing(4);
+    grid.set_margin_bottom(12);
+    grid
+}
+
+// Mark the widget as having the problem described by the message, or clear the mark
+fn set_problem_hint<W: IsA<gtk::Widget>>(widget: &W, message: Option<&str>) {
+    let style_context = widget.get_style_context();
+    if message.is_some() {
+        style_context.add_class(&gtk::STYLE_CLASS_ERROR);
+    } else {
+        style_context.remove_class(&gtk::STYLE_CLASS_ERROR);
+    }
+
+    // Entries also get an icon explaining the problem
+    if let Some(entry) = widget.dynamic_cast_ref::<gtk::Entry>() {
+        entry.set_icon_from_icon_name(
+            gtk::EntryIconPosition::Secondary,
+            message.map(|_| "dialog-warning-symbolic"),
+        );
+        entry.set_icon_tooltip_text(gtk::EntryIconPosition::Secondary, message);
+    }
+}
+
+// Dropdown of the resolution presets which also takes any other size typed in
+fn resolution_combo() -> gtk::ComboBoxText {
+    let combo = gtk::ComboBoxText::new_with_entry();
+    for &(width, height) in VideoResolution::PRESETS {
+        combo.append_text(&VideoResolution::new(width, height).to_string());
+    }
+    combo
+}
+
+fn combo_entry(combo: &gtk::ComboBoxText) -> Option<gtk::Entry> {
+    combo
+        .get_child()
+        .and_then(|child| child.downcast::<gtk::Entry>().ok())
+}
+
+fn set_combo_entry_text(combo: &gtk::ComboBoxText, text: &str) {
+    if let Some(entry) = combo_entry(combo) {
+        entry.set_text(text);
+    }
+}
+
+// Text of an entry, or None if it is empty
+fn entry_text(entry: &gtk::Entry) -> Option<std::string::String> {
+    entry
+        .get_text()
+        .map(|t| t.to_string())
+        .filter(|t| !t.is_empty())
+}
+
+impl SettingsDialog {
+    // Downgrade to a weak reference
+    fn downgrade(&self) -> SettingsDialogWeak {
+        SettingsDialogWeak(Rc::downgrade(&self.0))
+    }
+
+    // Take current settings value from all our widgets and store into the configuration file
+    fn save_settings(&self) {
+        let h264_encoder = match self.h264_encoder.get_text() {
+            Some(e) => e,
+            None => {
+                utils::show_error_dialog(false, "Please specify an H.264 encoder chain");
+                return;
+            }
+        };
+
+        let rtmp_location = self.rtmp_location.get_text().map(|l| l.into());
+
+        let encoder = match self.encoder.get_active_id() {
+            Some(ref id) if id != "custom" => Some(id.to_string()),
+            _ => None,
+        };
+
+        let encoder_options = self
+            .encoder_option_combos
+            .borrow()
+            .iter()
+            .filter_map(|(option, combo)| {
+                combo
+                    .get_active_text()
+                    .map(|value| (option.to_string(), value.to_string()))
+            })
+            .collect();
+
+        // Keep the previous address while the user is still typing a new one
+        let bind_address = match entry_text(&self.bind_address) {
+            Some(address) if address.parse::<IpAddr>().is_err() => {
+                self.bind_address
+                    .get_style_context()
+                    .add_class(&gtk::STYLE_CLASS_ERROR);
+                utils::load_settings().bind_address
+            }
+            address => {
+                self.bind_address
+                    .get_style_context()
+                    .remove_class(&gtk::STYLE_CLASS_ERROR);
+                address
+            }
+        };
+
+        let extra_rtmp_locations = self
+            .extra_rtmp_locations
+            .get_buffer()
+            .and_then(|buffer| {
+                buffer.get_text(&buffer.get_start_iter(), &buffer.get_end_iter(), false)
+            })
+            .map(|text| {
+                text.lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(|line| line.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let background = BackgroundSettings {
+            kind: BackgroundKind::from(self.background_kind.get_active_id()),
+            color: rgba_to_hex(&self.background_color.get_rgba()),
+            image: self
+                .background_image
+                .get_filename()
+                .map(|p| p.to_string_lossy().into_owned()),
+        };
+
+        let old_settings = utils::load_settings();
+
+        let (video_bitrate, keyframe_interval, video_profile) =
+            match *self.encoder_controls.borrow() {
+                Some(ref controls) if controls.custom => (
+                    old_settings.video_bitrate,
+                    controls.keyframe_interval.get_value_as_int() as u32,
+                    old_settings.video_profile.clone(),
+                ),
+                Some(ref controls) => (
+                    controls.bitrate.get_value_as_int() as u32,
+                    controls.keyframe_interval.get_value_as_int() as u32,
+                    controls
+                        .profile
+                        .get_active_id()
+                        .filter(|id| !id.is_empty())
+                        .map(|id| id.to_string()),
+                ),
+                None => (
+                    old_settings.video_bitrate,
+                    old_settings.keyframe_interval,
+                    old_settings.video_profile.clone(),
+                ),
+            };
+
+        let mut camera_orientations = old_settings.camera_orientations.clone();
+        camera_orientations.insert(
+            self.camera.clone(),
+            CameraOrientation {
+                rotation: CameraRotation::from(self.camera_rotation.get_active_id()),
+                mirror: self.camera_mirror.get_active(),
+            },
+        );
+
+        let mut zero_copy_cameras = old_settings.zero_copy_cameras.clone();
+        zero_copy_cameras.retain(|camera| *camera != self.camera);
+        if self.zero_copy.get_active() {
+            zero_copy_cameras.push(self.camera.clone());
+        }
+
+        let mut settings = Settings {
+            rtmp_location,
+            h264_encoder: h264_encoder.to_string(),
+            // Sizes still being typed keep the previous one
+            video_resolution: self
+                .video_resolution
+                .get_active_text()
+                .and_then(|text| text.parse().ok())
+                .unwrap_or(old_settings.video_resolution),
+            video_framerate: self
+                .video_framerate
+                .get_active_id()
+                .and_then(|id| id.parse().ok())
+                .unwrap_or_else(default_video_framerate),
+            output_resolution: match self.output_resolution.get_active_text() {
+                Some(ref text) if !text.trim().is_empty() => {
+                    text.parse().ok().or(old_settings.output_resolution)
+                }
+                _ => None,
+            },
+            encoder,
+            video_bitrate,
+            keyframe_interval,
+            video_profile,
+            broadcast_delay: self.broadcast_delay.get_value_as_int() as u32,
+            ui_scale: self.ui_scale.get_value(),
+            high_contrast: self.high_contrast.get_active(),
+            debug_hud_preview_only: self.debug_hud_preview_only.get_active(),
+            recordings_directory: self
+                .recordings_directory
+                .get_filename()
+                .map(|p| p.to_string_lossy().into_owned()),
+            snapshots_directory: self
+                .snapshots_directory
+                .get_filename()
+                .map(|p| p.to_string_lossy().into_owned()),
+            stats_log_format: StatsLogFormat::from(self.stats_log_format.get_active_id()),
+            stats_directory: self
+                .stats_directory
+                .get_filename()
+                .map(|p| p.to_string_lossy().into_owned()),
+            encoder_options,
+            background,
+            camera_orientations,
+            zero_copy_cameras,
+            test_sources: self.test_sources.get_active(),
+            projector_monitor: self
+                .projector_monitor
+                .get_active_id()
+                .and_then(|id| id.parse().ok())
+                .unwrap_or_else(default_projector_monitor),
+            camera_device: self
+                .camera_device
+                .get_active_id()
+                .filter(|id| !id.is_empty())
+                .map(|id| id.to_string()),
+            camera_backend: CameraBackend::from(self.camera_backend.get_active_id()),
+            camera_format: CameraFormat::from(self.camera_format.get_active_id()),
+            camera_decoding: CameraDecoding::from(self.camera_decoding.get_active_id()),
+            audio_codec: AudioCodec::from(self.audio_codec.get_active_id()),
+            audio_bitrate: self.audio_bitrate.get_value_as_int() as u32,
+            overlay_bridge_port: self.overlay_bridge_port.get_value_as_int() as u16,
+            remote_port: self.remote_port.get_value_as_int() as u16,
+            lan_preview_port: self.lan_preview_port.get_value_as_int() as u16,
+            remote_token: entry_text(&self.remote_token),
+            rtmp_username: entry_text(&self.rtmp_username),
+            profile_template: self
+                .profile_template
+                .get_active_id()
+                .filter(|id| !id.is_empty())
+                .map(|id| id.to_string()),
+            camera_framerate: self
+                .camera_framerate
+                .get_active_id()
+                .and_then(|id| id.parse().ok())
+                .unwrap_or_else(default_camera_framerate),
+            output_mode: OutputMode::from(self.output_mode.get_active_id()),
+            local_recording: LocalRecording::from(self.local_recording.get_active_id()),
+            extra_rtmp_locations,
+            extra_rtmp_stream_keys: entry_text(&self.extra_rtmp_stream_keys)
+                .map(|keys| keys.split_whitespace().map(|key| key.to_string()).collect())
+                .unwrap_or_default(),
+            scene_transition: self.scene_transition.get_value_as_int() as u32,
+            slate_image: self
+                .slate_image
+                .get_filename()
+                .map(|p| p.to_string_lossy().into_owned()),
+            bind_address,
+            proxy: entry_text(&self.proxy),
+            disk_warning_threshold: self.disk_warning_threshold.get_value_as_int() as u32,
+            disk_stop_threshold: self.disk_stop_threshold.get_value_as_int() as u32,
+            // Keep the settings that are not changed from this dialog
+            ..old_settings
+        };
+
+        // Each page takes its own values into the settings
+        self.rtp_page.update_settings(&mut settings);
+        self.srt_page.update_settings(&mut settings);
+        self.whip_page.update_settings(&mut settings);
+        self.simulcast_page.update_settings(&mut settings);
+        self.rtsp_camera_page.update_settings(&mut settings);
+        self.screen_page.update_settings(&mut settings);
+        self.chroma_key_page.update_settings(&mut settings);
+        self.audio_page.update_settings(&mut settings);
+        self.audio_filters_page.update_settings(&mut settings);
+        self.upload_page.update_settings(&mut settings);
+        self.rotation_page.update_settings(&mut settings);
+        self.schedule_page.update_settings(&mut settings);
+        self.checklist_page.update_settings(&mut settings);
+        self.chat_page.update_settings(&mut settings);
+
+        self.show_problems(&validation::validate(&settings));
+        self.update_keyframe_hint(&settings);
+        utils::save_settings(&settings);
+    }
+
+    // Entries of all pages whose values are kept in the keyring
+    fn get_secret_entries(&self) -> Vec<(Secret, &gtk::Entry)> {
+        let mut entries = vec![
+            (Secret::StreamKey, &self.rtmp_stream_key),
+            (Secret::RtmpPassword, &self.rtmp_password),
+            (Secret::ExtraStreamKeys, &self.extra_rtmp_stream_keys),
+            (Secret::RemoteToken, &self.remote_token),
+        ];
+        entries.extend(self.upload_page.get_secret_entries());
+        entries.extend(self.rtsp_camera_page.get_secret_entries());
+        entries.extend(self.srt_page.get_secret_entries());
+        entries.extend(self.whip_page.get_secret_entries());
+        entries.extend(self.chat_page.get_secret_entries());
+        entries
+    }
+
+    // Store the changed secrets in the keyring
+    fn store_secrets(&self) {
+        let profile = profiles::active();
+        for (secret, entry) in self.get_secret_entries() {
+            let value = entry_text(entry);
+            if value == keyring::lookup(&profile, secret) {
+                continue;
+            }
+
+            if let Err(err) = keyring::store(&profile, secret, value.as_deref()) {
+                utils::show_error_dialog(
+                    false,
+                    format!(
+                        "The secret could not be stored in the keyring and is only used until \
+                         the application quits: {}",
+                        err
+                    )
+                    .as_str(),
+                );
+            }
+        }
+    }
+
+    // Point out the problems found in the settings at their widgets and list them below the pages
+    fn show_problems(&self, problems: &[validation::Problem]) {
+        let message = |field: Field| {
+            problems
+                .iter()
+                .find(|problem| problem.field == field)
+                .map(|problem| problem.message.as_str())
+        };
+
+        set_problem_hint(&self.rtmp_location, message(Field::RtmpLocation));
+        set_problem_hint(
+            &self.extra_rtmp_locations,
+            message(Field::ExtraRtmpLocations),
+        );
+        set_problem_hint(&self.audio_codec, message(Field::AudioEncoder));
+        self.srt_page.show_problem(message(Field::SrtUri));
+        self.whip_page.show_problem(message(Field::WhipEndpoint));
+        self.simulcast_page.show_problem(message(Field::Simulcast));
+
+        // Either the custom chain or the selected encoder family is at fault
+        let custom_encoder = self.encoder.get_active_id().as_deref() == Some("custom");
+        let video_encoder = message(Field::VideoEncoder);
+        set_problem_hint(&self.h264_encoder, video_encoder.filter(|_| custom_encoder));
+        set_problem_hint(&self.encoder, video_encoder.filter(|_| !custom_encoder));
+
+        let text = problems
+            .iter()
+            .map(|problem| problem.message.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.problems.set_text(&text);
+        self.problems.set_visible(!problems.is_empty());
+    }
+
+    // Point out sizes that can't be used and cameras that can't capture the video resolution.
+    // Neither keeps from going live
+    fn update_resolution_hints(&self) {
+        let parse_error = |combo: &gtk::ComboBoxText| {
+            combo
+                .get_active_text()
+                .filter(|text| !text.trim().is_empty())
+                .and_then(|text| text.parse::<VideoResolution>().err())
+        };
+
+        let video_resolution = parse_error(&self.video_resolution)
+            .or_else(|| validation::check_camera_resolution(&utils::load_settings()).err());
+        if let Some(entry) = combo_entry(&self.video_resolution) {
+            set_problem_hint(&entry, video_resolution.as_deref());
+        }
+        if let Some(entry) = combo_entry(&self.output_resolution) {
+            set_problem_hint(&entry, parse_error(&self.output_resolution).as_deref());
+        }
+    }
+
+    // Point out keyframe intervals streaming platforms don't take well. Going live still works
+    fn update_keyframe_hint(&self, settings: &Settings) {
+        if let Some(ref controls) = *self.encoder_controls.borrow() {
+            set_problem_hint(
+                &controls.keyframe_interval,
+                validation::check_keyframe_interval(settings)
+                    .err()
+                    .as_deref(),
+            );
+        }
+    }
+
+    // Fill the encoder options grid with one dropdown per option of the selected encoder family,
+    // listing the values found by introspecting the element
+    fn update_encoder_options(&self, settings: &Settings) {
+        for child in self.encoder_options.get_children() {
+            self.encoder_options.remove(&child);
+        }
+        self.encoder_option_combos.borrow_mut().clear();
+        self.encoder_controls.replace(None);
+
+        let id = self.encoder.get_active_id();
+        let automatic = id.as_deref() == Some(encoders::AUTO);
+        let family = match id {
+            Some(_) if automatic => encoders::best_family(),
+            Some(ref id) => encoders::find_family(id),
+            None => None,
+        };
+
+        // The raw encoder chain is only used for the custom encoder
+        self.h264_encoder.set_sensitive(family.is_none());
+        let profiles = family.map_or(&[][..], |family| family.profiles);
+
+        let bitrate_label = gtk::Label::new(Some("Video bitrate (kbit/s)"));
+        bitrate_label.set_halign(gtk::Align::Start);
+        let bitrate = gtk::SpinButton::new_with_range(250.0, 50000.0, 250.0);
+        bitrate.set_value(f64::from(settings.video_bitrate));
+        bitrate.set_hexpand(true);
+        bitrate.set_sensitive(family.is_some());
+
+        let keyframe_interval_label = gtk::Label::new(Some("Keyframe interval (frames)"));
+        keyframe_interval_label.set_halign(gtk::Align::Start);
+        let keyframe_interval = gtk::SpinButton::new_with_range(1.0, 600.0, 1.0);
+        keyframe_interval.set_value(f64::from(settings.keyframe_interval));
+        keyframe_interval.set_tooltip_text(Some(
+            "Most streaming platforms want a keyframe every two seconds, i.e. twice the frame rate",
+        ));
+
+        let profile_label = gtk::Label::new(Some("Profile"));
+        profile_label.set_halign(gtk::Align::Start);
+        let profile = gtk::ComboBoxText::new();
+        profile.append(Some(""), "Default");
+        for p in profiles {
+            profile.append(Some(p), p);
+        }
+        if !profile.set_active_id(settings.video_profile.as_deref()) {
+            profile.set_active_id(Some(""));
+        }
+        profile.set_sensitive(!profiles.is_empty());
+
+        for spin_button in &[&bitrate, &keyframe_interval] {
+            let settings_dialog_weak = self.downgrade();
+            spin_button.connect_value_changed(move |_| {
+                let settings_dialog = upgrade_weak!(settings_dialog_weak);
+                settings_dialog.save_settings();
+            });
+        }
+        let settings_dialog_weak = self.downgrade();
+        profile.connect_changed(move |_| {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+        });
+
+        self.encoder_options.attach(&bitrate_label, 0, 0, 1, 1);
+        self.encoder_options.attach(&bitrate, 1, 0, 3, 1);
+        self.encoder_options
+            .attach(&keyframe_interval_label, 0, 1, 1, 1);
+        self.encoder_options.attach(&keyframe_interval, 1, 1, 3, 1);
+        self.encoder_options.attach(&profile_label, 0, 2, 1, 1);
+        self.encoder_options.attach(&profile, 1, 2, 3, 1);
+        self.encoder_controls.replace(Some(EncoderControls {
+            bitrate,
+            keyframe_interval,
+            profile,
+            custom: family.is_none(),
+        }));
+        self.update_keyframe_hint(settings);
+
+        // The options of the automatically selected encoder depend on the machine, so they are
+        // left at their defaults
+        let family = match family {
+            Some(family) if !automatic => family,
+            _ => {
+                self.encoder_options.show_all();
+                return;
+            }
+        };
+
+        for (i, option) in family.options.iter().enumerate() {
+            let row = i + 3;
+            let label = gtk::Label::new(Some(*option));
+            label.set_halign(gtk::Align::Start);
+
+            let combo = gtk::ComboBoxText::new();
+            for value in encoders::option_values(family.factory, option) {
+                combo.append(Some(&value), &value);
+            }
+            if let Some(value) = settings.encoder_options.get(*option) {
+                combo.set_active_id(Some(value));
+            }
+            combo.set_hexpand(true);
+
+            let settings_dialog_weak = self.downgrade();
+            combo.connect_changed(move |_| {
+                let settings_dialog = upgrade_weak!(settings_dialog_weak);
+                settings_dialog.save_settings();
+            });
+
+            self.encoder_options.attach(&label, 0, row as i32, 1, 1);
+            self.encoder_options.attach(&combo, 1, row as i32, 3, 1);
+            self.encoder_option_combos
+                .borrow_mut()
+                .push((option, combo));
+        }
+
+        self.encoder_options.show_all();
+    }
+}
+
+// Selection, creation and removal of the settings profiles. The dialog shows the settings of the
+// selected profile, so it is opened again after switching
+fn profile_box(dialog: &gtk::Dialog, application: &gtk::Application, app: &App) -> gtk::Box {
+    let profile_label = gtk::Label::new(Some("Profile"));
+    let profile = gtk::ComboBoxText::new();
+    for name in profiles::list_names() {
+        profile.append(Some(&name), &name);
+    }
+    profile.set_active_id(Some(&profiles::active()));
+    profile.set_tooltip_text(Some("Set of settings, e.g. for a particular show"));
+
+    let remove_button =
+        gtk::Button::new_from_icon_name(Some("list-remove-symbolic"), gtk::IconSize::Button);
+    remove_button.set_tooltip_text(Some("Remove this profile"));
+    remove_button.set_sensitive(profiles::active() != profiles::DEFAULT_PROFILE);
+
+    let new_profile = gtk::Entry::new();
+    new_profile.set_placeholder_text(Some("New profile name"));
+    let add_button = gtk::Button::new_with_label("Add");
+    add_button.set_tooltip_text(Some("Create a profile from the current settings"));
+
+    let profile_box = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+    profile_box.set_margin_bottom(8);
+    profile_box.pack_start(&profile_label, false, false, 0);
+    profile_box.pack_start(&profile, true, true, 0);
+    profile_box.pack_start(&remove_button, false, false, 0);
+    profile_box.pack_start(&new_profile, false, false, 0);
+    profile_box.pack_start(&add_button, false, false, 0);
+
+    // Apply the settings of the now selected profile and show them in a new dialog
+    let reopen = {
+        let dialog = dialog.clone();
+        let weak_application = application.downgrade();
+        let weak_app = app.downgrade();
+        Rc::new(move |result: std::io::Result<()>| {
+            if let Err(err) = result {
+                utils::show_error_dialog(
+                    false,
+                    format!("Failed to switch the profile: {}", err).as_str(),
+                );
+                return;
+            }
+
+            dialog.destroy();
+            let application = upgrade_weak!(weak_application);
+            let app = upgrade_weak!(weak_app);
+            app.apply_profile();
+            show_settings_dialog(&application, &app);
+        })
+    };
+
+    let reopen_clone = reopen.clone();
+    profile.connect_changed(move |profile| {
+        if let Some(name) = profile.get_active_id() {
+            if name.as_str() != profiles::active() {
+                reopen_clone(profiles::set_active(&name, true));
+            }
+        }
+    });
+
+    let reopen_clone = reopen.clone();
+    remove_button.connect_clicked(move |_| {
+        reopen_clone(profiles::remove(&profiles::active()));
+    });
+
+    let reopen_clone = reopen;
+    let create = move |entry: &gtk::Entry| {
+        let name = entry
+            .get_text()
+            .map(|name| name.to_string())
+            .unwrap_or_default();
+        reopen_clone(profiles::create(&name).map(|_| ()));
+    };
+    let new_profile_clone = new_profile.clone();
+    let create_clone = create.clone();
+    add_button.connect_clicked(move |_| create_clone(&new_profile_clone));
+    new_profile.connect_activate(create);
+
+    profile_box
+}
+
+// Construct the settings dialog and ensure that the settings file exists and is loaded
+pub fn show_settings_dialog(application: &gtk::Application, app: &App) {
+    let s = utils::get_settings_file_path();
+
+    if !s.exists() {
+        if let Some(parent_dir) = s.parent() {
+            if !parent_dir.exists() {
+                if let Err(e) = create_dir_all(parent_dir) {
+                    utils::show_error_dialog(
+                        false,
+                        format!(
+                            "Error while trying to build settings snapshot_directory '{}': {}",
+                            parent_dir.display(),
+                            e
+                        )
+                        .as_str(),
+                    );
+                }
+            }
+        }
+    }
+
+    let settings = utils::load_settings();
+
+    // Create an empty dialog with close button
+    let dialog = gtk::Dialog::new_with_buttons(
+        Some("WPE overlay broadcast settings"),
+        application.get_active_window().as_ref(),
+        gtk::DialogFlags::MODAL,
+        &[("Close", gtk::ResponseType::Close)],
+    );
+
+    // All the UI widgets are going to be stored in a grid
+    let grid = page_grid();
+
+    let resolution_label = gtk::Label::new(Some("Video resolution"));
+    let video_resolution = resolution_combo();
+
+    resolution_label.set_halign(gtk::Align::Start);
+
+    set_combo_entry_text(&video_resolution, &settings.video_resolution.to_string());
+    video_resolution.set_tooltip_text(Some(
+        "Width and height of the program, e.g. 1080x1920 for vertical video",
+    ));
+    video_resolution.set_hexpand(true);
+
+    let video_framerate = gtk::ComboBoxText::new();
+    for framerate in &[24, 25, 30, 50, 60] {
+        let id = framerate.to_string();
+        video_framerate.append(Some(&id), &format!("{} fps", framerate));
+    }
+    let framerate_id = settings.video_framerate.to_string();
+    if !video_framerate.set_active_id(Some(&framerate_id)) {
+        video_framerate.append(
+            Some(&framerate_id),
+            &format!("{} fps", settings.video_framerate),
+        );
+        video_framerate.set_active_id(Some(&framerate_id));
+    }
+
+    grid.attach(&resolution_label, 0, 1, 1, 1);
+    grid.attach(&video_resolution, 1, 1, 2, 1);
+    grid.attach(&video_framerate, 3, 1, 1, 1);
+
+    let rtmp_label = gtk::Label::new(Some("RTMP server and key"));
+    let rtmp_location = gtk::Entry::new();
+    if let Some(location) = &settings.rtmp_location {
+        rtmp_location.set_text(location);
+    }
+    rtmp_location.set_placeholder_text(Some("rtmp://server/application"));
+    rtmp_location.set_hexpand(true);
+
+    // The key is masked and kept in the keyring so it doesn't leak with screenshots or backups
+    // of the settings
+    let rtmp_stream_key = gtk::Entry::new();
+    if let Some(key) = &settings.rtmp_stream_key {
+        rtmp_stream_key.set_text(key);
+    }
+    rtmp_stream_key.set_placeholder_text(Some("Stream key"));
+    rtmp_stream_key.set_tooltip_text(Some(
+        "Stored in the keyring of the session instead of the settings file",
+    ));
+    rtmp_stream_key.set_visibility(false);
+    rtmp_stream_key.set_icon_from_icon_name(
+        gtk::EntryIconPosition::Secondary,
+        Some("view-reveal-symbolic"),
+    );
+    rtmp_stream_key.set_icon_tooltip_text(
+        gtk::EntryIconPosition::Secondary,
+        Some("Show the stream key"),
+    );
+    rtmp_stream_key.connect_icon_press(|entry, _, _| {
+        entry.set_visibility(!entry.get_visibility());
+    });
+
+    rtmp_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&rtmp_label, 0, 3, 1, 1);
+    grid.attach(&rtmp_location, 1, 3, 2, 1);
+    grid.attach(&rtmp_stream_key, 3, 3, 1, 1);
+
+    let rtmp_login_label = gtk::Label::new(Some("RTMP login"));
+    let rtmp_username = gtk::Entry::new();
+    if let Some(username) = &settings.rtmp_username {
+        rtmp_username.set_text(username);
+    }
+    rtmp_username.set_placeholder_text(Some("Username, if required"));
+    rtmp_username.set_tooltip_text(Some(
+        "Only used with servers that require authentication. RTMPS and logins need the \
+         rtmp2sink element",
+    ));
+    let rtmp_password = gtk::Entry::new();
+    if let Some(password) = &settings.rtmp_password {
+        rtmp_password.set_text(password);
+    }
+    rtmp_password.set_placeholder_text(Some("Password"));
+    rtmp_password.set_tooltip_text(Some(
+        "Stored in the keyring of the session instead of the settings file",
+    ));
+    rtmp_password.set_visibility(false);
+
+    rtmp_login_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&rtmp_login_label, 0, 4, 1, 1);
+    grid.attach(&rtmp_username, 1, 4, 2, 1);
+    grid.attach(&rtmp_password, 3, 4, 1, 1);
+
+    let encoder_label = gtk::Label::new(Some("Video encoder"));
+    let encoder = gtk::ComboBoxText::new();
+    match encoders::best_family() {
+        Some(family) => encoder.append(
+            Some(encoders::AUTO),
+            &format!("Automatic ({})", family.label),
+        ),
+        None => encoder.append(Some(encoders::AUTO), "Automatic (no encoder found)"),
+    }
+    encoder.append(Some("custom"), "Custom H.264 chain");
+    for family in encoders::available_families() {
+        encoder.append(Some(family.factory), family.label);
+    }
+    if !encoder.set_active_id(settings.encoder.as_deref()) {
+        encoder.set_active_id(Some("custom"));
+    }
+
+    encoder_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&encoder_label, 0, 5, 1, 1);
+    grid.attach(&encoder, 1, 5, 2, 1);
+
+    // Runs the configured encoders once so problems show up before going live
+    let test_encoders = gtk::Button::new_with_label("Test");
+    test_encoders.set_tooltip_text(Some(
+        "Encode a few seconds of test data with the current settings",
+    ));
+    test_encoders.connect_clicked(|test_encoders| {
+        test_encoders.set_sensitive(false);
+        let test_encoders = test_encoders.clone();
+        pipeline::test_encoders(&utils::load_settings(), move |result| {
+            test_encoders.set_sensitive(true);
+            match result {
+                Ok(()) => utils::show_info_dialog("The encoder settings work"),
+                Err(err) => utils::show_error_dialog(
+                    false,
+                    format!("The encoder settings don't work: {}", err).as_str(),
+                ),
+            }
+        });
+    });
+
+    grid.attach(&test_encoders, 3, 5, 1, 1);
+
+    let custom_encoder_label = gtk::Label::new(Some("Custom encoder chain"));
+    let h264_encoder = gtk::Entry::new();
+    h264_encoder.set_text(&settings.h264_encoder);
+
+    custom_encoder_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&custom_encoder_label, 0, 6, 1, 1);
+    grid.attach(&h264_encoder, 1, 5, 3, 1);
+
+    let encoder_options = gtk::Grid::new();
+    encoder_options.set_column_spacing(4);
+    encoder_options.set_row_spacing(4);
+
+    grid.attach(&encoder_options, 0, 7, 4, 1);
+
+    let recordings_label = gtk::Label::new(Some("Recordings folder"));
+    let recordings_directory =
+        gtk::FileChooserButton::new("Recordings folder", gtk::FileChooserAction::SelectFolder);
+    let directory = utils::get_recordings_directory(&settings);
+    if let Err(e) = create_dir_all(&directory) {
+        utils::show_error_dialog(
+            false,
+            format!(
+                "Error while trying to create recordings folder '{}': {}",
+                directory.display(),
+                e
+            )
+            .as_str(),
+        );
+    }
+    recordings_directory.set_filename(&directory);
+
+    recordings_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&recordings_label, 0, 8, 1, 1);
+    grid.attach(&recordings_directory, 1, 8, 3, 1);
+
+    let disk_warning_label = gtk::Label::new(Some("Warn below free disk space (MiB)"));
+    let disk_warning_threshold = gtk::SpinButton::new_with_range(0.0, 1024.0 * 1024.0, 256.0);
+    disk_warning_threshold.set_value(f64::from(settings.disk_warning_threshold));
+
+    let disk_stop_label = gtk::Label::new(Some("Stop recording below (MiB)"));
+    let disk_stop_threshold = gtk::SpinButton::new_with_range(0.0, 1024.0 * 1024.0, 256.0);
+    disk_stop_threshold.set_value(f64::from(settings.disk_stop_threshold));
+
+    disk_warning_label.set_halign(gtk::Align::Start);
+    disk_stop_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&disk_warning_label, 0, 14, 1, 1);
+    grid.attach(&disk_warning_threshold, 1, 14, 3, 1);
+    grid.attach(&disk_stop_label, 0, 15, 1, 1);
+    grid.attach(&disk_stop_threshold, 1, 15, 3, 1);
+
+    let delay_label = gtk::Label::new(Some("Broadcast delay (seconds)"));
+    let broadcast_delay = gtk::SpinButton::new_with_range(0.0, 60.0, 1.0);
+    broadcast_delay.set_value(f64::from(settings.broadcast_delay));
+
+    delay_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&delay_label, 0, 11, 1, 1);
+    grid.attach(&broadcast_delay, 1, 11, 3, 1);
+
+    let background_label = gtk::Label::new(Some("Mixer background"));
+    let background_kind = gtk::ComboBoxText::new();
+    background_kind.append(Some(BackgroundKind::Black.id()), "Black");
+    background_kind.append(Some(BackgroundKind::Checker.id()), "Checker board");
+    background_kind.append(Some(BackgroundKind::Color.id()), "Color");
+    background_kind.append(Some(BackgroundKind::Image.id()), "Image");
+    background_kind.set_active_id(Some(settings.background.kind.id()));
+
+    let background_color = gtk::ColorButton::new_with_rgba(&hex_to_rgba(settings.background.rgb()));
+    let background_image =
+        gtk::FileChooserButton::new("Background image", gtk::FileChooserAction::Open);
+    if let Some(ref image) = settings.background.image {
+        background_image.set_filename(image);
+    }
+
+    background_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&background_label, 0, 12, 1, 1);
+    grid.attach(&background_kind, 1, 12, 1, 1);
+    grid.attach(&background_color, 2, 12, 1, 1);
+    grid.attach(&background_image, 3, 12, 1, 1);
+
+    let camera = app.camera_id();
+    let camera_orientation = settings.camera_orientation(&camera);
+
+    let camera_label = gtk::Label::new(Some("Camera rotation"));
+    let camera_rotation = gtk::ComboBoxText::new();
+    camera_rotation.append(Some(CameraRotation::None.id()), "None");
+    camera_rotation.append(Some(CameraRotation::Rotate90.id()), "90°");
+    camera_rotation.append(Some(CameraRotation::Rotate180.id()), "180°");
+    camera_rotation.append(Some(CameraRotation::Rotate270.id()), "270°");
+    camera_rotation.set_active_id(Some(camera_orientation.rotation.id()));
+
+    let camera_mirror = gtk::CheckButton::new_with_label("Mirror");
+    camera_mirror.set_active(camera_orientation.mirror);
+
+    camera_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&camera_label, 0, 13, 1, 1);
+    grid.attach(&camera_rotation, 1, 13, 2, 1);
+    grid.attach(&camera_mirror, 3, 13, 1, 1);
+
+    let output_mode_label = gtk::Label::new(Some("Go live via"));
+    let output_mode = gtk::ComboBoxText::new();
+    output_mode.append(Some(OutputMode::Rtmp.id()), "RTMP");
+    output_mode.append(Some(OutputMode::Rtp.id()), "RTP");
+    output_mode.append(Some(OutputMode::Srt.id()), "SRT");
+    output_mode.append(Some(OutputMode::Whip.id()), "WebRTC (WHIP)");
+    output_mode.set_active_id(Some(settings.output_mode.id()));
+
+    output_mode_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&output_mode_label, 0, 2, 1, 1);
+    grid.attach(&output_mode, 1, 2, 3, 1);
+
+    let bind_address_label = gtk::Label::new(Some("Send from local address"));
+    let bind_address = gtk::Entry::new();
+    if let Some(ref address) = settings.bind_address {
+        bind_address.set_text(address);
+    }
+    bind_address.set_placeholder_text(Some("Any"));
+    bind_address.set_tooltip_text(Some(
+        "IP address of the network interface to send RTP from. \
+         RTMP connections always follow the system routing table",
+    ));
+
+    bind_address_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&bind_address_label, 0, 16, 1, 1);
+    grid.attach(&bind_address, 1, 16, 3, 1);
+
+    let proxy_label = gtk::Label::new(Some("Proxy"));
+    let proxy = gtk::Entry::new();
+    if let Some(ref url) = settings.proxy {
+        proxy.set_text(url);
+    }
+    proxy.set_placeholder_text(Some("socks5://host:port or http://host:port"));
+    proxy.set_tooltip_text(Some(
+        "Used for uploads and other HTTP requests. \
+         RTMP can only go through SOCKS proxies",
+    ));
+
+    proxy_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&proxy_label, 0, 17, 1, 1);
+    grid.attach(&proxy, 1, 17, 3, 1);
+
+    let ui_scale_label = gtk::Label::new(Some("Interface scale"));
+    let ui_scale = gtk::SpinButton::new_with_range(1.0, 3.0, 0.25);
+    ui_scale.set_value(settings.ui_scale);
+
+    ui_scale_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&ui_scale_label, 0, 9, 1, 1);
+    grid.attach(&ui_scale, 1, 9, 3, 1);
+
+    let high_contrast = gtk::CheckButton::new_with_label("High contrast");
+    high_contrast.set_active(settings.high_contrast);
+
+    grid.attach(&high_contrast, 1, 10, 3, 1);
+
+    let debug_hud_preview_only = gtk::CheckButton::new_with_label("Hide debug HUD while live");
+    debug_hud_preview_only.set_active(settings.debug_hud_preview_only);
+
+    grid.attach(&debug_hud_preview_only, 1, 18, 3, 1);
+
+    let local_recording_label = gtk::Label::new(Some("Keep a local copy"));
+    let local_recording = gtk::ComboBoxText::new();
+    local_recording.append(Some(LocalRecording::Off.id()), "Off");
+    local_recording.append(Some(LocalRecording::Mp4.id()), "MP4");
+    local_recording.append(Some(LocalRecording::Matroska.id()), "Matroska");
+    local_recording.set_active_id(Some(settings.local_recording.id()));
+    local_recording.set_tooltip_text(Some(
+        "Write the program to the recordings folder as well, so the show survives a \
+         dropped connection",
+    ));
+
+    local_recording_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&local_recording_label, 0, 19, 1, 1);
+    grid.attach(&local_recording, 1, 19, 3, 1);
+
+    let extra_rtmp_locations_label = gtk::Label::new(Some("Also stream to"));
+    let extra_rtmp_locations = gtk::TextView::new();
+    if let Some(buffer) = extra_rtmp_locations.get_buffer() {
+        buffer.set_text(&settings.extra_rtmp_locations.join("\n"));
+    }
+    extra_rtmp_locations.set_tooltip_text(Some(
+        "Further RTMP servers with their application, one per line, e.g. to go live on several \
+         platforms at once",
+    ));
+    extra_rtmp_locations
+        .get_style_context()
+        .add_class(&gtk::STYLE_CLASS_VIEW);
+
+    // Masked and kept in the keyring like the key of the main end-point
+    let extra_rtmp_stream_keys = gtk::Entry::new();
+    extra_rtmp_stream_keys.set_text(&settings.extra_rtmp_stream_keys.join(" "));
+    extra_rtmp_stream_keys.set_placeholder_text(Some("Stream keys"));
+    extra_rtmp_stream_keys.set_tooltip_text(Some(
+        "Stream keys of the end-points above in the same order, separated by spaces. Stored in \
+         the keyring of the session instead of the settings file",
+    ));
+    extra_rtmp_stream_keys.set_visibility(false);
+    extra_rtmp_stream_keys.set_icon_from_icon_name(
+        gtk::EntryIconPosition::Secondary,
+        Some("view-reveal-symbolic"),
+    );
+    extra_rtmp_stream_keys.set_icon_tooltip_text(
+        gtk::EntryIconPosition::Secondary,
+        Some("Show the stream keys"),
+    );
+    extra_rtmp_stream_keys.connect_icon_press(|entry, _, _| {
+        entry.set_visibility(!entry.get_visibility());
+    });
+
+    let extra_rtmp_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
+    extra_rtmp_box.pack_start(&extra_rtmp_locations, true, true, 0);
+    extra_rtmp_box.pack_start(&extra_rtmp_stream_keys, false, false, 0);
+
+    extra_rtmp_locations_label.set_halign(gtk::Align::Start);
+    extra_rtmp_locations_label.set_valign(gtk::Align::Start);
+
+    grid.attach(&extra_rtmp_locations_label, 0, 20, 1, 1);
+    grid.attach(&extra_rtmp_box, 1, 20, 3, 1);
+
+    let scene_transition_label = gtk::Label::new(Some("Scene transition (ms)"));
+    let scene_transition = gtk::SpinButton::new_with_range(0.0, 5000.0, 50.0);
+    scene_transition.set_value(f64::from(settings.scene_transition));
+    scene_transition.set_tooltip_text(Some("0 cuts between scenes without animation"));
+
+    scene_transition_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&scene_transition_label, 0, 21, 1, 1);
+    grid.attach(&scene_transition, 1, 21, 3, 1);
+
+    let camera_device_label = gtk::Label::new(Some("Camera"));
+    let camera_device = gtk::ComboBoxText::new();
+    camera_device.append(Some(""), "Default camera");
+    for name in devices::list_device_names("Video/Source") {
+        camera_device.append(Some(&name), &name);
+    }
+    let camera_name = settings.camera_device.clone().unwrap_or_default();
+    if !camera_device.set_active_id(Some(&camera_name)) {
+        // The device is gone, keep it selectable so the setting isn't lost
+        camera_device.append(Some(&camera_name), &camera_name);
+        camera_device.set_active_id(Some(&camera_name));
+    }
+
+    camera_device_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&camera_device_label, 0, 22, 1, 1);
+    grid.attach(&camera_device, 1, 22, 3, 1);
+
+    let camera_format_label = gtk::Label::new(Some("Camera format"));
+    let camera_format = gtk::ComboBoxText::new();
+    camera_format.append(Some(CameraFormat::Mjpeg.id()), "MJPEG");
+    camera_format.append(Some(CameraFormat::Yuy2.id()), "YUY2");
+    camera_format.append(Some(CameraFormat::H264.id()), "H.264");
+    camera_format.set_active_id(Some(settings.camera_format.id()));
+
+    camera_format_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&camera_format_label, 0, 23, 1, 1);
+    grid.attach(&camera_format, 1, 23, 3, 1);
+
+    let camera_framerate_label = gtk::Label::new(Some("Camera framerate"));
+    let camera_framerate = gtk::ComboBoxText::new();
+    for framerate in &[15, 24, 25, 30, 50, 60] {
+        let id = framerate.to_string();
+        camera_framerate.append(Some(&id), &format!("{} fps", framerate));
+    }
+    let framerate_id = settings.camera_framerate.to_string();
+    if !camera_framerate.set_active_id(Some(&framerate_id)) {
+        camera_framerate.append(
+            Some(&framerate_id),
+            &format!("{} fps", settings.camera_framerate),
+        );
+        camera_framerate.set_active_id(Some(&framerate_id));
+    }
+
+    camera_framerate_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&camera_framerate_label, 0, 24, 1, 1);
+    grid.attach(&camera_framerate, 1, 24, 3, 1);
+
+    let camera_decoding_label = gtk::Label::new(Some("Camera decoding"));
+    let camera_decoding = gtk::ComboBoxText::new();
+    camera_decoding.append(Some(CameraDecoding::Auto.id()), "Automatic");
+    camera_decoding.append(Some(CameraDecoding::Hardware.id()), "Hardware");
+    camera_decoding.append(Some(CameraDecoding::Software.id()), "Software");
+    camera_decoding.set_active_id(Some(settings.camera_decoding.id()));
+    camera_decoding.set_tooltip_text(Some(
+        "Hardware decoding of MJPEG and H.264 cameras takes load off the CPU",
+    ));
+
+    // Like the orientation, zero-copy capture is set for the current camera
+    let zero_copy = gtk::CheckButton::new_with_label("Zero-copy");
+    zero_copy.set_active(settings.zero_copy_cameras.contains(&camera));
+    zero_copy.set_tooltip_text(Some(
+        "Capture this camera in a raw format and hand its buffers to the mixer as DMABuf, \
+         without decoding or copying them. The camera has to deliver YUY2 at the output \
+         resolution",
+    ));
+
+    camera_decoding_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&camera_decoding_label, 0, 25, 1, 1);
+    grid.attach(&camera_decoding, 1, 25, 2, 1);
+    grid.attach(&zero_copy, 3, 25, 1, 1);
+
+    let slate_image_label = gtk::Label::new(Some("Be right back image"));
+    let slate_image =
+        gtk::FileChooserButton::new("Be right back image", gtk::FileChooserAction::Open);
+    if let Some(ref image) = settings.slate_image {
+        slate_image.set_filename(image);
+    }
+    slate_image.set_tooltip_text(Some(
+        "Shown instead of the program while the stream is paused",
+    ));
+
+    slate_image_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&slate_image_label, 0, 26, 1, 1);
+    grid.attach(&slate_image, 1, 26, 3, 1);
+
+    // Only encoders that are installed can be picked, but keep the configured one
+    let audio_codec_label = gtk::Label::new(Some("Audio encoder"));
+    let audio_codec = gtk::ComboBoxText::new();
+    for codec in &AudioCodec::ALL {
+        if *codec == settings.audio_codec || gst::ElementFactory::find(codec.factory()).is_some() {
+            audio_codec.append(Some(codec.id()), codec.label());
+        }
+    }
+    audio_codec.set_active_id(Some(settings.audio_codec.id()));
+    audio_codec.set_tooltip_text(Some("Opus can only be sent over SRT and WHIP"));
+
+    audio_codec_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&audio_codec_label, 0, 27, 1, 1);
+    grid.attach(&audio_codec, 1, 27, 3, 1);
+
+    let audio_bitrate_label = gtk::Label::new(Some("Audio bitrate (kbit/s)"));
+    let audio_bitrate = gtk::SpinButton::new_with_range(32.0, 512.0, 16.0);
+    audio_bitrate.set_value(f64::from(settings.audio_bitrate));
+
+    audio_bitrate_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&audio_bitrate_label, 0, 28, 1, 1);
+    grid.attach(&audio_bitrate, 1, 28, 3, 1);
+
+    let overlay_bridge_port_label = gtk::Label::new(Some("Overlay event port"));
+    let overlay_bridge_port = gtk::SpinButton::new_with_range(0.0, 65535.0, 1.0);
+    overlay_bridge_port.set_value(f64::from(settings.overlay_bridge_port));
+    overlay_bridge_port.set_tooltip_text(Some(
+        "The overlay page posts its events to http://127.0.0.1:<port>/events, 0 to disable. \
+         Takes effect after a restart",
+    ));
+
+    overlay_bridge_port_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&overlay_bridge_port_label, 0, 29, 1, 1);
+    grid.attach(&overlay_bridge_port, 1, 29, 3, 1);
+
+    let snapshots_label = gtk::Label::new(Some("Snapshots folder"));
+    let snapshots_directory =
+        gtk::FileChooserButton::new("Snapshots folder", gtk::FileChooserAction::SelectFolder);
+    let directory = utils::get_snapshots_directory(&settings);
+    // The folder is only created with the first snapshot, but the chooser needs it
+    if directory.is_dir() {
+        snapshots_directory.set_filename(&directory);
+    }
+
+    snapshots_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&snapshots_label, 0, 30, 1, 1);
+    grid.attach(&snapshots_directory, 1, 30, 3, 1);
+
+    let remote_port_label = gtk::Label::new(Some("Remote control port"));
+    let remote_port = gtk::SpinButton::new_with_range(0.0, 65535.0, 1.0);
+    remote_port.set_value(f64::from(settings.remote_port));
+    remote_port.set_tooltip_text(Some(
+        "Stream decks and companion software trigger actions through \
+         JSON posted to http://<host>:<port>/actions or ws://<host>:<port>/ws, 0 to disable. \
+         Takes effect after a restart",
+    ));
+
+    remote_port_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&remote_port_label, 0, 31, 1, 1);
+    grid.attach(&remote_port, 1, 31, 3, 1);
+
+    let remote_token_label = gtk::Label::new(Some("Remote control token"));
+    let remote_token = gtk::Entry::new();
+    if let Some(ref token) = settings.remote_token {
+        remote_token.set_text(token);
+    }
+    remote_token.set_placeholder_text(Some("Only local clients if empty"));
+    remote_token.set_tooltip_text(Some(
+        "Clients send it as bearer token or as token query parameter. \
+         Takes effect after a restart",
+    ));
+    remote_token.set_visibility(false);
+
+    remote_token_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&remote_token_label, 0, 32, 1, 1);
+    grid.attach(&remote_token, 1, 32, 3, 1);
+
+    let lan_preview_port_label = gtk::Label::new(Some("LAN preview port"));
+    let lan_preview_port = gtk::SpinButton::new_with_range(0.0, 65535.0, 1.0);
+    lan_preview_port.set_value(f64::from(settings.lan_preview_port));
+    lan_preview_port.set_tooltip_text(Some(
+        "While live, anyone on the network can watch the program in a browser at \
+         http://<host>:<port>/, 0 to disable. Takes effect after a restart",
+    ));
+
+    lan_preview_port_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&lan_preview_port_label, 0, 37, 1, 1);
+    grid.attach(&lan_preview_port, 1, 37, 3, 1);
+
+    let stats_log_format_label = gtk::Label::new(Some("Statistics log"));
+    let stats_log_format = gtk::ComboBoxText::new();
+    stats_log_format.append(Some(StatsLogFormat::Off.id()), "Off");
+    stats_log_format.append(Some(StatsLogFormat::Csv.id()), "CSV");
+    stats_log_format.append(Some(StatsLogFormat::Json.id()), "JSON lines");
+    stats_log_format.set_active_id(Some(settings.stats_log_format.id()));
+    stats_log_format.set_tooltip_text(Some(
+        "Write bitrate, frame rate, dropped frames, queue levels and audio peaks every second \
+         while live, e.g. to look into glitches viewers reported after the show",
+    ));
+
+    stats_log_format_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&stats_log_format_label, 0, 38, 1, 1);
+    grid.attach(&stats_log_format, 1, 38, 3, 1);
+
+    let stats_directory_label = gtk::Label::new(Some("Statistics folder"));
+    let stats_directory =
+        gtk::FileChooserButton::new("Statistics folder", gtk::FileChooserAction::SelectFolder);
+    let directory = utils::get_stats_directory(&settings);
+    // The folder is only created with the first log, but the chooser needs it
+    if directory.is_dir() {
+        stats_directory.set_filename(&directory);
+    }
+
+    stats_directory_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&stats_directory_label, 0, 39, 1, 1);
+    grid.attach(&stats_directory, 1, 39, 3, 1);
+
+    let camera_backend_label = gtk::Label::new(Some("Camera access"));
+    let camera_backend = gtk::ComboBoxText::new();
+    camera_backend.append(Some(CameraBackend::Auto.id()), "Automatic");
+    camera_backend.append(Some(CameraBackend::V4l2.id()), "V4L2 devices");
+    camera_backend.append(Some(CameraBackend::PipeWire.id()), "PipeWire");
+    camera_backend.set_active_id(Some(settings.camera_backend.id()));
+    camera_backend.set_tooltip_text(Some(
+        "Automatic uses PipeWire when running sandboxed, e.g. as Flatpak, or when the camera \
+         devices can't be opened directly",
+    ));
+
+    camera_backend_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&camera_backend_label, 0, 40, 1, 1);
+    grid.attach(&camera_backend, 1, 40, 3, 1);
+
+    let profile_template_label = gtk::Label::new(Some("Overlay of this profile"));
+    let profile_template = gtk::ComboBoxText::new();
+    profile_template.append(Some(""), "Keep the current overlay");
+    for name in templates::list_names() {
+        profile_template.append(Some(&name), &name);
+    }
+    if !profile_template.set_active_id(settings.profile_template.as_deref()) {
+        profile_template.set_active_id(Some(""));
+    }
+    profile_template.set_tooltip_text(Some(
+        "Overlay template loaded when switching to this profile",
+    ));
+
+    profile_template_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&profile_template_label, 0, 33, 1, 1);
+    grid.attach(&profile_template, 1, 33, 3, 1);
+
+    let test_sources =
+        gtk::CheckButton::new_with_label("Use test sources instead of camera and microphone");
+    test_sources.set_active(settings.test_sources);
+
+    grid.attach(&test_sources, 1, 34, 3, 1);
+
+    let projector_monitor_label = gtk::Label::new(Some("Projector monitor"));
+    let projector_monitor = gtk::ComboBoxText::new();
+    if let Some(screen) = gdk::Screen::get_default() {
+        for monitor in 0..screen.get_n_monitors() {
+            let label = match screen.get_monitor_plug_name(monitor) {
+                Some(name) => format!("{} ({})", monitor + 1, name),
+                None => (monitor + 1).to_string(),
+            };
+            projector_monitor.append(Some(&monitor.to_string()), &label);
+        }
+    }
+    // Keep a monitor that is currently unplugged
+    if !projector_monitor.set_active_id(Some(&settings.projector_monitor.to_string())) {
+        projector_monitor.append(
+            Some(&settings.projector_monitor.to_string()),
+            &(settings.projector_monitor + 1).to_string(),
+        );
+        projector_monitor.set_active_id(Some(&settings.projector_monitor.to_string()));
+    }
+    projector_monitor.set_tooltip_text(Some(
+        "Where the projector shows the program fullscreen, e.g. a stage display",
+    ));
+
+    projector_monitor_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&projector_monitor_label, 0, 35, 1, 1);
+    grid.attach(&projector_monitor, 1, 35, 3, 1);
+
+    let output_resolution_label = gtk::Label::new(Some("Stream resolution"));
+    let output_resolution = resolution_combo();
+    if let Some(ref resolution) = settings.output_resolution {
+        set_combo_entry_text(&output_resolution, &resolution.to_string());
+    }
+    if let Some(entry) = combo_entry(&output_resolution) {
+        entry.set_placeholder_text(Some("Same as the video resolution"));
+    }
+    output_resolution.set_tooltip_text(Some(
+        "Scale the streams and recordings, e.g. mixing in 1080P but sending 720P. \
+         Applies from the next time going live",
+    ));
+
+    output_resolution_label.set_halign(gtk::Align::Start);
+
+    grid.attach(&output_resolution_label, 0, 36, 1, 1);
+    grid.attach(&output_resolution, 1, 36, 3, 1);
+
+    let rtp_page = RtpPage::new(&settings);
+    let srt_page = SrtPage::new(&settings);
+    let whip_page = WhipPage::new(&settings);
+    let simulcast_page = SimulcastPage::new(&settings);
+    let rtsp_camera_page = RtspCameraPage::new(&settings);
+    let screen_page = ScreenPage::new(&settings);
+    let chroma_key_page = ChromaKeyPage::new(&settings);
+    let audio_page = AudioPage::new(&settings);
+    let audio_filters_page = AudioFiltersPage::new(&settings);
+    let upload_page = UploadPage::new(&settings);
+    let rotation_page = RotationPage::new(&settings);
+    let schedule_page = SchedulePage::new(&settings);
+    let checklist_page = ChecklistPage::new(&settings);
+    let chat_page = ChatPage::new(&settings);
+
+    let notebook = gtk::Notebook::new();
+    notebook.append_page(&grid, Some(&gtk::Label::new(Some("General"))));
+    notebook.append_page(rtp_page.get_widget(), Some(&gtk::Label::new(Some("RTP"))));
+    notebook.append_page(srt_page.get_widget(), Some(&gtk::Label::new(Some("SRT"))));
+    notebook.append_page(whip_page.get_widget(), Some(&gtk::Label::new(Some("WHIP"))));
+    notebook.append_page(
+        simulcast_page.get_widget(),
+        Some(&gtk::Label::new(Some("Simulcast"))),
+    );
+    notebook.append_page(
+        rtsp_camera_page.get_widget(),
+        Some(&gtk::Label::new(Some("IP camera"))),
+    );
+    notebook.append_page(
+        screen_page.get_widget(),
+        Some(&gtk::Label::new(Some("Screen"))),
+    );
+    notebook.append_page(
+        chroma_key_page.get_widget(),
+        Some(&gtk::Label::new(Some("Chroma key"))),
+    );
+    notebook.append_page(
+        audio_page.get_widget(),
+        Some(&gtk::Label::new(Some("Audio"))),
+    );
+    notebook.append_page(
+        audio_filters_page.get_widget(),
+        Some(&gtk::Label::new(Some("Audio filters"))),
+    );
+    notebook.append_page(
+        upload_page.get_widget(),
+        Some(&gtk::Label::new(Some("Upload"))),
+    );
+    notebook.append_page(
+        rotation_page.get_widget(),
+        Some(&gtk::Label::new(Some("Automation"))),
+    );
+    notebook.append_page(
+        schedule_page.get_widget(),
+        Some(&gtk::Label::new(Some("Schedule"))),
+    );
+    notebook.append_page(
+        checklist_page.get_widget(),
+        Some(&gtk::Label::new(Some("Checklist"))),
+    );
+    notebook.append_page(chat_page.get_widget(), Some(&gtk::Label::new(Some("Chat"))));
+
+    let midi_page = MidiPage::new(app.get_midi().downgrade());
+    notebook.append_page(midi_page.get_widget(), Some(&gtk::Label::new(Some("MIDI"))));
+
+    // Put the profile selection and the notebook into the dialog's content area
+    let content_area = dialog.get_content_area();
+    content_area.pack_start(&profile_box(&dialog, application, app), false, false, 0);
+    content_area.pack_start(&notebook, true, true, 0);
+
+    let problems = gtk::Label::new(None);
+    problems.set_halign(gtk::Align::Start);
+    problems.set_line_wrap(true);
+    problems.set_margin_top(8);
+    problems
+        .get_style_context()
+        .add_class(&gtk::STYLE_CLASS_ERROR);
+    // Only shown while there are problems
+    problems.set_no_show_all(true);
+    content_area.pack_start(&problems, false, false, 0);
+    content_area.set_border_width(10);
+
+    let settings_dialog = SettingsDialog(Rc::new(SettingsDialogInner {
+        rtmp_location,
+        rtmp_stream_key,
+        rtmp_username,
+        rtmp_password,
+        h264_encoder,
+        video_resolution,
+        video_framerate,
+        output_resolution,
+        encoder,
+        encoder_options,
+        encoder_option_combos: RefCell::new(Vec::new()),
+        encoder_controls: RefCell::new(None),
+        recordings_directory,
+        snapshots_directory,
+        stats_log_format,
+        stats_directory,
+        broadcast_delay,
+        background_kind,
+        background_color,
+        background_image,
+        camera,
+        camera_rotation,
+        camera_mirror,
+        output_mode,
+        local_recording,
+        extra_rtmp_locations,
+        extra_rtmp_stream_keys,
+        scene_transition,
+        slate_image,
+        camera_device,
+        camera_backend,
+        camera_format,
+        camera_framerate,
+        camera_decoding,
+        zero_copy,
+        test_sources,
+        projector_monitor,
+        audio_codec,
+        audio_bitrate,
+        overlay_bridge_port,
+        remote_port,
+        lan_preview_port,
+        remote_token,
+        profile_template,
+        bind_address,
+        proxy,
+        disk_warning_threshold,
+        disk_stop_threshold,
+        problems,
+        ui_scale,
+        high_contrast,
+        debug_hud_preview_only,
+        rtp_page,
+        srt_page,
+        whip_page,
+        simulcast_page,
+        rtsp_camera_page,
+        screen_page,
+        chroma_key_page,
+        audio_page,
+        audio_filters_page,
+        upload_page,
+        rotation_page,
+        schedule_page,
+        checklist_page,
+        chat_page,
+        midi_page,
+    }));
+
+    settings_dialog.update_encoder_options(&settings);
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog
+        .rtmp_location
+        .connect_property_text_notify(move |_| {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+        });
+
+    // Secrets are only stored once complete, not on every keystroke
+    for (_, entry) in settings_dialog.get_secret_entries() {
+        let settings_dialog_weak = settings_dialog.downgrade();
+        entry.connect_activate(move |_| {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.store_secrets();
+        });
+        let settings_dialog_weak = settings_dialog.downgrade();
+        entry.connect_focus_out_event(move |_, _| {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak, Inhibit(false));
+            settings_dialog.store_secrets();
+            Inhibit(false)
+        });
+    }
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog
+        .h264_encoder
+        .connect_property_text_notify(move |_| {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+        });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog
+        .broadcast_delay
+        .connect_value_changed(move |_| {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+        });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    let weak_app = app.downgrade();
+    settings_dialog.background_kind.connect_changed(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+        let app = upgrade_weak!(weak_app);
+        app.refresh_pipeline();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    let weak_app = app.downgrade();
+    settings_dialog
+        .background_color
+        .connect_color_set(move |_| {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+            let app = upgrade_weak!(weak_app);
+            app.refresh_pipeline();
+        });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    let weak_app = app.downgrade();
+    settings_dialog.background_image.connect_file_set(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+        let app = upgrade_weak!(weak_app);
+        app.refresh_pipeline();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    let weak_app = app.downgrade();
+    settings_dialog.slate_image.connect_file_set(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+        let app = upgrade_weak!(weak_app);
+        app.refresh_pipeline();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    let weak_app = app.downgrade();
+    settings_dialog.camera_rotation.connect_changed(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+        let app = upgrade_weak!(weak_app);
+        app.refresh_camera_orientation();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    let weak_app = app.downgrade();
+    settings_dialog.zero_copy.connect_toggled(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+        let app = upgrade_weak!(weak_app);
+        app.refresh_pipeline();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    let weak_app = app.downgrade();
+    settings_dialog.test_sources.connect_toggled(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+        let app = upgrade_weak!(weak_app);
+        app.refresh_pipeline();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    let weak_app = app.downgrade();
+    settings_dialog.projector_monitor.connect_changed(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+        let app = upgrade_weak!(weak_app);
+        app.refresh_projector();
+    });
+
+    // The outputs are only created when going live
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.output_resolution.connect_changed(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+        settings_dialog.update_resolution_hints();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    let weak_app = app.downgrade();
+    settings_dialog.camera_mirror.connect_toggled(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+        let app = upgrade_weak!(weak_app);
+        app.refresh_camera_orientation();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.output_mode.connect_changed(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.local_recording.connect_changed(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.audio_codec.connect_changed(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog
+        .audio_bitrate
+        .connect_value_changed(move |_| {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+        });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog
+        .overlay_bridge_port
+        .connect_value_changed(move |_| {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+        });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.remote_port.connect_value_changed(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog
+        .lan_preview_port
+        .connect_value_changed(move |_| {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+        });
+
+    if let Some(buffer) = settings_dialog.extra_rtmp_locations.get_buffer() {
+        let settings_dialog_weak = settings_dialog.downgrade();
+        buffer.connect_changed(move |_| {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+        });
+    }
+
+    for entry in &[
+        &settings_dialog.bind_address,
+        &settings_dialog.proxy,
+        &settings_dialog.remote_token,
+        &settings_dialog.rtmp_username,
+    ] {
+        let settings_dialog_weak = settings_dialog.downgrade();
+        entry.connect_property_text_notify(move |_| {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+        });
+    }
+
+    for spin in &[
+        &settings_dialog.scene_transition,
+        &settings_dialog.disk_warning_threshold,
+        &settings_dialog.disk_stop_threshold,
+    ] {
+        let settings_dialog_weak = settings_dialog.downgrade();
+        spin.connect_value_changed(move |_| {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+        });
+    }
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    let weak_app = app.downgrade();
+    settings_dialog.ui_scale.connect_value_changed(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+        let app = upgrade_weak!(weak_app);
+        app.refresh_appearance();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    let weak_app = app.downgrade();
+    settings_dialog.high_contrast.connect_toggled(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+        let app = upgrade_weak!(weak_app);
+        app.refresh_appearance();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog
+        .debug_hud_preview_only
+        .connect_toggled(move |_| {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+        });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog
+        .recordings_directory
+        .connect_file_set(move |_| {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+        });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog
+        .snapshots_directory
+        .connect_file_set(move |_| {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+        });
+
+    // The statistics are only written from the next time going live
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.stats_log_format.connect_changed(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.stats_directory.connect_file_set(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.encoder.connect_changed(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.update_encoder_options(&utils::load_settings());
+        settings_dialog.save_settings();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.profile_template.connect_changed(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    let weak_app = app.downgrade();
+    settings_dialog
+        .video_resolution
+        .connect_changed(move |combo| {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+            settings_dialog.update_resolution_hints();
+            // Typed sizes are only applied once done with the entry
+            if combo.get_active().is_some() {
+                let app = upgrade_weak!(weak_app);
+                app.refresh_pipeline();
+            }
+        });
+
+    if let Some(entry) = combo_entry(&settings_dialog.video_resolution) {
+        let weak_app = app.downgrade();
+        entry.connect_activate(move |_| {
+            let app = upgrade_weak!(weak_app);
+            app.refresh_pipeline();
+        });
+
+        let weak_app = app.downgrade();
+        entry.connect_focus_out_event(move |_, _| {
+            let app = upgrade_weak!(weak_app, Inhibit(false));
+            app.refresh_pipeline();
+            Inhibit(false)
+        });
+    }
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    let weak_app = app.downgrade();
+    settings_dialog.video_framerate.connect_changed(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+        let app = upgrade_weak!(weak_app);
+        app.refresh_pipeline();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    let weak_app = app.downgrade();
+    settings_dialog.camera_device.connect_changed(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+        settings_dialog.update_resolution_hints();
+        let app = upgrade_weak!(weak_app);
+        app.refresh_pipeline();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    let weak_app = app.downgrade();
+    settings_dialog.camera_backend.connect_changed(move |_| {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+        let app = upgrade_weak!(weak_app);
+        app.refresh_pipeline();
+    });
+
+    for combo in &[
+        &settings_dialog.camera_format,
+        &settings_dialog.camera_framerate,
+        &settings_dialog.camera_decoding,
+    ] {
+        let settings_dialog_weak = settings_dialog.downgrade();
+        let weak_app = app.downgrade();
+        combo.connect_changed(move |_| {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+            let app = upgrade_weak!(weak_app);
+            app.refresh_pipeline();
+        });
+    }
+
+    // The pages only report that something changed, saving is left to the dialog
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.rtp_page.connect_changed(move || {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.srt_page.connect_changed(move || {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.whip_page.connect_changed(move || {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.simulcast_page.connect_changed(move || {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.upload_page.connect_changed(move || {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.checklist_page.connect_changed(move || {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+    });
+
+    // These pages apply the changes to the running pipeline themselves once saved
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog
+        .rtsp_camera_page
+        .connect_changed(app, move || {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+        });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.screen_page.connect_changed(app, move || {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog
+        .chroma_key_page
+        .connect_changed(app, move || {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+        });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.audio_page.connect_changed(app, move || {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog
+        .audio_filters_page
+        .connect_changed(app, move || {
+            let settings_dialog = upgrade_weak!(settings_dialog_weak);
+            settings_dialog.save_settings();
+        });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.rotation_page.connect_changed(app, move || {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.schedule_page.connect_changed(app, move || {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+    });
+
+    let settings_dialog_weak = settings_dialog.downgrade();
+    settings_dialog.chat_page.connect_changed(app, move || {
+        let settings_dialog = upgrade_weak!(settings_dialog_weak);
+        settings_dialog.save_settings();
+    });
+
+    settings_dialog.show_problems(&validation::validate(&settings));
+    settings_dialog.update_resolution_hints();
+
+    // Close the dialog when the close button is clicked. We don't need to save the settings here
+    // as we already did that whenever the user changed something in the UI.
+    //
+    // The closure keeps the one and only strong reference to our settings dialog struct and it
+    // will be freed once the dialog is destroyed
+    let settings_dialog_storage = RefCell::new(Some(settings_dialog));
+    let weak_app = app.downgrade();
+    dialog.connect_response(move |dialog, _| {
+        if let Some(settings_dialog) = settings_dialog_storage.borrow().as_ref() {
+            settings_dialog.store_secrets();
+        }
+        dialog.destroy();
+
+        let _ = settings_dialog_storage.borrow_mut().take();
+        let app = upgrade_weak!(weak_app);
+        app.refresh_pipeline();
+    });
+
+    dialog.set_resizable(false);
+    dialog.show_all();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The values of the default settings as stored, changed by the given values
+    fn stored(changes: serde_json::Value) -> serde_json::Value {
+        let mut values = match serde_json::to_value(Settings::default()) {
+            Ok(serde_json::Value::Object(values)) => values,
+            _ => unreachable!(),
+        };
+        if let serde_json::Value::Object(changes) = changes {
+            for (name, value) in changes {
+                if value.is_null() {
+                    values.remove(&name);
+                } else {
+                    values.insert(name, value);
+                }
+            }
+        }
+        serde_json::Value::Object(values)
+    }
+
+    #[test]
+    fn migrate_unversioned() {
+        let stored = Settings::from_stored(stored(serde_json::json!({
+            "version": null,
+            "video_bitrate": 4000,
+            "video_resolution": "V1080P",
+            "output_resolution": "V480P",
+        })));
+
+        assert!(stored.migrated);
+        assert!(stored.reset.is_empty());
+        assert!(stored.problems.is_empty());
+        assert_eq!(stored.settings.version, SETTINGS_VERSION);
+        assert_eq!(stored.settings.video_bitrate, 4000);
+        assert_eq!(
+            stored.settings.video_resolution,
+            VideoResolution {
+                width: 1920,
+                height: 1080
+            }
+        );
+        assert_eq!(
+            stored.settings.output_resolution,
+            Some(VideoResolution {
+                width: 640,
+                height: 480
+            })
+        );
+        // The backup keeps the values as they were read
+        assert_eq!(stored.backup["video_resolution"], "V1080P");
+        assert!(stored.backup.get("version").is_none());
+    }
+
+    #[test]
+    fn current_version_is_not_migrated() {
+        let stored = Settings::from_stored(stored(serde_json::json!({})));
+
+        assert!(!stored.migrated);
+        assert!(stored.reset.is_empty());
+    }
+
+    #[test]
+    fn reset_unreadable_values() {
+        let stored = Settings::from_stored(stored(serde_json::json!({
+            "camera_framerate": "fast",
+            "video_bitrate": 4000,
+        })));
+
+        assert!(stored.migrated);
+        assert_eq!(stored.reset, vec!["camera_framerate".to_string()]);
+        assert_eq!(stored.settings.camera_framerate, default_camera_framerate());
+        assert_eq!(stored.settings.video_bitrate, 4000);
+    }
+
+    #[test]
+    fn keep_newer_version() {
+        let stored = Settings::from_stored(stored(serde_json::json!({
+            "version": SETTINGS_VERSION + 1,
+            "video_bitrate": 4000,
+            "setting_from_the_future": true,
+        })));
+
+        // Nothing is migrated or written back, the values known to this version are used
+        assert!(!stored.migrated);
+        assert!(stored.reset.is_empty());
+        assert_eq!(stored.settings.version, SETTINGS_VERSION + 1);
+        assert_eq!(stored.settings.video_bitrate, 4000);
+    }
+
+    #[test]
+    fn split_stream_keys() {
+        assert_eq!(
+            split_stream_key("rtmp://h/app/key"),
+            Some(("rtmp://h/app".to_string(), "key".to_string()))
+        );
+        assert_eq!(
+            split_stream_key("rtmps://live.example.com:443/live/app/key/"),
+            Some((
+                "rtmps://live.example.com:443/live/app".to_string(),
+                "key".to_string()
+            ))
+        );
+        // librtmp options stay with the server
+        assert_eq!(
+            split_stream_key("rtmp://h/app/key live=1 timeout=10"),
+            Some((
+                "rtmp://h/app live=1 timeout=10".to_string(),
+                "key".to_string()
+            ))
+        );
+        // Without application or key there is nothing to split
+        assert_eq!(split_stream_key("rtmp://h/app"), None);
+        assert_eq!(split_stream_key("rtmp://h"), None);
+        assert_eq!(split_stream_key("not a url"), None);
+    }
+
+    #[test]
+    fn join_stream_keys() {
+        assert_eq!(
+            with_stream_key("rtmp://h/app live=1", Some("key")),
+            "rtmp://h/app/key live=1"
+        );
+        assert_eq!(
+            with_stream_key("rtmp://h/app/", Some("key")),
+            "rtmp://h/app/key"
+        );
+        assert_eq!(
+            with_stream_key("rtmp://h/app/key", None),
+            "rtmp://h/app/key"
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use gtk::{self, prelude::*};
+
+use crate::app::App;
+use crate::scenes;
+use crate::templates;
+
+use std::rc::Rc;
+
+use super::{page_grid, RotationSettings, Settings};
+
+// Automatic rotation through overlay templates and scenes
+pub struct RotationPage {
+    grid: gtk::Grid,
+    enabled: gtk::CheckButton,
+    interval: gtk::SpinButton,
+    templates: Vec<(std::string::String, gtk::CheckButton)>,
+    scenes: Vec<(&'static str, gtk::CheckButton)>,
+}
+
+impl RotationPage {
+    pub fn new(settings: &Settings) -> Self {
+        let grid = page_grid();
+
+        let enabled = gtk::CheckButton::new_with_label("Rotate overlay templates and scenes");
+        enabled.set_active(settings.rotation.enabled);
+        grid.attach(&enabled, 0, 0, 4, 1);
+
+        let interval_label = gtk::Label::new(Some("Seconds per step"));
+        let interval = gtk::SpinButton::new_with_range(5.0, 3600.0, 5.0);
+        interval.set_value(f64::from(settings.rotation.interval));
+        interval.set_hexpand(true);
+
+        interval_label.set_halign(gtk::Align::Start);
+
+        grid.attach(&interval_label, 0, 1, 1, 1);
+        grid.attach(&interval, 1, 1, 3, 1);
+
+        let mut template_checks = Vec::new();
+        for (row, name) in templates::list_names().into_iter().enumerate() {
+            let check = gtk::CheckButton::new_with_label(&name);
+            check.set_active(settings.rotation.templates.contains(&name));
+            grid.attach(&check, 0, row as i32 + 2, 4, 1);
+            template_checks.push((name, check));
+        }
+        if template_checks.is_empty() {
+            let label = gtk::Label::new(Some("Install templates from the gallery to rotate them"));
+            label.set_halign(gtk::Align::Start);
+            grid.attach(&label, 0, 2, 4, 1);
+        }
+
+        let first_scene_row = template_checks.len().max(1) as i32 + 2;
+        let mut scene_checks = Vec::new();
+        for (row, scene) in scenes::SCENES.iter().enumerate() {
+            let check = gtk::CheckButton::new_with_label(&format!("Scene: {}", scene.label));
+            check.set_active(settings.rotation.scenes.iter().any(|s| s == scene.name));
+            grid.attach(&check, 0, first_scene_row + row as i32, 4, 1);
+            scene_checks.push((scene.name, check));
+        }
+
+        RotationPage {
+            grid,
+            enabled,
+            interval,
+            templates: template_checks,
+            scenes: scene_checks,
+        }
+    }
+
+    pub fn get_widget(&self) -> &gtk::Grid {
+        &self.grid
+    }
+
+    // Take the values of the page into the settings
+    pub fn update_settings(&self, settings: &mut Settings) {
+        settings.rotation = RotationSettings {
+            enabled: self.enabled.get_active(),
+            interval: self.interval.get_value_as_int() as u32,
+            templates: self
+                .templates
+                .iter()
+                .filter(|(_, check)| check.get_active())
+                .map(|(name, _)| name.clone())
+                .collect(),
+            scenes: self
+                .scenes
+                .iter()
+                .filter(|(_, check)| check.get_active())
+                .map(|(name, _)| name.to_string())
+                .collect(),
+        };
+    }
+
+    // Save the settings with the given function whenever something on the page was changed and
+    // restart the rotation with them
+    pub fn connect_changed<F: Fn() + 'static>(&self, app: &App, save: F) {
+        let weak_app = app.downgrade();
+        let changed = Rc::new(move || {
+            save();
+            let app = upgrade_weak!(weak_app);
+            app.refresh_rotation();
+        });
+
+        let checks = self
+            .templates
+            .iter()
+            .map(|(_, check)| check)
+            .chain(self.scenes.iter().map(|(_, check)| check))
+            .chain(std::iter::once(&self.enabled));
+        for check in checks {
+            let changed = changed.clone();
+            check.connect_toggled(move |_| changed());
+        }
+
+        self.interval.connect_value_changed(move |_| changed());
+    }
+}
@@ -0,0 +1,92 @@
+use gtk::{self, prelude::*};
+
+use std::rc::Rc;
+
+use super::{entry_text, page_grid, RtpSettings, Settings};
+
+// RTP contribution output
+pub struct RtpPage {
+    grid: gtk::Grid,
+    host: gtk::Entry,
+    video_port: gtk::SpinButton,
+    audio_port: gtk::SpinButton,
+    sdp_file: gtk::Entry,
+}
+
+impl RtpPage {
+    pub fn new(settings: &Settings) -> Self {
+        let grid = page_grid();
+
+        let host_label = gtk::Label::new(Some("Receiver host"));
+        let host = gtk::Entry::new();
+        host.set_text(&settings.rtp.host);
+        host.set_hexpand(true);
+
+        let video_port_label = gtk::Label::new(Some("Video port"));
+        let video_port = gtk::SpinButton::new_with_range(1024.0, 65534.0, 2.0);
+        video_port.set_value(f64::from(settings.rtp.video_port));
+
+        let audio_port_label = gtk::Label::new(Some("Audio port"));
+        let audio_port = gtk::SpinButton::new_with_range(1024.0, 65534.0, 2.0);
+        audio_port.set_value(f64::from(settings.rtp.audio_port));
+
+        let sdp_file_label = gtk::Label::new(Some("Write SDP file to"));
+        let sdp_file = gtk::Entry::new();
+        if let Some(ref path) = settings.rtp.sdp_file {
+            sdp_file.set_text(path);
+        }
+        sdp_file.set_placeholder_text(Some("Not written if empty"));
+
+        for (row, (label, widget)) in [
+            (&host_label, host.clone().upcast::<gtk::Widget>()),
+            (&video_port_label, video_port.clone().upcast()),
+            (&audio_port_label, audio_port.clone().upcast()),
+            (&sdp_file_label, sdp_file.clone().upcast()),
+        ]
+        .iter()
+        .enumerate()
+        {
+            label.set_halign(gtk::Align::Start);
+            grid.attach(*label, 0, row as i32, 1, 1);
+            grid.attach(widget, 1, row as i32, 3, 1);
+        }
+
+        RtpPage {
+            grid,
+            host,
+            video_port,
+            audio_port,
+            sdp_file,
+        }
+    }
+
+    pub fn get_widget(&self) -> &gtk::Grid {
+        &self.grid
+    }
+
+    // Take the values of the page into the settings
+    pub fn update_settings(&self, settings: &mut Settings) {
+        settings.rtp = RtpSettings {
+            host: entry_text(&self.host).unwrap_or_default(),
+            video_port: self.video_port.get_value_as_int() as u16,
+            audio_port: self.audio_port.get_value_as_int() as u16,
+            sdp_file: entry_text(&self.sdp_file),
+        };
+    }
+
+    // Call the function whenever something on the page was changed. The output is only created
+    // when going live
+    pub fn connect_changed<F: Fn() + 'static>(&self, changed: F) {
+        let changed = Rc::new(changed);
+
+        for entry in &[&self.host, &self.sdp_file] {
+            let changed = changed.clone();
+            entry.connect_property_text_notify(move |_| changed());
+        }
+
+        for spin in &[&self.video_port, &self.audio_port] {
+            let changed = changed.clone();
+            spin.connect_value_changed(move |_| changed());
+        }
+    }
+}
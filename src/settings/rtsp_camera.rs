@@ -0,0 +1,135 @@
+use gtk::{self, prelude::*};
+
+use crate::app::App;
+use crate::keyring::Secret;
+
+use std::rc::Rc;
+
+use super::{entry_text, page_grid, RtspCameraSettings, Settings};
+
+// Network camera used instead of the local one
+pub struct RtspCameraPage {
+    grid: gtk::Grid,
+    enabled: gtk::CheckButton,
+    url: gtk::Entry,
+    latency: gtk::SpinButton,
+    username: gtk::Entry,
+    password: gtk::Entry,
+}
+
+impl RtspCameraPage {
+    pub fn new(settings: &Settings) -> Self {
+        let grid = page_grid();
+
+        let enabled = gtk::CheckButton::new_with_label("Use a network camera");
+        enabled.set_active(settings.rtsp_camera.enabled);
+        enabled.set_tooltip_text(Some(
+            "Instead of the local camera selected on the General page",
+        ));
+        grid.attach(&enabled, 0, 0, 4, 1);
+
+        let url_label = gtk::Label::new(Some("RTSP URL"));
+        let url = gtk::Entry::new();
+        if let Some(ref value) = settings.rtsp_camera.url {
+            url.set_text(value);
+        }
+        url.set_placeholder_text(Some("rtsp://host:554/stream"));
+        url.set_hexpand(true);
+
+        let latency_label = gtk::Label::new(Some("Latency (ms)"));
+        let latency = gtk::SpinButton::new_with_range(0.0, 5000.0, 10.0);
+        latency.set_value(f64::from(settings.rtsp_camera.latency));
+
+        let username_label = gtk::Label::new(Some("Username"));
+        let username = gtk::Entry::new();
+        if let Some(ref value) = settings.rtsp_camera.username {
+            username.set_text(value);
+        }
+
+        let password_label = gtk::Label::new(Some("Password"));
+        let password = gtk::Entry::new();
+        if let Some(ref value) = settings.rtsp_camera.password {
+            password.set_text(value);
+        }
+        password.set_visibility(false);
+
+        for (row, (label, widget)) in [
+            (&url_label, url.clone().upcast::<gtk::Widget>()),
+            (&latency_label, latency.clone().upcast()),
+            (&username_label, username.clone().upcast()),
+            (&password_label, password.clone().upcast()),
+        ]
+        .iter()
+        .enumerate()
+        {
+            label.set_halign(gtk::Align::Start);
+            grid.attach(*label, 0, row as i32 + 1, 1, 1);
+            grid.attach(widget, 1, row as i32 + 1, 3, 1);
+        }
+
+        RtspCameraPage {
+            grid,
+            enabled,
+            url,
+            latency,
+            username,
+            password,
+        }
+    }
+
+    pub fn get_widget(&self) -> &gtk::Grid {
+        &self.grid
+    }
+
+    // Take the values of the page into the settings
+    pub fn update_settings(&self, settings: &mut Settings) {
+        settings.rtsp_camera = RtspCameraSettings {
+            enabled: self.enabled.get_active(),
+            url: entry_text(&self.url),
+            latency: self.latency.get_value_as_int() as u32,
+            username: entry_text(&self.username),
+            password: entry_text(&self.password),
+        };
+    }
+
+    // Entries of the page whose values are kept in the keyring
+    pub fn get_secret_entries(&self) -> Vec<(Secret, &gtk::Entry)> {
+        vec![(Secret::RtspPassword, &self.password)]
+    }
+
+    // Save the settings with the given function whenever something on the page was changed and
+    // reconnect the camera
+    pub fn connect_changed<F: Fn() + 'static>(&self, app: &App, save: F) {
+        let save = Rc::new(save);
+
+        // The camera is only reconnected once the settings are complete, not on every keystroke
+        for entry in &[&self.url, &self.username, &self.password] {
+            let save = save.clone();
+            entry.connect_property_text_notify(move |_| save());
+
+            let weak_app = app.downgrade();
+            entry.connect_activate(move |_| {
+                let app = upgrade_weak!(weak_app);
+                app.refresh_pipeline();
+            });
+
+            let weak_app = app.downgrade();
+            entry.connect_focus_out_event(move |_, _| {
+                let app = upgrade_weak!(weak_app, Inhibit(false));
+                app.refresh_pipeline();
+                Inhibit(false)
+            });
+        }
+
+        let weak_app = app.downgrade();
+        let changed = Rc::new(move || {
+            save();
+            let app = upgrade_weak!(weak_app);
+            app.refresh_pipeline();
+        });
+
+        let changed_clone = changed.clone();
+        self.enabled.connect_toggled(move |_| changed_clone());
+        self.latency.connect_value_changed(move |_| changed());
+    }
+}
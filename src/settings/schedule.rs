@@ -0,0 +1,117 @@
+use gtk::{self, prelude::*};
+
+use crate::app::App;
+use crate::scheduler;
+
+use std::rc::Rc;
+
+use super::{entry_text, page_grid, set_problem_hint, ScheduleSettings, Settings};
+
+// Scheduled start and end of the stream
+pub struct SchedulePage {
+    grid: gtk::Grid,
+    enabled: gtk::CheckButton,
+    start: gtk::Entry,
+    end: gtk::Entry,
+    countdown: gtk::SpinButton,
+}
+
+impl SchedulePage {
+    pub fn new(settings: &Settings) -> Self {
+        let grid = page_grid();
+
+        let enabled = gtk::CheckButton::new_with_label("Go live automatically");
+        enabled.set_active(settings.schedule.enabled);
+        grid.attach(&enabled, 0, 0, 4, 1);
+
+        let start_label = gtk::Label::new(Some("Start"));
+        let start = gtk::Entry::new();
+        start.set_text(&settings.schedule.start);
+        start.set_placeholder_text(Some("YYYY-MM-DD HH:MM"));
+        start.set_hexpand(true);
+
+        let end_label = gtk::Label::new(Some("End"));
+        let end = gtk::Entry::new();
+        if let Some(ref time) = settings.schedule.end {
+            end.set_text(time);
+        }
+        end.set_placeholder_text(Some("Keep streaming until stopped if empty"));
+
+        let countdown_label = gtk::Label::new(Some("Countdown (minutes)"));
+        let countdown = gtk::SpinButton::new_with_range(0.0, 120.0, 1.0);
+        countdown.set_value(f64::from(settings.schedule.countdown));
+        countdown.set_tooltip_text(Some(
+            "Runs the countdown in the overlay before the start, 0 for none",
+        ));
+
+        for (row, (label, widget)) in [
+            (&start_label, start.clone().upcast::<gtk::Widget>()),
+            (&end_label, end.clone().upcast::<gtk::Widget>()),
+            (&countdown_label, countdown.clone().upcast::<gtk::Widget>()),
+        ]
+        .iter()
+        .enumerate()
+        {
+            label.set_halign(gtk::Align::Start);
+            grid.attach(*label, 0, row as i32 + 1, 1, 1);
+            grid.attach(widget, 1, row as i32 + 1, 3, 1);
+        }
+
+        SchedulePage {
+            grid,
+            enabled,
+            start,
+            end,
+            countdown,
+        }
+    }
+
+    pub fn get_widget(&self) -> &gtk::Grid {
+        &self.grid
+    }
+
+    // Take the values of the page into the settings
+    pub fn update_settings(&self, settings: &mut Settings) {
+        settings.schedule = ScheduleSettings {
+            enabled: self.enabled.get_active(),
+            start: entry_text(&self.start).unwrap_or_default(),
+            end: entry_text(&self.end),
+            countdown: self.countdown.get_value_as_int() as u32,
+        };
+    }
+
+    // Save the settings with the given function whenever something on the page was changed and
+    // schedule the stream again with them. Times that can't be read are pointed out
+    pub fn connect_changed<F: Fn() + 'static>(&self, app: &App, save: F) {
+        let save = Rc::new(save);
+
+        for entry in &[&self.start, &self.end] {
+            let save = save.clone();
+            let weak_app = app.downgrade();
+            entry.connect_property_text_notify(move |entry| {
+                save();
+                let text = entry_text(entry);
+                let valid = text
+                    .as_deref()
+                    .is_none_or(|text| scheduler::parse_date_time(text).is_some());
+                set_problem_hint(
+                    entry,
+                    Some("Please enter the date and time as YYYY-MM-DD HH:MM").filter(|_| !valid),
+                );
+                let app = upgrade_weak!(weak_app);
+                app.refresh_schedule();
+            });
+        }
+
+        let weak_app = app.downgrade();
+        let changed = Rc::new(move || {
+            save();
+            let app = upgrade_weak!(weak_app);
+            app.refresh_schedule();
+        });
+
+        let changed_clone = changed.clone();
+        self.enabled.connect_toggled(move |_| changed_clone());
+        self.countdown.connect_value_changed(move |_| changed());
+    }
+}
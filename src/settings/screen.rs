@@ -0,0 +1,128 @@
+use gtk::{self, prelude::*};
+
+use crate::app::App;
+
+use std::rc::Rc;
+
+use super::{entry_text, page_grid, ScreenSettings, ScreenSource, Settings};
+
+// Screen capture layer
+pub struct ScreenPage {
+    grid: gtk::Grid,
+    enabled: gtk::CheckButton,
+    source: gtk::ComboBoxText,
+    pipewire_path: gtk::Entry,
+    x: gtk::SpinButton,
+    y: gtk::SpinButton,
+    width: gtk::SpinButton,
+    height: gtk::SpinButton,
+}
+
+impl ScreenPage {
+    pub fn new(settings: &Settings) -> Self {
+        let grid = page_grid();
+
+        let enabled = gtk::CheckButton::new_with_label("Capture the screen");
+        enabled.set_active(settings.screen.enabled);
+        grid.attach(&enabled, 0, 0, 4, 1);
+
+        let source_label = gtk::Label::new(Some("Source"));
+        let source = gtk::ComboBoxText::new();
+        source.append(Some(ScreenSource::X11.id()), "X11");
+        source.append(Some(ScreenSource::PipeWire.id()), "PipeWire");
+        source.set_active_id(Some(settings.screen.source.id()));
+
+        let pipewire_path_label = gtk::Label::new(Some("PipeWire node"));
+        let pipewire_path = gtk::Entry::new();
+        if let Some(ref path) = settings.screen.pipewire_path {
+            pipewire_path.set_text(path);
+        }
+        pipewire_path.set_placeholder_text(Some("Default stream if empty"));
+        pipewire_path.set_hexpand(true);
+
+        // The layout is given in percent of the output size
+        let percent_spin = |value: f64| {
+            let spin = gtk::SpinButton::new_with_range(0.0, 100.0, 1.0);
+            spin.set_value(value * 100.0);
+            spin
+        };
+        let x_label = gtk::Label::new(Some("Left (%)"));
+        let x = percent_spin(settings.screen.x);
+        let y_label = gtk::Label::new(Some("Top (%)"));
+        let y = percent_spin(settings.screen.y);
+        let width_label = gtk::Label::new(Some("Width (%)"));
+        let width = percent_spin(settings.screen.width);
+        let height_label = gtk::Label::new(Some("Height (%)"));
+        let height = percent_spin(settings.screen.height);
+
+        for (row, (label, widget)) in [
+            (&source_label, source.clone().upcast::<gtk::Widget>()),
+            (&pipewire_path_label, pipewire_path.clone().upcast()),
+            (&x_label, x.clone().upcast()),
+            (&y_label, y.clone().upcast()),
+            (&width_label, width.clone().upcast()),
+            (&height_label, height.clone().upcast()),
+        ]
+        .iter()
+        .enumerate()
+        {
+            label.set_halign(gtk::Align::Start);
+            grid.attach(*label, 0, row as i32 + 1, 1, 1);
+            grid.attach(widget, 1, row as i32 + 1, 3, 1);
+        }
+
+        ScreenPage {
+            grid,
+            enabled,
+            source,
+            pipewire_path,
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn get_widget(&self) -> &gtk::Grid {
+        &self.grid
+    }
+
+    // Take the values of the page into the settings
+    pub fn update_settings(&self, settings: &mut Settings) {
+        settings.screen = ScreenSettings {
+            enabled: self.enabled.get_active(),
+            source: ScreenSource::from(self.source.get_active_id()),
+            pipewire_path: entry_text(&self.pipewire_path),
+            x: self.x.get_value() / 100.0,
+            y: self.y.get_value() / 100.0,
+            width: self.width.get_value() / 100.0,
+            height: self.height.get_value() / 100.0,
+        };
+    }
+
+    // Save the settings with the given function whenever something on the page was changed and
+    // update the capture layer. The node is only taken once done with the entry
+    pub fn connect_changed<F: Fn() + 'static>(&self, app: &App, save: F) {
+        let weak_app = app.downgrade();
+        let changed = Rc::new(move || {
+            save();
+            let app = upgrade_weak!(weak_app);
+            app.refresh_pipeline();
+        });
+
+        let changed_clone = changed.clone();
+        self.enabled.connect_toggled(move |_| changed_clone());
+
+        let changed_clone = changed.clone();
+        self.source.connect_changed(move |_| changed_clone());
+
+        let changed_clone = changed.clone();
+        self.pipewire_path
+            .connect_activate(move |_| changed_clone());
+
+        for spin in &[&self.x, &self.y, &self.width, &self.height] {
+            let changed = changed.clone();
+            spin.connect_value_changed(move |_| changed());
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use gtk::{self, prelude::*};
+
+use crate::validation;
+
+use std::rc::Rc;
+
+use super::{
+    entry_text, page_grid, resolution_combo, set_combo_entry_text, set_problem_hint, Rendition,
+    Settings, SimulcastSettings,
+};
+
+// Simulcast ladder, one row per rendition
+pub struct SimulcastPage {
+    grid: gtk::Grid,
+    enabled: gtk::CheckButton,
+    resolutions: Vec<gtk::ComboBoxText>,
+    bitrates: Vec<gtk::SpinButton>,
+    locations: Vec<gtk::Entry>,
+}
+
+impl SimulcastPage {
+    pub fn new(settings: &Settings) -> Self {
+        let grid = page_grid();
+
+        let enabled =
+            gtk::CheckButton::new_with_label("Also stream lower renditions of the program");
+        enabled.set_active(settings.simulcast.enabled);
+        enabled.set_tooltip_text(Some(
+            "Every rendition is encoded separately, so each one adds to the CPU or GPU load",
+        ));
+        grid.attach(&enabled, 0, 0, 3, 1);
+
+        for (column, title) in ["Resolution", "Bitrate (kbit/s)", "RTMP server and key"]
+            .iter()
+            .enumerate()
+        {
+            let label = gtk::Label::new(Some(title));
+            label.set_halign(gtk::Align::Start);
+            grid.attach(&label, column as i32, 1, 1, 1);
+        }
+
+        let default_renditions = SimulcastSettings::default().renditions;
+        let mut resolutions = Vec::new();
+        let mut bitrates = Vec::new();
+        let mut locations = Vec::new();
+        for i in 0..SimulcastSettings::MAX_RENDITIONS {
+            let rendition = settings
+                .simulcast
+                .renditions
+                .get(i)
+                .or_else(|| default_renditions.get(i))
+                .cloned()
+                .unwrap_or_default();
+
+            let resolution = resolution_combo();
+            set_combo_entry_text(&resolution, &rendition.resolution.to_string());
+
+            let bitrate = gtk::SpinButton::new_with_range(250.0, 50000.0, 250.0);
+            bitrate.set_value(f64::from(rendition.video_bitrate));
+
+            let location = gtk::Entry::new();
+            if let Some(ref url) = rendition.location {
+                location.set_text(url);
+            }
+            location.set_placeholder_text(Some("Not sent if empty"));
+            location.set_hexpand(true);
+
+            let row = i as i32 + 2;
+            grid.attach(&resolution, 0, row, 1, 1);
+            grid.attach(&bitrate, 1, row, 1, 1);
+            grid.attach(&location, 2, row, 1, 1);
+
+            resolutions.push(resolution);
+            bitrates.push(bitrate);
+            locations.push(location);
+        }
+
+        SimulcastPage {
+            grid,
+            enabled,
+            resolutions,
+            bitrates,
+            locations,
+        }
+    }
+
+    pub fn get_widget(&self) -> &gtk::Grid {
+        &self.grid
+    }
+
+    // Take the values of the page into the settings. Sizes still being typed keep the previous one
+    pub fn update_settings(&self, settings: &mut Settings) {
+        let renditions = self
+            .resolutions
+            .iter()
+            .zip(&self.bitrates)
+            .zip(&self.locations)
+            .enumerate()
+            .map(|(i, ((resolution, bitrate), location))| Rendition {
+                resolution: resolution
+                    .get_active_text()
+                    .and_then(|text| text.parse().ok())
+                    .or_else(|| {
+                        settings
+                            .simulcast
+                            .renditions
+                            .get(i)
+                            .map(|rendition| rendition.resolution)
+                    })
+                    .unwrap_or_default(),
+                video_bitrate: bitrate.get_value_as_int() as u32,
+                location: entry_text(location),
+            })
+            .collect();
+
+        settings.simulcast = SimulcastSettings {
+            enabled: self.enabled.get_active(),
+            renditions,
+        };
+    }
+
+    // Mark the end-points that are not valid RTMP URLs with the problem described by the message,
+    // or clear the marks
+    pub fn show_problem(&self, message: Option<&str>) {
+        for entry in &self.locations {
+            let invalid = entry_text(entry)
+                .is_some_and(|location| validation::check_rtmp_url(&location).is_err());
+            set_problem_hint(entry, message.filter(|_| invalid));
+        }
+    }
+
+    // Call the function whenever something on the page was changed. The renditions are only sent
+    // from the next time going live
+    pub fn connect_changed<F: Fn() + 'static>(&self, changed: F) {
+        let changed = Rc::new(changed);
+
+        let changed_clone = changed.clone();
+        self.enabled.connect_toggled(move |_| changed_clone());
+
+        for combo in &self.resolutions {
+            let changed = changed.clone();
+            combo.connect_changed(move |_| changed());
+        }
+
+        for spin in &self.bitrates {
+            let changed = changed.clone();
+            spin.connect_value_changed(move |_| changed());
+        }
+
+        for entry in &self.locations {
+            let changed = changed.clone();
+            entry.connect_property_text_notify(move |_| changed());
+        }
+    }
+}
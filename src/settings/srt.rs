@@ -0,0 +1,97 @@
+use gtk::{self, prelude::*};
+
+use crate::keyring::Secret;
+
+use std::rc::Rc;
+
+use super::{entry_text, page_grid, set_problem_hint, Settings, SrtSettings};
+
+// SRT output
+pub struct SrtPage {
+    grid: gtk::Grid,
+    uri: gtk::Entry,
+    latency: gtk::SpinButton,
+    passphrase: gtk::Entry,
+}
+
+impl SrtPage {
+    pub fn new(settings: &Settings) -> Self {
+        let grid = page_grid();
+
+        let uri_label = gtk::Label::new(Some("SRT URI"));
+        let uri = gtk::Entry::new();
+        if let Some(ref value) = settings.srt.uri {
+            uri.set_text(value);
+        }
+        uri.set_placeholder_text(Some("srt://host:port?streamid=..."));
+        uri.set_hexpand(true);
+
+        let latency_label = gtk::Label::new(Some("Latency (ms)"));
+        let latency = gtk::SpinButton::new_with_range(20.0, 8000.0, 5.0);
+        latency.set_value(f64::from(settings.srt.latency));
+
+        let passphrase_label = gtk::Label::new(Some("Passphrase"));
+        let passphrase = gtk::Entry::new();
+        if let Some(ref value) = settings.srt.passphrase {
+            passphrase.set_text(value);
+        }
+        passphrase.set_placeholder_text(Some("Unencrypted if empty"));
+        passphrase.set_visibility(false);
+
+        for (row, (label, widget)) in [
+            (&uri_label, uri.clone().upcast::<gtk::Widget>()),
+            (&latency_label, latency.clone().upcast()),
+            (&passphrase_label, passphrase.clone().upcast()),
+        ]
+        .iter()
+        .enumerate()
+        {
+            label.set_halign(gtk::Align::Start);
+            grid.attach(*label, 0, row as i32, 1, 1);
+            grid.attach(widget, 1, row as i32, 3, 1);
+        }
+
+        SrtPage {
+            grid,
+            uri,
+            latency,
+            passphrase,
+        }
+    }
+
+    pub fn get_widget(&self) -> &gtk::Grid {
+        &self.grid
+    }
+
+    // Take the values of the page into the settings
+    pub fn update_settings(&self, settings: &mut Settings) {
+        settings.srt = SrtSettings {
+            uri: entry_text(&self.uri),
+            latency: self.latency.get_value_as_int() as u32,
+            passphrase: entry_text(&self.passphrase),
+        };
+    }
+
+    // Entries of the page whose values are kept in the keyring
+    pub fn get_secret_entries(&self) -> Vec<(Secret, &gtk::Entry)> {
+        vec![(Secret::SrtPassphrase, &self.passphrase)]
+    }
+
+    // Mark the URI as having the problem described by the message, or clear the mark
+    pub fn show_problem(&self, message: Option<&str>) {
+        set_problem_hint(&self.uri, message);
+    }
+
+    // Call the function whenever something on the page was changed. The output is only created
+    // when going live
+    pub fn connect_changed<F: Fn() + 'static>(&self, changed: F) {
+        let changed = Rc::new(changed);
+
+        for entry in &[&self.uri, &self.passphrase] {
+            let changed = changed.clone();
+            entry.connect_property_text_notify(move |_| changed());
+        }
+
+        self.latency.connect_value_changed(move |_| changed());
+    }
+}
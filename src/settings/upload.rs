@@ -0,0 +1,110 @@
+use gtk::{self, prelude::*};
+
+use crate::keyring::Secret;
+
+use std::rc::Rc;
+
+use super::{entry_text, page_grid, Settings, UploadDestination, UploadSettings};
+
+// Where finished recordings are uploaded to
+pub struct UploadPage {
+    grid: gtk::Grid,
+    destination: gtk::ComboBoxText,
+    url: gtk::Entry,
+    region: gtk::Entry,
+    username: gtk::Entry,
+    secret: gtk::Entry,
+}
+
+impl UploadPage {
+    pub fn new(settings: &Settings) -> Self {
+        let grid = page_grid();
+
+        let destination_label = gtk::Label::new(Some("Upload recordings to"));
+        let destination = gtk::ComboBoxText::new();
+        destination.append(Some(UploadDestination::Disabled.id()), "Nowhere");
+        destination.append(Some(UploadDestination::S3.id()), "S3-compatible bucket");
+        destination.append(Some(UploadDestination::Sftp.id()), "SFTP server");
+        destination.append(Some(UploadDestination::YouTube.id()), "YouTube");
+        destination.set_active_id(Some(settings.upload.destination.id()));
+        destination.set_hexpand(true);
+
+        destination_label.set_halign(gtk::Align::Start);
+
+        grid.attach(&destination_label, 0, 0, 1, 1);
+        grid.attach(&destination, 1, 0, 3, 1);
+
+        let mut entries = Vec::new();
+        for (row, (label, value)) in [
+            ("Bucket or SFTP URL", &settings.upload.url),
+            ("S3 region", &settings.upload.region),
+            ("Access key or user name", &settings.upload.username),
+            ("Secret key, password or token", &settings.upload.secret),
+        ]
+        .iter()
+        .enumerate()
+        {
+            let label = gtk::Label::new(Some(*label));
+            let entry = gtk::Entry::new();
+            if let Some(value) = value {
+                entry.set_text(value);
+            }
+
+            label.set_halign(gtk::Align::Start);
+
+            grid.attach(&label, 0, row as i32 + 1, 1, 1);
+            grid.attach(&entry, 1, row as i32 + 1, 3, 1);
+            entries.push(entry);
+        }
+        let secret = entries.pop().unwrap();
+        let username = entries.pop().unwrap();
+        let region = entries.pop().unwrap();
+        let url = entries.pop().unwrap();
+        secret.set_visibility(false);
+        secret.set_tooltip_text(Some(
+            "Stored in the keyring of the session instead of the settings file",
+        ));
+
+        UploadPage {
+            grid,
+            destination,
+            url,
+            region,
+            username,
+            secret,
+        }
+    }
+
+    pub fn get_widget(&self) -> &gtk::Grid {
+        &self.grid
+    }
+
+    // Take the values of the page into the settings
+    pub fn update_settings(&self, settings: &mut Settings) {
+        settings.upload = UploadSettings {
+            destination: UploadDestination::from(self.destination.get_active_id()),
+            url: entry_text(&self.url),
+            region: entry_text(&self.region),
+            username: entry_text(&self.username),
+            secret: entry_text(&self.secret),
+        };
+    }
+
+    // Entries of the page whose values are kept in the keyring
+    pub fn get_secret_entries(&self) -> Vec<(Secret, &gtk::Entry)> {
+        vec![(Secret::UploadCredential, &self.secret)]
+    }
+
+    // Call the function whenever something on the page was changed. The secret is stored
+    // separately once complete
+    pub fn connect_changed<F: Fn() + 'static>(&self, changed: F) {
+        let changed = Rc::new(changed);
+
+        for entry in &[&self.url, &self.region, &self.username] {
+            let changed = changed.clone();
+            entry.connect_property_text_notify(move |_| changed());
+        }
+
+        self.destination.connect_changed(move |_| changed());
+    }
+}
@@ -0,0 +1,87 @@
+use gtk::{self, prelude::*};
+
+use crate::keyring::Secret;
+
+use std::rc::Rc;
+
+use super::{entry_text, page_grid, set_problem_hint, Settings, WhipSettings};
+
+// WebRTC output to a WHIP endpoint
+pub struct WhipPage {
+    grid: gtk::Grid,
+    endpoint: gtk::Entry,
+    auth_token: gtk::Entry,
+}
+
+impl WhipPage {
+    pub fn new(settings: &Settings) -> Self {
+        let grid = page_grid();
+
+        let endpoint_label = gtk::Label::new(Some("WHIP endpoint"));
+        let endpoint = gtk::Entry::new();
+        if let Some(ref url) = settings.whip.endpoint {
+            endpoint.set_text(url);
+        }
+        endpoint.set_placeholder_text(Some("https://sfu.example.com/whip/endpoint"));
+        endpoint.set_hexpand(true);
+
+        let auth_token_label = gtk::Label::new(Some("Bearer token"));
+        let auth_token = gtk::Entry::new();
+        if let Some(ref token) = settings.whip.auth_token {
+            auth_token.set_text(token);
+        }
+        auth_token.set_placeholder_text(Some("No authentication if empty"));
+        auth_token.set_visibility(false);
+
+        for (row, (label, widget)) in [
+            (&endpoint_label, endpoint.clone()),
+            (&auth_token_label, auth_token.clone()),
+        ]
+        .iter()
+        .enumerate()
+        {
+            label.set_halign(gtk::Align::Start);
+            grid.attach(*label, 0, row as i32, 1, 1);
+            grid.attach(widget, 1, row as i32, 3, 1);
+        }
+
+        WhipPage {
+            grid,
+            endpoint,
+            auth_token,
+        }
+    }
+
+    pub fn get_widget(&self) -> &gtk::Grid {
+        &self.grid
+    }
+
+    // Take the values of the page into the settings
+    pub fn update_settings(&self, settings: &mut Settings) {
+        settings.whip = WhipSettings {
+            endpoint: entry_text(&self.endpoint),
+            auth_token: entry_text(&self.auth_token),
+        };
+    }
+
+    // Entries of the page whose values are kept in the keyring
+    pub fn get_secret_entries(&self) -> Vec<(Secret, &gtk::Entry)> {
+        vec![(Secret::WhipToken, &self.auth_token)]
+    }
+
+    // Mark the endpoint as having the problem described by the message, or clear the mark
+    pub fn show_problem(&self, message: Option<&str>) {
+        set_problem_hint(&self.endpoint, message);
+    }
+
+    // Call the function whenever something on the page was changed. The output is only created
+    // when going live
+    pub fn connect_changed<F: Fn() + 'static>(&self, changed: F) {
+        let changed = Rc::new(changed);
+
+        for entry in &[&self.endpoint, &self.auth_token] {
+            let changed = changed.clone();
+            entry.connect_property_text_notify(move |_| changed());
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use gtk::{self, prelude::*};
+
+use crate::app::{Action, RecordState};
+use crate::scenes;
+
+// Detailed name of the action switching to the nth scene, counted from 1
+pub fn scene_action_name(number: i32) -> String {
+    format!("{}({})", Action::SwitchScene(number).full_name(), number)
+}
+
+// Groups of shortcuts shown in the window, with the detailed names of their actions
+fn shortcut_groups() -> Vec<(&'static str, Vec<(String, String)>)> {
+    let action = |action: Action, title: &str| (action.full_name().to_string(), title.to_string());
+
+    vec![
+        (
+            "Live",
+            vec![
+                action(
+                    Action::Record(RecordState::Recording),
+                    "Start or stop streaming",
+                ),
+                action(Action::MuteAudio(true), "Mute or unmute the audio"),
+                action(Action::MonitorAudio(true), "Monitor the program audio"),
+                action(Action::Panic(true), "Cut to standby"),
+                action(Action::Snapshot, "Save a snapshot of the program"),
+                action(Action::Marker, "Add a marker to the session log"),
+                action(Action::RundownNext, "Next rundown item"),
+            ],
+        ),
+        (
+            "Scenes",
+            scenes::SCENES
+                .iter()
+                .take(9)
+                .zip(1..)
+                .map(|(scene, number)| (scene_action_name(number), scene.label.to_string()))
+                .collect(),
+        ),
+        (
+            "General",
+            vec![
+                action(Action::DebugHud(true), "Show or hide the debug HUD"),
+                action(Action::Projector(true), "Open or close the projector"),
+                action(
+                    Action::DetachEditor(true),
+                    "Show the editor in its own window or next to the program",
+                ),
+                action(Action::LogViewer, "Show the log"),
+                action(Action::Shortcuts, "Keyboard shortcuts"),
+                action(Action::Quit, "Quit"),
+            ],
+        ),
+    ]
+}
+
+// Builder definition of the window, listing the accelerators currently registered for the
+// actions so it can't get out of sync with them
+fn window_definition(application: &gtk::Application) -> String {
+    let mut groups = String::new();
+    for (title, shortcuts) in shortcut_groups() {
+        let mut children = String::new();
+        for (action_name, shortcut_title) in shortcuts {
+            let accels = application.get_accels_for_action(&action_name);
+            if accels.is_empty() {
+                continue;
+            }
+            let accels = accels
+                .iter()
+                .map(|accel| accel.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            children.push_str(&format!(
+                "<child><object class=\"GtkShortcutsShortcut\">\
+                 <property name=\"visible\">1</property>\
+                 <property name=\"accelerator\">{}</property>\
+                 <property name=\"title\">{}</property>\
+                 </object></child>",
+                glib::markup_escape_text(&accels),
+                glib::markup_escape_text(&shortcut_title)
+            ));
+        }
+
+        groups.push_str(&format!(
+            "<child><object class=\"GtkShortcutsGroup\">\
+             <property name=\"visible\">1</property>\
+             <property name=\"title\">{}</property>{}\
+             </object></child>",
+            glib::markup_escape_text(title),
+            children
+        ));
+    }
+
+    format!(
+        "<interface><object class=\"GtkShortcutsWindow\" id=\"shortcuts\">\
+         <property name=\"modal\">1</property>\
+         <child><object class=\"GtkShortcutsSection\">\
+         <property name=\"visible\">1</property>\
+         <property name=\"section-name\">shortcuts</property>{}\
+         </object></child>\
+         </object></interface>",
+        groups
+    )
+}
+
+pub fn show_shortcuts_window(application: &gtk::Application) {
+    let builder = gtk::Builder::new_from_string(&window_definition(application));
+    // GtkShortcutsWindow is only bound with newer GTK feature flags, but is a plain window otherwise
+    let window: gtk::Window = builder
+        .get_object("shortcuts")
+        .expect("Shortcuts window missing in its definition");
+
+    window.set_transient_for(application.get_active_window().as_ref());
+    window.show_all();
+}
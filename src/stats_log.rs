@@ -0,0 +1,257 @@
+use std::cell::RefCell;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::ops;
+use std::rc::{Rc, Weak};
+use std::time::Instant;
+
+use crate::debug_hud::DebugStats;
+use crate::pipeline::PipelineWeak;
+use crate::settings::{Settings, StatsLogFormat};
+use crate::stream_health::{ConnectionState, StreamStats};
+use crate::utils;
+
+const CSV_HEADER: &str = "time,fps,encoded_fps,bitrate_kbps,dropped_frames,queue_fill_percent,\
+                          audio_peak_db,preview_latency_ms,encode_latency_ms,connection";
+
+fn now() -> String {
+    glib::DateTime::new_now_local()
+        .format("%FT%T%z")
+        .map(|t| t.to_string())
+        .unwrap_or_default()
+}
+
+// Counters of the pipeline at one point in time
+struct Sample {
+    time: Instant,
+    debug: DebugStats,
+    stream: StreamStats,
+}
+
+impl Sample {
+    fn take(pipeline: &PipelineWeak) -> Option<Self> {
+        let pipeline = pipeline.upgrade()?;
+        Some(Sample {
+            time: Instant::now(),
+            debug: pipeline.get_debug_stats(),
+            stream: pipeline.get_stream_stats(),
+        })
+    }
+}
+
+// One line of the log, the rates are for the second before it
+struct Row {
+    time: String,
+    fps: f64,
+    encoded_fps: f64,
+    bitrate_kbps: f64,
+    dropped_frames: u64,
+    queue_fill_percent: Option<f64>,
+    audio_peak_db: Option<f64>,
+    preview_latency_ms: Option<u128>,
+    encode_latency_ms: Option<u128>,
+    connection: &'static str,
+}
+
+impl Row {
+    fn new(previous: &Sample, current: &Sample) -> Self {
+        let seconds = current
+            .time
+            .duration_since(previous.time)
+            .as_secs_f64()
+            .max(0.001);
+        let rate = |previous: u64, current: u64| current.saturating_sub(previous) as f64 / seconds;
+
+        Row {
+            time: now(),
+            fps: rate(previous.debug.frames, current.debug.frames),
+            encoded_fps: rate(
+                previous.stream.encoded_frames,
+                current.stream.encoded_frames,
+            ),
+            bitrate_kbps: rate(previous.stream.output_bytes, current.stream.output_bytes) * 8.0
+                / 1000.0,
+            dropped_frames: current
+                .stream
+                .dropped_frames
+                .saturating_sub(previous.stream.dropped_frames),
+            queue_fill_percent: current.stream.buffer_fill.map(|fill| fill * 100.0),
+            audio_peak_db: current.debug.audio_peak,
+            preview_latency_ms: current.stream.preview_latency.map(|l| l.as_millis()),
+            encode_latency_ms: current.stream.encode_latency.map(|l| l.as_millis()),
+            connection: match current.stream.connection {
+                ConnectionState::Offline => "offline",
+                ConnectionState::Connected => "connected",
+                ConnectionState::Reconnecting => "reconnecting",
+            },
+        }
+    }
+
+    // Missing values are left empty
+    fn to_csv(&self) -> String {
+        fn optional<T: ToString>(value: Option<T>) -> String {
+            value.map(|value| value.to_string()).unwrap_or_default()
+        }
+
+        format!(
+            "{},{:.2},{:.2},{:.0},{},{},{},{},{},{}",
+            self.time,
+            self.fps,
+            self.encoded_fps,
+            self.bitrate_kbps,
+            self.dropped_frames,
+            optional(self.queue_fill_percent.map(|fill| format!("{:.0}", fill))),
+            optional(self.audio_peak_db.map(|peak| format!("{:.1}", peak))),
+            optional(self.preview_latency_ms),
+            optional(self.encode_latency_ms),
+            self.connection,
+        )
+    }
+
+    // Missing values are null
+    fn to_json(&self) -> String {
+        serde_json::json!({
+            "time": self.time,
+            "fps": self.fps,
+            "encoded_fps": self.encoded_fps,
+            "bitrate_kbps": self.bitrate_kbps,
+            "dropped_frames": self.dropped_frames,
+            "queue_fill_percent": self.queue_fill_percent,
+            "audio_peak_db": self.audio_peak_db,
+            "preview_latency_ms": self.preview_latency_ms.map(|l| l as u64),
+            "encode_latency_ms": self.encode_latency_ms.map(|l| l as u64),
+            "connection": self.connection,
+        })
+        .to_string()
+    }
+}
+
+// Log being written while live
+struct Writer {
+    format: StatsLogFormat,
+    file: BufWriter<File>,
+    previous: Sample,
+}
+
+// Our refcounted statistics log, writing the pipeline metrics every second while live so glitches
+// can be looked into after the show
+#[derive(Clone)]
+pub struct StatsLog(Rc<StatsLogInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for StatsLog {
+    type Target = StatsLogInner;
+
+    fn deref(&self) -> &StatsLogInner {
+        &self.0
+    }
+}
+
+pub struct StatsLogInner {
+    pipeline: PipelineWeak,
+    writer: RefCell<Option<Writer>>,
+    timeout: RefCell<Option<glib::SourceId>>,
+}
+
+pub struct StatsLogWeak(Weak<StatsLogInner>);
+impl StatsLogWeak {
+    pub fn upgrade(&self) -> Option<StatsLog> {
+        self.0.upgrade().map(StatsLog)
+    }
+}
+
+impl StatsLog {
+    pub fn new(pipeline: PipelineWeak) -> Self {
+        StatsLog(Rc::new(StatsLogInner {
+            pipeline,
+            writer: RefCell::new(None),
+            timeout: RefCell::new(None),
+        }))
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> StatsLogWeak {
+        StatsLogWeak(Rc::downgrade(&self.0))
+    }
+
+    // Start a new log file if enabled in the settings, each time going live gets its own
+    pub fn start(&self, settings: &Settings) -> Result<(), String> {
+        self.stop();
+
+        let extension = match settings.stats_log_format.extension() {
+            Some(extension) => extension,
+            None => return Ok(()),
+        };
+        let previous = match Sample::take(&self.pipeline) {
+            Some(sample) => sample,
+            None => return Ok(()),
+        };
+
+        let directory = utils::get_stats_directory(settings);
+        let name = now().replace(':', "-");
+        let path = directory.join(format!("stats-{}.{}", name, extension));
+        let file = fs::create_dir_all(&directory)
+            .and_then(|_| File::create(&path))
+            .map_err(|err| format!("Failed to create {}: {}", path.display(), err))?;
+        let mut file = BufWriter::new(file);
+        if settings.stats_log_format == StatsLogFormat::Csv {
+            writeln!(file, "{}", CSV_HEADER)
+                .map_err(|err| format!("Failed to write {}: {}", path.display(), err))?;
+        }
+
+        self.writer.replace(Some(Writer {
+            format: settings.stats_log_format,
+            file,
+            previous,
+        }));
+
+        let stats_log_weak = self.downgrade();
+        let source_id = glib::timeout_add_seconds_local(1, move || {
+            let stats_log = upgrade_weak!(stats_log_weak, glib::Continue(false));
+            glib::Continue(stats_log.write_row())
+        });
+        self.timeout.replace(Some(source_id));
+
+        Ok(())
+    }
+
+    // Close the current log file, if any
+    pub fn stop(&self) {
+        if let Some(source_id) = self.timeout.borrow_mut().take() {
+            glib::source_remove(source_id);
+        }
+        if let Some(mut writer) = self.writer.borrow_mut().take() {
+            let _ = writer.file.flush();
+        }
+    }
+
+    // Returns false once the log can't be written anymore
+    fn write_row(&self) -> bool {
+        let mut writer = self.writer.borrow_mut();
+        let writer = match *writer {
+            Some(ref mut writer) => writer,
+            None => return false,
+        };
+        let current = match Sample::take(&self.pipeline) {
+            Some(sample) => sample,
+            None => return false,
+        };
+
+        let row = Row::new(&writer.previous, &current);
+        let line = match writer.format {
+            StatsLogFormat::Json => row.to_json(),
+            _ => row.to_csv(),
+        };
+        writer.previous = current;
+
+        // Flushed every second, so little is lost if the application crashes during the show
+        if writeln!(writer.file, "{}", line)
+            .and_then(|_| writer.file.flush())
+            .is_err()
+        {
+            self.timeout.replace(None);
+            return false;
+        }
+        true
+    }
+}
@@ -0,0 +1,195 @@
+use gtk::{self, prelude::*};
+
+use std::cell::RefCell;
+use std::ops;
+use std::rc::{Rc, Weak};
+use std::time::{Duration, Instant};
+
+use crate::pipeline::PipelineWeak;
+
+// State of the main stream output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConnectionState {
+    Offline,
+    Connected,
+    // The output failed and waits to be connected again
+    Reconnecting,
+}
+
+// Raw counters of the outputs, the panel shows the rates between two samples
+#[derive(Debug, Clone, Copy)]
+pub struct StreamStats {
+    // Video frames that went into the encoder of the stream
+    pub encoded_frames: u64,
+    // Encoded bytes sent to all outputs
+    pub output_bytes: u64,
+    // Frames dropped by all elements according to their QoS messages
+    pub dropped_frames: u64,
+    // Fill level of the fullest video queue in front of an encoder, between 0 and 1
+    pub buffer_fill: Option<f64>,
+    pub connection: ConnectionState,
+    pub connected_since: Option<Instant>,
+    // Delay from capture to the preview and to the encoder output of the stream
+    pub preview_latency: Option<Duration>,
+    pub encode_latency: Option<Duration>,
+}
+
+// Our refcounted stream health panel struct, showing whether the stream is doing fine
+#[derive(Clone)]
+pub struct StreamHealth(Rc<StreamHealthInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for StreamHealth {
+    type Target = StreamHealthInner;
+
+    fn deref(&self) -> &StreamHealthInner {
+        &self.0
+    }
+}
+
+pub struct StreamHealthInner {
+    expander: gtk::Expander,
+    bitrate: gtk::Label,
+    fps: gtk::Label,
+    dropped: gtk::Label,
+    buffer_fill: gtk::Label,
+    connection: gtk::Label,
+    connected_time: gtk::Label,
+    preview_latency: gtk::Label,
+    encode_latency: gtk::Label,
+    pipeline: PipelineWeak,
+    // Previous sample to compute the rates from
+    sample: RefCell<Option<(Instant, StreamStats)>>,
+}
+
+pub struct StreamHealthWeak(Weak<StreamHealthInner>);
+impl StreamHealthWeak {
+    pub fn upgrade(&self) -> Option<StreamHealth> {
+        self.0.upgrade().map(StreamHealth)
+    }
+}
+
+impl StreamHealth {
+    pub fn new(pipeline: PipelineWeak) -> Self {
+        let expander = gtk::Expander::new(Some("Stream health"));
+        expander.set_margin_top(6);
+
+        let grid = gtk::Grid::new();
+        grid.set_column_spacing(12);
+        grid.set_row_spacing(2);
+        grid.set_margin_start(12);
+
+        let add_row = |row: i32, title: &str| {
+            let title = gtk::Label::new(Some(title));
+            title.set_halign(gtk::Align::Start);
+            let value = gtk::Label::new(Some("-"));
+            value.set_halign(gtk::Align::Start);
+            grid.attach(&title, 0, row, 1, 1);
+            grid.attach(&value, 1, row, 1, 1);
+            value
+        };
+
+        let bitrate = add_row(0, "Bitrate");
+        let fps = add_row(1, "Frame rate");
+        let dropped = add_row(2, "Dropped frames");
+        let buffer_fill = add_row(3, "Buffer fill");
+        let connection = add_row(4, "Connection");
+        let connected_time = add_row(5, "Time connected");
+        let preview_latency = add_row(6, "Preview latency");
+        let encode_latency = add_row(7, "Encode latency");
+
+        expander.add(&grid);
+
+        let health = StreamHealth(Rc::new(StreamHealthInner {
+            expander,
+            bitrate,
+            fps,
+            dropped,
+            buffer_fill,
+            connection,
+            connected_time,
+            preview_latency,
+            encode_latency,
+            pipeline,
+            sample: RefCell::new(None),
+        }));
+
+        let health_weak = health.downgrade();
+        glib::timeout_add_seconds_local(1, move || {
+            let health = upgrade_weak!(health_weak, glib::Continue(false));
+            health.update();
+            glib::Continue(true)
+        });
+
+        health
+    }
+
+    pub fn downgrade(&self) -> StreamHealthWeak {
+        StreamHealthWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> &gtk::Expander {
+        &self.expander
+    }
+
+    fn update(&self) {
+        let pipeline = &self.pipeline;
+        let pipeline = upgrade_weak!(pipeline);
+
+        let now = Instant::now();
+        let stats = pipeline.get_stream_stats();
+        let previous = self.sample.replace(Some((now, stats)));
+
+        if stats.connection == ConnectionState::Offline {
+            for label in &[&self.bitrate, &self.fps, &self.buffer_fill] {
+                label.set_text("-");
+            }
+        } else if let Some((time, previous)) = previous {
+            let seconds = now.duration_since(time).as_secs_f64().max(0.001);
+            let kbps = stats.output_bytes.saturating_sub(previous.output_bytes) as f64 * 8.0
+                / seconds
+                / 1000.0;
+            let fps = stats.encoded_frames.saturating_sub(previous.encoded_frames) as f64 / seconds;
+            self.bitrate.set_text(&format!("{:.0} kbit/s", kbps));
+            self.fps.set_text(&format!("{:.1} fps", fps));
+            self.buffer_fill.set_text(
+                &stats
+                    .buffer_fill
+                    .map(|fill| format!("{:.0} %", fill * 100.0))
+                    .unwrap_or_else(|| "-".to_string()),
+            );
+        }
+
+        self.dropped.set_text(&stats.dropped_frames.to_string());
+        self.connection.set_text(match stats.connection {
+            ConnectionState::Offline => "Offline",
+            ConnectionState::Connected => "Connected",
+            ConnectionState::Reconnecting => "Reconnecting",
+        });
+        self.connected_time.set_text(
+            &stats
+                .connected_since
+                .map(|since| {
+                    let seconds = since.elapsed().as_secs();
+                    format!(
+                        "{:02}:{:02}:{:02}",
+                        seconds / 3600,
+                        seconds / 60 % 60,
+                        seconds % 60
+                    )
+                })
+                .unwrap_or_else(|| "-".to_string()),
+        );
+
+        for (label, latency) in &[
+            (&self.preview_latency, stats.preview_latency),
+            (&self.encode_latency, stats.encode_latency),
+        ] {
+            label.set_text(
+                &latency
+                    .map(|latency| format!("{} ms", latency.as_millis()))
+                    .unwrap_or_else(|| "-".to_string()),
+            );
+        }
+    }
+}
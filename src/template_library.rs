@@ -0,0 +1,332 @@
+use gtk::{self, prelude::*};
+
+use std::cell::RefCell;
+use std::ops;
+use std::rc::{Rc, Weak};
+
+use crate::templates;
+use crate::utils;
+
+type TemplateLoader = Box<dyn Fn(&str, &str)>;
+
+// Our refcounted template library struct, the sidebar listing the local overlay templates next
+// to the editor
+#[derive(Clone)]
+pub struct TemplateLibrary(Rc<TemplateLibraryInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for TemplateLibrary {
+    type Target = TemplateLibraryInner;
+
+    fn deref(&self) -> &TemplateLibraryInner {
+        &self.0
+    }
+}
+
+pub struct TemplateLibraryInner {
+    container: gtk::Box,
+    list_box: gtk::ListBox,
+    // Template the editor is working on, edits are saved into it
+    selected: RefCell<Option<String>>,
+    // Loads the HTML and CSS of a selected template into the editor and overlay
+    load: RefCell<Option<TemplateLoader>>,
+}
+
+pub struct TemplateLibraryWeak(Weak<TemplateLibraryInner>);
+impl TemplateLibraryWeak {
+    pub fn upgrade(&self) -> Option<TemplateLibrary> {
+        self.0.upgrade().map(TemplateLibrary)
+    }
+}
+
+impl TemplateLibrary {
+    pub fn new() -> Self {
+        let list_box = gtk::ListBox::new();
+        let scrolled_window = gtk::ScrolledWindow::new(gtk::NONE_ADJUSTMENT, gtk::NONE_ADJUSTMENT);
+        scrolled_window.set_size_request(160, -1);
+        scrolled_window.add(&list_box);
+        utils::set_accessible(
+            &list_box,
+            "Templates",
+            "Overlay templates, the selected one is edited",
+        );
+
+        let new_button =
+            gtk::Button::new_from_icon_name(Some("document-new-symbolic"), gtk::IconSize::Button);
+        new_button.set_tooltip_text(Some("New template"));
+        let duplicate_button =
+            gtk::Button::new_from_icon_name(Some("edit-copy-symbolic"), gtk::IconSize::Button);
+        duplicate_button.set_tooltip_text(Some("Duplicate template"));
+        let rename_button =
+            gtk::Button::new_from_icon_name(Some("document-edit-symbolic"), gtk::IconSize::Button);
+        rename_button.set_tooltip_text(Some("Rename template"));
+        let delete_button =
+            gtk::Button::new_from_icon_name(Some("edit-delete-symbolic"), gtk::IconSize::Button);
+        delete_button.set_tooltip_text(Some("Delete template"));
+
+        let button_box = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+        button_box.get_style_context().add_class("linked");
+        button_box.pack_start(&new_button, false, false, 0);
+        button_box.pack_start(&duplicate_button, false, false, 0);
+        button_box.pack_start(&rename_button, false, false, 0);
+        button_box.pack_start(&delete_button, false, false, 0);
+
+        let container = gtk::Box::new(gtk::Orientation::Vertical, 4);
+        container.pack_start(&scrolled_window, true, true, 0);
+        container.pack_start(&button_box, false, false, 0);
+
+        let library = TemplateLibrary(Rc::new(TemplateLibraryInner {
+            container,
+            list_box,
+            selected: RefCell::new(None),
+            load: RefCell::new(None),
+        }));
+
+        let library_weak = library.downgrade();
+        library.list_box.connect_row_selected(move |_, row| {
+            let library = upgrade_weak!(library_weak);
+            let name = row.and_then(|row| row.get_widget_name()).map(String::from);
+            library.on_selected(name);
+        });
+
+        let library_weak = library.downgrade();
+        new_button.connect_clicked(move |_| {
+            let library = upgrade_weak!(library_weak);
+            library.ask_name("New template", "", |library, name| {
+                library.create(name, templates::DEFAULT_HTML, templates::DEFAULT_CSS);
+            });
+        });
+
+        let library_weak = library.downgrade();
+        duplicate_button.connect_clicked(move |_| {
+            let library = upgrade_weak!(library_weak);
+            let selected = match library.get_selected() {
+                Some(selected) => selected,
+                None => return,
+            };
+            let (html, css) = match templates::load(&selected) {
+                Ok(markup) => markup,
+                Err(err) => {
+                    utils::show_error_dialog(
+                        false,
+                        format!("Failed to load template {}: {}", selected, err).as_str(),
+                    );
+                    return;
+                }
+            };
+            library.ask_name(
+                "Duplicate template",
+                &format!("{}-copy", selected),
+                move |library, name| library.create(name, &html, &css),
+            );
+        });
+
+        let library_weak = library.downgrade();
+        rename_button.connect_clicked(move |_| {
+            let library = upgrade_weak!(library_weak);
+            let selected = match library.get_selected() {
+                Some(selected) => selected,
+                None => return,
+            };
+            let initial = selected.clone();
+            library.ask_name("Rename template", &initial, move |library, name| {
+                if let Err(err) = templates::rename(&selected, name) {
+                    utils::show_error_dialog(
+                        false,
+                        format!("Failed to rename template {}: {}", selected, err).as_str(),
+                    );
+                    return;
+                }
+                library
+                    .selected
+                    .replace(Some(templates::directory_name(name)));
+                library.refresh();
+            });
+        });
+
+        let library_weak = library.downgrade();
+        delete_button.connect_clicked(move |_| {
+            let library = upgrade_weak!(library_weak);
+            let selected = match library.get_selected() {
+                Some(selected) => selected,
+                None => return,
+            };
+            if let Err(err) = templates::remove(&selected) {
+                utils::show_error_dialog(
+                    false,
+                    format!("Failed to delete template {}: {}", selected, err).as_str(),
+                );
+                return;
+            }
+            library.selected.replace(None);
+            library.refresh();
+        });
+
+        library.refresh();
+
+        library
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> TemplateLibraryWeak {
+        TemplateLibraryWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> &gtk::Box {
+        &self.container
+    }
+
+    pub fn set_loader<F: Fn(&str, &str) + 'static>(&self, load: F) {
+        self.load.replace(Some(Box::new(load)));
+    }
+
+    pub fn get_selected(&self) -> Option<String> {
+        self.selected.borrow().clone()
+    }
+
+    // Store the edited markup in the selected template, if any
+    pub fn save_selected(&self, html: &str, css: &str) {
+        let selected = match self.get_selected() {
+            Some(selected) => selected,
+            None => return,
+        };
+        if let Err(err) = templates::install(&selected, html, css) {
+            utils::show_error_dialog(
+                false,
+                format!("Failed to save template {}: {}", selected, err).as_str(),
+            );
+        }
+    }
+
+    // List the templates in the library again
+    pub fn refresh(&self) {
+        for child in self.list_box.get_children() {
+            self.list_box.remove(&child);
+        }
+
+        let selected = self.get_selected();
+        for name in templates::list_names() {
+            let label = gtk::Label::new(Some(&name));
+            label.set_halign(gtk::Align::Start);
+            let row = gtk::ListBoxRow::new();
+            row.set_widget_name(&name);
+            row.add(&label);
+            row.show_all();
+            self.list_box.insert(&row, -1);
+
+            if selected.as_deref() == Some(name.as_str()) {
+                self.list_box.select_row(Some(&row));
+            }
+        }
+    }
+
+    fn on_selected(&self, name: Option<String>) {
+        let name = match name {
+            Some(name) => name,
+            None => return,
+        };
+        // Selecting the row again after refreshing doesn't reload the editor
+        if self.get_selected().as_deref() == Some(name.as_str()) {
+            return;
+        }
+
+        match templates::load(&name) {
+            Ok((html, css)) => {
+                self.selected.replace(Some(name));
+                if let Some(ref load) = *self.load.borrow() {
+                    load(&html, &css);
+                }
+            }
+            Err(err) => utils::show_error_dialog(
+                false,
+                format!("Failed to load template {}: {}", name, err).as_str(),
+            ),
+        }
+    }
+
+    // Add a template and start editing it
+    fn create(&self, name: &str, html: &str, css: &str) {
+        if let Err(err) = templates::install(name, html, css) {
+            utils::show_error_dialog(
+                false,
+                format!("Failed to create template {}: {}", name, err).as_str(),
+            );
+            return;
+        }
+
+        self.selected.replace(None);
+        self.refresh();
+
+        let name = templates::directory_name(name);
+        if let Some(row) = self
+            .list_box
+            .get_children()
+            .into_iter()
+            .find(|row| row.get_widget_name().as_deref() == Some(name.as_str()))
+        {
+            if let Ok(row) = row.downcast::<gtk::ListBoxRow>() {
+                self.list_box.select_row(Some(&row));
+            }
+        }
+    }
+
+    // Ask for the name of a template, which must not be taken yet, and pass it on
+    fn ask_name<F: Fn(&TemplateLibrary, &str) + 'static>(
+        &self,
+        title: &str,
+        initial: &str,
+        callback: F,
+    ) {
+        let dialog = gtk::Dialog::new_with_buttons(
+            Some(title),
+            self.container
+                .get_toplevel()
+                .and_then(|toplevel| toplevel.downcast::<gtk::Window>().ok())
+                .as_ref(),
+            gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+            &[
+                ("Cancel", gtk::ResponseType::Cancel),
+                ("OK", gtk::ResponseType::Ok),
+            ],
+        );
+        dialog.set_default_response(gtk::ResponseType::Ok);
+
+        let entry = gtk::Entry::new();
+        entry.set_text(initial);
+        entry.set_activates_default(true);
+
+        let content_area = dialog.get_content_area();
+        content_area.set_border_width(10);
+        content_area.pack_start(&entry, false, false, 0);
+
+        let library_weak = self.downgrade();
+        dialog.connect_response(move |dialog, response| {
+            if response != gtk::ResponseType::Ok {
+                dialog.destroy();
+                return;
+            }
+
+            let name = entry
+                .get_text()
+                .map(|name| name.trim().to_string())
+                .unwrap_or_default();
+            if name.is_empty() {
+                utils::show_error_dialog(false, "Please enter a name for the template");
+                return;
+            }
+            if templates::exists(&name) {
+                utils::show_error_dialog(
+                    false,
+                    format!("There already is a template called {}", name).as_str(),
+                );
+                return;
+            }
+
+            dialog.destroy();
+            let library = upgrade_weak!(library_weak);
+            callback(&library, &name);
+        });
+
+        dialog.show_all();
+    }
+}
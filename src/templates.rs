@@ -0,0 +1,113 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::APPLICATION_NAME;
+
+// File names of the markup inside each template directory
+const HTML_FILE: &str = "index.html";
+const CSS_FILE: &str = "style.css";
+
+// Markup of the built-in overlay, also used for new templates
+pub const DEFAULT_HTML: &str = include_str!("../data/index.html");
+pub const DEFAULT_CSS: &str = include_str!("../data/style.css");
+
+// Directory of the local template library, with one directory per template
+pub fn get_templates_directory() -> PathBuf {
+    let mut path = glib::get_user_data_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push(APPLICATION_NAME);
+    path.push("templates");
+    path
+}
+
+// Directory the markup currently on air is kept in, so it survives restarts
+fn get_current_directory() -> PathBuf {
+    let mut path = glib::get_user_config_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push(APPLICATION_NAME);
+    path.push("overlay");
+    path
+}
+
+// Directory of the images and fonts imported for use in the overlay
+pub fn get_assets_directory() -> PathBuf {
+    get_current_directory().join("assets")
+}
+
+// HTML and CSS of the overlay from the last session, or the built-in overlay
+pub fn load_current() -> (String, String) {
+    let directory = get_current_directory();
+    match fs::read_to_string(directory.join(HTML_FILE)) {
+        Ok(html) => {
+            let css = fs::read_to_string(directory.join(CSS_FILE)).unwrap_or_default();
+            (html, css)
+        }
+        Err(_) => (DEFAULT_HTML.to_string(), DEFAULT_CSS.to_string()),
+    }
+}
+
+pub fn save_current(html: &str, css: &str) -> io::Result<()> {
+    let directory = get_current_directory();
+    fs::create_dir_all(&directory)?;
+    fs::write(directory.join(HTML_FILE), html)?;
+    fs::write(directory.join(CSS_FILE), css)
+}
+
+// Turn a template name into something usable as directory name. This is also the name the
+// template is listed under
+pub fn directory_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+// Add a template to the library, replacing any template with the same name
+pub fn install(name: &str, html: &str, css: &str) -> io::Result<PathBuf> {
+    let directory = get_templates_directory().join(directory_name(name));
+    fs::create_dir_all(&directory)?;
+    fs::write(directory.join(HTML_FILE), html)?;
+    fs::write(directory.join(CSS_FILE), css)?;
+    Ok(directory)
+}
+
+// Names of all templates in the library, sorted
+pub fn list_names() -> Vec<String> {
+    let entries = match fs::read_dir(get_templates_directory()) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut names = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.join(HTML_FILE).is_file())
+        .filter_map(|path| path.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+// HTML and CSS of the template with the given name
+pub fn load(name: &str) -> io::Result<(String, String)> {
+    let directory = get_templates_directory().join(directory_name(name));
+    let html = fs::read_to_string(directory.join(HTML_FILE))?;
+    let css = fs::read_to_string(directory.join(CSS_FILE)).unwrap_or_default();
+    Ok((html, css))
+}
+
+pub fn exists(name: &str) -> bool {
+    get_templates_directory()
+        .join(directory_name(name))
+        .join(HTML_FILE)
+        .is_file()
+}
+
+pub fn rename(name: &str, new_name: &str) -> io::Result<()> {
+    let directory = get_templates_directory();
+    fs::rename(
+        directory.join(directory_name(name)),
+        directory.join(directory_name(new_name)),
+    )
+}
+
+pub fn remove(name: &str) -> io::Result<()> {
+    fs::remove_dir_all(get_templates_directory().join(directory_name(name)))
+}
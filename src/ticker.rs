@@ -0,0 +1,214 @@
+use gtk::{self, prelude::*};
+
+use std::cell::RefCell;
+use std::ops;
+use std::rc::{Rc, Weak};
+
+use crate::pipeline::PipelineWeak;
+use crate::utils;
+
+// Variable the ticker items are available as in the overlay HTML
+pub const VARIABLE: &str = "ticker_items";
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+// Markup of the items inside the ticker of the default overlay
+pub fn items_html(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|item| format!("<div class=\"ticker__item\">{}</div>", escape_html(item)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Entry and buttons of one item
+struct ItemRow {
+    container: gtk::Box,
+    entry: gtk::Entry,
+}
+
+// Our refcounted ticker panel struct, editing the items scrolling through the ticker
+#[derive(Clone)]
+pub struct Ticker(Rc<TickerInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for Ticker {
+    type Target = TickerInner;
+
+    fn deref(&self) -> &TickerInner {
+        &self.0
+    }
+}
+
+pub struct TickerInner {
+    container: gtk::Box,
+    rows_box: gtk::Box,
+    rows: RefCell<Vec<ItemRow>>,
+    pipeline: PipelineWeak,
+    // Renders the overlay again with the new items
+    changed: RefCell<Option<Box<dyn Fn()>>>,
+}
+
+pub struct TickerWeak(Weak<TickerInner>);
+impl TickerWeak {
+    pub fn upgrade(&self) -> Option<Ticker> {
+        self.0.upgrade().map(Ticker)
+    }
+}
+
+impl Ticker {
+    pub fn new(pipeline: PipelineWeak) -> Self {
+        let label = gtk::Label::new(Some(
+            "Items of the scrolling ticker, available as {ticker_items} in the HTML of the overlay",
+        ));
+        label.set_halign(gtk::Align::Start);
+        label.set_line_wrap(true);
+
+        let rows_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
+        let scrolled_window = gtk::ScrolledWindow::new(gtk::NONE_ADJUSTMENT, gtk::NONE_ADJUSTMENT);
+        scrolled_window.add(&rows_box);
+
+        let add_button = gtk::Button::new_with_mnemonic("_Add item");
+
+        let container = gtk::Box::new(gtk::Orientation::Vertical, 4);
+        container.set_border_width(6);
+        container.pack_start(&label, false, false, 0);
+        container.pack_start(&scrolled_window, true, true, 0);
+        container.pack_start(&add_button, false, false, 0);
+
+        let ticker = Ticker(Rc::new(TickerInner {
+            container,
+            rows_box,
+            rows: RefCell::new(Vec::new()),
+            pipeline,
+            changed: RefCell::new(None),
+        }));
+
+        for item in &utils::load_settings().ticker_items {
+            ticker.add_row(item);
+        }
+
+        let ticker_weak = ticker.downgrade();
+        add_button.connect_clicked(move |_| {
+            let ticker = upgrade_weak!(ticker_weak);
+            ticker.add_row("");
+            ticker.apply();
+        });
+
+        ticker
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> TickerWeak {
+        TickerWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> &gtk::Box {
+        &self.container
+    }
+
+    pub fn set_changed<F: Fn() + 'static>(&self, changed: F) {
+        self.changed.replace(Some(Box::new(changed)));
+    }
+
+    fn add_row(&self, item: &str) {
+        let entry = gtk::Entry::new();
+        entry.set_text(item);
+
+        let up_button =
+            gtk::Button::new_from_icon_name(Some("go-up-symbolic"), gtk::IconSize::Button);
+        up_button.set_tooltip_text(Some("Move up"));
+        let down_button =
+            gtk::Button::new_from_icon_name(Some("go-down-symbolic"), gtk::IconSize::Button);
+        down_button.set_tooltip_text(Some("Move down"));
+        let remove_button =
+            gtk::Button::new_from_icon_name(Some("list-remove-symbolic"), gtk::IconSize::Button);
+        remove_button.set_tooltip_text(Some("Remove item"));
+
+        let container = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        container.pack_start(&entry, true, true, 0);
+        container.pack_start(&up_button, false, false, 0);
+        container.pack_start(&down_button, false, false, 0);
+        container.pack_start(&remove_button, false, false, 0);
+        container.show_all();
+        self.rows_box.pack_start(&container, false, false, 0);
+
+        let ticker_weak = self.downgrade();
+        entry.connect_changed(move |_| {
+            let ticker = upgrade_weak!(ticker_weak);
+            ticker.apply();
+        });
+
+        for (button, offset) in &[(&up_button, -1), (&down_button, 1)] {
+            let ticker_weak = self.downgrade();
+            let row_container = container.clone();
+            let offset = *offset;
+            button.connect_clicked(move |_| {
+                let ticker = upgrade_weak!(ticker_weak);
+                ticker.move_row(&row_container, offset);
+            });
+        }
+
+        let ticker_weak = self.downgrade();
+        let row_container = container.clone();
+        remove_button.connect_clicked(move |_| {
+            let ticker = upgrade_weak!(ticker_weak);
+            ticker
+                .rows
+                .borrow_mut()
+                .retain(|row| row.container != row_container);
+            ticker.rows_box.remove(&row_container);
+            ticker.apply();
+        });
+
+        self.rows.borrow_mut().push(ItemRow { container, entry });
+    }
+
+    // Move the row up or down by the given number of positions
+    fn move_row(&self, container: &gtk::Box, offset: i32) {
+        {
+            let mut rows = self.rows.borrow_mut();
+            let position = match rows.iter().position(|row| row.container == *container) {
+                Some(position) => position as i32,
+                None => return,
+            };
+            let new_position = position + offset;
+            if new_position < 0 || new_position >= rows.len() as i32 {
+                return;
+            }
+
+            rows.swap(position as usize, new_position as usize);
+            self.rows_box.reorder_child(container, new_position);
+        }
+        self.apply();
+    }
+
+    // Store the items and render the overlay with them
+    fn apply(&self) {
+        let items = self
+            .rows
+            .borrow()
+            .iter()
+            .filter_map(|row| row.entry.get_text().map(String::from))
+            .filter(|item| !item.trim().is_empty())
+            .collect::<Vec<_>>();
+
+        let pipeline = &self.pipeline;
+        let pipeline = upgrade_weak!(pipeline);
+        pipeline.set_overlay_variable(VARIABLE, &items_html(&items));
+
+        let mut settings = utils::load_settings();
+        settings.ticker_items = items;
+        utils::save_settings(&settings);
+
+        if let Some(ref changed) = *self.changed.borrow() {
+            changed();
+        }
+    }
+}
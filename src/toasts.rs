@@ -0,0 +1,167 @@
+use gtk::{self, prelude::*};
+
+use std::cell::RefCell;
+use std::ops;
+use std::rc::{Rc, Weak};
+
+// Seconds a toast stays visible
+const TIMEOUT: u32 = 8;
+// Messages shown at once, older ones are dropped from the toast
+const MAX_MESSAGES: usize = 3;
+
+thread_local! {
+    // The toast area of the main window, GTK widgets can only be used from the main thread
+    static TOASTS: RefCell<Option<ToastsWeak>> = const { RefCell::new(None) };
+}
+
+// Our refcounted toast area, showing warnings on top of the main window for a few seconds without
+// taking the focus away from what the operator is doing
+#[derive(Clone)]
+pub struct Toasts(Rc<ToastsInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for Toasts {
+    type Target = ToastsInner;
+
+    fn deref(&self) -> &ToastsInner {
+        &self.0
+    }
+}
+
+pub struct ToastsInner {
+    overlay: gtk::Overlay,
+    revealer: gtk::Revealer,
+    label: gtk::Label,
+    messages: RefCell<Vec<String>>,
+    timeout: RefCell<Option<glib::SourceId>>,
+}
+
+pub struct ToastsWeak(Weak<ToastsInner>);
+impl ToastsWeak {
+    pub fn upgrade(&self) -> Option<Toasts> {
+        self.0.upgrade().map(Toasts)
+    }
+}
+
+impl Toasts {
+    // Put the toast area on top of the given main window content
+    pub fn new<P: IsA<gtk::Widget>>(child: &P) -> Self {
+        let label = gtk::Label::new(None);
+        label.set_line_wrap(true);
+        label.set_max_width_chars(80);
+        label.set_halign(gtk::Align::Start);
+
+        let close_button =
+            gtk::Button::new_from_icon_name(Some("window-close-symbolic"), gtk::IconSize::Button);
+        close_button.set_relief(gtk::ReliefStyle::None);
+        close_button.set_valign(gtk::Align::Start);
+        close_button.set_tooltip_text(Some("Dismiss"));
+
+        let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+        hbox.pack_start(&label, true, true, 0);
+        hbox.pack_start(&close_button, false, false, 0);
+
+        // Styled like the in-app notifications of other GTK applications
+        let frame = gtk::Frame::new(None);
+        frame.get_style_context().add_class("app-notification");
+        frame.add(&hbox);
+
+        let revealer = gtk::Revealer::new();
+        revealer.set_transition_type(gtk::RevealerTransitionType::SlideDown);
+        revealer.set_halign(gtk::Align::Center);
+        revealer.set_valign(gtk::Align::Start);
+        revealer.add(&frame);
+
+        let overlay = gtk::Overlay::new();
+        overlay.add(child);
+        overlay.add_overlay(&revealer);
+
+        let toasts = Toasts(Rc::new(ToastsInner {
+            overlay,
+            revealer,
+            label,
+            messages: RefCell::new(Vec::new()),
+            timeout: RefCell::new(None),
+        }));
+
+        let toasts_weak = toasts.downgrade();
+        close_button.connect_clicked(move |_| {
+            let toasts = upgrade_weak!(toasts_weak);
+            toasts.hide();
+        });
+
+        TOASTS.with(|current| current.replace(Some(toasts.downgrade())));
+
+        toasts
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> ToastsWeak {
+        ToastsWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> &gtk::Overlay {
+        &self.overlay
+    }
+
+    // Show the message below the ones still visible and start the timeout over
+    pub fn show(&self, text: &str) {
+        {
+            let mut messages = self.messages.borrow_mut();
+            messages.push(text.to_string());
+            let excess = messages.len().saturating_sub(MAX_MESSAGES);
+            messages.drain(..excess);
+            self.label.set_text(&messages.join("\n"));
+        }
+        self.revealer.show_all();
+        self.revealer.set_reveal_child(true);
+
+        let toasts_weak = self.downgrade();
+        let source_id = glib::timeout_add_seconds_local(TIMEOUT, move || {
+            let toasts = upgrade_weak!(toasts_weak, glib::Continue(false));
+            toasts.timeout.replace(None);
+            toasts.hide();
+            glib::Continue(false)
+        });
+        if let Some(old_source_id) = self.timeout.replace(Some(source_id)) {
+            glib::source_remove(old_source_id);
+        }
+    }
+
+    fn hide(&self) {
+        if let Some(source_id) = self.timeout.borrow_mut().take() {
+            glib::source_remove(source_id);
+        }
+        self.messages.borrow_mut().clear();
+        self.revealer.set_reveal_child(false);
+    }
+}
+
+// Show the message as toast on the main window. Returns false if it would not be seen there, e.g.
+// while a modal dialog is open in front of it
+pub fn show_toast(application: &gtk::Application, text: &str) -> bool {
+    if application
+        .get_active_window()
+        .is_some_and(|window| window.get_modal())
+    {
+        return false;
+    }
+
+    let toasts = match TOASTS.with(|current| current.borrow().as_ref().and_then(|t| t.upgrade())) {
+        Some(toasts) => toasts,
+        None => return false,
+    };
+    toasts.show(text);
+    true
+}
+
+// Log a warning and show it as toast, but never in a dialog
+pub fn show_warning(text: &str) {
+    log::warn!("{}", text);
+
+    let application = gio::Application::get_default()
+        .and_then(|application| application.downcast::<gtk::Application>().ok());
+    if let Some(application) = application {
+        show_toast(&application, text);
+    }
+}
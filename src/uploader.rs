@@ -0,0 +1,339 @@
+use gtk::{self, prelude::*};
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::ops;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::rc::{Rc, Weak};
+use std::thread;
+
+use crate::settings::{UploadDestination, UploadSettings};
+use crate::utils;
+
+// Number of attempts for each file before giving up
+const MAX_ATTEMPTS: u32 = 5;
+
+// Our refcounted uploader struct, taking care of sending finished recordings to the configured
+// destination in the background, one at a time
+#[derive(Clone)]
+pub struct Uploader(Rc<UploaderInner>);
+
+// Deref into the contained struct to make usage a bit more ergonomic
+impl ops::Deref for Uploader {
+    type Target = UploaderInner;
+
+    fn deref(&self) -> &UploaderInner {
+        &self.0
+    }
+}
+
+pub struct UploaderInner {
+    progress_bar: gtk::ProgressBar,
+    pending: RefCell<VecDeque<PathBuf>>,
+    // The file currently being uploaded and the number of attempts made so far
+    current: RefCell<Option<(PathBuf, u32)>>,
+}
+
+// Weak reference to our uploader struct
+pub struct UploaderWeak(Weak<UploaderInner>);
+impl UploaderWeak {
+    pub fn upgrade(&self) -> Option<Uploader> {
+        self.0.upgrade().map(Uploader)
+    }
+}
+
+// Events sent from the upload thread to the main thread
+enum UploadEvent {
+    Progress(f64),
+    Done(Result<(), String>),
+}
+
+impl Uploader {
+    pub fn new() -> Self {
+        let progress_bar = gtk::ProgressBar::new();
+        progress_bar.set_show_text(true);
+        // Only shown while an upload is running
+        progress_bar.set_no_show_all(true);
+
+        Uploader(Rc::new(UploaderInner {
+            progress_bar,
+            pending: RefCell::new(VecDeque::new()),
+            current: RefCell::new(None),
+        }))
+    }
+
+    // Downgrade to a weak reference
+    pub fn downgrade(&self) -> UploaderWeak {
+        UploaderWeak(Rc::downgrade(&self.0))
+    }
+
+    pub fn get_widget(&self) -> &gtk::ProgressBar {
+        &self.progress_bar
+    }
+
+    // Queue a finished recording for upload, if uploads are enabled in the settings
+    pub fn upload(&self, path: &Path) {
+        let settings = utils::load_settings();
+        if settings.upload.destination == UploadDestination::Disabled {
+            return;
+        }
+
+        self.pending.borrow_mut().push_back(path.to_path_buf());
+        self.start_next();
+    }
+
+    fn start_next(&self) {
+        if self.current.borrow().is_some() {
+            return;
+        }
+
+        let path = match self.pending.borrow_mut().pop_front() {
+            Some(path) => path,
+            None => {
+                self.progress_bar.hide();
+                return;
+            }
+        };
+
+        *self.current.borrow_mut() = Some((path, 0));
+        self.run_attempt();
+    }
+
+    // Spawn curl for the current file in a separate thread and forward its progress to the UI
+    fn run_attempt(&self) {
+        let (path, attempt) = match &*self.current.borrow() {
+            Some((path, attempt)) => (path.clone(), *attempt),
+            None => return,
+        };
+
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.progress_bar.set_fraction(0.0);
+        self.progress_bar
+            .set_text(Some(&format!("Uploading {}", file_name)));
+        self.progress_bar.show();
+
+        let settings = utils::load_settings();
+        let (arguments, config) =
+            match curl_arguments(&settings.upload, settings.proxy.as_deref(), &path) {
+                Ok(command) => command,
+                Err(err) => {
+                    self.finish(Err(err));
+                    return;
+                }
+            };
+
+        let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+        thread::spawn(move || {
+            let mut child = match Command::new("curl")
+                .args(&arguments)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .spawn()
+            {
+                Ok(child) => child,
+                Err(err) => {
+                    let _ = sender.send(UploadEvent::Done(Err(format!(
+                        "Failed to run curl: {}",
+                        err
+                    ))));
+                    return;
+                }
+            };
+
+            // Closing stdin right after lets curl go on with the upload
+            if let Some(mut stdin) = child.stdin.take() {
+                let _ = stdin.write_all(config.as_bytes());
+            }
+
+            // curl's progress bar is a sequence of "###   12.3%" lines separated by \r
+            if let Some(mut stderr) = child.stderr.take() {
+                let mut buffer = [0; 256];
+                let mut output = String::new();
+                while let Ok(n) = stderr.read(&mut buffer) {
+                    if n == 0 {
+                        break;
+                    }
+                    output.push_str(&String::from_utf8_lossy(&buffer[..n]));
+                    if let Some(progress) = parse_progress(&output) {
+                        let _ = sender.send(UploadEvent::Progress(progress));
+                    }
+                    if let Some(pos) = output.rfind('\r') {
+                        output.drain(..=pos);
+                    }
+                }
+            }
+
+            let result = match child.wait() {
+                Ok(status) if status.success() => Ok(()),
+                Ok(status) => Err(format!("curl exited with {}", status)),
+                Err(err) => Err(format!("Failed to wait for curl: {}", err)),
+            };
+            let _ = sender.send(UploadEvent::Done(result));
+        });
+
+        let uploader_weak = self.downgrade();
+        receiver.attach(None, move |event| {
+            let uploader = upgrade_weak!(uploader_weak, glib::Continue(false));
+            match event {
+                UploadEvent::Progress(progress) => {
+                    uploader.progress_bar.set_fraction(progress);
+                    glib::Continue(true)
+                }
+                UploadEvent::Done(result) => {
+                    uploader.on_attempt_done(attempt, result);
+                    glib::Continue(false)
+                }
+            }
+        });
+    }
+
+    // Retry failed uploads with exponential backoff, or move on to the next file
+    fn on_attempt_done(&self, attempt: u32, result: Result<(), String>) {
+        if result.is_ok() || attempt + 1 >= MAX_ATTEMPTS {
+            self.finish(result);
+            return;
+        }
+
+        if let Some((_, attempts)) = &mut *self.current.borrow_mut() {
+            *attempts += 1;
+        }
+
+        let delay = 5 * 2u32.pow(attempt);
+        self.progress_bar.set_text(Some(&format!(
+            "Upload failed, retrying in {} seconds",
+            delay
+        )));
+
+        let uploader_weak = self.downgrade();
+        glib::timeout_add_seconds_local(delay, move || {
+            let uploader = upgrade_weak!(uploader_weak, glib::Continue(false));
+            uploader.run_attempt();
+            glib::Continue(false)
+        });
+    }
+
+    fn finish(&self, result: Result<(), String>) {
+        let path = match self.current.borrow_mut().take() {
+            Some((path, _)) => path,
+            None => return,
+        };
+
+        if let Err(err) = result {
+            utils::show_error_dialog(
+                false,
+                format!("Failed to upload '{}': {}", path.display(), err).as_str(),
+            );
+        }
+
+        self.start_next();
+    }
+}
+
+// Extract the last progress percentage from curl's progress bar output
+fn parse_progress(output: &str) -> Option<f64> {
+    let end = output.rfind('%')?;
+    let start = output[..end]
+        .rfind(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .map(|pos| pos + 1)
+        .unwrap_or(0);
+
+    output[start..end]
+        .parse::<f64>()
+        .ok()
+        .map(|p| num::clamp(p / 100.0, 0.0, 1.0))
+}
+
+// Build the curl command line for uploading the given file to the configured destination,
+// optionally going through a proxy. The credentials are returned separately as config for curl to
+// read from its stdin, as any user can see the command line of running processes
+fn curl_arguments(
+    settings: &UploadSettings,
+    proxy: Option<&str>,
+    path: &Path,
+) -> Result<(Vec<String>, String), String> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("Invalid recording path '{}'", path.display()))?
+        .to_string_lossy()
+        .into_owned();
+    let path = path.to_string_lossy().into_owned();
+    let secret = settings.secret.clone().unwrap_or_default();
+    let config;
+
+    let mut arguments = vec![
+        "--fail".to_string(),
+        "--progress-bar".to_string(),
+        "--config".to_string(),
+        "-".to_string(),
+    ];
+    if let Some(proxy) = proxy {
+        arguments.extend(vec!["--proxy".to_string(), proxy.to_string()]);
+    }
+
+    match settings.destination {
+        UploadDestination::Disabled => return Err("Uploads are disabled".to_string()),
+        UploadDestination::S3 => {
+            let url = settings
+                .url
+                .as_ref()
+                .ok_or("Please set the bucket URL in the upload settings")?;
+            let region = settings.region.as_deref().unwrap_or("us-east-1");
+            let username = settings.username.clone().unwrap_or_default();
+
+            config = config_option("user", &format!("{}:{}", username, secret));
+            arguments.extend(vec![
+                "--aws-sigv4".to_string(),
+                format!("aws:amz:{}:s3", region),
+                "-T".to_string(),
+                path,
+                format!("{}/{}", url.trim_end_matches('/'), file_name),
+            ]);
+        }
+        UploadDestination::Sftp => {
+            let url = settings
+                .url
+                .as_ref()
+                .ok_or("Please set the SFTP URL in the upload settings")?;
+            let username = settings.username.clone().unwrap_or_default();
+
+            config = config_option("user", &format!("{}:{}", username, secret));
+            arguments.extend(vec![
+                "-T".to_string(),
+                path,
+                format!("{}/", url.trim_end_matches('/')),
+            ]);
+        }
+        UploadDestination::YouTube => {
+            config = config_option("header", &format!("Authorization: Bearer {}", secret));
+            arguments.extend(vec![
+                "-X".to_string(),
+                "POST".to_string(),
+                "-H".to_string(),
+                "Content-Type: application/octet-stream".to_string(),
+                "-T".to_string(),
+                path,
+                "https://www.googleapis.com/upload/youtube/v3/videos?uploadType=media&part=snippet"
+                    .to_string(),
+            ]);
+        }
+    }
+
+    Ok((arguments, config))
+}
+
+// A line of a curl config file setting the option to the value, quoted so that it can contain
+// anything
+fn config_option(option: &str, value: &str) -> String {
+    let value = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r");
+    format!("{} = \"{}\"\n", option, value)
+}
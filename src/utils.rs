@@ -1,26 +1,113 @@
+use atk::{self, prelude::*};
 use gio::{self, prelude::*};
-use glib;
 use gtk::{self, prelude::*};
 
-use std::path::PathBuf;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
 
-use serde_any;
+use serde::Serialize;
 
+use crate::cli;
+use crate::keyring::{self, Secret};
+use crate::profiles;
 use crate::settings::Settings;
+use crate::toasts;
 use crate::APPLICATION_NAME;
 
-// Get the default path for the settings file
+// Get the path of the settings file of the selected profile
 pub fn get_settings_file_path() -> PathBuf {
-    let mut path = glib::get_user_config_dir().unwrap_or_else(|| PathBuf::from("."));
-    path.push(APPLICATION_NAME);
-    path.push("settings.toml");
-    path
+    profiles::get_settings_file_path(&profiles::active())
 }
 
-// Save the provided settings to the settings path
+// Get the directory local recordings are written to
+pub fn get_recordings_directory(settings: &Settings) -> PathBuf {
+    match settings.recordings_directory {
+        Some(ref directory) => PathBuf::from(directory),
+        None => {
+            let mut path = glib::get_user_special_dir(glib::UserDirectory::Videos)
+                .unwrap_or_else(|| PathBuf::from("."));
+            path.push(APPLICATION_NAME);
+            path
+        }
+    }
+}
+
+pub fn get_snapshots_directory(settings: &Settings) -> PathBuf {
+    match settings.snapshots_directory {
+        Some(ref directory) => PathBuf::from(directory),
+        None => {
+            let mut path = glib::get_user_special_dir(glib::UserDirectory::Pictures)
+                .unwrap_or_else(|| PathBuf::from("."));
+            path.push(APPLICATION_NAME);
+            path
+        }
+    }
+}
+
+// Get the directory the statistics logs are written to
+pub fn get_stats_directory(settings: &Settings) -> PathBuf {
+    match settings.stats_directory {
+        Some(ref directory) => PathBuf::from(directory),
+        None => {
+            let mut path = glib::get_user_data_dir().unwrap_or_else(|| PathBuf::from("."));
+            path.push(APPLICATION_NAME);
+            path.push("stats");
+            path
+        }
+    }
+}
+
+// Free space in bytes on the filesystem the recordings are written to
+pub fn get_free_disk_space(settings: &Settings) -> Option<u64> {
+    let directory = get_recordings_directory(settings);
+    gio::File::new_for_path(&directory)
+        .query_filesystem_info("filesystem::free", gio::NONE_CANCELLABLE)
+        .ok()
+        .map(|info| info.get_attribute_uint64("filesystem::free"))
+}
+
+// Download the given URL with curl in a separate thread, going through the configured proxy, and
+// call the callback with the body on the main thread once done
+pub fn fetch<F: FnOnce(Result<Vec<u8>, String>) + 'static>(url: &str, callback: F) {
+    let mut arguments = vec![
+        "--silent".to_string(),
+        "--show-error".to_string(),
+        "--fail".to_string(),
+        "--location".to_string(),
+    ];
+    if let Some(proxy) = load_settings().proxy {
+        arguments.extend(vec!["--proxy".to_string(), proxy]);
+    }
+    arguments.push(url.to_string());
+
+    let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+    thread::spawn(move || {
+        let result = match Command::new("curl").args(&arguments).output() {
+            Ok(output) if output.status.success() => Ok(output.stdout),
+            Ok(output) => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+            Err(err) => Err(format!("Failed to run curl: {}", err)),
+        };
+        let _ = sender.send(result);
+    });
+
+    let mut callback = Some(callback);
+    receiver.attach(None, move |result| {
+        if let Some(callback) = callback.take() {
+            callback(result);
+        }
+        glib::Continue(false)
+    });
+}
+
+// Save the provided settings to the settings path. Settings overridden on the command line keep
+// their stored values
 pub fn save_settings(settings: &Settings) {
     let s = get_settings_file_path();
-    if let Err(e) = serde_any::to_file(&s, &settings) {
+    let stored = cli::without_overrides(settings, read_settings_file);
+    if let Err(e) = write_settings_file(&s, stored.as_ref().unwrap_or(settings)) {
         show_error_dialog(
             false,
             format!("Error while trying to save file: {}", e).as_str(),
@@ -28,33 +115,211 @@ pub fn save_settings(settings: &Settings) {
     }
 }
 
-// Load the current settings
+// Load the current settings, with the overrides given on the command line
 pub fn load_settings() -> Settings {
-    let s = get_settings_file_path();
-    if s.exists() && s.is_file() {
-        match serde_any::from_file::<Settings, _>(&s) {
-            Ok(s) => s,
-            Err(e) => {
-                show_error_dialog(
-                    false,
-                    format!("Error while opening '{}': {}", s.display(), e).as_str(),
-                );
-                Settings::default()
-            }
+    let mut settings = read_settings_file();
+    let profile = profiles::active();
+    settings.rtmp_stream_key = keyring::lookup(&profile, Secret::StreamKey);
+    settings.rtmp_password = keyring::lookup(&profile, Secret::RtmpPassword);
+    settings.upload.secret = keyring::lookup(&profile, Secret::UploadCredential);
+    settings.extra_rtmp_stream_keys = keyring::lookup(&profile, Secret::ExtraStreamKeys)
+        .map(|keys| keys.split_whitespace().map(String::from).collect())
+        .unwrap_or_default();
+    settings.rtsp_camera.password = keyring::lookup(&profile, Secret::RtspPassword);
+    settings.srt.passphrase = keyring::lookup(&profile, Secret::SrtPassphrase);
+    settings.whip.auth_token = keyring::lookup(&profile, Secret::WhipToken);
+    settings.chat.youtube_api_key = keyring::lookup(&profile, Secret::YouTubeApiKey);
+    settings.remote_token = keyring::lookup(&profile, Secret::RemoteToken);
+    cli::apply_overrides(&mut settings);
+    settings
+}
+
+// Write the settings to a temporary file renamed over the settings file, so that a crash while
+// saving never leaves a truncated file behind
+fn write_settings_file(path: &Path, settings: &impl Serialize) -> io::Result<()> {
+    // TOML needs all plain values of a table before its sub-tables, which the serializer only
+    // takes care of itself when going through a toml::Value
+    let contents = toml::Value::try_from(settings)
+        .and_then(|value| toml::to_string(&value))
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temporary = path.with_extension("toml.tmp");
+    let mut file = fs::File::create(&temporary)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&temporary, path)
+}
+
+// Copy of the settings file kept before it is migrated or replaced
+fn get_settings_backup_path(path: &Path) -> PathBuf {
+    path.with_extension("toml.bak")
+}
+
+fn read_settings_file() -> Settings {
+    read_settings_file_at(&get_settings_file_path())
+}
+
+fn read_settings_file_at(path: &Path) -> Settings {
+    if !path.is_file() {
+        return Settings::default();
+    }
+
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            show_error_dialog(
+                false,
+                format!("Error while opening '{}': {}", path.display(), e).as_str(),
+            );
+            return Settings::default();
+        }
+    };
+
+    let backup = get_settings_backup_path(path);
+    let stored = match serde_any::from_str::<serde_json::Value>(&contents, serde_any::Format::Toml)
+    {
+        Ok(stored) => Settings::from_stored(stored),
+        Err(e) => {
+            // Move the file out of the way so it's not lost with the next save, and the error
+            // is only reported once
+            let _ = fs::rename(path, &backup);
+            show_error_dialog(
+                false,
+                format!(
+                    "Error while opening '{}': {}. The defaults are used and the file was moved \
+                     to '{}'",
+                    path.display(),
+                    e,
+                    backup.display()
+                )
+                .as_str(),
+            );
+            return Settings::default();
+        }
+    };
+
+    // Store the migrated settings right away, keeping the old values in case the migration lost
+    // something
+    if stored.migrated {
+        let result = write_settings_file(&backup, &stored.backup)
+            .and_then(|_| write_settings_file(path, &stored.settings));
+        if let Err(e) = result {
+            show_error_dialog(
+                false,
+                format!("Error while migrating '{}': {}", path.display(), e).as_str(),
+            );
         }
-    } else {
-        Settings::default()
+    }
+    if !stored.reset.is_empty() {
+        show_error_dialog(
+            false,
+            format!(
+                "Some settings in '{}' could not be read and were reset: {}. The previous file \
+                 was kept as '{}'",
+                path.display(),
+                stored.reset.join(", "),
+                backup.display()
+            )
+            .as_str(),
+        );
+    }
+    for problem in &stored.problems {
+        show_error_dialog(
+            false,
+            format!(
+                "{}. The previous settings were kept as '{}'",
+                problem,
+                backup.display()
+            )
+            .as_str(),
+        );
+    }
+
+    stored.settings
+}
+
+// Set the name and description read by screen readers for the given widget
+pub fn set_accessible<W: IsA<gtk::Widget>>(widget: &W, name: &str, description: &str) {
+    if let Some(accessible) = widget.get_accessible() {
+        accessible.set_name(name);
+        accessible.set_description(description);
     }
 }
 
+// Shows a non-modal informational dialog
+pub fn show_info_dialog(text: &str) {
+    let app = gio::Application::get_default()
+        .expect("No default application")
+        .downcast::<gtk::Application>()
+        .expect("Default application has wrong type");
+
+    let dialog = gtk::MessageDialog::new(
+        app.get_active_window().as_ref(),
+        gtk::DialogFlags::DESTROY_WITH_PARENT,
+        gtk::MessageType::Info,
+        gtk::ButtonsType::Ok,
+        text,
+    );
+
+    dialog.connect_response(|dialog, _| {
+        dialog.destroy();
+    });
+
+    dialog.set_resizable(false);
+    dialog.show_all();
+}
+
+// Shows a non-modal warning that doesn't interrupt whatever the user is doing
+pub fn show_warning_dialog(text: &str) {
+    let app = gio::Application::get_default()
+        .expect("No default application")
+        .downcast::<gtk::Application>()
+        .expect("Default application has wrong type");
+
+    let dialog = gtk::MessageDialog::new(
+        app.get_active_window().as_ref(),
+        gtk::DialogFlags::DESTROY_WITH_PARENT,
+        gtk::MessageType::Warning,
+        gtk::ButtonsType::Ok,
+        text,
+    );
+
+    dialog.connect_response(|dialog, _| {
+        dialog.destroy();
+    });
+
+    dialog.set_resizable(false);
+    dialog.show_all();
+}
+
 // Shows an error dialog, and if it's fatal it will quit the application once
-// the dialog is closed
+// the dialog is closed. Errors that are not fatal are shown as toast on the main window instead
+// where possible, so they don't take the focus away while live
 pub fn show_error_dialog(fatal: bool, text: &str) {
+    log::error!("{}", text);
+
+    // Nobody would see the dialog, the logger already printed the error
+    if cli::is_headless() {
+        if fatal {
+            if let Some(app) = gio::Application::get_default() {
+                app.quit();
+            }
+        }
+        return;
+    }
+
     let app = gio::Application::get_default()
         .expect("No default application")
         .downcast::<gtk::Application>()
         .expect("Default application has wrong type");
 
+    if !fatal && toasts::show_toast(&app, text) {
+        return;
+    }
+
     let dialog = gtk::MessageDialog::new(
         app.get_active_window().as_ref(),
         gtk::DialogFlags::MODAL,
@@ -76,3 +341,32 @@ pub fn show_error_dialog(fatal: bool, text: &str) {
     dialog.set_resizable(false);
     dialog.show_all();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::process;
+
+    // Settings with values after tables, which TOML only accepts in a certain order
+    #[test]
+    fn settings_round_trip() {
+        let directory = std::env::temp_dir().join(format!("settings-test-{}", process::id()));
+        let path = directory.join("settings.toml");
+
+        let mut settings = Settings::default();
+        settings.zero_copy_cameras.push("/dev/video0".to_string());
+        settings
+            .encoder_options
+            .insert("x264enc".to_string(), "tune=zerolatency".to_string());
+
+        write_settings_file(&path, &settings).expect("Failed to write the settings");
+        let read = read_settings_file_at(&path);
+        let _ = fs::remove_dir_all(&directory);
+
+        assert_eq!(
+            serde_json::to_value(&read).unwrap(),
+            serde_json::to_value(&settings).unwrap()
+        );
+    }
+}
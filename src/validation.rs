@@ -0,0 +1,287 @@
+use gst::prelude::*;
+
+use crate::devices;
+use crate::encoders;
+use crate::settings::{CameraFormat, OutputMode, Settings};
+
+// Setting a problem was found in, so the settings dialog can point at its widget
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    RtmpLocation,
+    ExtraRtmpLocations,
+    Simulcast,
+    SrtUri,
+    WhipEndpoint,
+    VideoEncoder,
+    AudioEncoder,
+}
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub field: Field,
+    pub message: String,
+}
+
+// Host and port of a URL, with the scheme already checked
+struct Authority<'a> {
+    host: &'a str,
+    port: Option<u16>,
+}
+
+// Split the URL into its parts far enough to catch typos, without resolving anything
+fn parse_url<'a>(url: &'a str, schemes: &[&str]) -> Result<(Authority<'a>, &'a str), String> {
+    let (scheme, rest) = url
+        .split_once("://")
+        .ok_or_else(|| format!("'{}' is not a URL", url))?;
+    if !schemes.iter().any(|s| s.eq_ignore_ascii_case(scheme)) {
+        return Err(format!(
+            "'{}' URLs are not supported here, use {}://",
+            scheme,
+            schemes.join(":// or ")
+        ));
+    }
+
+    let end = rest.find(['/', '?']).unwrap_or(rest.len());
+    let (authority, path) = rest.split_at(end);
+    // Credentials are allowed in front of the host
+    let authority = authority.rsplit('@').next().unwrap_or_default();
+
+    // IPv6 addresses are enclosed in brackets as they contain colons themselves
+    let (host, port) = if let Some(address) = authority.strip_prefix('[') {
+        let (host, rest) = address
+            .split_once(']')
+            .ok_or_else(|| format!("Unterminated IPv6 address in '{}'", url))?;
+        (host, rest.strip_prefix(':'))
+    } else {
+        match authority.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        }
+    };
+
+    if host.contains(char::is_whitespace) {
+        return Err(format!("The host in '{}' contains spaces", url));
+    }
+    let port = match port {
+        Some(port) => match port.parse::<u16>() {
+            Ok(port) if port > 0 => Some(port),
+            _ => return Err(format!("'{}' is not a valid port", port)),
+        },
+        None => None,
+    };
+
+    Ok((Authority { host, port }, path))
+}
+
+// RTMP URLs need a server and the application to publish to, usually followed by the stream
+// key. librtmp options may follow separated by spaces
+pub fn check_rtmp_url(location: &str) -> Result<(), String> {
+    let url = location.split_whitespace().next().unwrap_or_default();
+    let (authority, path) = parse_url(url, &["rtmp", "rtmps"])?;
+    if authority.host.is_empty() {
+        return Err(format!("'{}' is missing the server", url));
+    }
+    if path.trim_matches('/').is_empty() {
+        return Err(format!("'{}' is missing the application path", url));
+    }
+    Ok(())
+}
+
+// Host and port to connect to for the RTMP URL, with the default port of its scheme
+pub fn rtmp_server_address(location: &str) -> Result<(String, u16), String> {
+    check_rtmp_url(location)?;
+    let url = location.split_whitespace().next().unwrap_or_default();
+    let (authority, _) = parse_url(url, &["rtmp", "rtmps"])?;
+    let default_port = if url.to_ascii_lowercase().starts_with("rtmps") {
+        443
+    } else {
+        1935
+    };
+    Ok((
+        authority.host.to_string(),
+        authority.port.unwrap_or(default_port),
+    ))
+}
+
+// SRT URIs need a port, the host may be left out when listening for a caller
+pub fn check_srt_uri(uri: &str) -> Result<(), String> {
+    let (authority, _) = parse_url(uri.trim(), &["srt"])?;
+    if authority.port.is_none() {
+        return Err(format!("'{}' is missing the port", uri));
+    }
+    Ok(())
+}
+
+pub fn check_whip_endpoint(endpoint: &str) -> Result<(), String> {
+    let (authority, _) = parse_url(endpoint.trim(), &["http", "https"])?;
+    if authority.host.is_empty() {
+        return Err(format!("'{}' is missing the server", endpoint));
+    }
+    Ok(())
+}
+
+// Check that all elements of a launch description fragment are installed. Caps, properties and
+// references to named elements are skipped
+pub fn check_launch_elements(description: &str) -> Result<(), String> {
+    let missing = description
+        .split('!')
+        .filter_map(|part| part.split_whitespace().next())
+        .filter(|name| !name.contains(['/', '.', '=', '(', '"']))
+        .filter(|name| gst::ElementFactory::find(name).is_none())
+        .collect::<Vec<_>>();
+
+    match missing.as_slice() {
+        [] => Ok(()),
+        [name] => Err(format!("The element {} is not installed", name)),
+        names => Err(format!(
+            "The elements {} are not installed",
+            names.join(", ")
+        )),
+    }
+}
+
+fn check_video_encoder(settings: &Settings) -> Result<(), String> {
+    match settings.encoder.as_deref() {
+        Some(encoders::AUTO) => encoders::best_family()
+            .map(|_| ())
+            .ok_or_else(|| "No supported H.264 encoder is installed".to_string()),
+        Some(factory) => {
+            if gst::ElementFactory::find(factory).is_some() {
+                Ok(())
+            } else {
+                Err(format!("The video encoder {} is not installed", factory))
+            }
+        }
+        None => {
+            if settings.h264_encoder.trim().is_empty() {
+                Err("Please specify an H.264 encoder chain".to_string())
+            } else {
+                check_launch_elements(&settings.h264_encoder)
+            }
+        }
+    }
+}
+
+// Streaming platforms like Twitch and YouTube want a keyframe at least every this many seconds, and
+// the segments of the LAN preview can only be cut at keyframes
+const MAX_KEYFRAME_SECONDS: u32 = 2;
+
+// Whether the keyframes of the stream are close enough together at the configured frame rate
+pub fn check_keyframe_interval(settings: &Settings) -> Result<(), String> {
+    if settings.output_mode != OutputMode::Rtmp && settings.lan_preview_port == 0 {
+        return Ok(());
+    }
+
+    let max_interval = MAX_KEYFRAME_SECONDS * settings.video_framerate;
+    if settings.keyframe_interval <= max_interval {
+        return Ok(());
+    }
+    Err(format!(
+        "A keyframe every {:.1} seconds at {} fps, streaming platforms like Twitch want one at \
+         least every {} seconds, i.e. at most every {} frames",
+        f64::from(settings.keyframe_interval) / f64::from(settings.video_framerate.max(1)),
+        settings.video_framerate,
+        MAX_KEYFRAME_SECONDS,
+        max_interval
+    ))
+}
+
+// Whether the selected camera can capture at the video resolution. Cameras that can't are scaled
+// by the mixer, which works but looks worse, so this is only pointed out in the settings
+pub fn check_camera_resolution(settings: &Settings) -> Result<(), String> {
+    if settings.test_sources {
+        return Ok(());
+    }
+
+    let devices = devices::list_devices("Video/Source");
+    let device = match settings.camera_device {
+        Some(ref name) => devices
+            .iter()
+            .find(|device| device.get_display_name() == *name),
+        None => devices.first(),
+    };
+    // Without device nothing is known about the camera
+    let supported = match device.and_then(|device| device.get_caps()) {
+        Some(supported) => supported,
+        None => return Ok(()),
+    };
+
+    let (width, height) = settings.video_resolution.size();
+    let fits = CameraFormat::ALL.iter().any(|format| {
+        format!("{},width={},height={}", format.caps(), width, height)
+            .parse::<gst::Caps>()
+            .is_ok_and(|caps| caps.can_intersect(&supported))
+    });
+    if fits {
+        Ok(())
+    } else {
+        Err(format!(
+            "The camera can't capture {} and is scaled to it",
+            settings.video_resolution
+        ))
+    }
+}
+
+// Structural problems with the settings used by the selected outputs, which would otherwise only
+// show up as cryptic errors once streaming starts
+pub fn validate(settings: &Settings) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut check = |field: Field, result: Result<(), String>| {
+        if let Err(message) = result {
+            problems.push(Problem { field, message });
+        }
+    };
+
+    match settings.output_mode {
+        OutputMode::Rtmp => check(
+            Field::RtmpLocation,
+            match settings.rtmp_location {
+                Some(ref location) => check_rtmp_url(location),
+                None => Err("Please set the RTMP end-point URL in the settings".to_string()),
+            },
+        ),
+        OutputMode::Srt => check(
+            Field::SrtUri,
+            match settings.srt.uri {
+                Some(ref uri) => check_srt_uri(uri),
+                None => Err("Please set the SRT URI in the settings".to_string()),
+            },
+        ),
+        OutputMode::Whip => check(
+            Field::WhipEndpoint,
+            match settings.whip.endpoint {
+                Some(ref endpoint) => check_whip_endpoint(endpoint),
+                None => Err("Please set the WHIP endpoint in the settings".to_string()),
+            },
+        ),
+        // The host and ports are plain values checked by the dialog
+        OutputMode::Rtp => (),
+    }
+
+    for location in &settings.extra_rtmp_locations {
+        check(Field::ExtraRtmpLocations, check_rtmp_url(location));
+    }
+
+    for location in settings
+        .active_renditions()
+        .iter()
+        .filter_map(|rendition| rendition.location.as_ref())
+    {
+        check(Field::Simulcast, check_rtmp_url(location));
+    }
+
+    check(Field::VideoEncoder, check_video_encoder(settings));
+    check(
+        Field::AudioEncoder,
+        if gst::ElementFactory::find(settings.audio_codec.factory()).is_some() {
+            Ok(())
+        } else {
+            Err(format!(
+                "The audio encoder {} is not installed, please choose another one in the settings",
+                settings.audio_codec.factory()
+            ))
+        },
+    );
+
+    problems
+}